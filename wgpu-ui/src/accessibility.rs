@@ -0,0 +1,328 @@
+use std::num::NonZeroU128;
+
+use accesskit::{NodeBuilder, NodeClassSet, NodeId, Tree, TreeUpdate};
+use accesskit_winit::{ActionRequestEvent, Adapter};
+use winit::{event::WindowEvent, event_loop::EventLoopProxy, window::Window};
+
+use crate::math::Rect;
+use crate::ui::{check_box::CheckState, Ui, Uid, Widget};
+
+/// Kind of a widget, as announced by screen readers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Group,
+    Window,
+    Dialog,
+    Button,
+    CheckBox,
+    Slider,
+    ProgressIndicator,
+    TextInput,
+    Label,
+    List,
+    /// Graphic conveying meaning on its own, e.g. an icon
+    Image,
+    /// Indicator of a state, e.g. a connection status dot
+    Status,
+}
+
+impl From<Role> for accesskit::Role {
+    fn from(role: Role) -> Self {
+        match role {
+            Role::Group => Self::Group,
+            Role::Window => Self::Window,
+            Role::Dialog => Self::Dialog,
+            Role::Button => Self::Button,
+            Role::CheckBox => Self::CheckBox,
+            Role::Slider => Self::Slider,
+            Role::ProgressIndicator => Self::ProgressIndicator,
+            Role::TextInput => Self::TextField,
+            Role::Label => Self::StaticText,
+            Role::List => Self::List,
+            Role::Image => Self::Image,
+            Role::Status => Self::Status,
+        }
+    }
+}
+
+impl From<CheckState> for accesskit::CheckedState {
+    fn from(state: CheckState) -> Self {
+        match state {
+            CheckState::Unchecked => Self::False,
+            CheckState::Checked => Self::True,
+            CheckState::Indeterminate => Self::Mixed,
+        }
+    }
+}
+
+/// Urgency of an announcement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// Spoken once the screen reader is idle
+    Polite,
+    /// Interrupts the current speech
+    Assertive,
+}
+
+impl From<Priority> for accesskit::Live {
+    fn from(priority: Priority) -> Self {
+        match priority {
+            Priority::Polite => Self::Polite,
+            Priority::Assertive => Self::Assertive,
+        }
+    }
+}
+
+/// Message spoken by screen readers, see [`crate::Context::announce`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Announcement {
+    pub message: String,
+    pub priority: Priority,
+}
+
+/// Accessibility description of a widget
+#[derive(Debug, Clone)]
+pub struct AccessNode {
+    pub role: Role,
+    /// Label of the widget (button text, window title, ..)
+    pub name: Option<String>,
+    pub bounds: Rect,
+    pub focused: bool,
+    /// State of a check box, `None` for widgets which can't be checked
+    pub checked: Option<CheckState>,
+    /// Textual value (content of a text field, ..)
+    pub value: Option<String>,
+    /// Numeric value (slider position, progress, ..)
+    pub numeric_value: Option<f64>,
+}
+
+impl AccessNode {
+    pub fn new(role: Role, bounds: Rect) -> Self {
+        Self {
+            role,
+            name: None,
+            bounds,
+            focused: false,
+            checked: None,
+            value: None,
+            numeric_value: None,
+        }
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+
+        self
+    }
+
+    pub fn with_value(mut self, value: &str) -> Self {
+        self.value = Some(value.to_string());
+
+        self
+    }
+
+    pub fn with_numeric_value(mut self, value: f64) -> Self {
+        self.numeric_value = Some(value);
+
+        self
+    }
+}
+
+/// Meaning of a purely graphical element (icon, status dot, ..), which would otherwise be
+/// exposed as an anonymous rectangle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Semantics {
+    pub role: Role,
+    /// Text spoken in place of the graphic, e.g. `Delete` or `Connected`
+    pub label: String,
+}
+
+impl Semantics {
+    pub fn new(role: Role, label: &str) -> Self {
+        Self {
+            role,
+            label: label.to_string(),
+        }
+    }
+
+    /// Returns the description of the element covering `bounds`
+    pub fn node(&self, bounds: Rect) -> AccessNode {
+        AccessNode::new(self.role, bounds).with_name(&self.label)
+    }
+}
+
+fn node_id(uid: Uid) -> NodeId {
+    NodeId(NonZeroU128::new(uid.get() as u128).unwrap())
+}
+
+/// Identity of the live region announcing messages of `priority`, out of the range of widget
+/// identities
+fn live_region_id(priority: Priority) -> NodeId {
+    let id = match priority {
+        Priority::Polite => u128::MAX,
+        Priority::Assertive => u128::MAX - 1,
+    };
+
+    NodeId(NonZeroU128::new(id).unwrap())
+}
+
+/// Builds the accessibility tree of a widget and its descendants
+///
+/// # Arguments
+///
+/// * `widget` - Root of the subtree
+/// * `classes` - Shared node classes of the tree
+/// * `nodes` - Nodes of the tree update, filled in depth-first order
+/// * `focus` - Set to the focused widget if found in the subtree
+fn collect(
+    widget: &dyn Widget,
+    classes: &mut NodeClassSet,
+    nodes: &mut Vec<(NodeId, accesskit::Node)>,
+    focus: &mut Option<NodeId>,
+) -> NodeId {
+    let id = node_id(widget.uid());
+    let description = widget.accessibility();
+
+    let mut builder = NodeBuilder::new(description.role.into());
+    builder.set_bounds(accesskit::Rect {
+        x0: description.bounds.x as f64,
+        y0: description.bounds.y as f64,
+        x1: (description.bounds.x + description.bounds.width) as f64,
+        y1: (description.bounds.y + description.bounds.height) as f64,
+    });
+    if let Some(name) = description.name {
+        builder.set_name(name);
+    }
+    if let Some(value) = description.value {
+        builder.set_value(value);
+    }
+    if let Some(value) = description.numeric_value {
+        builder.set_numeric_value(value);
+    }
+    if let Some(checked) = description.checked {
+        builder.set_checked_state(checked.into());
+    }
+    if !widget.visible() {
+        builder.set_hidden();
+    }
+    if description.focused {
+        *focus = Some(id);
+    }
+
+    let children = widget
+        .children()
+        .into_iter()
+        .map(|child| collect(child, classes, nodes, focus))
+        .collect::<Vec<_>>();
+    builder.set_children(children);
+
+    nodes.push((id, builder.build(classes)));
+
+    id
+}
+
+/// Exposes the widget tree to screen readers through AccessKit
+pub struct Accessibility {
+    adapter: Adapter,
+    classes: NodeClassSet,
+    /// Last message of each live region, polite then assertive
+    announcements: [Option<String>; 2],
+}
+
+impl Accessibility {
+    /// Must be called before the window is made visible
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - Window whose content is exposed
+    /// * `proxy` - Used by AccessKit to forward action requests (focus, click, ..) to the event loop
+    pub fn new(window: &Window, proxy: EventLoopProxy<ActionRequestEvent>) -> Self {
+        let adapter = Adapter::new(
+            window,
+            || {
+                let mut classes = NodeClassSet::new();
+                let root = node_id(Uid::ROOT);
+                let node = NodeBuilder::new(accesskit::Role::Window).build(&mut classes);
+
+                TreeUpdate {
+                    nodes: vec![(root, node)],
+                    tree: Some(Tree::new(root)),
+                    focus: None,
+                }
+            },
+            proxy,
+        );
+
+        Self {
+            adapter,
+            classes: NodeClassSet::new(),
+            announcements: [None, None],
+        }
+    }
+
+    /// Forward a window event to AccessKit, returns `true` if it was consumed
+    pub fn process_event(&self, window: &Window, event: &WindowEvent) -> bool {
+        self.adapter.on_event(window, event)
+    }
+
+    /// Speak `announcement` through the live region matching its priority, the message is sent
+    /// with the next update
+    ///
+    /// Screen readers only speak a live region when its content changes, repeating the last
+    /// message of the same priority is silent.
+    pub fn announce(&mut self, announcement: Announcement) {
+        tracing::debug!("Announcing {:?}", announcement);
+
+        let index = match announcement.priority {
+            Priority::Polite => 0,
+            Priority::Assertive => 1,
+        };
+        self.announcements[index] = Some(announcement.message);
+    }
+
+    /// Push the current state of the widget tree, does nothing when no assistive technology listens
+    ///
+    /// # Arguments
+    ///
+    /// * `ui` - Widget tree
+    pub fn update(&mut self, ui: &Ui) {
+        let classes = &mut self.classes;
+        let announcements = &self.announcements;
+
+        self.adapter.update_if_active(|| {
+            let mut nodes = Vec::new();
+            let mut focus = None;
+            let root = node_id(Uid::ROOT);
+
+            let mut children = ui
+                .widgets()
+                .into_iter()
+                .map(|widget| collect(widget, classes, &mut nodes, &mut focus))
+                .collect::<Vec<_>>();
+
+            for (priority, message) in [Priority::Polite, Priority::Assertive]
+                .into_iter()
+                .zip(announcements)
+            {
+                let id = live_region_id(priority);
+                let mut builder = NodeBuilder::new(accesskit::Role::StaticText);
+                builder.set_live(priority.into());
+                if let Some(message) = message {
+                    builder.set_name(message.clone());
+                }
+                nodes.push((id, builder.build(classes)));
+                children.push(id);
+            }
+
+            let mut builder = NodeBuilder::new(accesskit::Role::Window);
+            builder.set_children(children);
+            nodes.push((root, builder.build(classes)));
+
+            TreeUpdate {
+                nodes,
+                tree: Some(Tree::new(root)),
+                focus: focus.or(Some(root)),
+            }
+        });
+    }
+}
@@ -0,0 +1,270 @@
+//! Additional windows sharing the device, queue, pipelines and assets of the main one
+
+use winit::{
+    event::{Touch, WindowEvent},
+    window::{CursorIcon, Window, WindowId},
+};
+
+use crate::graphics::{layer::Layer, msaa, Drawable};
+use crate::math::{letterbox, Rect};
+use crate::ui::Ui;
+use crate::Ctx;
+
+/// A window with its own surface, drawables and widgets
+///
+/// Drawables and widgets are laid out in the UI space shared by every window, which is scaled to
+/// fit the window while preserving its aspect ratio.
+pub struct AppWindow {
+    context: Ctx,
+    window: Window,
+    surface: wgpu::Surface,
+    config: wgpu::SurfaceConfiguration,
+    /// Multisampled texture resolved into the surface, when the pipelines are multisampled
+    msaa: Option<wgpu::TextureView>,
+    /// Content drawn through a movable camera
+    world: Layer<'static>,
+    /// Screen space layer the widgets are drawn in
+    overlay: Layer<'static>,
+    ui: Ui,
+    /// Cursor last shown over the window
+    cursor: CursorIcon,
+}
+
+impl AppWindow {
+    /// Create a surface for `window`
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context of the main window
+    /// * `instance` - Instance the device was requested from
+    /// * `adapter` - Adapter of the device
+    /// * `window` - Window to draw to
+    pub fn new(
+        context: Ctx,
+        instance: &wgpu::Instance,
+        adapter: &wgpu::Adapter,
+        window: Window,
+    ) -> Self {
+        let surface = unsafe { instance.create_surface(&window).unwrap() };
+        let size = window.inner_size();
+
+        let ctx = context.lock().unwrap();
+        let surface_caps = surface.get_capabilities(adapter);
+        // Pipelines are built for the format of the main surface
+        if !surface_caps.formats.contains(&ctx.config.format) {
+            tracing::warn!(
+                "{:?} isn't supported by the surface of window {:?}",
+                ctx.config.format,
+                window.id()
+            );
+        }
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: ctx.config.format,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: surface_caps.present_modes[0],
+            alpha_mode: surface_caps.alpha_modes[0],
+            view_formats: vec![],
+        };
+        surface.configure(&ctx.device, &config);
+        let msaa = msaa::create_view(
+            &ctx.device,
+            config.format,
+            (config.width, config.height),
+            ctx.sample_count,
+            &format!("{:?}/msaa", window.id()),
+        );
+        drop(ctx);
+
+        Self {
+            world: Layer::new(context.clone(), &format!("{:?}/world", window.id())),
+            overlay: Layer::new(context.clone(), &format!("{:?}/ui", window.id())),
+            ui: Ui::new(context.clone()),
+            context,
+            window,
+            surface,
+            cursor: CursorIcon::Default,
+            config,
+            msaa,
+        }
+    }
+
+    pub fn id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    /// Returns the layer drawn in the window, to add drawables or move its camera
+    pub fn world_mut(&mut self) -> &mut Layer<'static> {
+        &mut self.world
+    }
+
+    /// Returns the widgets of the window
+    pub fn ui_mut(&mut self) -> &mut Ui {
+        &mut self.ui
+    }
+
+    /// Returns the area of the surface the UI space is rendered to, in physical pixels
+    pub fn viewport(&self) -> Rect {
+        let screen_size = self.context.lock().unwrap().screen_size();
+
+        letterbox(
+            (self.config.width as f32, self.config.height as f32).into(),
+            screen_size.into(),
+        )
+    }
+
+    /// Convert a position in the window to the UI space
+    fn window_to_ui(&self, position: glam::Vec2) -> glam::Vec2 {
+        let viewport = self.viewport();
+        let (width, height) = self.context.lock().unwrap().screen_size();
+
+        glam::Vec2 {
+            x: (position.x - viewport.x) * width / viewport.width,
+            y: (position.y - viewport.y) * height / viewport.height,
+        }
+    }
+
+    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        if new_size.width == 0 || new_size.height == 0 {
+            return;
+        }
+
+        self.config.width = new_size.width;
+        self.config.height = new_size.height;
+        let mut ctx = self.context.lock().unwrap();
+        self.surface.configure(&ctx.device, &self.config);
+        let msaa = msaa::create_view(
+            &ctx.device,
+            self.config.format,
+            (new_size.width, new_size.height),
+            ctx.sample_count,
+            &format!("{:?}/msaa", self.window.id()),
+        );
+        if let Some(previous) = std::mem::replace(&mut self.msaa, msaa) {
+            ctx.retire(previous);
+        }
+    }
+
+    /// Forward an event of the window to its widgets, positions are converted to the UI space
+    // `CursorMoved::modifiers` is deprecated but still has to be forwarded
+    #[allow(deprecated)]
+    pub fn process_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::CursorMoved {
+                device_id,
+                position,
+                modifiers,
+            } => {
+                let position = self.window_to_ui((position.x as f32, position.y as f32).into());
+
+                self.ui.process_events(&WindowEvent::CursorMoved {
+                    device_id: *device_id,
+                    position: (position.x as f64, position.y as f64).into(),
+                    modifiers: *modifiers,
+                });
+            }
+            WindowEvent::Touch(touch) => {
+                let location =
+                    self.window_to_ui((touch.location.x as f32, touch.location.y as f32).into());
+
+                self.ui.process_events(&WindowEvent::Touch(Touch {
+                    location: (location.x as f64, location.y as f64).into(),
+                    ..*touch
+                }));
+            }
+            WindowEvent::Resized(physical_size) => self.resize(*physical_size),
+            WindowEvent::ScaleFactorChanged { new_inner_size, .. } => self.resize(**new_inner_size),
+            _ => self.ui.process_events(event),
+        }
+    }
+
+    /// Advance the widgets of the window
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Time elapsed since the last frame, in seconds
+    pub fn tick(&mut self, dt: f32) {
+        self.ui.tick(dt);
+
+        let cursor = self.ui.cursor_icon().unwrap_or_default();
+        if cursor != self.cursor {
+            self.window.set_cursor_icon(cursor);
+            self.cursor = cursor;
+        }
+    }
+
+    /// Upload the cameras, must be called when the UI space changes
+    pub fn update(&mut self) {
+        self.world.update();
+        self.overlay.update();
+    }
+
+    /// Draw the window content and present it
+    ///
+    /// # Arguments
+    ///
+    /// * `index_buffer` - Index buffer of the quads, shared by every window
+    pub fn render(&mut self, index_buffer: &wgpu::Buffer) -> Result<(), wgpu::SurfaceError> {
+        let output = self.surface.get_current_texture()?;
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let viewport = self.viewport();
+
+        let context = self.context.lock().unwrap();
+        let mut encoder = context
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some(&format!("{:?} render encoder", self.id())),
+            });
+        let clear_color = context.clear_color;
+        drop(context);
+
+        self.ui.prepare(&mut encoder, index_buffer);
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(&format!("{:?} pass", self.id())),
+                color_attachments: &[Some(msaa::attachment(
+                    self.msaa.as_ref(),
+                    &view,
+                    wgpu::LoadOp::Clear(self.world.clear().unwrap_or(clear_color)),
+                ))],
+                depth_stencil_attachment: None,
+            });
+
+            render_pass.set_viewport(
+                viewport.x,
+                viewport.y,
+                viewport.width,
+                viewport.height,
+                0.,
+                1.,
+            );
+            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+
+            render_pass.push_debug_group("World");
+            self.world.draw(&mut render_pass);
+            render_pass.pop_debug_group();
+
+            render_pass.push_debug_group("Ui");
+            self.overlay.draw(&mut render_pass);
+            self.ui.draw(&mut render_pass);
+            render_pass.pop_debug_group();
+        }
+
+        let mut context = self.context.lock().unwrap();
+        let context = &mut *context;
+        context.queue.submit(std::iter::once(encoder.finish()));
+        context.frames.submitted(&context.queue);
+        drop(context);
+        output.present();
+
+        Ok(())
+    }
+}
@@ -1,26 +1,57 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use rusttype::Font;
-use std::{collections::HashMap, path::Path};
+use std::{collections::HashMap, path::Path, sync::RwLock};
 
-/// Resources holder (in this case the holder only handle Font but it can be extended to hold textures, sounds, ..)
+use crate::audio::SoundBank;
+use crate::i18n::Translations;
+
+/// Resources holder (fonts, sounds and translations, it can be extended to hold textures, ..)
 pub struct Assets<'a> {
     fonts: HashMap<String, Font<'a>>,
+    /// Font files, parsed again by the text shaper
+    font_data: HashMap<String, Vec<u8>>,
+    /// Fonts looked up when a glyph is missing from the requested font, sorted by priority
+    fallbacks: Vec<(u32, String)>,
+    /// Fonts preloaded by scenes once the assets are shared, kept until exit as texts borrow them
+    scene_fonts: RwLock<HashMap<String, (&'static Font<'static>, &'static [u8])>>,
+    /// Sound effects of the widgets, loadable while the assets are shared
+    sounds: SoundBank,
+    /// Catalogs of the translated strings, loadable while the assets are shared
+    translations: Translations,
 }
 
 impl<'a> Assets<'a> {
     pub fn new() -> Self {
         Self {
             fonts: HashMap::new(),
+            font_data: HashMap::new(),
+            fallbacks: Vec::new(),
+            scene_fonts: RwLock::new(HashMap::new()),
+            sounds: SoundBank::new(),
+            translations: Translations::new(),
         }
     }
 
+    /// Returns the sounds played by the widgets
+    pub fn sounds(&self) -> &SoundBank {
+        &self.sounds
+    }
+
+    /// Returns the catalogs the translation keys are resolved against
+    pub fn translations(&self) -> &Translations {
+        &self.translations
+    }
+
     /// Returns a reference to the named font
     ///
     /// # Arguments
     ///
     /// * `name` - Font name
     pub fn get_font(&self, name: &str) -> Option<&Font> {
-        self.fonts.get(name)
+        self.fonts.get(name).or_else(|| {
+            let scene_fonts = self.scene_fonts.read().unwrap();
+            scene_fonts.get(name).map(|(font, _)| *font as &Font)
+        })
     }
 
     /// Load font into Assets holder
@@ -30,13 +61,117 @@ impl<'a> Assets<'a> {
     /// * `path` - Path to the font file
     pub fn load_font(&mut self, path: &Path) -> Result<()> {
         let bytes = std::fs::read(path)?;
-        let font = rusttype::Font::try_from_vec(bytes).unwrap();
+        let font = rusttype::Font::try_from_vec(bytes.clone())
+            .ok_or_else(|| anyhow!("invalid font file {}", path.display()))?;
+        let name = path.file_name().unwrap().to_str().unwrap().to_string();
+
+        self.fonts.insert(name.clone(), font);
+        self.font_data.insert(name, bytes);
+
+        Ok(())
+    }
+
+    /// Load a font while the assets are shared, e.g. by a scene preloading, does nothing if the
+    /// font is already loaded
+    ///
+    /// Unlike [`Assets::load_font`], the font is never unloaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the font file
+    pub fn preload_font(&self, path: &Path) -> Result<()> {
+        let name = path.file_name().unwrap().to_str().unwrap().to_string();
+        if self.get_font(&name).is_some() {
+            return Ok(());
+        }
+
+        let bytes: &'static [u8] = Vec::leak(std::fs::read(path)?);
+        let font = rusttype::Font::try_from_bytes(bytes)
+            .ok_or_else(|| anyhow!("invalid font file {}", path.display()))?;
 
-        self.fonts.insert(
-            path.file_name().unwrap().to_str().unwrap().to_string(),
-            font,
-        );
+        self.scene_fonts
+            .write()
+            .unwrap()
+            .entry(name)
+            .or_insert((&*Box::leak(Box::new(font)), bytes));
 
         Ok(())
     }
+
+    /// Returns the shaping face of a loaded font, `None` if the font isn't an asset
+    ///
+    /// # Arguments
+    ///
+    /// * `font` - Font returned by [`Assets::get_font`]
+    pub fn shaping_face(&self, font: &Font) -> Option<rustybuzz::Face> {
+        let name = self
+            .fonts
+            .iter()
+            .find_map(|(name, other)| std::ptr::eq(other, font).then_some(name));
+
+        let data = match name {
+            Some(name) => self.font_data.get(name)?.as_slice(),
+            None => self
+                .scene_fonts
+                .read()
+                .unwrap()
+                .values()
+                .find_map(|(other, data)| std::ptr::eq(*other, font).then_some(*data))?,
+        };
+
+        rustybuzz::Face::from_slice(data, 0)
+    }
+
+    /// Register a loaded font in the fallback chain, used for characters missing from the
+    /// font of a text (CJK, emoji, symbols, ..)
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of an already loaded font
+    /// * `priority` - Fonts with a lower priority are looked up first
+    pub fn add_fallback(&mut self, name: &str, priority: u32) -> Result<()> {
+        if !self.fonts.contains_key(name) {
+            return Err(anyhow!("font {name} is not loaded"));
+        }
+
+        self.fallbacks.retain(|(_, fallback)| fallback != name);
+        self.fallbacks.push((priority, name.to_string()));
+        self.fallbacks.sort_by_key(|(priority, _)| *priority);
+
+        Ok(())
+    }
+
+    /// Returns the fallback fonts, in lookup order
+    pub fn fallback_fonts(&self) -> Vec<&Font> {
+        self.fallbacks
+            .iter()
+            .filter_map(|(_, name)| self.fonts.get(name))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Assets;
+    use std::path::Path;
+
+    #[test]
+    fn fallback_requires_loaded_font() {
+        let mut assets = Assets::new();
+        assert!(assets.add_fallback("Roboto.ttf", 0).is_err());
+
+        assets.load_font(Path::new("assets/Roboto.ttf")).unwrap();
+        assert!(assets.add_fallback("Roboto.ttf", 0).is_ok());
+        assert_eq!(assets.fallback_fonts().len(), 1);
+    }
+
+    #[test]
+    fn fallback_registered_once() {
+        let mut assets = Assets::new();
+        assets.load_font(Path::new("assets/Roboto.ttf")).unwrap();
+        assets.add_fallback("Roboto.ttf", 10).unwrap();
+        assets.add_fallback("Roboto.ttf", 0).unwrap();
+
+        assert_eq!(assets.fallback_fonts().len(), 1);
+    }
 }
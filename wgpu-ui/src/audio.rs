@@ -0,0 +1,177 @@
+//! Sound effects giving an audible feedback to the widgets, played on a dedicated thread through
+//! the default output device
+//!
+//! Sounds are only played with the `audio` feature, they are silently skipped otherwise or when
+//! no output device is available.
+
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+        Arc, Mutex, RwLock,
+    },
+};
+
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use tracing::warn;
+
+use crate::ASSETS;
+
+static MUTED: AtomicBool = AtomicBool::new(false);
+/// Sounds waiting to be played by the audio thread, `None` without output device
+static OUTPUT: Lazy<Option<Mutex<Sender<Arc<[u8]>>>>> =
+    Lazy::new(|| spawn_output().map(Mutex::new));
+
+/// Sounds played by the widgets, encoded files (WAV, Ogg Vorbis) decoded when played
+#[derive(Debug, Default)]
+pub struct SoundBank {
+    sounds: RwLock<HashMap<String, Arc<[u8]>>>,
+}
+
+impl SoundBank {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a sound named after its file name, e.g. `click.wav`
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the sound file
+    pub fn load(&self, path: &Path) -> Result<()> {
+        let bytes = std::fs::read(path)?;
+        let name = path.file_name().unwrap().to_str().unwrap();
+        self.insert(name, bytes);
+
+        Ok(())
+    }
+
+    /// Add an already read sound file, replacing the sound named `name`
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name the widgets refer to the sound by
+    /// * `bytes` - Content of the sound file
+    pub fn insert(&self, name: &str, bytes: impl Into<Arc<[u8]>>) {
+        self.sounds
+            .write()
+            .unwrap()
+            .insert(name.to_string(), bytes.into());
+    }
+
+    /// Returns the content of the sound file named `name`
+    pub fn get(&self, name: &str) -> Option<Arc<[u8]>> {
+        self.sounds.read().unwrap().get(name).cloned()
+    }
+}
+
+/// Sounds a widget plays, read from the theme when it is created
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SoundStyle {
+    /// Played when the cursor enters the widget
+    pub on_hover_sound: Option<&'static str>,
+    /// Played when the widget is clicked or toggled
+    pub on_click_sound: Option<&'static str>,
+}
+
+/// Play a sound of the bank of [`ASSETS`], does nothing when it isn't loaded or the audio is
+/// muted
+///
+/// # Arguments
+///
+/// * `name` - Name of the sound, see [`SoundBank::load`]
+pub fn play(name: &str) {
+    if MUTED.load(Ordering::Relaxed) {
+        return;
+    }
+    let Some(sound) = ASSETS.sounds().get(name) else {
+        warn!("Sound {name} is not loaded");
+        return;
+    };
+
+    if let Some(output) = OUTPUT.as_ref() {
+        let _ = output.lock().unwrap().send(sound);
+    }
+}
+
+/// Play `name` if it is set, see [`play`]
+pub fn play_optional(name: Option<&str>) {
+    if let Some(name) = name {
+        play(name);
+    }
+}
+
+/// Silence every sound, e.g. from an accessibility or settings menu
+///
+/// # Arguments
+///
+/// * `muted` - `true` to stop playing the sounds
+pub fn set_muted(muted: bool) {
+    MUTED.store(muted, Ordering::Relaxed);
+}
+
+pub fn is_muted() -> bool {
+    MUTED.load(Ordering::Relaxed)
+}
+
+/// Start the thread owning the output stream, returns the sender of the sounds to play
+#[cfg(feature = "audio")]
+fn spawn_output() -> Option<Sender<Arc<[u8]>>> {
+    use rodio::Source;
+    use std::{io::Cursor, sync::mpsc};
+
+    let (sender, receiver) = mpsc::channel::<Arc<[u8]>>();
+    let (ready_sender, ready) = mpsc::channel();
+
+    std::thread::Builder::new()
+        .name("audio".to_string())
+        .spawn(move || {
+            // The stream can't be sent to another thread, it lives as long as this one
+            let (_stream, handle) = match rodio::OutputStream::try_default() {
+                Ok(output) => {
+                    let _ = ready_sender.send(true);
+                    output
+                }
+                Err(e) => {
+                    warn!("Audio output unavailable: {e}");
+                    let _ = ready_sender.send(false);
+                    return;
+                }
+            };
+
+            for sound in receiver {
+                let played = rodio::Decoder::new(Cursor::new(sound))
+                    .map_err(anyhow::Error::from)
+                    .and_then(|source| Ok(handle.play_raw(source.convert_samples())?));
+                if let Err(e) = played {
+                    warn!("Unable to play a sound: {e}");
+                }
+            }
+        })
+        .ok()?;
+
+    ready.recv().unwrap_or(false).then_some(sender)
+}
+
+#[cfg(not(feature = "audio"))]
+fn spawn_output() -> Option<Sender<Arc<[u8]>>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SoundBank;
+
+    #[test]
+    fn sounds_are_named_by_the_widgets() {
+        let bank = SoundBank::new();
+        assert!(bank.get("click.wav").is_none());
+
+        bank.insert("click.wav", vec![1, 2, 3]);
+        bank.insert("click.wav", vec![4]);
+        assert_eq!(bank.get("click.wav").as_deref(), Some(&[4u8][..]));
+    }
+}
@@ -0,0 +1,61 @@
+use std::fmt;
+
+use tracing::warn;
+
+/// System clipboard access
+///
+/// Falls back to an in-process buffer when the platform clipboard is not available
+/// (headless sessions, unsupported platforms, ..).
+pub struct Clipboard {
+    backend: Option<arboard::Clipboard>,
+    fallback: String,
+}
+
+impl Clipboard {
+    pub fn new() -> Self {
+        let backend = match arboard::Clipboard::new() {
+            Ok(clipboard) => Some(clipboard),
+            Err(e) => {
+                warn!("System clipboard unavailable: {e}");
+                None
+            }
+        };
+
+        Self {
+            backend,
+            fallback: String::new(),
+        }
+    }
+
+    /// Returns the text currently held by the clipboard
+    pub fn get_text(&mut self) -> Option<String> {
+        match &mut self.backend {
+            Some(clipboard) => clipboard.get_text().ok(),
+            None => Some(self.fallback.clone()),
+        }
+    }
+
+    /// Replace the clipboard content
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - Text to copy into the clipboard
+    pub fn set_text(&mut self, text: &str) {
+        match &mut self.backend {
+            Some(clipboard) => {
+                if let Err(e) = clipboard.set_text(text) {
+                    warn!("Unable to write into the clipboard: {e}");
+                }
+            }
+            None => self.fallback = text.to_string(),
+        }
+    }
+}
+
+impl fmt::Debug for Clipboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Clipboard")
+            .field("system", &self.backend.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,455 @@
+use std::collections::VecDeque;
+use std::sync::{
+    atomic::{AtomicBool, AtomicU32, Ordering},
+    Arc,
+};
+
+use glam::Vec2;
+use tracing::info;
+
+use crate::graphics::{
+    color::{Color, GREEN, RED, WHITE},
+    shape::{RectangleShape, Shape},
+    text::Text,
+    Drawable, Transformable,
+};
+use crate::{Ctx, ASSETS};
+
+/// Number of frames kept in the frame time graph
+const FRAME_HISTORY: usize = 120;
+/// Frame time drawn at the top of the graph, in seconds
+const GRAPH_MAX_FRAME_TIME: f32 = 1. / 30.;
+const GRAPH_HEIGHT: f32 = 40.;
+const BAR_WIDTH: f32 = 2.;
+/// Number of text lines above the graph
+const LINES: usize = 3;
+const LINE_HEIGHT: f32 = 18.;
+
+static DRAW_CALLS: AtomicU32 = AtomicU32::new(0);
+static VERTICES: AtomicU32 = AtomicU32::new(0);
+
+/// Programmatic frame capture, triggered for a single frame.
+///
+/// When the application runs under RenderDoc (or any tool hooked through the wgpu
+/// backend capture API) the captured frame is bracketed by `start_capture`/`stop_capture`
+/// so only the requested frame ends up in the capture.
+#[derive(Debug, Default)]
+pub struct FrameCapture {
+    requested: bool,
+    capturing: bool,
+}
+
+impl FrameCapture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask for the next rendered frame to be captured
+    pub fn request(&mut self) {
+        self.requested = true;
+    }
+
+    /// Returns `true` while a frame capture is in progress
+    pub fn capturing(&self) -> bool {
+        self.capturing
+    }
+
+    /// Start the capture if one has been requested, must be called before recording the frame
+    ///
+    /// # Arguments
+    ///
+    /// * `device` - The device recording the frame
+    pub fn begin(&mut self, device: &wgpu::Device) {
+        if !self.requested {
+            return;
+        }
+
+        self.requested = false;
+        self.capturing = true;
+        info!("Starting frame capture");
+        device.start_capture();
+    }
+
+    /// Stop the capture started by [`FrameCapture::begin`], must be called once the frame is submitted
+    ///
+    /// # Arguments
+    ///
+    /// * `device` - The device recording the frame
+    pub fn end(&mut self, device: &wgpu::Device) {
+        if !self.capturing {
+            return;
+        }
+
+        self.capturing = false;
+        device.stop_capture();
+        info!("Frame capture done");
+    }
+}
+
+/// Count a draw call issued for the current frame
+///
+/// # Arguments
+///
+/// * `vertices` - Number of vertices submitted by the draw call
+pub fn record_draw(vertices: u32) {
+    DRAW_CALLS.fetch_add(1, Ordering::Relaxed);
+    VERTICES.fetch_add(vertices, Ordering::Relaxed);
+}
+
+/// Statistics of a rendered frame
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FrameStats {
+    /// CPU time between two frames, in seconds
+    pub frame_time: f32,
+    pub draw_calls: u32,
+    pub vertices: u32,
+    /// GPU time spent on the frame, in milliseconds, only measured when the adapter supports
+    /// timestamp queries
+    pub gpu_time: Option<f32>,
+}
+
+impl FrameStats {
+    /// Returns the statistics recorded since the last call, counters are reset
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_time` - CPU time of the frame, in seconds
+    pub fn collect(frame_time: f32) -> Self {
+        Self {
+            frame_time,
+            draw_calls: DRAW_CALLS.swap(0, Ordering::Relaxed),
+            vertices: VERTICES.swap(0, Ordering::Relaxed),
+            gpu_time: None,
+        }
+    }
+}
+
+/// Rolling window of the last frame times
+#[derive(Debug, Default)]
+pub struct FrameTimes {
+    times: VecDeque<f32>,
+}
+
+impl FrameTimes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a frame time, in seconds, dropping the oldest one once the history is full
+    pub fn push(&mut self, frame_time: f32) {
+        if self.times.len() == FRAME_HISTORY {
+            self.times.pop_front();
+        }
+
+        self.times.push_back(frame_time);
+    }
+
+    /// Returns the frame times, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &f32> {
+        self.times.iter()
+    }
+
+    /// Returns the average number of frames per second over the history
+    pub fn fps(&self) -> f32 {
+        let total: f32 = self.times.iter().sum();
+
+        if total > 0. {
+            self.times.len() as f32 / total
+        } else {
+            0.
+        }
+    }
+}
+
+/// Measures the GPU time of a frame through timestamp queries
+///
+/// Results are read back asynchronously, the reported time lags a few frames behind.
+pub struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    /// Set by the map callback once the readback buffer can be read
+    mapped: Arc<AtomicBool>,
+    /// A copy to the readback buffer is pending, no new one can be recorded meanwhile
+    pending: bool,
+    period: f32,
+}
+
+impl GpuTimer {
+    /// Returns `None` when `device` was not created with [`wgpu::Features::TIMESTAMP_QUERY`]
+    ///
+    /// # Arguments
+    ///
+    /// * `device` - The device recording the frames
+    /// * `queue` - The queue frames are submitted to
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+
+        let size = 2 * std::mem::size_of::<u64>() as wgpu::BufferAddress;
+
+        Some(Self {
+            query_set: device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("Frame timestamps"),
+                ty: wgpu::QueryType::Timestamp,
+                count: 2,
+            }),
+            resolve_buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Frame timestamps resolve buffer"),
+                size,
+                usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            }),
+            readback_buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Frame timestamps readback buffer"),
+                size,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            }),
+            mapped: Arc::new(AtomicBool::new(false)),
+            pending: false,
+            period: queue.get_timestamp_period(),
+        })
+    }
+
+    /// Write the start timestamp, must be called before recording the frame
+    pub fn begin(&self, encoder: &mut wgpu::CommandEncoder) {
+        if !self.pending {
+            encoder.write_timestamp(&self.query_set, 0);
+        }
+    }
+
+    /// Write the end timestamp and copy both to the readback buffer
+    pub fn end(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if self.pending {
+            return;
+        }
+
+        encoder.write_timestamp(&self.query_set, 1);
+        encoder.resolve_query_set(&self.query_set, 0..2, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            self.resolve_buffer.size(),
+        );
+    }
+
+    /// Start reading the timestamps back, must be called once the frame is submitted
+    pub fn submitted(&mut self) {
+        if self.pending {
+            return;
+        }
+
+        self.pending = true;
+        let mapped = self.mapped.clone();
+        self.readback_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                mapped.store(result.is_ok(), Ordering::Release);
+            });
+    }
+
+    /// Returns the GPU time of the last measured frame, in milliseconds, once available
+    ///
+    /// # Arguments
+    ///
+    /// * `device` - The device recording the frames, polled without blocking
+    pub fn read(&mut self, device: &wgpu::Device) -> Option<f32> {
+        device.poll(wgpu::Maintain::Poll);
+
+        if !self.mapped.swap(false, Ordering::Acquire) {
+            return None;
+        }
+
+        let timestamps: [u64; 2] = {
+            let view = self.readback_buffer.slice(..).get_mapped_range();
+            bytemuck::pod_read_unaligned(&view)
+        };
+        self.readback_buffer.unmap();
+        self.pending = false;
+
+        let ticks = timestamps[1].saturating_sub(timestamps[0]);
+
+        Some(ticks as f32 * self.period / 1_000_000.)
+    }
+}
+
+/// Frame statistics drawn over the UI: FPS, frame time graph, draw calls, vertices and GPU time
+pub struct DebugOverlay {
+    visible: bool,
+    position: Vec2,
+    background: RectangleShape,
+    /// One text per line, texts don't break lines
+    lines: Vec<Text<'static>>,
+    bars: Vec<RectangleShape>,
+    times: FrameTimes,
+    gpu_time: Option<f32>,
+}
+
+impl DebugOverlay {
+    pub fn new(context: Ctx) -> Self {
+        let mut background = RectangleShape::new_with_label(
+            context.clone(),
+            (
+                FRAME_HISTORY as f32 * BAR_WIDTH,
+                GRAPH_HEIGHT + LINES as f32 * LINE_HEIGHT + 8.,
+            )
+                .into(),
+            "DebugOverlay/background",
+        );
+        background.set_fill_color(Color::from((20, 20, 20)));
+
+        let lines = (0..LINES)
+            .map(|i| {
+                let mut line = Text::new_with_label(
+                    context.clone(),
+                    "",
+                    ASSETS.get_font("Roboto.ttf").unwrap(),
+                    LINE_HEIGHT - 4.,
+                    &format!("DebugOverlay/line#{i}"),
+                );
+                line.set_fill_color(WHITE);
+
+                line
+            })
+            .collect();
+
+        let bars = (0..FRAME_HISTORY)
+            .map(|i| {
+                RectangleShape::new_with_label(
+                    context.clone(),
+                    (BAR_WIDTH, 0.).into(),
+                    &format!("DebugOverlay/bar#{i}"),
+                )
+            })
+            .collect();
+
+        let mut overlay = Self {
+            visible: false,
+            position: Vec2::new(5., 5.),
+            background,
+            lines,
+            bars,
+            times: FrameTimes::new(),
+            gpu_time: None,
+        };
+        overlay.set_position(overlay.position);
+
+        overlay
+    }
+
+    /// Show the overlay if hidden, hide it otherwise
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Record the statistics of a frame, the display is only refreshed while visible
+    pub fn update(&mut self, stats: &FrameStats) {
+        self.times.push(stats.frame_time);
+
+        if stats.gpu_time.is_some() {
+            self.gpu_time = stats.gpu_time;
+        }
+
+        if !self.visible {
+            return;
+        }
+
+        let gpu_time = match self.gpu_time {
+            Some(gpu_time) => format!("{gpu_time:.2} ms"),
+            None => "n/a".to_string(),
+        };
+        let contents = [
+            format!(
+                "{:.0} FPS ({:.2} ms)",
+                self.times.fps(),
+                stats.frame_time * 1000.
+            ),
+            format!(
+                "{} draw calls, {} vertices",
+                stats.draw_calls, stats.vertices
+            ),
+            format!("GPU {gpu_time}"),
+        ];
+        self.lines
+            .iter_mut()
+            .zip(contents)
+            .for_each(|(line, content)| line.set_string(&content));
+
+        let bottom = self.position.y + self.background.size().y;
+        let bars = self.bars.iter_mut().rev();
+        for (bar, time) in bars.zip(self.times.iter().rev()) {
+            let height = (time / GRAPH_MAX_FRAME_TIME).min(1.) * GRAPH_HEIGHT;
+            bar.set_size((BAR_WIDTH, height).into());
+            bar.set_position(Vec2::new(bar.position().x, bottom - height));
+            bar.set_fill_color(if *time > 1. / 60. { RED } else { GREEN });
+        }
+    }
+}
+
+impl Transformable for DebugOverlay {
+    fn position(&self) -> &Vec2 {
+        &self.position
+    }
+
+    fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+        self.background.set_position(position);
+        self.lines.iter_mut().enumerate().for_each(|(i, line)| {
+            line.set_position(position + Vec2::new(4., 4. + i as f32 * LINE_HEIGHT));
+        });
+
+        let bottom = position.y + self.background.size().y;
+        self.bars.iter_mut().enumerate().for_each(|(i, bar)| {
+            bar.set_position(Vec2::new(position.x + i as f32 * BAR_WIDTH, bottom));
+        });
+    }
+}
+
+impl Drawable for DebugOverlay {
+    fn draw<'b>(&'b mut self, render_pass: &mut wgpu::RenderPass<'b>) {
+        if !self.visible {
+            return;
+        }
+
+        self.background.draw(render_pass);
+        self.bars.iter_mut().for_each(|bar| bar.draw(render_pass));
+        self.lines
+            .iter_mut()
+            .for_each(|line| line.draw(render_pass));
+    }
+}
+
+/// Returns the path where wgpu API traces should be written, read from `WGPU_TRACE`
+///
+/// Traces are only recorded when wgpu is built with its `trace` feature.
+pub fn trace_path() -> Option<std::path::PathBuf> {
+    std::env::var_os("WGPU_TRACE").map(std::path::PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FrameCapture, FrameTimes, FRAME_HISTORY};
+
+    #[test]
+    fn capture_is_not_running_by_default() {
+        let capture = FrameCapture::new();
+        assert!(!capture.capturing());
+    }
+
+    #[test]
+    fn frame_times_keep_a_bounded_history() {
+        let mut times = FrameTimes::new();
+        (0..FRAME_HISTORY + 10).for_each(|_| times.push(0.01));
+
+        assert_eq!(times.iter().count(), FRAME_HISTORY);
+        assert!((times.fps() - 100.).abs() < 0.01);
+    }
+}
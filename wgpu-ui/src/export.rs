@@ -0,0 +1,269 @@
+//! Rendering of a widget and its children into an image of any resolution, e.g. to export a
+//! chart at print quality whatever the size of the window
+
+use std::path::Path;
+
+use anyhow::{anyhow, bail};
+use image::{imageops, RgbaImage};
+
+use crate::graphics::{color::Color, layer::Layer, msaa};
+use crate::headless::read_texture;
+use crate::math::Rect;
+use crate::ui::Widget;
+use crate::{create_index_buffer, Ctx};
+
+/// Resolution and look of an export
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExportOptions {
+    /// Pixels of the image per pixel of the UI space, e.g. 300 / 96 to print at 300 DPI
+    pub scale: f32,
+    /// Samples rendered per pixel along each axis then averaged, smoothing the edges
+    pub supersampling: u32,
+    /// Room kept around the bounds of the widget, for its shadow, in pixels of the UI space
+    pub margin: f32,
+    /// Color behind the widget, transparent when `None`
+    pub background: Option<Color>,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            scale: 1.,
+            supersampling: 2,
+            margin: 0.,
+            background: None,
+        }
+    }
+}
+
+/// Returns the tiles covering an image, as `(x, y, width, height)` in pixels, rows first
+///
+/// # Arguments
+///
+/// * `width` - Width of the image
+/// * `height` - Height of the image
+/// * `tile` - Largest side of a tile, e.g. the largest texture the device supports
+fn tiles(width: u32, height: u32, tile: u32) -> Vec<(u32, u32, u32, u32)> {
+    let tile = tile.max(1);
+
+    (0..height)
+        .step_by(tile as usize)
+        .flat_map(|y| {
+            (0..width)
+                .step_by(tile as usize)
+                .map(move |x| (x, y, tile.min(width - x), tile.min(height - y)))
+        })
+        .collect()
+}
+
+/// Turn pixels whose colors are multiplied by their alpha, as rendered, into straight ones as
+/// stored in PNG files
+fn unpremultiply(image: &mut RgbaImage) {
+    for pixel in image.pixels_mut() {
+        let alpha = pixel[3] as u32;
+        if alpha == 0 || alpha == 255 {
+            continue;
+        }
+
+        for channel in &mut pixel.0[..3] {
+            *channel = (*channel as u32 * 255 / alpha).min(255) as u8;
+        }
+    }
+}
+
+/// Render `widget` alone into an image, independently of the window
+///
+/// The image is rendered in tiles the device can hold, each supersampled then averaged. Shapes
+/// stay sharp at any scale, bitmap texts are magnified so texts meant to be exported should be
+/// rendered with [`crate::graphics::text::TextRendering::Sdf`].
+///
+/// # Arguments
+///
+/// * `context` - Rendering context
+/// * `widget` - Rendered widget, along with its children
+/// * `options` - Resolution and look of the image
+pub fn render(
+    context: &Ctx,
+    widget: &mut dyn Widget,
+    options: &ExportOptions,
+) -> anyhow::Result<RgbaImage> {
+    let bounds = widget.bounds();
+    let region = Rect {
+        x: bounds.x - options.margin,
+        y: bounds.y - options.margin,
+        width: bounds.width + 2. * options.margin,
+        height: bounds.height + 2. * options.margin,
+    };
+    let width = (region.width * options.scale).round().max(0.) as u32;
+    let height = (region.height * options.scale).round().max(0.) as u32;
+    if width == 0 || height == 0 {
+        bail!("Nothing to export, {} is empty", widget.debug_name());
+    }
+
+    let samples = options.supersampling.max(1);
+    let (index_buffer, max_size) = {
+        let ctx = context.lock().unwrap();
+        let max_size = ctx.device.limits().max_texture_dimension_2d;
+
+        (create_index_buffer(&ctx.device), max_size)
+    };
+    let mut camera = Layer::new(context.clone(), "export");
+    let mut image = RgbaImage::new(width, height);
+
+    for (x, y, tile_width, tile_height) in tiles(width, height, max_size / samples) {
+        camera.frame(Rect {
+            x: region.x + x as f32 / options.scale,
+            y: region.y + y as f32 / options.scale,
+            width: tile_width as f32 / options.scale,
+            height: tile_height as f32 / options.scale,
+        });
+
+        let size = (tile_width * samples, tile_height * samples);
+        let pixels = render_tile(context, widget, &camera, &index_buffer, size, options)?;
+        let mut tile = RgbaImage::from_raw(size.0, size.1, pixels)
+            .ok_or_else(|| anyhow!("Rendered tile doesn't match its size"))?;
+        if samples > 1 {
+            // Averaging premultiplied colors keeps transparent pixels from darkening the edges
+            tile = imageops::resize(
+                &tile,
+                tile_width,
+                tile_height,
+                imageops::FilterType::Triangle,
+            );
+        }
+
+        imageops::replace(&mut image, &tile, x as i64, y as i64);
+    }
+
+    if options.background.is_none() {
+        unpremultiply(&mut image);
+    }
+
+    Ok(image)
+}
+
+/// Render `widget` into a PNG file, see [`render`]
+///
+/// # Arguments
+///
+/// * `context` - Rendering context
+/// * `widget` - Rendered widget, along with its children
+/// * `options` - Resolution and look of the image
+/// * `path` - Written file
+pub fn save_png(
+    context: &Ctx,
+    widget: &mut dyn Widget,
+    options: &ExportOptions,
+    path: &Path,
+) -> anyhow::Result<()> {
+    render(context, widget, options)?.save_with_format(path, image::ImageFormat::Png)?;
+
+    Ok(())
+}
+
+/// Returns the pixels of one tile of the export, as RGBA
+fn render_tile(
+    context: &Ctx,
+    widget: &mut dyn Widget,
+    camera: &Layer,
+    index_buffer: &wgpu::Buffer,
+    (width, height): (u32, u32),
+    options: &ExportOptions,
+) -> anyhow::Result<Vec<u8>> {
+    let ctx = context.lock().unwrap();
+    let format = ctx.config.format;
+
+    let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Export tile"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let msaa = msaa::create_view(
+        &ctx.device,
+        format,
+        (width, height),
+        ctx.sample_count,
+        "Export tile/msaa",
+    );
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Export encoder"),
+        });
+    // Drawables may lock the context while drawing
+    drop(ctx);
+
+    let clear = options
+        .background
+        .map_or(wgpu::Color::TRANSPARENT, wgpu::Color::from);
+    {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Export pass"),
+            color_attachments: &[Some(msaa::attachment(
+                msaa.as_ref(),
+                &view,
+                wgpu::LoadOp::Clear(clear),
+            ))],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        camera.bind(&mut render_pass);
+        widget.draw(&mut render_pass);
+    }
+
+    let ctx = context.lock().unwrap();
+    ctx.queue.submit(std::iter::once(encoder.finish()));
+    let mut pixels = read_texture(&ctx.device, &ctx.queue, &texture)?;
+
+    if matches!(
+        format,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+    ) {
+        pixels
+            .chunks_exact_mut(4)
+            .for_each(|pixel| pixel.swap(0, 2));
+    }
+
+    Ok(pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+
+    use super::{tiles, unpremultiply};
+
+    #[test]
+    fn tiles_cover_the_image_once() {
+        let tiles = tiles(10, 5, 4);
+
+        assert_eq!(tiles.len(), 6);
+        assert_eq!(tiles[0], (0, 0, 4, 4));
+        assert_eq!(tiles[2], (8, 0, 2, 4));
+        assert_eq!(tiles[5], (8, 4, 2, 1));
+
+        let area: u32 = tiles
+            .iter()
+            .map(|(_, _, width, height)| width * height)
+            .sum();
+        assert_eq!(area, 50);
+    }
+
+    #[test]
+    fn half_transparent_pixels_keep_their_color() {
+        let mut image = RgbaImage::from_pixel(1, 1, Rgba([100, 50, 0, 128]));
+        unpremultiply(&mut image);
+
+        assert_eq!(image.get_pixel(0, 0).0, [199, 99, 0, 128]);
+    }
+}
@@ -0,0 +1,207 @@
+//! Frame time budget, optional visual features are degraded while the frames take longer and
+//! restored once there is headroom again, e.g. on low-end integrated GPUs
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Frames the average frame time settles over before the quality changes again
+const SETTLE_FRAMES: u32 = 60;
+/// Weight of the latest frame in the average frame time
+const SMOOTHING: f32 = 0.1;
+/// Fraction of the budget the average may exceed, frames paced by the display jitter around it
+const TOLERANCE: f32 = 1.1;
+/// Fraction of the budget the average must fall under to restore a feature
+const HEADROOM: f32 = 0.7;
+
+/// Optional visual features the governor trades for frame time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quality {
+    /// Factor applied to the blur of the drop shadows
+    pub shadow_blur: f32,
+    /// Draw through the multisampled textures, the shape edges are aliased otherwise
+    pub multisampling: bool,
+    /// Number of frames between two animation updates
+    pub animation_step: u32,
+}
+
+/// Qualities from the full one to the cheapest, each level degrades one more feature
+pub const QUALITY_LEVELS: [Quality; 4] = [
+    Quality {
+        shadow_blur: 1.,
+        multisampling: true,
+        animation_step: 1,
+    },
+    Quality {
+        shadow_blur: 0.5,
+        multisampling: true,
+        animation_step: 1,
+    },
+    Quality {
+        shadow_blur: 0.5,
+        multisampling: false,
+        animation_step: 1,
+    },
+    Quality {
+        shadow_blur: 0.25,
+        multisampling: false,
+        animation_step: 2,
+    },
+];
+
+static QUALITY: Mutex<Quality> = Mutex::new(QUALITY_LEVELS[0]);
+
+/// Returns the quality the frames are drawn with
+pub fn quality() -> Quality {
+    *QUALITY.lock().unwrap()
+}
+
+/// Set the quality of the next frames, the drawables follow it when they are drawn
+///
+/// # Arguments
+///
+/// * `quality` - New quality, usually one of [`QUALITY_LEVELS`]
+pub fn set_quality(quality: Quality) {
+    *QUALITY.lock().unwrap() = quality;
+}
+
+/// Lowers the quality while the average frame time exceeds the budget, raises it back once the
+/// frames fit in the budget with headroom
+#[derive(Debug, Clone)]
+pub struct FrameGovernor {
+    /// Target frame time, in seconds
+    budget: f32,
+    /// Smoothed frame time, in seconds
+    average: Option<f32>,
+    /// Index in [`QUALITY_LEVELS`]
+    level: usize,
+    /// Frames since the quality last changed
+    settled: u32,
+}
+
+impl FrameGovernor {
+    /// Create a governor starting at the full quality
+    ///
+    /// # Arguments
+    ///
+    /// * `budget` - Target frame time, the refresh period of the display to keep up with it
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            budget: budget.as_secs_f32(),
+            average: None,
+            level: 0,
+            settled: 0,
+        }
+    }
+
+    pub fn budget(&self) -> Duration {
+        Duration::from_secs_f32(self.budget)
+    }
+
+    /// Returns the index of the current quality in [`QUALITY_LEVELS`], 0 being the full one
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    pub fn quality(&self) -> Quality {
+        QUALITY_LEVELS[self.level]
+    }
+
+    /// Record the time of a frame, returns the new quality when it changes
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_time` - Time between the frame and the previous one, in seconds
+    pub fn frame(&mut self, frame_time: f32) -> Option<Quality> {
+        let average = match self.average {
+            Some(average) => average + (frame_time - average) * SMOOTHING,
+            None => frame_time,
+        };
+        self.average = Some(average);
+        self.settled = self.settled.saturating_add(1);
+
+        if self.settled < SETTLE_FRAMES {
+            return None;
+        }
+
+        if average > self.budget * TOLERANCE && self.level + 1 < QUALITY_LEVELS.len() {
+            self.level += 1;
+        } else if average < self.budget * HEADROOM && self.level > 0 {
+            self.level -= 1;
+        } else {
+            return None;
+        }
+        self.settled = 0;
+
+        Some(self.quality())
+    }
+}
+
+/// Gathers the frame times of the animations updated every few frames
+#[derive(Debug, Default)]
+pub struct AnimationClock {
+    /// Time elapsed since the last update, in seconds
+    pending: f32,
+    /// Frames since the last update
+    frames: u32,
+}
+
+impl AnimationClock {
+    /// Returns the time to advance the animations by when they are due this frame
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Time elapsed since the last frame, in seconds
+    /// * `step` - Number of frames between two updates, see [`Quality::animation_step`]
+    pub fn tick(&mut self, dt: f32, step: u32) -> Option<f32> {
+        self.pending += dt;
+        self.frames += 1;
+
+        if self.frames < step {
+            return None;
+        }
+        self.frames = 0;
+
+        Some(std::mem::take(&mut self.pending))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{AnimationClock, FrameGovernor, QUALITY_LEVELS, SETTLE_FRAMES};
+
+    #[test]
+    fn degrades_over_budget_and_restores_with_headroom() {
+        let mut governor = FrameGovernor::new(Duration::from_millis(16));
+        let mut run = |frame_time: f32, frames: u32| {
+            (0..frames)
+                .filter_map(|_| governor.frame(frame_time))
+                .count()
+        };
+
+        // Jitter around the budget doesn't change anything
+        assert_eq!(run(0.017, SETTLE_FRAMES * 2), 0);
+        // One level per settling period
+        assert_eq!(run(0.030, SETTLE_FRAMES * 2), 2);
+        assert_eq!(governor.quality(), QUALITY_LEVELS[2]);
+        assert!(!governor.quality().multisampling);
+
+        let mut run = |frame_time: f32, frames: u32| {
+            (0..frames)
+                .filter_map(|_| governor.frame(frame_time))
+                .last()
+        };
+        assert_eq!(run(0.008, SETTLE_FRAMES * 4), Some(QUALITY_LEVELS[0]));
+        assert_eq!(governor.level(), 0);
+    }
+
+    #[test]
+    fn animations_advance_by_the_gathered_time() {
+        let mut clock = AnimationClock::default();
+
+        assert_eq!(clock.tick(0.25, 1), Some(0.25));
+        assert_eq!(clock.tick(0.25, 2), None);
+        assert_eq!(clock.tick(0.5, 2), Some(0.75));
+    }
+}
@@ -0,0 +1,98 @@
+use glam::Vec2;
+
+use super::{
+    color::Color,
+    shape::{RectangleShape, Shape},
+    Drawable,
+};
+use crate::Ctx;
+
+/// A full-screen layer drawn behind the widgets
+#[derive(Debug, Clone, Copy)]
+pub enum BackgroundLayer {
+    /// Uniform color
+    Solid(Color),
+    /// Linear gradient going from `from` to `to`
+    Gradient {
+        from: Color,
+        to: Color,
+        vertical: bool,
+    },
+}
+
+/// Stack of background layers, drawn in insertion order after the clear color
+pub struct Background {
+    context: Ctx,
+    layers: Vec<(BackgroundLayer, RectangleShape)>,
+}
+
+impl Background {
+    pub fn new(context: Ctx) -> Self {
+        Self {
+            context,
+            layers: Vec::new(),
+        }
+    }
+
+    /// Add a layer on top of the existing ones
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - Layer description
+    pub fn push(&mut self, layer: BackgroundLayer) {
+        let ctx = self.context.lock().unwrap();
+        let size: Vec2 = ctx.screen_size().into();
+        drop(ctx);
+
+        let mut shape = RectangleShape::new_with_label(
+            self.context.clone(),
+            size,
+            &format!("Background/layer{}", self.layers.len()),
+        );
+        Self::apply(&layer, &mut shape);
+
+        self.layers.push((layer, shape));
+    }
+
+    /// Remove every layer, only the clear color remains
+    pub fn clear(&mut self) {
+        self.layers.clear();
+    }
+
+    pub fn layers(&self) -> impl Iterator<Item = &BackgroundLayer> {
+        self.layers.iter().map(|(layer, _)| layer)
+    }
+
+    /// Stretch layers over the new surface size
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - New surface size
+    pub fn resize(&mut self, size: Vec2) {
+        self.layers
+            .iter_mut()
+            .for_each(|(_, shape)| shape.set_size(size));
+    }
+
+    fn apply(layer: &BackgroundLayer, shape: &mut RectangleShape) {
+        match *layer {
+            BackgroundLayer::Solid(color) => shape.set_fill_color(color),
+            BackgroundLayer::Gradient { from, to, vertical } => {
+                // Corners order: top-left, bottom-left, bottom-right, top-right
+                if vertical {
+                    shape.set_point_colors([from, to, to, from]);
+                } else {
+                    shape.set_point_colors([from, from, to, to]);
+                }
+            }
+        }
+    }
+}
+
+impl Drawable for Background {
+    fn draw<'a>(&'a mut self, render_pass: &mut wgpu::RenderPass<'a>) {
+        self.layers
+            .iter_mut()
+            .for_each(|(_, shape)| shape.draw(render_pass));
+    }
+}
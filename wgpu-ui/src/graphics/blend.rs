@@ -0,0 +1,63 @@
+//! Blend modes of the drawables, a pipeline set is created for each of them and the drawables
+//! pick the one of the mode set while they are recorded
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+static BLEND_MODE: AtomicU8 = AtomicU8::new(BlendMode::Alpha as u8);
+
+/// How the colors of a drawable are combined with the colors already drawn, the colors are
+/// premultiplied by their alpha
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BlendMode {
+    /// Drawn over, showing through the transparent parts
+    #[default]
+    Alpha,
+    /// Added to the colors beneath, lights and glows
+    Additive,
+    /// Multiplies the colors beneath, shades and tints
+    Multiply,
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 3] = [Self::Alpha, Self::Additive, Self::Multiply];
+
+    pub fn state(self) -> wgpu::BlendState {
+        let alpha = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+            operation: wgpu::BlendOperation::Add,
+        };
+        let color = |src_factor, dst_factor| wgpu::BlendComponent {
+            src_factor,
+            dst_factor,
+            operation: wgpu::BlendOperation::Add,
+        };
+
+        match self {
+            Self::Alpha => wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            Self::Additive => wgpu::BlendState {
+                color: color(wgpu::BlendFactor::One, wgpu::BlendFactor::One),
+                alpha,
+            },
+            // Transparent parts keep the colors beneath
+            Self::Multiply => wgpu::BlendState {
+                color: color(wgpu::BlendFactor::Dst, wgpu::BlendFactor::OneMinusSrcAlpha),
+                alpha,
+            },
+        }
+    }
+}
+
+/// Returns the blend mode of the drawables recorded from now on
+pub fn blend_mode() -> BlendMode {
+    BlendMode::ALL[BLEND_MODE.load(Ordering::Relaxed) as usize]
+}
+
+/// Set the blend mode of the drawables recorded from now on, e.g. for a layer of widgets
+///
+/// # Arguments
+///
+/// * `mode` - New blend mode, [`BlendMode::Alpha`] by default
+pub fn set_blend_mode(mode: BlendMode) {
+    BLEND_MODE.store(mode as u8, Ordering::Relaxed);
+}
@@ -1,7 +1,7 @@
 use derive_more::From;
 
 /// Describe color as RGB format
-#[derive(From, Clone, Copy, Debug)]
+#[derive(From, Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Color(u8, u8, u8);
 
 #[allow(dead_code)]
@@ -21,6 +21,103 @@ impl Into<[f32; 3]> for Color {
     }
 }
 
+impl Color {
+    /// Returns the color with its components multiplied by `alpha`, as expected by the pipelines
+    ///
+    /// # Arguments
+    ///
+    /// * `alpha` - Opacity, from 0 (transparent) to 1 (opaque)
+    pub fn premultiplied(self, alpha: f32) -> [f32; 4] {
+        let [r, g, b]: [f32; 3] = self.into();
+
+        [r * alpha, g * alpha, b * alpha, alpha]
+    }
+
+    /// Returns the color between `self` and `other`
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - Color reached at the end
+    /// * `t` - Progress from `self` to `other`, from 0 to 1
+    pub fn mix(self, other: Color, t: f32) -> Color {
+        let t = t.clamp(0., 1.);
+        let channel =
+            |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * t).round() as u8;
+
+        Color(
+            channel(self.0, other.0),
+            channel(self.1, other.1),
+            channel(self.2, other.2),
+        )
+    }
+}
+
+impl From<Color> for wgpu::Color {
+    fn from(color: Color) -> Self {
+        Self {
+            r: color.0 as f64 / 255.,
+            g: color.1 as f64 / 255.,
+            b: color.2 as f64 / 255.,
+            a: 1.,
+        }
+    }
+}
+
+/// Colors used by the widgets
+#[derive(Clone, Copy, Debug)]
+pub struct Palette {
+    pub background: Color,
+    pub surface: Color,
+    pub text: Color,
+    pub primary: Color,
+    pub secondary: Color,
+    pub success: Color,
+    pub warning: Color,
+    pub error: Color,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            background: Color(20, 20, 20),
+            surface: Color(60, 60, 60),
+            text: WHITE,
+            primary: Color(0, 120, 215),
+            secondary: Color(140, 140, 140),
+            success: Color(0, 170, 0),
+            warning: Color(255, 200, 0),
+            error: RED,
+        }
+    }
+}
+
+impl Palette {
+    /// Palette for red-green deficiencies (deuteranopia and protanopia), status colors use the
+    /// blue/yellow/vermillion entries of the Okabe-Ito palette
+    pub fn red_green_safe() -> Self {
+        Self {
+            primary: Color(86, 180, 233),
+            secondary: Color(230, 159, 0),
+            success: Color(0, 114, 178),
+            warning: Color(240, 228, 66),
+            error: Color(213, 94, 0),
+            ..Self::default()
+        }
+    }
+
+    /// Palette for blue-yellow deficiency (tritanopia)
+    pub fn blue_yellow_safe() -> Self {
+        Self {
+            primary: Color(0, 114, 178),
+            secondary: Color(230, 159, 0),
+            success: Color(0, 158, 115),
+            warning: Color(204, 121, 167),
+            error: Color(213, 94, 0),
+            ..Self::default()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Color;
@@ -32,4 +129,23 @@ mod tests {
         let color: [f32; 3] = Color(155, 155, 155).into();
         assert_eq!(color, [(155 / 255) as f32; 3])
     }
+
+    #[test]
+    fn premultiply_alpha() {
+        assert_eq!(Color(255, 0, 255).premultiplied(0.5), [0.5, 0., 0.5, 0.5]);
+    }
+
+    #[test]
+    fn into_clear_color() {
+        let color: wgpu::Color = Color(255, 0, 51).into();
+        assert_eq!(
+            color,
+            wgpu::Color {
+                r: 1.,
+                g: 0.,
+                b: 0.2,
+                a: 1.
+            }
+        );
+    }
 }
@@ -0,0 +1,357 @@
+use wgpu::util::DeviceExt;
+
+use super::color::{Color, Palette};
+use crate::Ctx;
+
+/// Color vision deficiency
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Deficiency {
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+impl Deficiency {
+    /// Returns the matrix simulating the deficiency in linear RGB, rows first
+    ///
+    /// Values from Machado, Oliveira and Fernandes (2009), at full severity.
+    pub fn matrix(&self) -> [[f32; 3]; 3] {
+        match self {
+            Self::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Self::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Self::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+
+    /// Returns the palette designed for the deficiency
+    pub fn palette(&self) -> Palette {
+        match self {
+            Self::Protanopia | Self::Deuteranopia => Palette::red_green_safe(),
+            Self::Tritanopia => Palette::blue_yellow_safe(),
+        }
+    }
+
+    /// Returns `color` as perceived with the deficiency, in linear RGB
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - sRGB color
+    pub fn simulate(&self, color: Color) -> [f32; 3] {
+        let color: wgpu::Color = color.into();
+        let linear = [color.r, color.g, color.b].map(|c| {
+            let c = c as f32;
+
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        });
+
+        self.matrix()
+            .map(|row| (row[0] * linear[0] + row[1] * linear[1] + row[2] * linear[2]).clamp(0., 1.))
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct FilterUniform {
+    /// Rows of the simulation matrix, padded to 16 bytes
+    rows: [[f32; 4]; 3],
+}
+
+impl From<Deficiency> for FilterUniform {
+    fn from(deficiency: Deficiency) -> Self {
+        Self {
+            rows: deficiency.matrix().map(|row| [row[0], row[1], row[2], 0.]),
+        }
+    }
+}
+
+/// Debug post-process simulating a color vision deficiency over the whole frame
+///
+/// While active, passes draw into an intermediate texture which is then drawn to the surface
+/// through the simulation matrix.
+pub struct ColorBlindFilter {
+    context: Ctx,
+    deficiency: Option<Deficiency>,
+    view: wgpu::TextureView,
+    sampler: wgpu::Sampler,
+    uniform: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl ColorBlindFilter {
+    pub fn new(context: Ctx) -> Self {
+        let ctx = context.lock().unwrap();
+        let device = &ctx.device;
+
+        let shader =
+            device.create_shader_module(wgpu::include_wgsl!("../shaders/color_blind.wgsl"));
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("ColorBlindFilter bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("ColorBlindFilter pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("ColorBlindFilter pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: ctx.config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("ColorBlindFilter sampler"),
+            ..Default::default()
+        });
+        let uniform = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("ColorBlindFilter uniform"),
+            contents: bytemuck::cast_slice(&[FilterUniform::from(Deficiency::Deuteranopia)]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let view = Self::create_target(&ctx);
+        let bind_group =
+            Self::create_bind_group(device, &bind_group_layout, &view, &sampler, &uniform);
+        drop(ctx);
+
+        Self {
+            context,
+            deficiency: None,
+            view,
+            sampler,
+            uniform,
+            bind_group_layout,
+            bind_group,
+            pipeline,
+        }
+    }
+
+    fn create_target(ctx: &crate::Context) -> wgpu::TextureView {
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("ColorBlindFilter target"),
+            size: wgpu::Extent3d {
+                width: ctx.config.width,
+                height: ctx.config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: ctx.config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        view: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+        uniform: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("ColorBlindFilter bind group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniform.as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    /// Returns the simulated deficiency, `None` when the filter is disabled
+    pub fn deficiency(&self) -> Option<Deficiency> {
+        self.deficiency
+    }
+
+    /// Simulate `deficiency`, `None` disables the filter
+    ///
+    /// # Arguments
+    ///
+    /// * `deficiency` - Deficiency to simulate
+    pub fn set_deficiency(&mut self, deficiency: Option<Deficiency>) {
+        self.deficiency = deficiency;
+
+        if let Some(deficiency) = deficiency {
+            self.context.lock().unwrap().queue.write_buffer(
+                &self.uniform,
+                0,
+                bytemuck::cast_slice(&[FilterUniform::from(deficiency)]),
+            );
+        }
+    }
+
+    /// Switch to the next deficiency, going back to no filter after the last one
+    pub fn cycle(&mut self) {
+        self.set_deficiency(match self.deficiency {
+            None => Some(Deficiency::Protanopia),
+            Some(Deficiency::Protanopia) => Some(Deficiency::Deuteranopia),
+            Some(Deficiency::Deuteranopia) => Some(Deficiency::Tritanopia),
+            Some(Deficiency::Tritanopia) => None,
+        });
+    }
+
+    /// Returns the view passes must draw into while the filter is active
+    pub fn target(&self) -> Option<&wgpu::TextureView> {
+        self.deficiency.map(|_| &self.view)
+    }
+
+    /// Recreate the intermediate texture, must be called when the surface is resized
+    pub fn resize(&mut self) {
+        let mut ctx = self.context.lock().unwrap();
+        let view = Self::create_target(&ctx);
+        let bind_group = Self::create_bind_group(
+            &ctx.device,
+            &self.bind_group_layout,
+            &view,
+            &self.sampler,
+            &self.uniform,
+        );
+
+        // The previous frame may still sample the old texture
+        ctx.retire(std::mem::replace(&mut self.bind_group, bind_group));
+        ctx.retire(std::mem::replace(&mut self.view, view));
+    }
+
+    /// Draw the intermediate texture to `output` through the simulation, does nothing while
+    /// the filter is disabled
+    ///
+    /// # Arguments
+    ///
+    /// * `encoder` - Encoder recording the frame
+    /// * `output` - Surface view
+    pub fn apply(&self, encoder: &mut wgpu::CommandEncoder, output: &wgpu::TextureView) {
+        if self.deficiency.is_none() {
+            return;
+        }
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Color blindness simulation pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Deficiency;
+
+    #[test]
+    fn palettes_keep_status_colors_apart() {
+        for deficiency in [
+            Deficiency::Protanopia,
+            Deficiency::Deuteranopia,
+            Deficiency::Tritanopia,
+        ] {
+            let palette = deficiency.palette();
+            let colors = [palette.success, palette.warning, palette.error]
+                .map(|color| deficiency.simulate(color));
+
+            for (i, a) in colors.iter().enumerate() {
+                for b in &colors[i + 1..] {
+                    let distance = a
+                        .iter()
+                        .zip(b)
+                        .map(|(a, b)| (a - b).powi(2))
+                        .sum::<f32>()
+                        .sqrt();
+
+                    assert!(distance > 0.15, "{deficiency:?}: {a:?} too close to {b:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn simulation_keeps_white() {
+        let white = Deficiency::Tritanopia.simulate(crate::graphics::color::WHITE);
+        assert!(white.iter().all(|c| (c - 1.).abs() < 0.01));
+    }
+}
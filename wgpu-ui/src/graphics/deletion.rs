@@ -0,0 +1,183 @@
+//! Deferred destruction of the resources still used by frames the GPU hasn't finished
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+/// Tracks which submitted frames the GPU has completed
+#[derive(Debug, Default)]
+pub struct FrameFence {
+    /// Number of frames submitted so far
+    submitted: u64,
+    /// Index of the last completed frame, written from the queue callback
+    completed: Arc<AtomicU64>,
+}
+
+impl FrameFence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the index of the frame being recorded
+    pub fn current(&self) -> u64 {
+        self.submitted + 1
+    }
+
+    /// Returns the index of the last frame the GPU completed
+    pub fn completed(&self) -> u64 {
+        self.completed.load(Ordering::Acquire)
+    }
+
+    /// Returns the number of submitted frames the GPU hasn't completed yet
+    pub fn in_flight(&self) -> u64 {
+        self.submitted.saturating_sub(self.completed())
+    }
+
+    /// Close the frame being recorded, must be called right after its submission
+    ///
+    /// # Arguments
+    ///
+    /// * `queue` - Queue the frame was submitted to
+    pub fn submitted(&mut self, queue: &wgpu::Queue) {
+        self.submitted += 1;
+
+        let (frame, completed) = (self.submitted, self.completed.clone());
+        queue.on_submitted_work_done(move || {
+            completed.fetch_max(frame, Ordering::AcqRel);
+        });
+    }
+}
+
+/// A GPU resource waiting for its destruction
+#[derive(Debug)]
+pub enum GpuResource {
+    Buffer(wgpu::Buffer),
+    Texture(wgpu::Texture),
+    TextureView(wgpu::TextureView),
+    BindGroup(wgpu::BindGroup),
+}
+
+impl GpuResource {
+    /// Free the GPU memory now, rather than whenever the last handle is dropped
+    fn destroy(self) {
+        match self {
+            Self::Buffer(buffer) => buffer.destroy(),
+            Self::Texture(texture) => texture.destroy(),
+            Self::TextureView(_) | Self::BindGroup(_) => {}
+        }
+    }
+}
+
+impl From<wgpu::Buffer> for GpuResource {
+    fn from(buffer: wgpu::Buffer) -> Self {
+        Self::Buffer(buffer)
+    }
+}
+
+impl From<wgpu::Texture> for GpuResource {
+    fn from(texture: wgpu::Texture) -> Self {
+        Self::Texture(texture)
+    }
+}
+
+impl From<wgpu::TextureView> for GpuResource {
+    fn from(view: wgpu::TextureView) -> Self {
+        Self::TextureView(view)
+    }
+}
+
+impl From<wgpu::BindGroup> for GpuResource {
+    fn from(bind_group: wgpu::BindGroup) -> Self {
+        Self::BindGroup(bind_group)
+    }
+}
+
+/// Resources kept alive until the frames which may reference them are completed
+#[derive(Debug)]
+pub struct DeletionQueue<T> {
+    /// Resources and the frame they were retired during, oldest first
+    pending: Vec<(u64, T)>,
+}
+
+impl<T> Default for DeletionQueue<T> {
+    fn default() -> Self {
+        Self {
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<T> DeletionQueue<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep `resource` alive until `frame` is completed
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - Frame being recorded, see [`FrameFence::current`]
+    /// * `resource` - Resource no longer used by the following frames
+    pub fn retire(&mut self, frame: u64, resource: T) {
+        self.pending.push((frame, resource));
+    }
+
+    /// Returns the resources whose frame is completed, removing them from the queue
+    ///
+    /// # Arguments
+    ///
+    /// * `completed` - Index of the last completed frame, see [`FrameFence::completed`]
+    pub fn collect(&mut self, completed: u64) -> Vec<T> {
+        let count = self
+            .pending
+            .iter()
+            .take_while(|(frame, _)| *frame <= completed)
+            .count();
+
+        self.pending
+            .drain(..count)
+            .map(|(_, resource)| resource)
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+impl DeletionQueue<GpuResource> {
+    /// Destroy the resources whose frame is completed
+    ///
+    /// # Arguments
+    ///
+    /// * `completed` - Index of the last completed frame
+    pub fn destroy(&mut self, completed: u64) {
+        self.collect(completed)
+            .into_iter()
+            .for_each(GpuResource::destroy);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DeletionQueue;
+
+    #[test]
+    fn resources_outlive_their_frame() {
+        let mut queue = DeletionQueue::new();
+        queue.retire(1, "a");
+        queue.retire(2, "b");
+        queue.retire(2, "c");
+
+        assert!(queue.collect(0).is_empty());
+        assert_eq!(queue.collect(1), vec!["a"]);
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.collect(5), vec!["b", "c"]);
+        assert!(queue.is_empty());
+    }
+}
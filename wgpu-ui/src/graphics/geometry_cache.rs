@@ -0,0 +1,172 @@
+//! Tessellated geometry shared by the shapes, so shapes created again every frame with the same
+//! parameters, e.g. by immediate mode code, aren't tessellated again
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use glam::Vec2;
+use once_cell::sync::Lazy;
+
+use super::polyline::{LineCap, LineJoin};
+
+/// Geometries kept before the least recently used ones are dropped
+const CAPACITY: usize = 256;
+
+static GEOMETRY: Lazy<Mutex<GeometryCache>> =
+    Lazy::new(|| Mutex::new(GeometryCache::new(CAPACITY)));
+
+/// Parameters a geometry is tessellated from, floats are compared by their bits
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GeometryKey {
+    Polyline {
+        points: Vec<[u32; 2]>,
+        thickness: u32,
+        join: LineJoin,
+        cap: LineCap,
+    },
+}
+
+impl GeometryKey {
+    pub fn polyline(points: &[Vec2], thickness: f32, join: LineJoin, cap: LineCap) -> Self {
+        Self::Polyline {
+            points: points
+                .iter()
+                .map(|point| [point.x.to_bits(), point.y.to_bits()])
+                .collect(),
+            thickness: thickness.to_bits(),
+            join,
+            cap,
+        }
+    }
+}
+
+/// Triangle lists by the parameters they were tessellated from
+#[derive(Debug)]
+pub struct GeometryCache {
+    /// Triangles and the use they were last requested at
+    geometries: HashMap<GeometryKey, (Arc<[Vec2]>, u64)>,
+    capacity: usize,
+    /// Number of requests so far
+    uses: u64,
+    misses: u64,
+}
+
+impl GeometryCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            geometries: HashMap::new(),
+            capacity,
+            uses: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns the triangles of `key`, tessellated on the first request
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Parameters of the geometry
+    /// * `tessellate` - Returns the triangle list of the geometry
+    pub fn get_or_tessellate(
+        &mut self,
+        key: GeometryKey,
+        tessellate: impl FnOnce() -> Vec<Vec2>,
+    ) -> Arc<[Vec2]> {
+        self.uses += 1;
+
+        if let Some((triangles, used)) = self.geometries.get_mut(&key) {
+            *used = self.uses;
+            return triangles.clone();
+        }
+        self.misses += 1;
+
+        if self.geometries.len() >= self.capacity {
+            let oldest = self
+                .geometries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.geometries.remove(&oldest);
+            }
+        }
+
+        let triangles: Arc<[Vec2]> = tessellate().into();
+        self.geometries.insert(key, (triangles.clone(), self.uses));
+
+        triangles
+    }
+
+    /// Returns the number of requests and how many of them were tessellated
+    pub fn stats(&self) -> (u64, u64) {
+        (self.uses, self.misses)
+    }
+
+    pub fn clear(&mut self) {
+        self.geometries.clear();
+    }
+}
+
+/// Returns the triangles of `key` from the cache shared by every shape, see
+/// [`GeometryCache::get_or_tessellate`]
+///
+/// # Arguments
+///
+/// * `key` - Parameters of the geometry
+/// * `tessellate` - Returns the triangle list of the geometry
+pub fn tessellated(key: GeometryKey, tessellate: impl FnOnce() -> Vec<Vec2>) -> Arc<[Vec2]> {
+    GEOMETRY.lock().unwrap().get_or_tessellate(key, tessellate)
+}
+
+/// Returns the number of geometries requested by the shapes and how many were tessellated
+pub fn stats() -> (u64, u64) {
+    GEOMETRY.lock().unwrap().stats()
+}
+
+/// Drop every cached geometry, e.g. after a scene change
+pub fn clear() {
+    GEOMETRY.lock().unwrap().clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::{GeometryCache, GeometryKey};
+    use crate::graphics::polyline::{LineCap, LineJoin};
+
+    #[test]
+    fn identical_shapes_are_tessellated_once() {
+        let mut cache = GeometryCache::new(2);
+        let key = |thickness: f32| {
+            GeometryKey::polyline(
+                &[Vec2::ZERO, Vec2::new(10., 0.)],
+                thickness,
+                LineJoin::Miter,
+                LineCap::Butt,
+            )
+        };
+        let mut tessellations = 0;
+        let mut request = |cache: &mut GeometryCache, thickness: f32| {
+            cache.get_or_tessellate(key(thickness), || {
+                tessellations += 1;
+                vec![Vec2::splat(thickness)]
+            })
+        };
+
+        for _ in 0..3 {
+            assert_eq!(&*request(&mut cache, 1.), &[Vec2::ONE]);
+        }
+        request(&mut cache, 2.);
+        request(&mut cache, 1.);
+        // Evicts the thickness 2, used before the thickness 1
+        request(&mut cache, 3.);
+        request(&mut cache, 1.);
+        request(&mut cache, 2.);
+
+        assert_eq!(tessellations, 4);
+        assert_eq!(cache.stats(), (8, 4));
+    }
+}
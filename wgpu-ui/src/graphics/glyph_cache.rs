@@ -0,0 +1,212 @@
+//! Rasterized glyphs shared by every text, filled on first use or ahead of time with [`Prewarm`]
+
+use std::{
+    collections::HashMap,
+    ops::RangeInclusive,
+    sync::{Arc, Mutex},
+};
+
+use once_cell::sync::Lazy;
+use rusttype::{point, Font, GlyphId, Scale, ScaledGlyph};
+
+/// Printable ASCII characters
+pub const BASIC_LATIN: RangeInclusive<char> = ' '..='~';
+/// Accented letters and symbols of western European languages
+pub const LATIN_1_SUPPLEMENT: RangeInclusive<char> = '\u{a0}'..='\u{ff}';
+
+static GLYPHS: Lazy<Mutex<HashMap<GlyphKey, Option<Arc<GlyphBitmap>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Coverage of a glyph rasterized at its origin
+#[derive(Debug)]
+pub struct GlyphBitmap {
+    pub width: u32,
+    pub height: u32,
+    /// Position of the top-left pixel relative to the glyph origin
+    pub offset: (i32, i32),
+    /// Coverage of each pixel, rows first
+    pub coverage: Vec<u8>,
+}
+
+/// Identifies a glyph at a size
+///
+/// Fonts are identified by their address, they must outlive the texts using them as assets do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GlyphKey {
+    font: usize,
+    glyph: GlyphId,
+    /// Bits of the pixel height
+    size: u32,
+}
+
+impl GlyphKey {
+    pub fn new(font: &Font, glyph: &ScaledGlyph) -> Self {
+        Self {
+            font: font as *const Font as usize,
+            glyph: glyph.id(),
+            size: glyph.scale().y.to_bits(),
+        }
+    }
+}
+
+fn rasterize(glyph: &ScaledGlyph) -> Option<GlyphBitmap> {
+    let glyph = glyph.clone().positioned(point(0., 0.));
+    let bb = glyph.pixel_bounding_box()?;
+    let (width, height) = (bb.width() as u32, bb.height() as u32);
+
+    let mut coverage = vec![0; (width * height) as usize];
+    glyph.draw(|x, y, v| coverage[(y * width + x) as usize] = (v * 255.).round() as u8);
+
+    Some(GlyphBitmap {
+        width,
+        height,
+        offset: (bb.min.x, bb.min.y),
+        coverage,
+    })
+}
+
+/// Returns the bitmap of `glyph`, rasterized on the first request, `None` for blank glyphs
+///
+/// # Arguments
+///
+/// * `font` - Font the glyph is taken from
+/// * `glyph` - Glyph at its size
+pub fn bitmap(font: &Font, glyph: &ScaledGlyph) -> Option<Arc<GlyphBitmap>> {
+    GLYPHS
+        .lock()
+        .unwrap()
+        .entry(GlyphKey::new(font, glyph))
+        .or_insert_with(|| rasterize(glyph).map(Arc::new))
+        .clone()
+}
+
+/// Rasterize glyphs ahead of time, e.g. during a loading screen, so they don't stall the first
+/// frame displaying them
+pub trait Prewarm {
+    /// Returns the job rasterizing the glyphs of `charsets` at each of `sizes`
+    ///
+    /// Characters missing from the font are skipped, their fallback fonts must be prewarmed too.
+    ///
+    /// # Arguments
+    ///
+    /// * `sizes` - Pixel heights the glyphs are displayed at
+    /// * `charsets` - Characters displayed, e.g. [`BASIC_LATIN`]
+    fn prewarm(&self, sizes: &[f32], charsets: &[RangeInclusive<char>]) -> Prewarming<'_, '_>;
+}
+
+impl<'f> Prewarm for Font<'f> {
+    fn prewarm(&self, sizes: &[f32], charsets: &[RangeInclusive<char>]) -> Prewarming<'_, '_> {
+        let mut ids = charsets
+            .iter()
+            .flat_map(|charset| charset.clone())
+            .map(|c| self.glyph(c).id())
+            // Glyph 0 is the "missing glyph"
+            .filter(|id| id.0 != 0)
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids.dedup();
+
+        Prewarming {
+            font: self,
+            glyphs: sizes
+                .iter()
+                .flat_map(|size| ids.iter().map(move |id| (*id, *size)))
+                .collect(),
+            done: 0,
+        }
+    }
+}
+
+/// Glyphs being rasterized by [`Prewarm::prewarm`], a few per frame to report the progress
+pub struct Prewarming<'a, 'f> {
+    font: &'a Font<'f>,
+    glyphs: Vec<(GlyphId, f32)>,
+    done: usize,
+}
+
+impl<'a, 'f> Prewarming<'a, 'f> {
+    /// Returns the number of glyphs to rasterize
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done == self.glyphs.len()
+    }
+
+    /// Returns the rasterized fraction of the glyphs, from 0 to 1
+    pub fn progress(&self) -> f32 {
+        if self.glyphs.is_empty() {
+            return 1.;
+        }
+
+        self.done as f32 / self.glyphs.len() as f32
+    }
+
+    /// Rasterize the next glyphs and returns the progress, e.g. the value of a progress bar
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - Maximum number of glyphs rasterized by this call
+    pub fn step(&mut self, count: usize) -> f32 {
+        let end = self.done.saturating_add(count).min(self.glyphs.len());
+
+        for (id, size) in &self.glyphs[self.done..end] {
+            bitmap(
+                self.font,
+                &self.font.glyph(*id).scaled(Scale::uniform(*size)),
+            );
+        }
+        self.done = end;
+
+        self.progress()
+    }
+
+    /// Rasterize the remaining glyphs at once
+    pub fn finish(mut self) {
+        self.step(usize::MAX);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rusttype::{Font, Scale};
+
+    use super::{bitmap, Prewarm, BASIC_LATIN};
+
+    fn roboto() -> Font<'static> {
+        Font::try_from_vec(std::fs::read("assets/Roboto.ttf").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn bitmaps_are_rasterized_once() {
+        let font = roboto();
+        let glyph = font.glyph('A').scaled(Scale::uniform(20.));
+
+        let first = bitmap(&font, &glyph).unwrap();
+        assert!(first.width > 0 && first.height > 0);
+        assert!(Arc::ptr_eq(&first, &bitmap(&font, &glyph).unwrap()));
+        assert!(bitmap(&font, &font.glyph(' ').scaled(Scale::uniform(20.))).is_none());
+    }
+
+    #[test]
+    fn prewarming_reports_its_progress() {
+        let font = roboto();
+        let glyphs = font.prewarm(&[12.], &[BASIC_LATIN]).len();
+        let mut prewarming = font.prewarm(&[12., 24.], &[BASIC_LATIN, 'a'..='z']);
+
+        // Lowercase letters are already part of the printable ASCII characters
+        assert_eq!(prewarming.len(), 2 * glyphs);
+        assert_eq!(prewarming.progress(), 0.);
+        assert_eq!(prewarming.step(glyphs), 0.5);
+        assert!(!prewarming.is_done());
+        assert_eq!(prewarming.step(1000), 1.);
+        assert!(prewarming.is_done());
+    }
+}
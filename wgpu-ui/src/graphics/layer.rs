@@ -0,0 +1,272 @@
+use glam::{Mat4, Vec2, Vec3};
+use wgpu::util::DeviceExt;
+
+use super::Drawable;
+use crate::{math::Rect, Ctx, CAMERA_BIND_GROUP_LAYOUT};
+
+/// View over the UI space, in pixels
+#[derive(Debug, Clone, Copy)]
+pub struct Camera {
+    /// Offset of the view, in pixels
+    pub position: Vec2,
+    /// Magnification around the center of the screen
+    pub zoom: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            position: Vec2::ZERO,
+            zoom: 1.,
+        }
+    }
+}
+
+impl Camera {
+    /// Returns the matrix applied to clip space positions
+    ///
+    /// # Arguments
+    ///
+    /// * `screen_size` - Size of the UI space, in pixels
+    pub fn matrix(&self, screen_size: (f32, f32)) -> Mat4 {
+        let translation = Vec3::new(
+            -2. * self.position.x / screen_size.0,
+            2. * self.position.y / screen_size.1,
+            0.,
+        );
+
+        Mat4::from_scale(Vec3::new(self.zoom, self.zoom, 1.)) * Mat4::from_translation(translation)
+    }
+
+    /// Returns the part of the UI space shown on screen, in pixels
+    ///
+    /// # Arguments
+    ///
+    /// * `screen_size` - Size of the UI space, in pixels
+    pub fn visible_rect(&self, screen_size: (f32, f32)) -> Rect {
+        let (width, height) = (screen_size.0 / self.zoom, screen_size.1 / self.zoom);
+        let center = self.position + Vec2::new(screen_size.0, screen_size.1) / 2.;
+
+        Rect {
+            x: center.x - width / 2.,
+            y: center.y - height / 2.,
+            width,
+            height,
+        }
+    }
+}
+
+/// Returns the matrix applied to clip space positions to show only `region`, stretched over the
+/// whole target
+///
+/// # Arguments
+///
+/// * `region` - Shown part of the UI space, in pixels
+/// * `screen_size` - Size of the UI space, in pixels
+pub fn region_matrix(region: Rect, screen_size: (f32, f32)) -> Mat4 {
+    let scale = Vec3::new(
+        screen_size.0 / region.width,
+        screen_size.1 / region.height,
+        1.,
+    );
+    // Center of the region in clip space, the y axis pointing up
+    let center = Vec3::new(
+        2. * (region.x + region.width / 2.) / screen_size.0 - 1.,
+        1. - 2. * (region.y + region.height / 2.) / screen_size.1,
+        0.,
+    );
+
+    Mat4::from_scale(scale) * Mat4::from_translation(-center)
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct CameraUniform {
+    view_proj: [[f32; 4]; 4],
+}
+
+/// A set of drawables rendered in their own pass, through their own camera
+pub struct Layer<'a> {
+    context: Ctx,
+    name: String,
+    camera: Camera,
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    /// Clear the target before drawing, otherwise draw over the previous layers
+    clear: Option<wgpu::Color>,
+    drawables: Vec<Box<dyn Drawable + 'a>>,
+}
+
+impl<'a> Layer<'a> {
+    pub fn new(context: Ctx, name: &str) -> Self {
+        let ctx = context.lock().unwrap();
+        let camera = Camera::default();
+        let uniform = CameraUniform {
+            view_proj: camera.matrix(ctx.screen_size()).to_cols_array_2d(),
+        };
+
+        let buffer = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("Layer#{name}/camera")),
+                contents: bytemuck::cast_slice(&[uniform]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+        let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: CAMERA_BIND_GROUP_LAYOUT.get().unwrap(),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+            label: Some(&format!("Layer#{name}/camera-bind-group")),
+        });
+        drop(ctx);
+
+        Self {
+            context,
+            name: name.to_string(),
+            camera,
+            buffer,
+            bind_group,
+            clear: None,
+            drawables: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    /// Move or zoom the view of the layer
+    ///
+    /// # Arguments
+    ///
+    /// * `camera` - New camera
+    pub fn set_camera(&mut self, camera: Camera) {
+        self.camera = camera;
+
+        self.update();
+    }
+
+    /// Clear the target with `color` before drawing the layer, `None` keeps what previous layers drew
+    pub fn set_clear(&mut self, color: Option<wgpu::Color>) {
+        self.clear = color;
+    }
+
+    pub fn clear(&self) -> Option<wgpu::Color> {
+        self.clear
+    }
+
+    pub fn add(&mut self, drawable: Box<dyn Drawable + 'a>) {
+        self.drawables.push(drawable);
+    }
+
+    /// Upload the camera, must be called when the screen size changes
+    pub fn update(&mut self) {
+        let ctx = self.context.lock().unwrap();
+        let uniform = CameraUniform {
+            view_proj: self.camera.matrix(ctx.screen_size()).to_cols_array_2d(),
+        };
+
+        ctx.queue
+            .write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    /// Show only `region` over the whole target, until the camera is set or uploaded again, e.g.
+    /// to render a widget alone
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - Shown part of the UI space, in pixels
+    pub fn frame(&mut self, region: Rect) {
+        let ctx = self.context.lock().unwrap();
+        let uniform = CameraUniform {
+            view_proj: region_matrix(region, ctx.screen_size()).to_cols_array_2d(),
+        };
+
+        ctx.queue
+            .write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    /// Record the work of the drawables before the passes, e.g. culling them against the camera
+    ///
+    /// # Arguments
+    ///
+    /// * `encoder` - Encoder recording the frame
+    pub fn prepare(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let screen_size = self.context.lock().unwrap().screen_size();
+        let view = self.camera.visible_rect(screen_size);
+
+        self.drawables
+            .iter_mut()
+            .for_each(|drawable| drawable.prepare(encoder, view));
+    }
+
+    /// Bind the camera of the layer, following draws of the pass go through it
+    pub fn bind<'b>(&'b self, render_pass: &mut wgpu::RenderPass<'b>) {
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+    }
+}
+
+impl<'a> Drawable for Layer<'a> {
+    fn draw<'b>(&'b mut self, render_pass: &mut wgpu::RenderPass<'b>) {
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+
+        self.drawables
+            .iter_mut()
+            .for_each(|drawable| drawable.draw(render_pass));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{region_matrix, Camera};
+    use crate::math::Rect;
+    use glam::{Vec2, Vec4};
+
+    #[test]
+    fn default_camera_is_identity() {
+        let matrix = Camera::default().matrix((800., 600.));
+        let point = Vec4::new(0.5, -0.25, 1., 1.);
+
+        assert_eq!(matrix * point, point);
+    }
+
+    #[test]
+    fn framed_region_covers_the_target() {
+        let region = Rect {
+            x: 200.,
+            y: 150.,
+            width: 100.,
+            height: 50.,
+        };
+        let matrix = region_matrix(region, (800., 600.));
+
+        // Clip space corners of the region, top-left then bottom-right
+        let top_left = matrix * Vec4::new(-0.5, 0.5, 0., 1.);
+        let bottom_right = matrix * Vec4::new(-0.25, 1. / 3., 0., 1.);
+        assert!(top_left.truncate().distance(glam::Vec3::new(-1., 1., 0.)) < 1e-5);
+        assert!(
+            bottom_right
+                .truncate()
+                .distance(glam::Vec3::new(1., -1., 0.))
+                < 1e-5
+        );
+    }
+
+    #[test]
+    fn camera_pans_in_pixels() {
+        let camera = Camera {
+            position: Vec2::new(400., 300.),
+            zoom: 1.,
+        };
+        // The center of the screen moves to the top-left corner
+        let center = camera.matrix((800., 600.)) * Vec4::new(0., 0., 1., 1.);
+
+        assert_eq!(center, Vec4::new(-1., 1., 1., 1.));
+    }
+}
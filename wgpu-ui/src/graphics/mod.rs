@@ -1,8 +1,27 @@
-use glam::Vec2;
+use glam::{Mat3, Vec2};
 
+use crate::math::Rect;
+
+pub mod background;
+pub mod blend;
 pub mod color;
+pub mod color_blind;
+pub mod deletion;
+pub mod geometry_cache;
+pub mod glyph_cache;
+pub mod layer;
+pub mod msaa;
+pub mod polyline;
+pub mod render_texture;
+pub mod sdf;
+pub mod shadow;
 pub mod shape;
+pub mod sprite_batch;
 pub mod text;
+pub mod text_style;
+pub mod transform;
+pub mod transition;
+pub mod vertex_array;
 
 pub trait Drawable {
     /// Draw the object to the screen
@@ -11,6 +30,14 @@ pub trait Drawable {
     ///
     /// * `wgpu::RenderPass` - The render pass which process the object
     fn draw<'a>(&'a mut self, render_pass: &mut wgpu::RenderPass<'a>);
+
+    /// Record the work the object needs before the passes of the frame, nothing by default
+    ///
+    /// # Arguments
+    ///
+    /// * `encoder` - Encoder recording the frame
+    /// * `view` - Part of the UI space visible through the camera of the layer, in pixels
+    fn prepare(&mut self, _encoder: &mut wgpu::CommandEncoder, _view: Rect) {}
 }
 
 pub trait Transformable {
@@ -23,13 +50,82 @@ pub trait Transformable {
 
     /// Get the position of the object
     fn position(&self) -> &Vec2;
+
+    /// Returns the rotation, scale and origin of the object, none by default
+    fn transform(&self) -> transform::Transform {
+        transform::Transform::default()
+    }
+
+    /// Rotate and scale the object around an origin, ignored by objects which can't be
+    /// transformed
+    ///
+    /// # Arguments
+    ///
+    /// * `transform` - New rotation, scale and origin
+    fn set_transform(&mut self, _transform: transform::Transform) {}
+
+    /// Returns the matrix moving the points of the object where they are displayed
+    fn matrix(&self) -> Mat3 {
+        self.transform().matrix(*self.position())
+    }
+
+    /// Returns the clockwise rotation, in radians
+    fn rotation(&self) -> f32 {
+        self.transform().rotation
+    }
+
+    /// Set the clockwise rotation around the origin
+    ///
+    /// # Arguments
+    ///
+    /// * `rotation` - Angle in radians
+    fn set_rotation(&mut self, rotation: f32) {
+        let transform = self.transform();
+        self.set_transform(transform::Transform {
+            rotation,
+            ..transform
+        });
+    }
+
+    fn scale(&self) -> Vec2 {
+        self.transform().scale
+    }
+
+    /// Set the scale around the origin
+    ///
+    /// # Arguments
+    ///
+    /// * `scale` - Horizontal and vertical factors, 1 to keep the size
+    fn set_scale(&mut self, scale: Vec2) {
+        let transform = self.transform();
+        self.set_transform(transform::Transform { scale, ..transform });
+    }
+
+    /// Returns the point the object rotates and scales around, relative to its top-left corner
+    fn origin(&self) -> Vec2 {
+        self.transform().origin
+    }
+
+    /// Set the point the object rotates and scales around
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - Point relative to the top-left corner, e.g. the center
+    fn set_origin(&mut self, origin: Vec2) {
+        let transform = self.transform();
+        self.set_transform(transform::Transform {
+            origin,
+            ..transform
+        });
+    }
 }
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Vertex {
     pub position: [f32; 2],
-    pub color: [f32; 3],
+    /// Premultiplied RGBA color
+    pub color: [f32; 4],
     pub tex_coords: [f32; 2],
 }
 
@@ -47,10 +143,10 @@ impl Vertex {
                 wgpu::VertexAttribute {
                     offset: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
                     shader_location: 1,
-                    format: wgpu::VertexFormat::Float32x3,
+                    format: wgpu::VertexFormat::Float32x4,
                 },
                 wgpu::VertexAttribute {
-                    offset: std::mem::size_of::<[f32; 5]>() as wgpu::BufferAddress,
+                    offset: std::mem::size_of::<[f32; 6]>() as wgpu::BufferAddress,
                     shader_location: 2,
                     format: wgpu::VertexFormat::Float32x2,
                 },
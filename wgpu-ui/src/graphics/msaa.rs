@@ -0,0 +1,124 @@
+//! Multisample anti-aliasing, passes draw into a multisampled texture resolved into their target
+
+use crate::governor;
+
+/// Sample counts tried from the highest
+const SAMPLE_COUNTS: [u32; 4] = [8, 4, 2, 1];
+
+/// Returns the highest sample count up to `requested` supported by the adapter
+///
+/// # Arguments
+///
+/// * `requested` - Wanted sample count, e.g. 4 for 4x MSAA
+/// * `supported` - Returns `true` if a sample count is supported
+pub fn supported_sample_count(requested: u32, supported: impl Fn(u32) -> bool) -> u32 {
+    SAMPLE_COUNTS
+        .into_iter()
+        .filter(|count| *count <= requested)
+        .find(|count| *count == 1 || supported(*count))
+        .unwrap_or(1)
+}
+
+/// Returns the highest sample count up to `requested` usable with `format` on `adapter`
+///
+/// # Arguments
+///
+/// * `adapter` - Adapter of the device
+/// * `format` - Format of the render targets
+/// * `requested` - Wanted sample count
+pub fn adapter_sample_count(
+    adapter: &wgpu::Adapter,
+    format: wgpu::TextureFormat,
+    requested: u32,
+) -> u32 {
+    let flags = adapter.get_texture_format_features(format).flags;
+
+    supported_sample_count(requested, |count| flags.sample_count_supported(count))
+}
+
+/// Returns `false` while the frame governor turns multisampling off, the passes then draw
+/// directly into their target with the single sampled pipelines
+pub fn enabled() -> bool {
+    governor::quality().multisampling
+}
+
+/// Returns the multisampled texture passes draw into before being resolved into a target of
+/// `size`, `None` without multisampling
+///
+/// # Arguments
+///
+/// * `device` - Device creating the texture
+/// * `format` - Format of the resolved target
+/// * `size` - Size of the resolved target, in pixels
+/// * `sample_count` - Number of samples per pixel
+/// * `label` - Debug name shown by GPU debuggers
+pub fn create_view(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    size: (u32, u32),
+    sample_count: u32,
+    label: &str,
+) -> Option<wgpu::TextureView> {
+    if sample_count <= 1 {
+        return None;
+    }
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d {
+            width: size.0.max(1),
+            height: size.1.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+
+    Some(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+}
+
+/// Returns the color attachment of a pass drawing into `target`, through `msaa` when set
+///
+/// The multisampled texture is stored, so a following pass can load it and draw on top. It is
+/// skipped while multisampling is turned off, see [`enabled`].
+///
+/// # Arguments
+///
+/// * `msaa` - Multisampled texture, see [`create_view`]
+/// * `target` - Texture the pass is resolved into
+/// * `load` - Operation applied to the texture at the start of the pass
+pub fn attachment<'a>(
+    msaa: Option<&'a wgpu::TextureView>,
+    target: &'a wgpu::TextureView,
+    load: wgpu::LoadOp<wgpu::Color>,
+) -> wgpu::RenderPassColorAttachment<'a> {
+    let (view, resolve_target) = match msaa.filter(|_| enabled()) {
+        Some(msaa) => (msaa, Some(target)),
+        None => (target, None),
+    };
+
+    wgpu::RenderPassColorAttachment {
+        view,
+        resolve_target,
+        ops: wgpu::Operations { load, store: true },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::supported_sample_count;
+
+    #[test]
+    fn falls_back_to_a_supported_count() {
+        let supported = |count| count == 4;
+
+        assert_eq!(supported_sample_count(8, supported), 4);
+        assert_eq!(supported_sample_count(4, supported), 4);
+        assert_eq!(supported_sample_count(2, supported), 1);
+        assert_eq!(supported_sample_count(0, supported), 1);
+    }
+}
@@ -0,0 +1,299 @@
+use std::f32::consts::{FRAC_PI_8, PI};
+
+use glam::Vec2;
+
+use super::{
+    color::{Color, WHITE},
+    geometry_cache::{self, GeometryKey},
+    vertex_array::{Point, PrimitiveType, VertexArray},
+    Drawable, Transformable,
+};
+use crate::{math::Rect, Ctx};
+
+/// Longest miter allowed, as a multiple of half the thickness, sharper joins are beveled
+const MITER_LIMIT: f32 = 4.;
+
+/// How two segments are connected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineJoin {
+    /// Outer edges extended until they meet
+    Miter,
+    /// Outer corners cut by a straight edge
+    Bevel,
+    /// Outer corners rounded
+    Round,
+}
+
+/// How the ends of the line are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineCap {
+    /// The line stops at its end points
+    Butt,
+    /// The line is extended by half its thickness
+    Square,
+    /// Half circle around the end points
+    Round,
+}
+
+/// Append the triangles of a fan around `center`, starting at `center + from`
+fn arc(center: Vec2, from: Vec2, angle: f32, triangles: &mut Vec<Vec2>) {
+    let steps = (angle.abs() / FRAC_PI_8).ceil().max(1.);
+    let rotation = Vec2::from_angle(angle / steps);
+
+    let mut current = from;
+    for _ in 0..steps as u32 {
+        let next = rotation.rotate(current);
+        triangles.extend([center, center + current, center + next]);
+        current = next;
+    }
+}
+
+/// Tessellate a polyline into a triangle list
+///
+/// # Arguments
+///
+/// * `points` - Points of the line, consecutive duplicates are ignored
+/// * `thickness` - Width of the line, in pixels
+/// * `join` - Style of the connections between segments
+/// * `cap` - Style of the two ends
+pub fn tessellate(points: &[Vec2], thickness: f32, join: LineJoin, cap: LineCap) -> Vec<Vec2> {
+    let mut points = points.to_vec();
+    points.dedup();
+
+    let half = thickness / 2.;
+    let mut triangles = Vec::new();
+
+    if points.len() < 2 || half <= 0. {
+        return triangles;
+    }
+
+    let last = points.len() - 1;
+    let first_direction = (points[1] - points[0]).normalize();
+    let last_direction = (points[last] - points[last - 1]).normalize();
+
+    if cap == LineCap::Square {
+        points[0] -= first_direction * half;
+        points[last] += last_direction * half;
+    }
+
+    for segment in points.windows(2) {
+        let (start, end) = (segment[0], segment[1]);
+        let normal = (end - start).normalize().perp() * half;
+
+        triangles.extend([start + normal, start - normal, end - normal]);
+        triangles.extend([start + normal, end - normal, end + normal]);
+    }
+
+    for corner in points.windows(3) {
+        let (previous, point, next) = (corner[0], corner[1], corner[2]);
+        let incoming = (point - previous).normalize();
+        let outgoing = (next - point).normalize();
+        let turn = incoming.perp_dot(outgoing);
+
+        if turn.abs() < f32::EPSILON {
+            continue;
+        }
+
+        // The gap to fill is on the outer side of the turn
+        let side = -turn.signum();
+        let from = incoming.perp() * half * side;
+        let to = outgoing.perp() * half * side;
+
+        match join {
+            LineJoin::Bevel => triangles.extend([point, point + from, point + to]),
+            LineJoin::Miter => {
+                let direction = (from + to).normalize();
+                let length = half / direction.dot(from / half);
+
+                if length > MITER_LIMIT * half {
+                    triangles.extend([point, point + from, point + to]);
+                } else {
+                    let tip = point + direction * length;
+                    triangles.extend([point, point + from, tip]);
+                    triangles.extend([point, tip, point + to]);
+                }
+            }
+            LineJoin::Round => arc(point, from, from.angle_between(to), &mut triangles),
+        }
+    }
+
+    if cap == LineCap::Round {
+        arc(points[0], first_direction.perp() * half, PI, &mut triangles);
+        arc(
+            points[last],
+            -last_direction.perp() * half,
+            PI,
+            &mut triangles,
+        );
+    }
+
+    triangles
+}
+
+/// Line going through several points, with a thickness
+pub struct Polyline {
+    array: VertexArray,
+    points: Vec<Vec2>,
+    thickness: f32,
+    join: LineJoin,
+    cap: LineCap,
+    color: Color,
+}
+
+impl Polyline {
+    /// Create a line going through `points`, relative to the position of the line
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `points` - Points of the line, in pixels
+    /// * `thickness` - Width of the line, in pixels
+    pub fn new(context: Ctx, points: Vec<Vec2>, thickness: f32) -> Self {
+        let mut polyline = Self {
+            array: VertexArray::new_with_label(context, PrimitiveType::Triangles, "Polyline"),
+            points,
+            thickness,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            color: WHITE,
+        };
+        polyline.update();
+
+        polyline
+    }
+
+    /// Create a straight line between two points
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `from` - Start of the line
+    /// * `to` - End of the line
+    /// * `thickness` - Width of the line, in pixels
+    pub fn line(context: Ctx, from: Vec2, to: Vec2, thickness: f32) -> Self {
+        Self::new(context, vec![from, to], thickness)
+    }
+
+    pub fn points(&self) -> &[Vec2] {
+        &self.points
+    }
+
+    pub fn set_points(&mut self, points: Vec<Vec2>) {
+        self.points = points;
+        self.update();
+    }
+
+    pub fn set_thickness(&mut self, thickness: f32) {
+        self.thickness = thickness;
+        self.update();
+    }
+
+    pub fn set_join(&mut self, join: LineJoin) {
+        self.join = join;
+        self.update();
+    }
+
+    pub fn set_cap(&mut self, cap: LineCap) {
+        self.cap = cap;
+        self.update();
+    }
+
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+        self.update();
+    }
+
+    /// Set the opacity of the line, from 0 (transparent) to 1 (opaque)
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.array.set_opacity(opacity);
+    }
+
+    /// Returns the bounds of the tessellated line, in the UI space
+    pub fn bounds(&self) -> Rect {
+        self.array.bounds()
+    }
+
+    fn update(&mut self) {
+        let color = self.color;
+        let key = GeometryKey::polyline(&self.points, self.thickness, self.join, self.cap);
+        let triangles = geometry_cache::tessellated(key, || {
+            tessellate(&self.points, self.thickness, self.join, self.cap)
+        })
+        .iter()
+        .map(|position| Point::new(*position, color))
+        .collect();
+
+        self.array.set_points(triangles);
+    }
+}
+
+impl Transformable for Polyline {
+    fn position(&self) -> &Vec2 {
+        self.array.position()
+    }
+
+    fn set_position(&mut self, position: Vec2) {
+        self.array.set_position(position);
+    }
+}
+
+impl Drawable for Polyline {
+    fn draw<'b>(&'b mut self, render_pass: &mut wgpu::RenderPass<'b>) {
+        self.array.draw(render_pass);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::{tessellate, LineCap, LineJoin};
+
+    /// Sum of the areas of a triangle list
+    fn area(triangles: &[Vec2]) -> f32 {
+        triangles
+            .chunks_exact(3)
+            .map(|t| (t[1] - t[0]).perp_dot(t[2] - t[0]).abs() / 2.)
+            .sum()
+    }
+
+    #[test]
+    fn straight_line_is_a_quad() {
+        let points = [Vec2::ZERO, Vec2::new(10., 0.)];
+        let triangles = tessellate(&points, 2., LineJoin::Miter, LineCap::Butt);
+
+        assert_eq!(triangles.len(), 6);
+        assert!((area(&triangles) - 20.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn square_cap_extends_the_line() {
+        let points = [Vec2::ZERO, Vec2::new(10., 0.)];
+        let triangles = tessellate(&points, 2., LineJoin::Miter, LineCap::Square);
+
+        assert!((area(&triangles) - 24.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn right_angle_joins() {
+        let points = [Vec2::ZERO, Vec2::new(10., 0.), Vec2::new(10., 10.)];
+
+        let bevel = tessellate(&points, 2., LineJoin::Bevel, LineCap::Butt);
+        assert_eq!(bevel.len(), 12 + 3);
+        assert!((area(&bevel[12..]) - 0.5).abs() < 1e-4);
+
+        // The miter fills the whole outer square corner
+        let miter = tessellate(&points, 2., LineJoin::Miter, LineCap::Butt);
+        assert!((area(&miter[12..]) - 1.).abs() < 1e-4);
+        assert!(miter
+            .iter()
+            .any(|point| point.distance(Vec2::new(11., -1.)) < 1e-4));
+    }
+
+    #[test]
+    fn degenerate_lines_are_empty() {
+        let points = [Vec2::ONE, Vec2::ONE];
+
+        assert!(tessellate(&points, 2., LineJoin::Round, LineCap::Round).is_empty());
+    }
+}
@@ -0,0 +1,221 @@
+use glam::Vec2;
+use wgpu::util::DeviceExt;
+
+use super::{color::WHITE, msaa, Drawable, Transformable, Vertex};
+use crate::{
+    debug,
+    math::{pixels_to_clip, pixels_to_texture_coord, Rect},
+    pipeline, Ctx, PIPELINES,
+};
+
+/// Offscreen target the size of the UI space, drawn back as a textured rectangle
+///
+/// Drawables are rendered into it once, then a region of it (e.g. the bounds of a widget) is
+/// displayed anywhere on screen without redrawing them.
+pub struct RenderTexture {
+    context: Ctx,
+    label: String,
+    view: wgpu::TextureView,
+    /// Multisampled texture resolved into `view`, when the pipelines are multisampled
+    msaa: Option<wgpu::TextureView>,
+    bind_group: wgpu::BindGroup,
+    vertex_buffer: wgpu::Buffer,
+    /// Region of the texture displayed, in pixels
+    source: Rect,
+    position: Vec2,
+    opacity: f32,
+    /// Displayed without colors
+    grayscale: bool,
+}
+
+impl RenderTexture {
+    /// Create an empty texture whose GPU resources are named after `label`
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `label` - Debug name shown by GPU debuggers
+    pub fn new(context: Ctx, label: &str) -> Self {
+        let ctx = context.lock().unwrap();
+        let (width, height) = ctx.screen_size();
+
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: width as u32,
+                height: height as u32,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: ctx.config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let msaa = msaa::create_view(
+            &ctx.device,
+            ctx.config.format,
+            (width as u32, height as u32),
+            ctx.sample_count,
+            &format!("{label}/msaa"),
+        );
+        let sampler = ctx.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some(&format!("{label}/sampler")),
+            ..Default::default()
+        });
+
+        let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&format!("{label}/bind-group")),
+            layout: PIPELINES
+                .get()
+                .unwrap()
+                .get("textured")
+                .unwrap()
+                .1
+                .as_ref()
+                .unwrap(),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        let vertex_buffer = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("{label}-vb")),
+                contents: bytemuck::cast_slice(
+                    &[Vertex {
+                        position: [0., 0.],
+                        color: WHITE.premultiplied(1.),
+                        tex_coords: [0., 0.],
+                    }; 4],
+                ),
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            });
+        drop(ctx);
+
+        let mut render_texture = Self {
+            context,
+            label: label.to_string(),
+            view,
+            msaa,
+            bind_group,
+            vertex_buffer,
+            source: Rect {
+                x: 0.,
+                y: 0.,
+                width,
+                height,
+            },
+            position: Vec2::default(),
+            opacity: 1.,
+            grayscale: false,
+        };
+        render_texture.update();
+
+        render_texture
+    }
+
+    /// Start a pass drawing into the texture, cleared to transparent
+    ///
+    /// The pass has no camera nor index buffer bound yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `encoder` - Encoder recording the frame
+    pub fn begin<'a>(&'a self, encoder: &'a mut wgpu::CommandEncoder) -> wgpu::RenderPass<'a> {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(&self.label),
+            color_attachments: &[Some(msaa::attachment(
+                self.msaa.as_ref(),
+                &self.view,
+                wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+            ))],
+            depth_stencil_attachment: None,
+        })
+    }
+
+    /// Display only `source` of the texture, at its size
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Region of the texture, in pixels
+    pub fn set_source(&mut self, source: Rect) {
+        self.source = source;
+        self.update();
+    }
+
+    /// Set the opacity of the displayed region, from 0 (transparent) to 1 (opaque)
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+        self.update();
+    }
+
+    /// Display the texture in shades of gray, e.g. for disabled widgets
+    pub fn set_grayscale(&mut self, grayscale: bool) {
+        self.grayscale = grayscale;
+    }
+
+    fn update(&mut self) {
+        let ctx = self.context.lock().unwrap();
+        let screen_size = ctx.screen_size();
+
+        // Top-left, bottom-left, bottom-right and top-right, as rectangles are indexed
+        let corners = [(0., 0.), (0., 1.), (1., 1.), (1., 0.)].map(|(x, y)| {
+            let offset = Vec2::new(x * self.source.width, y * self.source.height);
+            let position = self.position + offset;
+            let source = self.source.position() + offset;
+
+            Vertex {
+                position: pixels_to_clip(position.x, position.y, screen_size.0, screen_size.1),
+                color: WHITE.premultiplied(self.opacity),
+                tex_coords: pixels_to_texture_coord(
+                    source.x,
+                    source.y,
+                    screen_size.0,
+                    screen_size.1,
+                ),
+            }
+        });
+
+        ctx.queue
+            .write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&corners));
+    }
+}
+
+impl Transformable for RenderTexture {
+    fn position(&self) -> &Vec2 {
+        &self.position
+    }
+
+    fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+        self.update();
+    }
+}
+
+impl Drawable for RenderTexture {
+    fn draw<'b>(&'b mut self, render_pass: &mut wgpu::RenderPass<'b>) {
+        render_pass.insert_debug_marker(&self.label);
+        let key = if self.grayscale {
+            "textured_grayscale"
+        } else {
+            "textured"
+        };
+        render_pass.set_pipeline(&pipeline(key).0);
+        render_pass.set_bind_group(1, &self.bind_group, &[]);
+
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw_indexed(0..6, 0, 0..1);
+        debug::record_draw(4);
+    }
+}
@@ -0,0 +1,170 @@
+//! Signed distance field glyphs, staying sharp at any scale
+
+use glam::Vec2;
+
+use super::color::Color;
+
+/// Height glyphs are baked at, in pixels
+pub const SDF_SIZE: f32 = 32.;
+/// Distance covered by the field on each side of the outline, in texels
+pub const SPREAD: u32 = 4;
+
+/// Returns the distance field of a coverage bitmap, 0.5 being the outline, higher values inside
+///
+/// # Arguments
+///
+/// * `coverage` - Coverage of each pixel, from 0 to 1, rows first
+/// * `width` - Width of the bitmap
+/// * `height` - Height of the bitmap
+/// * `spread` - Distance mapped to the whole range of values, in pixels
+pub fn distance_field(coverage: &[f32], width: u32, height: u32, spread: u32) -> Vec<u8> {
+    let inside = |x: i64, y: i64| {
+        x >= 0
+            && y >= 0
+            && x < width as i64
+            && y < height as i64
+            && coverage[(y * width as i64 + x) as usize] >= 0.5
+    };
+    let radius = spread as i64;
+
+    (0..height as i64)
+        .flat_map(|y| (0..width as i64).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let state = inside(x, y);
+
+            // Nearest pixel on the other side of the outline, brute force as glyphs are small
+            let nearest = (-radius..=radius)
+                .flat_map(|dy| (-radius..=radius).map(move |dx| (dx, dy)))
+                .filter(|(dx, dy)| inside(x + dx, y + dy) != state)
+                .map(|(dx, dy)| ((dx * dx + dy * dy) as f32).sqrt())
+                .fold(spread as f32, f32::min);
+            // The outline lies halfway between the two pixels
+            let distance = (nearest - 0.5).max(0.);
+            let signed = if state { distance } else { -distance };
+
+            ((0.5 + signed / (2. * spread as f32)).clamp(0., 1.) * 255.).round() as u8
+        })
+        .collect()
+}
+
+/// Packs rectangles in rows, left to right then top to bottom
+#[derive(Debug)]
+pub struct ShelfPacker {
+    width: u32,
+    height: u32,
+    x: u32,
+    y: u32,
+    /// Height of the tallest rectangle of the current row
+    row_height: u32,
+}
+
+impl ShelfPacker {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            x: 0,
+            y: 0,
+            row_height: 0,
+        }
+    }
+
+    /// Returns the top-left corner of a free `width` x `height` area, `None` if full
+    pub fn pack(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        if self.x + width > self.width {
+            self.x = 0;
+            self.y += self.row_height;
+            self.row_height = 0;
+        }
+        if self.x + width > self.width || self.y + height > self.height {
+            return None;
+        }
+
+        let position = (self.x, self.y);
+        self.x += width;
+        self.row_height = self.row_height.max(height);
+
+        Some(position)
+    }
+}
+
+/// Outline and drop shadow of a text drawn with distance fields
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SdfEffects {
+    /// Thickness in pixels of a glyph baked at [`SDF_SIZE`], at most [`SPREAD`], and color
+    pub outline: Option<(f32, Color)>,
+    /// Offset in pixels, and color
+    pub shadow: Option<(Vec2, Color)>,
+}
+
+/// Uniform read by the distance field shader
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SdfStyle {
+    /// Premultiplied color of the outline
+    pub outline_color: [f32; 4],
+    /// Thickness of the outline, in field units
+    pub outline_width: f32,
+    /// Extra blur of the edges, in field units
+    pub softness: f32,
+    _padding: [f32; 2],
+}
+
+impl SdfStyle {
+    /// Returns the style of the glyphs themselves
+    ///
+    /// # Arguments
+    ///
+    /// * `effects` - Effects of the text
+    /// * `opacity` - Opacity of the text
+    pub fn fill(effects: &SdfEffects, opacity: f32) -> Self {
+        let (width, color) = effects
+            .outline
+            .map(|(width, color)| (width.clamp(0., SPREAD as f32), color.premultiplied(opacity)))
+            .unwrap_or((0., [0.; 4]));
+
+        Self {
+            outline_color: color,
+            outline_width: width / (2. * SPREAD as f32),
+            softness: 0.,
+            _padding: [0.; 2],
+        }
+    }
+
+    /// Returns the style of the drop shadow, slightly blurred
+    pub fn shadow() -> Self {
+        Self {
+            outline_color: [0.; 4],
+            outline_width: 0.,
+            softness: 0.1,
+            _padding: [0.; 2],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{distance_field, ShelfPacker};
+
+    #[test]
+    fn field_is_centered_on_the_outline() {
+        // Left half covered
+        let coverage = [1., 1., 0., 0.].repeat(4);
+        let field = distance_field(&coverage, 4, 4, 2);
+
+        assert!(field[0] > 128);
+        assert!(field[1] > 128 && field[1] < field[0]);
+        assert!(field[2] < 128);
+        assert!(field[3] < field[2]);
+    }
+
+    #[test]
+    fn packer_starts_new_rows() {
+        let mut packer = ShelfPacker::new(10, 10);
+
+        assert_eq!(packer.pack(6, 4), Some((0, 0)));
+        assert_eq!(packer.pack(6, 3), Some((0, 4)));
+        assert_eq!(packer.pack(4, 2), Some((6, 4)));
+        assert_eq!(packer.pack(4, 4), None);
+    }
+}
@@ -0,0 +1,304 @@
+//! Drop shadows of rectangles, blurred analytically in the fragment shader
+
+use glam::{Mat3, Vec2};
+use wgpu::util::DeviceExt;
+
+use super::{
+    color::{Color, BLACK},
+    Drawable, Vertex,
+};
+use crate::{
+    debug, governor,
+    math::{pixels_to_clip, Rect},
+    pipeline, Ctx, PIPELINES,
+};
+
+/// Look of a drop shadow
+#[derive(Debug, Clone, Copy)]
+pub struct Shadow {
+    /// Offset from the shape casting the shadow, in pixels
+    pub offset: Vec2,
+    /// Distance over which the shadow fades out, in pixels
+    pub blur: f32,
+    pub color: Color,
+    /// Opacity of the shadow under the shape, from 0 to 1
+    pub opacity: f32,
+}
+
+impl Shadow {
+    /// Returns the material-style shadow of a surface raised by `elevation`, `None` when it
+    /// lies flat
+    ///
+    /// # Arguments
+    ///
+    /// * `elevation` - Height of the surface above the background, in pixels
+    pub fn elevation(elevation: f32) -> Option<Self> {
+        if elevation <= 0. {
+            return None;
+        }
+
+        Some(Self {
+            offset: Vec2::new(0., elevation / 2.),
+            blur: elevation * 1.5,
+            color: BLACK,
+            // Higher surfaces cast softer shadows
+            opacity: (0.45 - elevation * 0.01).max(0.2),
+        })
+    }
+
+    /// Returns the standard deviation of the gaussian blur
+    fn sigma(&self) -> f32 {
+        // The gaussian is almost null past three standard deviations
+        self.blur.max(0.) / 3.
+    }
+}
+
+/// Returns the region covered by the shadow of a rectangle
+///
+/// # Arguments
+///
+/// * `bounds` - Rectangle casting the shadow
+/// * `shadow` - Look of the shadow
+fn extent(bounds: Rect, shadow: &Shadow) -> Rect {
+    let blur = shadow.blur.max(0.);
+
+    Rect {
+        x: bounds.x + shadow.offset.x - blur,
+        y: bounds.y + shadow.offset.y - blur,
+        width: bounds.width + 2. * blur,
+        height: bounds.height + 2. * blur,
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShadowUniform {
+    half_size: [f32; 2],
+    sigma: f32,
+    _padding: f32,
+}
+
+/// Shadow drawn under a rectangle, see [`super::shape::RectangleShape::set_shadow`]
+pub struct DropShadow {
+    context: Ctx,
+    label: String,
+    shadow: Shadow,
+    /// Rectangle casting the shadow
+    bounds: Rect,
+    /// Transform of the rectangle, the shadow turns and scales with it
+    matrix: Mat3,
+    opacity: f32,
+    /// Blur factor of the quality the shadow was last updated with
+    blur_scale: f32,
+    vertices: [Vertex; 4],
+    vertex_buffer: wgpu::Buffer,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl DropShadow {
+    /// Create the shadow of an empty rectangle whose GPU resources are named after `label`
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `shadow` - Look of the shadow
+    /// * `label` - Debug name shown by GPU debuggers, e.g. `Button#save/shadow`
+    pub fn new_with_label(context: Ctx, shadow: Shadow, label: &str) -> Self {
+        let ctx = context.lock().unwrap();
+        let vertices = [Vertex {
+            position: [0., 0.],
+            color: shadow.color.premultiplied(shadow.opacity),
+            tex_coords: [0., 0.],
+        }; 4];
+
+        let vertex_buffer = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("{label}-vb")),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            });
+        let uniform_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("{label}/uniform")),
+            size: std::mem::size_of::<ShadowUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&format!("{label}/bind-group")),
+            layout: PIPELINES
+                .get()
+                .unwrap()
+                .get("shadow")
+                .unwrap()
+                .1
+                .as_ref()
+                .unwrap(),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+        drop(ctx);
+
+        let mut drop_shadow = Self {
+            context,
+            label: label.to_string(),
+            shadow,
+            bounds: Rect::default(),
+            matrix: Mat3::IDENTITY,
+            opacity: 1.,
+            blur_scale: 1.,
+            vertices,
+            vertex_buffer,
+            uniform_buffer,
+            bind_group,
+        };
+        drop_shadow.update();
+
+        drop_shadow
+    }
+
+    pub fn shadow(&self) -> &Shadow {
+        &self.shadow
+    }
+
+    pub fn set_shadow(&mut self, shadow: Shadow) {
+        self.shadow = shadow;
+        self.update();
+    }
+
+    /// Move the shadow under the rectangle casting it
+    ///
+    /// # Arguments
+    ///
+    /// * `bounds` - Rectangle casting the shadow
+    pub fn set_bounds(&mut self, bounds: Rect) {
+        if self.bounds == bounds {
+            return;
+        }
+
+        self.bounds = bounds;
+        self.update();
+    }
+
+    /// Follow the rotation and scale of the rectangle casting the shadow
+    ///
+    /// # Arguments
+    ///
+    /// * `matrix` - Transform of the rectangle, see [`super::Transformable::matrix`]
+    pub fn set_matrix(&mut self, matrix: Mat3) {
+        if self.matrix == matrix {
+            return;
+        }
+
+        self.matrix = matrix;
+        self.update();
+    }
+
+    /// Set the opacity of the shape casting the shadow, the shadow fades with it
+    ///
+    /// # Arguments
+    ///
+    /// * `opacity` - Shape opacity, from 0 to 1
+    pub fn set_opacity(&mut self, opacity: f32) {
+        if self.opacity == opacity {
+            return;
+        }
+
+        self.opacity = opacity;
+        self.update();
+    }
+
+    fn update(&mut self) {
+        let ctx = self.context.lock().unwrap();
+        let (width, height) = ctx.screen_size();
+
+        self.blur_scale = governor::quality().shadow_blur;
+        let shadow = Shadow {
+            blur: self.shadow.blur * self.blur_scale,
+            ..self.shadow
+        };
+        let extent = extent(self.bounds, &shadow);
+        let half_size = Vec2::new(self.bounds.width, self.bounds.height) / 2.;
+        let color = self
+            .shadow
+            .color
+            .premultiplied(self.shadow.opacity * self.opacity);
+        // Same corner order as the rectangles: top-left, bottom-left, bottom-right, top-right
+        let corners = [
+            Vec2::new(0., 0.),
+            Vec2::new(0., extent.height),
+            Vec2::new(extent.width, extent.height),
+            Vec2::new(extent.width, 0.),
+        ];
+        let center = Vec2::new(extent.width, extent.height) / 2.;
+
+        for (vertex, corner) in self.vertices.iter_mut().zip(corners) {
+            let point = self.matrix.transform_point2(extent.position() + corner);
+            vertex.position = pixels_to_clip(point.x, point.y, width, height);
+            vertex.color = color;
+            vertex.tex_coords = (corner - center).into();
+        }
+
+        ctx.queue
+            .write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&self.vertices));
+        ctx.queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[ShadowUniform {
+                half_size: half_size.into(),
+                sigma: shadow.sigma(),
+                _padding: 0.,
+            }]),
+        );
+    }
+}
+
+impl Drawable for DropShadow {
+    fn draw<'b>(&'b mut self, render_pass: &mut wgpu::RenderPass<'b>) {
+        if self.blur_scale != governor::quality().shadow_blur {
+            self.update();
+        }
+
+        render_pass.insert_debug_marker(&self.label);
+        render_pass.set_pipeline(&pipeline("shadow").0);
+        render_pass.set_bind_group(1, &self.bind_group, &[]);
+
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw_indexed(0..6, 0, 0..1);
+        debug::record_draw(4);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::{extent, Shadow};
+    use crate::math::Rect;
+
+    #[test]
+    fn shadows_extend_past_their_shape_by_the_blur() {
+        let bounds = Rect {
+            x: 10.,
+            y: 20.,
+            width: 100.,
+            height: 50.,
+        };
+        let shadow = Shadow::elevation(4.).unwrap();
+
+        assert_eq!(shadow.offset, Vec2::new(0., 2.));
+        assert_eq!(
+            extent(bounds, &shadow),
+            Rect {
+                x: 4.,
+                y: 16.,
+                width: 112.,
+                height: 62.,
+            }
+        );
+        assert!(Shadow::elevation(0.).is_none());
+    }
+}
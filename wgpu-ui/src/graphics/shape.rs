@@ -1,13 +1,17 @@
 use crate::{
+    accessibility::Semantics,
+    debug,
     math::{pixels_to_clip, Rect},
-    Ctx, PIPELINES,
+    pipeline, Ctx,
 };
 
 use super::{
     color::{Color, WHITE},
+    shadow::{DropShadow, Shadow},
+    transform::Transform,
     Drawable, Transformable, Vertex,
 };
-use glam::Vec2;
+use glam::{Mat3, Vec2};
 use wgpu::util::DeviceExt;
 
 pub trait Shape: Transformable + Drawable {
@@ -18,6 +22,13 @@ pub trait Shape: Transformable + Drawable {
     /// * `color` - Shape color
     fn set_fill_color(&mut self, color: Color);
 
+    /// Set the opacity of the shape, from 0 (transparent) to 1 (opaque)
+    ///
+    /// # Arguments
+    ///
+    /// * `opacity` - Shape opacity
+    fn set_opacity(&mut self, opacity: f32);
+
     /// Returns the position of a point of the shape
     ///
     /// # Arguments
@@ -31,22 +42,40 @@ pub trait Shape: Transformable + Drawable {
 
 pub struct RectangleShape {
     context: Ctx,
+    label: String,
     vertex_buffer: wgpu::Buffer,
-    color: Color,
+    colors: [Color; 4],
+    opacity: f32,
     vertices: Vec<Vertex>,
     position: Vec2,
     size: Vec2,
+    semantics: Option<Semantics>,
+    shadow: Option<DropShadow>,
+    transform: Transform,
+    /// Matrix of `transform` at the current position
+    matrix: Mat3,
 }
 
 impl RectangleShape {
     pub fn new(context: Ctx, size: Vec2) -> Self {
+        Self::new_with_label(context, size, "RectangleShape")
+    }
+
+    /// Create a rectangle whose GPU resources are named after `label`
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `size` - Rectangle size
+    /// * `label` - Debug name shown by GPU debuggers, e.g. `Button#save/background`
+    pub fn new_with_label(context: Ctx, size: Vec2, label: &str) -> Self {
         let ctx = context.lock().unwrap();
         let mut vertices = Vec::new();
 
         for _ in 0..4 {
             vertices.push(Vertex {
                 position: [0., 0.],
-                color: WHITE.into(),
+                color: WHITE.premultiplied(1.),
                 tex_coords: [-1., -1.],
             });
         }
@@ -54,7 +83,7 @@ impl RectangleShape {
         let vertex_buffer = ctx
             .device
             .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Vertex buffer"),
+                label: Some(&format!("{label}-vb")),
                 contents: bytemuck::cast_slice(&vertices),
                 usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
             });
@@ -62,11 +91,17 @@ impl RectangleShape {
 
         let mut s = Self {
             context,
+            label: label.to_string(),
             position: Default::default(),
             size,
-            color: WHITE,
+            colors: [WHITE; 4],
+            opacity: 1.,
             vertices,
             vertex_buffer,
+            semantics: None,
+            shadow: None,
+            transform: Transform::default(),
+            matrix: Mat3::IDENTITY,
         };
         s.update();
 
@@ -91,21 +126,76 @@ impl RectangleShape {
         &self.size
     }
 
+    /// Returns the debug name of the shape
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Describe what the rectangle stands for when it's used as an icon or a status indicator,
+    /// read by widgets exposing it to screen readers
+    ///
+    /// # Arguments
+    ///
+    /// * `semantics` - Role and label of the rectangle, `None` for a decoration
+    pub fn set_semantics(&mut self, semantics: Option<Semantics>) {
+        self.semantics = semantics;
+    }
+
+    pub fn semantics(&self) -> Option<&Semantics> {
+        self.semantics.as_ref()
+    }
+
+    /// Cast a drop shadow under the rectangle
+    ///
+    /// # Arguments
+    ///
+    /// * `shadow` - Look of the shadow, `None` to remove it
+    pub fn set_shadow(&mut self, shadow: Option<Shadow>) {
+        let Some(shadow) = shadow else {
+            self.shadow = None;
+            return;
+        };
+
+        if let Some(drop_shadow) = &mut self.shadow {
+            drop_shadow.set_shadow(shadow);
+            return;
+        }
+
+        let mut drop_shadow = DropShadow::new_with_label(
+            self.context.clone(),
+            shadow,
+            &format!("{}/shadow", self.label),
+        );
+        drop_shadow.set_bounds(self.bounds());
+        drop_shadow.set_opacity(self.opacity);
+        self.shadow = Some(drop_shadow);
+    }
+
+    pub fn shadow(&self) -> Option<&Shadow> {
+        self.shadow.as_ref().map(DropShadow::shadow)
+    }
+
     fn update(&mut self) {
+        self.matrix = self.transform.matrix(self.position);
+
+        let bounds = self.bounds();
+        if let Some(shadow) = &mut self.shadow {
+            shadow.set_bounds(bounds);
+            shadow.set_matrix(self.matrix);
+            shadow.set_opacity(self.opacity);
+        }
+
         let ctx = self.context.lock().unwrap();
-        let screen_size = (ctx.config.width as f32, ctx.config.height as f32);
+        let screen_size = ctx.screen_size();
         drop(ctx);
 
         for i in 0..self.get_point_count() {
-            let point = self.get_point(i);
+            let point = self
+                .matrix
+                .transform_point2(self.position + self.get_point(i));
 
             if let Some(vertex) = self.vertices.get_mut(i) {
-                vertex.position = pixels_to_clip(
-                    self.position.x + point.x,
-                    self.position.y + point.y,
-                    screen_size.0,
-                    screen_size.1,
-                );
+                vertex.position = pixels_to_clip(point.x, point.y, screen_size.0, screen_size.1);
             }
         }
 
@@ -116,9 +206,20 @@ impl RectangleShape {
             .write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&self.vertices));
     }
 
+    /// Set a color per corner, colors are interpolated across the rectangle
+    ///
+    /// # Arguments
+    ///
+    /// * `colors` - Top-left, bottom-left, bottom-right and top-right colors
+    pub fn set_point_colors(&mut self, colors: [Color; 4]) {
+        self.colors = colors;
+
+        self.update();
+    }
+
     fn update_fill_color(&mut self) {
-        for vertex in &mut self.vertices {
-            vertex.color = self.color.into();
+        for (vertex, color) in self.vertices.iter_mut().zip(self.colors) {
+            vertex.color = color.premultiplied(self.opacity);
         }
     }
 }
@@ -138,11 +239,20 @@ impl Shape for RectangleShape {
     }
 
     fn set_fill_color(&mut self, color: Color) {
-        self.color = color;
+        self.colors = [color; 4];
 
         // self.update_fill_color();
         self.update();
     }
+
+    fn set_opacity(&mut self, opacity: f32) {
+        if self.opacity == opacity {
+            return;
+        }
+
+        self.opacity = opacity;
+        self.update();
+    }
 }
 
 impl Transformable for RectangleShape {
@@ -155,13 +265,33 @@ impl Transformable for RectangleShape {
     fn position(&self) -> &Vec2 {
         &self.position
     }
+
+    fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+
+        self.update();
+    }
+
+    fn matrix(&self) -> Mat3 {
+        self.matrix
+    }
 }
 
 impl Drawable for RectangleShape {
     fn draw<'b>(&'b mut self, render_pass: &mut wgpu::RenderPass<'b>) {
-        render_pass.set_pipeline(&PIPELINES.get().unwrap().get("std").unwrap().0);
+        if let Some(shadow) = &mut self.shadow {
+            shadow.draw(render_pass);
+        }
+
+        render_pass.insert_debug_marker(&self.label);
+        render_pass.set_pipeline(&pipeline("std").0);
 
         render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
         render_pass.draw_indexed(0..6, 0, 0..1);
+        debug::record_draw(4);
     }
 }
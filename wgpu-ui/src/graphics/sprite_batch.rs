@@ -0,0 +1,460 @@
+//! Huge amounts of colored quads, e.g. particles or bullets, culled against the view by a compute
+//! shader and drawn with a single indirect draw whatever their number
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use glam::Vec2;
+use once_cell::sync::OnceCell;
+
+use super::{color::Color, Drawable};
+use crate::{debug, math::Rect, pipeline, Ctx, PIPELINES};
+
+/// Sprites culled by each workgroup of the compute shader
+const WORKGROUP_SIZE: u32 = 64;
+/// Workgroups a single dispatch may be split in
+const MAX_WORKGROUPS: u32 = 65535;
+
+static GPU_CULLING: AtomicBool = AtomicBool::new(false);
+static CULL_PIPELINE: OnceCell<(wgpu::ComputePipeline, wgpu::BindGroupLayout)> = OnceCell::new();
+
+/// Returns `true` if the sprites are culled on the GPU, otherwise on the CPU
+pub fn gpu_culling() -> bool {
+    GPU_CULLING.load(Ordering::Relaxed)
+}
+
+/// Cull the sprites with a compute shader, set when the device is created
+///
+/// # Arguments
+///
+/// * `enabled` - `false` when the adapter can't run compute shaders or indirect draws, e.g.
+///   WebGL
+pub fn set_gpu_culling(enabled: bool) {
+    GPU_CULLING.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns `true` if the adapter can cull the sprites and draw them indirectly
+pub fn supports_gpu_culling(adapter: &wgpu::Adapter) -> bool {
+    adapter
+        .get_downlevel_capabilities()
+        .flags
+        .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS | wgpu::DownlevelFlags::INDIRECT_EXECUTION)
+}
+
+/// Quad of a batch, laid out as the sprites of the shaders
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SpriteInstance {
+    /// Top left corner, in pixels
+    pub position: [f32; 2],
+    pub size: [f32; 2],
+    /// Premultiplied by its alpha
+    pub color: [f32; 4],
+}
+
+impl SpriteInstance {
+    /// Create a sprite
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - Top left corner, in pixels
+    /// * `size` - Size, in pixels
+    /// * `color` - Fill color
+    /// * `opacity` - Opacity between 0 and 1
+    pub fn new(position: Vec2, size: Vec2, color: Color, opacity: f32) -> Self {
+        Self {
+            position: position.into(),
+            size: size.into(),
+            color: color.premultiplied(opacity),
+        }
+    }
+
+    /// Returns `true` if the sprite overlaps `view`, the test of the compute shader
+    pub fn is_visible(&self, view: &Rect) -> bool {
+        self.position[0] + self.size[0] >= view.x
+            && self.position[1] + self.size[1] >= view.y
+            && self.position[0] <= view.x + view.width
+            && self.position[1] <= view.y + view.height
+    }
+
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SpriteInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct CullUniform {
+    view_min: [f32; 2],
+    view_max: [f32; 2],
+    screen_size: [f32; 2],
+    count: u32,
+    _padding: u32,
+}
+
+/// Arguments of the indirect draw before culling, 6 indices and no instance
+const DRAW_ARGS: [u32; 5] = [6, 0, 0, 0, 0];
+
+/// Returns the compute pipeline culling the sprites and the layout of its bind group
+fn cull_pipeline(device: &wgpu::Device) -> &'static (wgpu::ComputePipeline, wgpu::BindGroupLayout) {
+    CULL_PIPELINE.get_or_init(|| {
+        let storage = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage(1, true),
+                storage(2, false),
+                storage(3, false),
+            ],
+            label: Some("Sprite cull bind group layout"),
+        });
+        let shader =
+            device.create_shader_module(wgpu::include_wgsl!("../shaders/sprite_cull.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Sprite cull pipeline layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Sprite cull pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: "cs_main",
+        });
+
+        (pipeline, layout)
+    })
+}
+
+/// Sprites drawn in a single draw call, only the ones overlapping the view of their layer are
+/// drawn
+///
+/// Each frame a compute shader copies the visible sprites at the start of a second buffer and
+/// counts them into the arguments of an indirect draw, the CPU never reads the count back. Without
+/// compute shaders the sprites are culled on the CPU and drawn with a regular draw.
+pub struct SpriteBatch {
+    context: Ctx,
+    label: String,
+    sprites: Vec<SpriteInstance>,
+    capacity: u32,
+    /// The sprites changed since their last upload
+    dirty: bool,
+    /// Every sprite, read by the compute shader
+    instances: wgpu::Buffer,
+    /// Visible sprites, at the start of the buffer
+    visible: wgpu::Buffer,
+    /// Visible sprites counted on the CPU, only without GPU culling
+    visible_count: u32,
+    /// Arguments of the indirect draw
+    args: wgpu::Buffer,
+    uniform: wgpu::Buffer,
+    cull_bind_group: Option<wgpu::BindGroup>,
+    bind_group: wgpu::BindGroup,
+}
+
+impl SpriteBatch {
+    /// Create an empty batch
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Context the buffers are created with
+    /// * `capacity` - Maximum number of sprites, up to 4 million
+    pub fn new(context: Ctx, capacity: u32) -> Self {
+        Self::new_with_label(context, capacity, "SpriteBatch")
+    }
+
+    /// Create an empty batch whose GPU resources are named after `label`
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Context the buffers are created with
+    /// * `capacity` - Maximum number of sprites, up to 4 million
+    /// * `label` - Debug name shown by GPU debuggers, e.g. `Particles#sparks`
+    pub fn new_with_label(context: Ctx, capacity: u32, label: &str) -> Self {
+        assert!(
+            capacity.div_ceil(WORKGROUP_SIZE) <= MAX_WORKGROUPS,
+            "A batch holds at most {} sprites",
+            MAX_WORKGROUPS * WORKGROUP_SIZE
+        );
+
+        let ctx = context.lock().unwrap();
+        let gpu = gpu_culling();
+        let size = (capacity.max(1) as usize * std::mem::size_of::<SpriteInstance>())
+            as wgpu::BufferAddress;
+
+        let instances = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("{label}/instances")),
+            size: if gpu { size } else { 4 },
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let visible = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("{label}/visible")),
+            size,
+            usage: if gpu {
+                wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::STORAGE
+            } else {
+                wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST
+            },
+            mapped_at_creation: false,
+        });
+        let args = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("{label}/args")),
+            size: std::mem::size_of_val(&DRAW_ARGS) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::INDIRECT
+                | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        ctx.queue
+            .write_buffer(&args, 0, bytemuck::cast_slice(&DRAW_ARGS));
+        let uniform = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("{label}/uniform")),
+            size: std::mem::size_of::<CullUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let cull_bind_group = gpu.then(|| {
+            ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some(&format!("{label}/cull-bind-group")),
+                layout: &cull_pipeline(&ctx.device).1,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: uniform.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: instances.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: visible.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: args.as_entire_binding(),
+                    },
+                ],
+            })
+        });
+        let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&format!("{label}/bind-group")),
+            layout: PIPELINES
+                .get()
+                .unwrap()
+                .get("sprite")
+                .unwrap()
+                .1
+                .as_ref()
+                .unwrap(),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform.as_entire_binding(),
+            }],
+        });
+        drop(ctx);
+
+        Self {
+            context,
+            label: label.to_string(),
+            sprites: Vec::new(),
+            capacity,
+            dirty: false,
+            instances,
+            visible,
+            visible_count: 0,
+            args,
+            uniform,
+            cull_bind_group,
+            bind_group,
+        }
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    pub fn sprites(&self) -> &[SpriteInstance] {
+        &self.sprites
+    }
+
+    /// Replace the sprites, the ones past the capacity are dropped
+    ///
+    /// # Arguments
+    ///
+    /// * `sprites` - New sprites, drawn in no particular order
+    pub fn set_sprites(&mut self, mut sprites: Vec<SpriteInstance>) {
+        if sprites.len() > self.capacity as usize {
+            tracing::warn!(
+                "{} holds {} sprites, {} dropped",
+                self.label,
+                self.capacity,
+                sprites.len() - self.capacity as usize
+            );
+            sprites.truncate(self.capacity as usize);
+        }
+
+        self.sprites = sprites;
+        self.dirty = true;
+    }
+
+    /// Returns the sprites to move or recolor them, they are uploaded again before the next frame
+    pub fn sprites_mut(&mut self) -> &mut [SpriteInstance] {
+        self.dirty = true;
+        &mut self.sprites
+    }
+
+    /// Returns the number of sprites drawn by the last frame, only known without GPU culling
+    pub fn visible_count(&self) -> Option<u32> {
+        self.cull_bind_group.is_none().then_some(self.visible_count)
+    }
+}
+
+impl Drawable for SpriteBatch {
+    fn prepare(&mut self, encoder: &mut wgpu::CommandEncoder, view: Rect) {
+        let ctx = self.context.lock().unwrap();
+        let (width, height) = ctx.screen_size();
+        let uniform = CullUniform {
+            view_min: [view.x, view.y],
+            view_max: [view.x + view.width, view.y + view.height],
+            screen_size: [width, height],
+            count: self.sprites.len() as u32,
+            _padding: 0,
+        };
+        ctx.queue
+            .write_buffer(&self.uniform, 0, bytemuck::cast_slice(&[uniform]));
+
+        let Some(bind_group) = &self.cull_bind_group else {
+            let visible: Vec<SpriteInstance> = self
+                .sprites
+                .iter()
+                .filter(|sprite| sprite.is_visible(&view))
+                .copied()
+                .collect();
+            self.visible_count = visible.len() as u32;
+            ctx.queue
+                .write_buffer(&self.visible, 0, bytemuck::cast_slice(&visible));
+            return;
+        };
+
+        if std::mem::take(&mut self.dirty) {
+            ctx.queue
+                .write_buffer(&self.instances, 0, bytemuck::cast_slice(&self.sprites));
+        }
+        // Written before the commands of the frame run, the shader counts from 0
+        ctx.queue
+            .write_buffer(&self.args, 0, bytemuck::cast_slice(&DRAW_ARGS));
+        drop(ctx);
+
+        if self.sprites.is_empty() {
+            return;
+        }
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some(&format!("{}/cull", self.label)),
+        });
+        pass.set_pipeline(&CULL_PIPELINE.get().unwrap().0);
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.dispatch_workgroups((self.sprites.len() as u32).div_ceil(WORKGROUP_SIZE), 1, 1);
+    }
+
+    fn draw<'a>(&'a mut self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.sprites.is_empty() {
+            return;
+        }
+
+        render_pass.insert_debug_marker(&self.label);
+        render_pass.set_pipeline(&pipeline("sprite").0);
+        render_pass.set_bind_group(1, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.visible.slice(..));
+
+        if self.cull_bind_group.is_some() {
+            render_pass.draw_indexed_indirect(&self.args, 0);
+            // The visible sprites are unknown to the CPU, every sprite is counted
+            debug::record_draw(4 * self.sprites.len() as u32);
+        } else if self.visible_count > 0 {
+            render_pass.draw_indexed(0..6, 0, 0..self.visible_count);
+            debug::record_draw(4 * self.visible_count);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::SpriteInstance;
+    use crate::{
+        graphics::{color::WHITE, layer::Camera},
+        math::Rect,
+    };
+
+    #[test]
+    fn sprites_match_the_layout_of_the_shaders() {
+        // vec2, vec2 then a vec4 aligned on 16 bytes
+        assert_eq!(std::mem::size_of::<SpriteInstance>(), 32);
+    }
+
+    #[test]
+    fn sprites_outside_the_camera_are_culled() {
+        let camera = Camera {
+            position: Vec2::new(100., 0.),
+            zoom: 2.,
+        };
+        let view = camera.visible_rect((800., 600.));
+        assert_eq!(
+            view,
+            Rect {
+                x: 300.,
+                y: 150.,
+                width: 400.,
+                height: 300.,
+            }
+        );
+
+        let sprite = |x, y| SpriteInstance::new(Vec2::new(x, y), Vec2::splat(10.), WHITE, 1.);
+        assert!(sprite(295., 145.).is_visible(&view));
+        assert!(sprite(500., 300.).is_visible(&view));
+        assert!(!sprite(280., 300.).is_visible(&view));
+        assert!(!sprite(500., 451.).is_visible(&view));
+    }
+}
@@ -1,185 +1,1194 @@
 use crate::{
+    debug,
+    i18n::Translation,
     math::{pixels_to_clip, Rect},
-    Ctx, TEXT_BRUSH,
+    text_brush,
+    ui::state::Binding,
+    Ctx, ASSETS, CAMERA_BIND_GROUP_LAYOUT, TEXT_BRUSH,
 };
 
 use super::{
+    blend::BlendMode,
     color::{Color, WHITE},
+    glyph_cache::{self, GlyphBitmap, GlyphKey},
+    sdf::{self, SdfEffects, SdfStyle, ShelfPacker, SDF_SIZE, SPREAD},
+    text_style::{TextStyle, TextStyleProperties},
+    transform::{self, Transform},
     Drawable, Transformable, Vertex,
 };
-use glam::Vec2;
-use rusttype::{gpu_cache::Cache, point, PositionedGlyph, Scale};
+use glam::{Mat3, Vec2};
+use rusttype::{point, Glyph, GlyphId, PositionedGlyph, Scale};
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::Range,
+    sync::Arc,
+};
+use unicode_bidi::{BidiInfo, Level};
 use wgpu::util::DeviceExt;
 
 const TEXTURE_WIDTH: u32 = 512;
 const TEXTURE_HEIGHT: u32 = 512;
 
+/// A run of characters sharing the same style
+#[derive(Clone)]
+pub struct TextSpan<'a> {
+    pub content: String,
+    pub color: Color,
+    pub size: f32,
+    /// Font used by the span, pick a bold/italic face to change the weight
+    pub font: &'a rusttype::Font<'a>,
+    /// Extra space after each glyph, in pixels
+    pub letter_spacing: f32,
+}
+
+impl<'a> TextSpan<'a> {
+    pub fn new(content: &str, font: &'a rusttype::Font<'a>, size: f32) -> Self {
+        Self {
+            content: content.to_string(),
+            color: WHITE,
+            size,
+            font,
+            letter_spacing: 0.,
+        }
+    }
+
+    /// Set the color of the span
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - Span color
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+
+        self
+    }
+
+    /// Set the extra space after each glyph of the span
+    ///
+    /// # Arguments
+    ///
+    /// * `letter_spacing` - Space in pixels, negative to tighten the span
+    pub fn with_letter_spacing(mut self, letter_spacing: f32) -> Self {
+        self.letter_spacing = letter_spacing;
+
+        self
+    }
+}
+
+/// Returns the glyph of `c` from the first font of the chain providing it, along with the
+/// position of that font in the chain (0 being `font`)
+fn resolve_glyph<'a>(
+    font: &'a rusttype::Font<'a>,
+    fallbacks: &[&'a rusttype::Font<'a>],
+    c: char,
+) -> (usize, Glyph<'a>) {
+    let glyph = font.glyph(c);
+    // Glyph 0 is the "missing glyph" (tofu)
+    if glyph.id().0 != 0 {
+        return (0, glyph);
+    }
+
+    fallbacks
+        .iter()
+        .enumerate()
+        .find_map(|(i, fallback)| {
+            let glyph = fallback.glyph(c);
+            (glyph.id().0 != 0).then_some((i + 1, glyph))
+        })
+        .unwrap_or((0, glyph))
+}
+
+/// Base direction of a text, mixed-direction strings are reordered with the Unicode bidi
+/// algorithm whatever the base direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextDirection {
+    /// Taken from the first strong character (Latin, Arabic, Hebrew, ..)
+    #[default]
+    Auto,
+    LeftToRight,
+    RightToLeft,
+}
+
+impl TextDirection {
+    fn level(&self) -> Option<Level> {
+        match self {
+            Self::Auto => None,
+            Self::LeftToRight => Some(Level::ltr()),
+            Self::RightToLeft => Some(Level::rtl()),
+        }
+    }
+}
+
+/// Part of a line sharing one span and one direction
+#[derive(Debug, Clone, PartialEq)]
+struct Run {
+    /// Index of the span providing the style
+    span: usize,
+    /// Bytes of the line covered by the run
+    range: Range<usize>,
+    rtl: bool,
+}
+
+/// Returns the runs of a line in visual order, left to right
+///
+/// # Arguments
+///
+/// * `line` - Concatenation of the spans contents, without line breaks
+/// * `spans` - Bytes of `line` covered by each span
+/// * `direction` - Base direction of the line
+fn visual_runs(line: &str, spans: &[Range<usize>], direction: TextDirection) -> Vec<Run> {
+    let bidi = BidiInfo::new(line, direction.level());
+    let mut runs = Vec::new();
+
+    for paragraph in &bidi.paragraphs {
+        let (levels, level_runs) = bidi.visual_runs(paragraph, paragraph.range.clone());
+
+        for level_run in level_runs {
+            let rtl = levels[level_run.start].is_rtl();
+            let mut pieces = spans
+                .iter()
+                .enumerate()
+                .filter_map(|(span, range)| {
+                    let range = range.start.max(level_run.start)..range.end.min(level_run.end);
+                    (!range.is_empty()).then_some(Run { span, range, rtl })
+                })
+                .collect::<Vec<_>>();
+
+            // Spans of a right-to-left run are displayed from the last to the first
+            if rtl {
+                pieces.reverse();
+            }
+            runs.extend(pieces);
+        }
+    }
+
+    runs
+}
+
+/// A glyph placed by the shaper, in pixels
+struct ShapedGlyph<'a> {
+    glyph: Glyph<'a>,
+    /// Position of the glyph font in the fallback chain
+    chain_index: usize,
+    /// Byte offset in the run of the first character drawn by the glyph
+    cluster: usize,
+    offset: Vec2,
+    advance: f32,
+}
+
+/// Shape a run with its span font, applying kerning, ligatures and contextual forms
+///
+/// Glyphs missing from the font are taken from the fallback chain, unshaped.
+fn shape_run<'a>(
+    text: &str,
+    span: &TextSpan<'a>,
+    fallbacks: &[&'a rusttype::Font<'a>],
+    rtl: bool,
+) -> Vec<ShapedGlyph<'a>> {
+    let font = span.font;
+    let scale = Scale::uniform(span.size);
+
+    let Some(face) = ASSETS.shaping_face(font) else {
+        // Not an asset, glyphs are only laid out one after the other
+        return text
+            .char_indices()
+            .map(|(cluster, c)| {
+                let (chain_index, glyph) = resolve_glyph(font, fallbacks, c);
+                let advance = glyph.clone().scaled(scale).h_metrics().advance_width;
+
+                ShapedGlyph {
+                    glyph,
+                    chain_index,
+                    cluster,
+                    offset: Vec2::ZERO,
+                    advance,
+                }
+            })
+            .collect();
+    };
+
+    let mut buffer = rustybuzz::UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.guess_segment_properties();
+    buffer.set_direction(if rtl {
+        rustybuzz::Direction::RightToLeft
+    } else {
+        rustybuzz::Direction::LeftToRight
+    });
+    let output = rustybuzz::shape(&face, &[], buffer);
+
+    // Same scaling as rusttype, the pixel height spans from the descent to the ascent
+    let v_metrics = font.v_metrics_unscaled();
+    let pixels_per_unit = span.size / (v_metrics.ascent - v_metrics.descent);
+
+    output
+        .glyph_infos()
+        .iter()
+        .zip(output.glyph_positions())
+        .map(|(info, position)| {
+            // Glyph 0 is the "missing glyph" (tofu)
+            if info.glyph_id == 0 {
+                let c = text[info.cluster as usize..].chars().next().unwrap_or(' ');
+                let (chain_index, glyph) = resolve_glyph(font, fallbacks, c);
+                if chain_index != 0 {
+                    let advance = glyph.clone().scaled(scale).h_metrics().advance_width;
+
+                    return ShapedGlyph {
+                        glyph,
+                        chain_index,
+                        cluster: info.cluster as usize,
+                        offset: Vec2::ZERO,
+                        advance,
+                    };
+                }
+            }
+
+            ShapedGlyph {
+                glyph: font.glyph(GlyphId(info.glyph_id as u16)),
+                chain_index: 0,
+                cluster: info.cluster as usize,
+                offset: Vec2::new(position.x_offset as f32, -position.y_offset as f32)
+                    * pixels_per_unit,
+                advance: position.x_advance as f32 * pixels_per_unit,
+            }
+        })
+        .collect()
+}
+
+/// A glyph positioned by [`layout_paragraph`]
+struct LaidOutGlyph<'a> {
+    glyph: PositionedGlyph<'a>,
+    /// Glyph cache font id, see [`glyph_font`]
+    font_id: usize,
+    /// Index of the span the glyph belongs to
+    span: usize,
+    /// Byte offset in its line of the first character drawn by the glyph
+    cluster: usize,
+    /// Horizontal position of the caret before the glyph, in pixels
+    caret: f32,
+}
+
+/// Positioned glyphs of the spans and the size they cover
 fn layout_paragraph<'a>(
-    font: &rusttype::Font<'a>,
-    scale: Scale,
+    spans: &[TextSpan<'a>],
+    fallbacks: &[&'a rusttype::Font<'a>],
     width: u32,
-    text: &str,
-) -> (Vec<PositionedGlyph<'a>>, Rect) {
+    direction: TextDirection,
+) -> (Vec<LaidOutGlyph<'a>>, Rect) {
     let mut result = Vec::new();
-    let v_metrics = font.v_metrics(scale);
-    let advance_height = v_metrics.ascent - v_metrics.descent + v_metrics.line_gap;
-    let mut caret = point(0.0, v_metrics.ascent);
-    let mut last_glyph_id = None;
+    // Lines are as high as the biggest span so mixed sizes share a baseline
+    let (ascent, advance_height) = spans.iter().fold((0f32, 0f32), |(ascent, height), span| {
+        let v_metrics = span.font.v_metrics(Scale::uniform(span.size));
+        (
+            ascent.max(v_metrics.ascent),
+            height.max(v_metrics.ascent - v_metrics.descent + v_metrics.line_gap),
+        )
+    });
+    let mut caret = point(0.0, ascent);
     let mut bounds = Rect::default();
 
-    for c in text.chars() {
-        if c.is_control() {
-            match c {
-                '\r' => {
-                    caret = point(0.0, caret.y + advance_height);
-                }
-                '\n' => {}
-                _ => {}
+    // Split every span on line breaks, so each line is reordered on its own
+    let mut lines = vec![(String::new(), Vec::new())];
+    for span in spans {
+        for (i, part) in span.content.split('\r').enumerate() {
+            if i > 0 {
+                lines.push((String::new(), Vec::new()));
             }
-            continue;
+
+            let (line, ranges) = lines.last_mut().unwrap();
+            let start = line.len();
+            line.extend(part.chars().filter(|c| !c.is_control()));
+            ranges.push(start..line.len());
         }
-        let base_glyph = font.glyph(c);
-        if let Some(id) = last_glyph_id.take() {
-            caret.x += font.pair_kerning(scale, id, base_glyph.id());
+    }
+
+    for (i, (line, ranges)) in lines.iter().enumerate() {
+        if i > 0 {
+            caret = point(0.0, caret.y + advance_height);
         }
-        last_glyph_id = Some(base_glyph.id());
-        let mut glyph = base_glyph.scaled(scale).positioned(caret);
 
-        if let Some(bb) = glyph.pixel_bounding_box() {
-            bounds.height = bounds.height.max(bb.max.y as f32);
+        for run in visual_runs(line, ranges, direction) {
+            let span = &spans[run.span];
+            let scale = Scale::uniform(span.size);
 
-            if bb.max.x > width as i32 {
-                caret = point(0.0, caret.y + advance_height);
-                glyph.set_position(caret);
-                last_glyph_id = None;
+            for shaped in shape_run(&line[run.range.clone()], span, fallbacks, run.rtl) {
+                let position = point(caret.x + shaped.offset.x, caret.y + shaped.offset.y);
+                let mut glyph = shaped.glyph.scaled(scale).positioned(position);
+
+                if let Some(bb) = glyph.pixel_bounding_box() {
+                    if bb.max.x > width as i32 {
+                        caret = point(0.0, caret.y + advance_height);
+                        glyph.set_position(point(shaped.offset.x, caret.y + shaped.offset.y));
+                    }
+                }
+                if let Some(bb) = glyph.pixel_bounding_box() {
+                    bounds.height = bounds.height.max(bb.max.y as f32);
+                }
+                let glyph_caret = caret.x;
+                caret.x += shaped.advance + span.letter_spacing;
+                bounds.width = bounds.width.max(caret.x);
+                result.push(LaidOutGlyph {
+                    glyph,
+                    // Every (span, font) pair gets its own glyph cache font id
+                    font_id: run.span * (fallbacks.len() + 1) + shaped.chain_index,
+                    span: run.span,
+                    cluster: run.range.start + shaped.cluster,
+                    caret: glyph_caret,
+                });
             }
         }
-        caret.x += glyph.unpositioned().h_metrics().advance_width;
-        bounds.width += glyph.unpositioned().h_metrics().advance_width;
-        result.push(glyph);
     }
 
     (result, bounds)
 }
 
-fn generate_vertices(
+/// Returns the two triangles of a glyph quad
+///
+/// # Arguments
+///
+/// * `uv_rect` - Area of the atlas, in texture coordinates
+/// * `screen_rect` - Area covered on screen, in pixels, relative to `position`
+/// * `position` - Position of the text
+/// * `screen_size` - Size of the UI space
+/// * `color` - Premultiplied color of the glyph
+fn quad_vertices(
+    uv_rect: rusttype::Rect<f32>,
+    screen_rect: rusttype::Rect<f32>,
+    position: Vec2,
+    screen_size: (f32, f32),
+    color: [f32; 4],
+) -> [Vertex; 6] {
+    let min = pixels_to_clip(
+        position.x + screen_rect.min.x,
+        position.y + screen_rect.min.y,
+        screen_size.0,
+        screen_size.1,
+    );
+    let min = point(min[0], min[1]);
+
+    let max = pixels_to_clip(
+        position.x + screen_rect.max.x,
+        position.y + screen_rect.max.y,
+        screen_size.0,
+        screen_size.1,
+    );
+    let max = point(max[0], max[1]);
+    let gl_rect = rusttype::Rect { min, max };
+
+    [
+        Vertex {
+            position: [gl_rect.min.x, gl_rect.max.y],
+            tex_coords: [uv_rect.min.x, uv_rect.max.y],
+            color,
+        },
+        Vertex {
+            position: [gl_rect.min.x, gl_rect.min.y],
+            tex_coords: [uv_rect.min.x, uv_rect.min.y],
+            color,
+        },
+        Vertex {
+            position: [gl_rect.max.x, gl_rect.min.y],
+            tex_coords: [uv_rect.max.x, uv_rect.min.y],
+            color,
+        },
+        Vertex {
+            position: [gl_rect.max.x, gl_rect.min.y],
+            tex_coords: [uv_rect.max.x, uv_rect.min.y],
+            color,
+        },
+        Vertex {
+            position: [gl_rect.max.x, gl_rect.max.y],
+            tex_coords: [uv_rect.max.x, uv_rect.max.y],
+            color,
+        },
+        Vertex {
+            position: [gl_rect.min.x, gl_rect.max.y],
+            tex_coords: [uv_rect.min.x, uv_rect.max.y],
+            color,
+        },
+    ]
+}
+
+/// Glyphs copied into the atlas of a text, kept between updates so an edit only uploads the
+/// glyphs it introduces
+struct GlyphAtlas {
+    packer: ShelfPacker,
+    /// Area of each glyph in the atlas, `None` for blank glyphs
+    glyphs: HashMap<GlyphKey, Option<(rusttype::Rect<f32>, Arc<GlyphBitmap>)>>,
+    /// A glyph didn't fit during the last update
+    full: bool,
+    /// Number of times the atlas was cleared
+    clears: u32,
+}
+
+impl GlyphAtlas {
+    fn new() -> Self {
+        Self {
+            packer: ShelfPacker::new(TEXTURE_WIDTH, TEXTURE_HEIGHT),
+            glyphs: HashMap::new(),
+            full: false,
+            clears: 0,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    /// Forget every glyph, e.g. once the texture was overwritten
+    fn clear(&mut self) {
+        *self = Self {
+            clears: self.clears + 1,
+            ..Self::new()
+        };
+    }
+
+    /// Returns the area of `glyph` in the atlas and its bitmap, uploading it on first use
+    ///
+    /// # Arguments
+    ///
+    /// * `queue` - Queue the upload is scheduled on
+    /// * `texture` - Atlas of the text
+    /// * `font` - Font the glyph is taken from
+    /// * `glyph` - Glyph at its size
+    fn get(
+        &mut self,
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+        font: &rusttype::Font,
+        glyph: &rusttype::ScaledGlyph,
+    ) -> Option<(rusttype::Rect<f32>, Arc<GlyphBitmap>)> {
+        let key = GlyphKey::new(font, glyph);
+        if let Some(entry) = self.glyphs.get(&key) {
+            return entry.clone();
+        }
+
+        // Rasterized once per process, possibly ahead of time by a prewarm
+        let Some(bitmap) = glyph_cache::bitmap(font, glyph) else {
+            self.glyphs.insert(key, None);
+            return None;
+        };
+        let Some(uv_rect) = upload(
+            queue,
+            texture,
+            &mut self.packer,
+            (bitmap.width, bitmap.height),
+            &bitmap.coverage,
+        ) else {
+            // Not remembered, it may fit once the atlas is cleared
+            self.full = true;
+            return None;
+        };
+
+        self.glyphs.insert(key, Some((uv_rect, bitmap.clone())));
+        Some((uv_rect, bitmap))
+    }
+}
+
+/// Glyph of the bitmap rendering, laid out at the origin of its text
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PlacedGlyph {
+    /// Byte offset in its line of the first character drawn by the glyph
+    cluster: usize,
+    /// Horizontal position of the caret before the glyph, in pixels
+    caret: f32,
+    /// Bottom of the glyph, in pixels
+    bottom: f32,
+    /// Index of the span the glyph belongs to
+    span: usize,
+    /// Area of the atlas, in texture coordinates, and area covered on screen, in pixels, `None`
+    /// for blank glyphs
+    quad: Option<(rusttype::Rect<f32>, rusttype::Rect<f32>)>,
+}
+
+/// Glyphs of a string laid out for the bitmap rendering
+#[derive(Debug, Clone, Default, PartialEq)]
+struct GlyphLayout {
+    glyphs: Vec<PlacedGlyph>,
+    /// Size covered by the glyphs, in pixels
+    size: Vec2,
+}
+
+/// Returns the glyph laid out at `glyph`, copying it into the atlas on first use
+///
+/// # Arguments
+///
+/// * `queue` - Queue the upload is scheduled on
+/// * `texture` - Atlas of the text
+/// * `atlas` - Glyphs already in `texture`
+/// * `font` - Font the glyph is taken from
+/// * `glyph` - Glyph at its size and position
+/// * `span` - Index of the span the glyph belongs to
+/// * `cluster` - Byte offset in its line of the first character drawn by the glyph
+/// * `caret` - Horizontal position of the caret before the glyph
+#[allow(clippy::too_many_arguments)]
+fn place_glyph(
     queue: &wgpu::Queue,
     texture: &wgpu::Texture,
+    atlas: &mut GlyphAtlas,
     font: &rusttype::Font,
-    text: &str,
-    character_size: f32,
+    glyph: &PositionedGlyph,
+    span: usize,
+    cluster: usize,
+    caret: f32,
+) -> PlacedGlyph {
+    let quad = atlas
+        .get(queue, texture, font, glyph.unpositioned())
+        .map(|(uv_rect, bitmap)| {
+            // Bitmaps are rasterized at the origin, glyphs are snapped to whole pixels
+            let origin = glyph.position();
+            let min = point(
+                origin.x.round() + bitmap.offset.0 as f32,
+                origin.y.round() + bitmap.offset.1 as f32,
+            );
+            let screen_rect = rusttype::Rect {
+                min,
+                max: point(min.x + bitmap.width as f32, min.y + bitmap.height as f32),
+            };
+
+            (uv_rect, screen_rect)
+        });
+
+    PlacedGlyph {
+        cluster,
+        caret,
+        bottom: glyph.pixel_bounding_box().map_or(0., |bb| bb.max.y as f32),
+        span,
+        quad,
+    }
+}
+
+/// Lay out and shape every glyph of the spans
+///
+/// # Arguments
+///
+/// * `queue` - Queue the uploads are scheduled on
+/// * `texture` - Atlas of the text
+/// * `atlas` - Glyphs already in `texture`, cleared when the new ones don't fit
+/// * `spans` - Spans to lay out
+/// * `width` - Width the lines wrap at
+/// * `direction` - Base direction of the lines
+fn layout_glyphs(
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    atlas: &mut GlyphAtlas,
+    spans: &[TextSpan],
+    width: u32,
+    direction: TextDirection,
+) -> GlyphLayout {
+    let fallbacks = ASSETS.fallback_fonts();
+    let (glyphs, bounds) = layout_paragraph(spans, &fallbacks, width, direction);
+
+    let place = |atlas: &mut GlyphAtlas| -> Vec<PlacedGlyph> {
+        glyphs
+            .iter()
+            .map(|laid_out| {
+                place_glyph(
+                    queue,
+                    texture,
+                    atlas,
+                    glyph_font(spans, &fallbacks, laid_out.font_id),
+                    &laid_out.glyph,
+                    laid_out.span,
+                    laid_out.cluster,
+                    laid_out.caret,
+                )
+            })
+            .collect()
+    };
+
+    let reused = !atlas.is_empty();
+    atlas.full = false;
+    let mut placed = place(atlas);
+    // Glyphs of the previous strings may fill the atlas, they are dropped to make room
+    if atlas.full && reused {
+        atlas.clear();
+        placed = place(atlas);
+    }
+
+    GlyphLayout {
+        glyphs: placed,
+        size: Vec2::new(bounds.width, bounds.height),
+    }
+}
+
+/// Returns the number of glyphs laid out for `previous_text` still valid for `text`
+///
+/// The glyphs drawing the characters both strings start with are kept, except the one drawing
+/// the last of them which kerning or a ligature may bind to the changed characters.
+///
+/// # Arguments
+///
+/// * `glyphs` - Glyphs of `previous_text`, left to right
+/// * `previous_text` - String the glyphs were laid out for
+/// * `text` - New string
+fn reusable_glyphs(glyphs: &[PlacedGlyph], previous_text: &str, text: &str) -> usize {
+    let common = text
+        .char_indices()
+        .zip(previous_text.chars())
+        .find(|((_, a), b)| a != b)
+        .map_or(text.len().min(previous_text.len()), |((i, _), _)| i);
+    // Start of the last common character
+    let resume = text[..common].char_indices().last().map_or(0, |(i, _)| i);
+
+    let mut kept = glyphs
+        .iter()
+        .take_while(|glyph| glyph.cluster < resume)
+        .count();
+    // A kept ligature mustn't cover the characters laid out again
+    while kept > 0
+        && glyphs
+            .get(kept)
+            .map_or(previous_text.len(), |glyph| glyph.cluster)
+            > resume
+    {
+        kept -= 1;
+    }
+
+    kept
+}
+
+/// Returns the layout of a single span text reusing the glyphs of its previous string, only the
+/// glyphs of the characters that changed are shaped again, e.g. the last digits of a timer
+///
+/// `None` when the text must be laid out entirely: several lines, right-to-left runs or a full
+/// atlas.
+///
+/// # Arguments
+///
+/// * `queue` - Queue the uploads are scheduled on
+/// * `texture` - Atlas of the text
+/// * `atlas` - Glyphs already in `texture`
+/// * `span` - Only span of the text
+/// * `previous_text` - String `previous` was laid out for
+/// * `previous` - Layout of the previous string, with the same font and size
+/// * `width` - Width the lines wrap at
+/// * `direction` - Base direction of the line
+#[allow(clippy::too_many_arguments)]
+fn relayout_suffix(
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    atlas: &mut GlyphAtlas,
+    span: &TextSpan,
+    previous_text: &str,
+    previous: &GlyphLayout,
+    width: u32,
+    direction: TextDirection,
+) -> Option<GlyphLayout> {
+    let text = span.content.as_str();
+    let single_ltr_line = |text: &str| {
+        !text.chars().any(char::is_control)
+            && visual_runs(text, &[0..text.len()], direction)
+                .iter()
+                .all(|run| !run.rtl)
+    };
+    if !single_ltr_line(text) || !single_ltr_line(previous_text) {
+        return None;
+    }
+
+    let kept = reusable_glyphs(&previous.glyphs, previous_text, text);
+    let (start, mut caret) = match previous.glyphs.get(kept) {
+        Some(glyph) if kept > 0 => (glyph.cluster, glyph.caret),
+        _ if kept > 0 => (previous_text.len(), previous.size.x),
+        _ => (0, 0.),
+    };
+
+    let fallbacks = ASSETS.fallback_fonts();
+    let scale = Scale::uniform(span.size);
+    let ascent = span.font.v_metrics(scale).ascent;
+    let mut glyphs = previous.glyphs[..kept].to_vec();
+
+    atlas.full = false;
+    for shaped in shape_run(&text[start..], span, &fallbacks, false) {
+        let position = point(caret + shaped.offset.x, ascent + shaped.offset.y);
+        let glyph = shaped.glyph.scaled(scale).positioned(position);
+        // Wrapped lines are laid out entirely
+        if glyph
+            .pixel_bounding_box()
+            .is_some_and(|bb| bb.max.x > width as i32)
+        {
+            return None;
+        }
+
+        let font = match shaped.chain_index {
+            0 => span.font,
+            chain_index => fallbacks[chain_index - 1],
+        };
+        glyphs.push(place_glyph(
+            queue,
+            texture,
+            atlas,
+            font,
+            &glyph,
+            0,
+            start + shaped.cluster,
+            caret,
+        ));
+        caret += shaped.advance + span.letter_spacing;
+    }
+    if atlas.full {
+        return None;
+    }
+
+    let height = glyphs
+        .iter()
+        .fold(0f32, |height, glyph| height.max(glyph.bottom));
+
+    Some(GlyphLayout {
+        glyphs,
+        size: Vec2::new(caret, height),
+    })
+}
+
+/// Returns the vertices of the glyphs of a text at `position`
+///
+/// # Arguments
+///
+/// * `layout` - Glyphs of the text
+/// * `spans` - Spans the glyphs belong to
+/// * `position` - Position of the text
+/// * `screen_size` - Size of the UI space
+/// * `opacity` - Opacity of the text, from 0 to 1
+fn glyph_vertices(
+    layout: &GlyphLayout,
+    spans: &[TextSpan],
     position: Vec2,
     screen_size: (f32, f32),
-    color: &Color,
-) -> (Vec<Vertex>, Rect) {
-    let (width, height) = (TEXTURE_WIDTH, TEXTURE_HEIGHT);
-    let mut cache = Cache::builder().dimensions(width, height).build();
-    let (glyphs, mut bounds) = layout_paragraph(
-        font,
-        Scale::uniform(character_size * 1.),
-        screen_size.0 as u32,
-        text,
+    opacity: f32,
+) -> Vec<Vertex> {
+    layout
+        .glyphs
+        .iter()
+        .filter_map(|glyph| {
+            let (uv_rect, screen_rect) = glyph.quad?;
+            let color = spans[glyph.span].color.premultiplied(opacity);
+
+            Some(quad_vertices(
+                uv_rect,
+                screen_rect,
+                position,
+                screen_size,
+                color,
+            ))
+        })
+        .flatten()
+        .collect()
+}
+
+/// Strings up to this length, in bytes, are kept in the [`TextCache`]
+const CACHED_STRING_LENGTH: usize = 16;
+/// Number of layouts kept in a [`TextCache`]
+const CACHE_CAPACITY: usize = 32;
+
+/// Identifies the layout of a single span string
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    text: String,
+    /// Address of the font, see [`GlyphKey`]
+    font: usize,
+    /// Bits of the character size
+    size: u32,
+    /// Bits of the letter spacing
+    letter_spacing: u32,
+}
+
+impl CacheKey {
+    fn new(span: &TextSpan) -> Self {
+        Self {
+            text: span.content.clone(),
+            font: span.font as *const rusttype::Font as usize,
+            size: span.size.to_bits(),
+            letter_spacing: span.letter_spacing.to_bits(),
+        }
+    }
+}
+
+/// What the cached layouts depend on besides their string, font and size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LayoutContext {
+    /// Width the lines wrap at
+    width: u32,
+    direction: TextDirection,
+    /// Number of times the atlas was cleared, the glyphs point into it
+    atlas_clears: u32,
+}
+
+/// Layouts of the short strings a text displayed lately, e.g. the values of an FPS counter,
+/// reused without shaping them again when they come back
+#[derive(Debug, Default)]
+struct TextCache {
+    layouts: HashMap<CacheKey, GlyphLayout>,
+    /// Keys from the oldest to the latest inserted
+    order: VecDeque<CacheKey>,
+    context: Option<LayoutContext>,
+}
+
+impl TextCache {
+    /// Forget the layouts when `context` differs from the one they were laid out in, returns
+    /// `false` in that case
+    fn validate(&mut self, context: LayoutContext) -> bool {
+        if self.context == Some(context) {
+            return true;
+        }
+
+        self.context = Some(context);
+        self.layouts.clear();
+        self.order.clear();
+
+        false
+    }
+
+    fn get(&self, key: &CacheKey) -> Option<&GlyphLayout> {
+        self.layouts.get(key)
+    }
+
+    /// Keep the layout of a short string, the oldest layout is dropped when the cache is full
+    fn insert(&mut self, key: CacheKey, layout: GlyphLayout) {
+        if key.text.len() > CACHED_STRING_LENGTH || self.layouts.contains_key(&key) {
+            return;
+        }
+
+        if self.order.len() == CACHE_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.layouts.remove(&oldest);
+            }
+        }
+        self.order.push_back(key.clone());
+        self.layouts.insert(key, layout);
+    }
+}
+
+/// Returns the range of `vertices` differing from `previous` at the same indices, `None` when
+/// they are the same
+///
+/// Vertices following an insertion or a removal are all shifted, only a replacement keeps the
+/// end of the text in place.
+///
+/// # Arguments
+///
+/// * `previous` - Vertices in the buffer
+/// * `vertices` - New vertices
+fn changed_range(previous: &[Vertex], vertices: &[Vertex]) -> Option<Range<usize>> {
+    let same = |a: &Vertex, b: &Vertex| bytemuck::bytes_of(a) == bytemuck::bytes_of(b);
+
+    let start = previous
+        .iter()
+        .zip(vertices)
+        .take_while(|(a, b)| same(a, b))
+        .count();
+    let end = if previous.len() == vertices.len() {
+        vertices.len()
+            - previous[start..]
+                .iter()
+                .rev()
+                .zip(vertices[start..].iter().rev())
+                .take_while(|(a, b)| same(a, b))
+                .count()
+    } else {
+        vertices.len()
+    };
+
+    (start < end).then_some(start..end)
+}
+
+/// Returns the font a glyph laid out by [`layout_paragraph`] is taken from
+///
+/// # Arguments
+///
+/// * `spans` - Laid out spans
+/// * `fallbacks` - Fallback chain used by the layout
+/// * `font_id` - Glyph cache font id of the glyph
+fn glyph_font<'a>(
+    spans: &[TextSpan<'a>],
+    fallbacks: &[&'a rusttype::Font<'a>],
+    font_id: usize,
+) -> &'a rusttype::Font<'a> {
+    let chain_length = fallbacks.len() + 1;
+
+    match font_id % chain_length {
+        0 => spans[font_id / chain_length].font,
+        chain_index => fallbacks[chain_index - 1],
+    }
+}
+
+/// Copy a single channel bitmap into the atlas
+///
+/// Returns the area of the atlas, in texture coordinates, `None` when the atlas is full.
+///
+/// # Arguments
+///
+/// * `queue` - Queue the copy is scheduled on
+/// * `texture` - Atlas of the text
+/// * `packer` - Free space of the atlas
+/// * `size` - Width and height of the bitmap
+/// * `data` - Bitmap, rows first
+fn upload(
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    packer: &mut ShelfPacker,
+    (width, height): (u32, u32),
+    data: &[u8],
+) -> Option<rusttype::Rect<f32>> {
+    // A pixel of padding keeps neighbours from bleeding in when sampling the edges
+    let (x, y) = packer.pack(width + 1, height + 1)?;
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d { x, y, z: 0 },
+            aspect: wgpu::TextureAspect::All,
+        },
+        data,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(width),
+            rows_per_image: Some(height),
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+
+    let texture_size = (TEXTURE_WIDTH as f32, TEXTURE_HEIGHT as f32);
+
+    Some(rusttype::Rect {
+        min: point(x as f32 / texture_size.0, y as f32 / texture_size.1),
+        max: point(
+            (x + width) as f32 / texture_size.0,
+            (y + height) as f32 / texture_size.1,
+        ),
+    })
+}
+
+/// Bake the distance field of a glyph at [`SDF_SIZE`] into the atlas
+///
+/// Returns the area of the atlas, in texture coordinates, and the area of the field around the
+/// glyph origin, in pixels at [`SDF_SIZE`]. `None` for blank glyphs or when the atlas is full.
+fn bake_glyph(
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    packer: &mut ShelfPacker,
+    glyph: &PositionedGlyph,
+) -> Option<(rusttype::Rect<f32>, rusttype::Rect<f32>)> {
+    let baked = glyph
+        .unpositioned()
+        .unscaled()
+        .clone()
+        .scaled(Scale::uniform(SDF_SIZE))
+        .positioned(point(0., 0.));
+    let bb = baked.pixel_bounding_box()?;
+
+    // The field spreads around the glyph
+    let (width, height) = (
+        bb.width() as u32 + 2 * SPREAD,
+        bb.height() as u32 + 2 * SPREAD,
     );
+    let mut coverage = vec![0.; (width * height) as usize];
+    baked.draw(|x, y, v| coverage[((y + SPREAD) * width + x + SPREAD) as usize] = v);
+    let field = sdf::distance_field(&coverage, width, height, SPREAD);
+
+    let uv_rect = upload(queue, texture, packer, (width, height), &field)?;
+    let spread = SPREAD as f32;
+    let local_rect = rusttype::Rect {
+        min: point(bb.min.x as f32 - spread, bb.min.y as f32 - spread),
+        max: point(bb.max.x as f32 + spread, bb.max.y as f32 + spread),
+    };
+
+    Some((uv_rect, local_rect))
+}
+
+/// Same as [`layout_glyphs`] and [`glyph_vertices`], with distance field glyphs
+///
+/// The quads of the drop shadow come first, the number of their vertices is returned along with
+/// the vertices.
+#[allow(clippy::too_many_arguments)]
+fn generate_sdf_vertices(
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    spans: &[TextSpan],
+    position: Vec2,
+    screen_size: (f32, f32),
+    opacity: f32,
+    direction: TextDirection,
+    shadow: Option<(Vec2, Color)>,
+) -> (Vec<Vertex>, u32, Rect) {
+    let fallbacks = ASSETS.fallback_fonts();
+    let (glyphs, mut bounds) = layout_paragraph(spans, &fallbacks, screen_size.0 as u32, direction);
     bounds.x = position.x;
     bounds.y = position.y;
 
-    for glyph in &glyphs {
-        cache.queue_glyph(0, glyph.clone());
-    }
+    let mut packer = ShelfPacker::new(TEXTURE_WIDTH, TEXTURE_HEIGHT);
+    let mut baked = HashMap::new();
 
-    cache
-        .cache_queued(|rect, data| {
-            queue.write_texture(
-                wgpu::ImageCopyTexture {
-                    texture,
-                    mip_level: 0,
-                    origin: wgpu::Origin3d {
-                        x: rect.min.x,
-                        y: rect.min.y,
-                        z: 0,
-                    },
-                    aspect: wgpu::TextureAspect::All,
-                },
-                data,
-                wgpu::ImageDataLayout {
-                    offset: 0,
-                    bytes_per_row: Some(rect.width()),
-                    rows_per_image: Some(rect.height()),
-                },
-                wgpu::Extent3d {
-                    width: rect.width(),
-                    height: rect.height(),
-                    depth_or_array_layers: 1,
-                },
-            );
+    let quads = glyphs
+        .iter()
+        .filter_map(|laid_out| {
+            let glyph = &laid_out.glyph;
+            let (uv_rect, local_rect) = *baked
+                .entry((laid_out.font_id, glyph.id()))
+                .or_insert_with(|| bake_glyph(queue, texture, &mut packer, glyph))
+                .as_ref()?;
+
+            // Fields are baked once and scaled to the size of each glyph
+            let factor = glyph.scale().y / SDF_SIZE;
+            let origin = glyph.position();
+            let screen_rect = rusttype::Rect {
+                min: point(
+                    origin.x + local_rect.min.x * factor,
+                    origin.y + local_rect.min.y * factor,
+                ),
+                max: point(
+                    origin.x + local_rect.max.x * factor,
+                    origin.y + local_rect.max.y * factor,
+                ),
+            };
+
+            Some((uv_rect, screen_rect, laid_out.span))
         })
-        .unwrap();
+        .collect::<Vec<_>>();
 
-    let color: [f32; 3] = (*color).into();
+    let mut vertices = Vec::new();
+    if let Some((offset, color)) = shadow {
+        let color = color.premultiplied(opacity);
+        vertices.extend(quads.iter().flat_map(|(uv_rect, screen_rect, _)| {
+            quad_vertices(
+                *uv_rect,
+                *screen_rect,
+                position + offset,
+                screen_size,
+                color,
+            )
+        }));
+    }
+    let shadow_vertices = vertices.len() as u32;
 
-    let vertices = glyphs
-        .iter()
-        .filter_map(|g| cache.rect_for(0, g).ok().flatten())
-        .flat_map(|(uv_rect, screen_rect)| {
-            let min = pixels_to_clip(
-                position.x + screen_rect.min.x as f32,
-                position.y + screen_rect.min.y as f32,
-                screen_size.0,
-                screen_size.1,
-            );
-            let min = point(min[0], min[1]);
+    vertices.extend(quads.iter().flat_map(|(uv_rect, screen_rect, index)| {
+        let color = spans[*index].color.premultiplied(opacity);
+        quad_vertices(*uv_rect, *screen_rect, position, screen_size, color)
+    }));
 
-            let max = pixels_to_clip(
-                position.x + screen_rect.max.x as f32,
-                position.y + screen_rect.max.y as f32,
-                screen_size.0,
-                screen_size.1,
-            );
-            let max = point(max[0], max[1]);
-            let gl_rect = rusttype::Rect { min, max };
+    (vertices, shadow_vertices, bounds)
+}
 
-            vec![
-                Vertex {
-                    position: [gl_rect.min.x, gl_rect.max.y],
-                    tex_coords: [uv_rect.min.x, uv_rect.max.y],
-                    color,
-                },
-                Vertex {
-                    position: [gl_rect.min.x, gl_rect.min.y],
-                    tex_coords: [uv_rect.min.x, uv_rect.min.y],
-                    color,
-                },
-                Vertex {
-                    position: [gl_rect.max.x, gl_rect.min.y],
-                    tex_coords: [uv_rect.max.x, uv_rect.min.y],
-                    color,
-                },
-                Vertex {
-                    position: [gl_rect.max.x, gl_rect.min.y],
-                    tex_coords: [uv_rect.max.x, uv_rect.min.y],
-                    color,
-                },
-                Vertex {
-                    position: [gl_rect.max.x, gl_rect.max.y],
-                    tex_coords: [uv_rect.max.x, uv_rect.max.y],
-                    color,
-                },
-                Vertex {
-                    position: [gl_rect.min.x, gl_rect.max.y],
-                    tex_coords: [uv_rect.min.x, uv_rect.max.y],
-                    color,
-                },
-            ]
-        })
-        .collect();
+/// How the glyphs of a text are rasterized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextRendering {
+    /// Glyphs rasterized at their size, blurry once scaled
+    #[default]
+    Bitmap,
+    /// Signed distance fields, sharp at any scale and supporting outlines and shadows
+    Sdf,
+}
+
+/// Range the character size of an auto-fitted text stays in, see [`Text::set_auto_fit`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoFit {
+    /// Smallest size, the text overflows its container past it
+    pub min_size: f32,
+    /// Biggest size, short strings don't grow past it
+    pub max_size: f32,
+}
+
+/// Measuring a string again after scaling it corrects the rounding of the glyph advances
+const FIT_PASSES: usize = 4;
+
+/// Returns the character size making a string fit its container
+///
+/// The size of a string grows about linearly with its character size.
+///
+/// # Arguments
+///
+/// * `character_size` - Current character size
+/// * `measured` - Size of the string at `character_size`
+/// * `container` - Room available
+/// * `limits` - Range the character size stays in
+fn fit_character_size(
+    character_size: f32,
+    measured: Vec2,
+    container: Vec2,
+    limits: AutoFit,
+) -> f32 {
+    let scale = [(measured.x, container.x), (measured.y, container.y)]
+        .into_iter()
+        .filter(|(measured, _)| *measured > 0.)
+        .map(|(measured, available)| available / measured)
+        .fold(f32::INFINITY, f32::min);
+    if !scale.is_finite() {
+        return character_size.clamp(limits.min_size, limits.max_size);
+    }
 
-    (vertices, bounds)
+    (character_size * scale).clamp(limits.min_size, limits.max_size)
+}
+
+/// GPU resources of the distance field rendering, created when a text first switches to it
+struct SdfBinding {
+    fill_style: wgpu::Buffer,
+    fill_bind_group: wgpu::BindGroup,
+    shadow_bind_group: wgpu::BindGroup,
+}
+
+impl SdfBinding {
+    fn new(device: &wgpu::Device, texture: &wgpu::Texture, label: &str) -> Self {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        // Fields are interpolated, unlike bitmaps
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            label: Some(&format!("{label}-sdf-sampler")),
+            ..Default::default()
+        });
+
+        let create_style = |name: &str, style: SdfStyle| {
+            let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("{label}-sdf-{name}")),
+                contents: bytemuck::cast_slice(&[style]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: TEXT_BRUSH.get().unwrap().sdf_bind_group_layout(),
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: buffer.as_entire_binding(),
+                    },
+                ],
+                label: Some(&format!("{label}-sdf-{name}-bind-group")),
+            });
+
+            (buffer, bind_group)
+        };
+        let (fill_style, fill_bind_group) =
+            create_style("fill", SdfStyle::fill(&SdfEffects::default(), 1.));
+        let (_, shadow_bind_group) = create_style("shadow", SdfStyle::shadow());
+
+        Self {
+            fill_style,
+            fill_bind_group,
+            shadow_bind_group,
+        }
+    }
 }
 
 pub struct Text<'a> {
     context: Ctx,
+    label: String,
+    /// Concatenation of every span content
     text: String,
-    character_size: f32,
+    spans: Vec<TextSpan<'a>>,
     vertex_buffer: wgpu::Buffer,
     num_vertices: u32,
     bind_group: wgpu::BindGroup,
@@ -187,9 +1196,29 @@ pub struct Text<'a> {
     geometry_need_update: bool,
     vertices: Vec<Vertex>,
     texture: wgpu::Texture,
-    font: &'a rusttype::Font<'a>,
+    /// Glyphs of the bitmap rendering in `texture`
+    atlas: GlyphAtlas,
+    /// Layouts of the short strings displayed lately
+    cache: TextCache,
+    /// Glyphs of the displayed string, reused by the next one when the text has a single span
+    last_layout: Option<(CacheKey, GlyphLayout)>,
     bounds: Rect,
-    color: Color,
+    opacity: f32,
+    direction: TextDirection,
+    rendering: TextRendering,
+    effects: SdfEffects,
+    /// Created when the text first switches to distance fields
+    sdf: Option<SdfBinding>,
+    /// Number of vertices of the drop shadow, drawn before the glyphs
+    shadow_vertices: u32,
+    auto_fit: Option<AutoFit>,
+    /// Room the auto-fitted text must fit in
+    container: Option<Vec2>,
+    transform: Transform,
+    /// Message the string is resolved from, again each time the locale changes
+    translation: Option<Translation>,
+    /// Shared style applied to every span, again each time it changes
+    style: Option<Binding<TextStyleProperties>>,
 }
 
 impl<'a> Text<'a> {
@@ -199,8 +1228,41 @@ impl<'a> Text<'a> {
         font: &'a rusttype::Font,
         character_size: f32,
     ) -> Text<'a> {
+        Self::new_with_label(context, text, font, character_size, "Text")
+    }
+
+    /// Create a text whose GPU resources are named after `label`
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `text` - String to display
+    /// * `font` - Font used to render the string
+    /// * `character_size` - Text size
+    /// * `label` - Debug name shown by GPU debuggers, e.g. `Button#save/label`
+    pub fn new_with_label(
+        context: Ctx,
+        text: &str,
+        font: &'a rusttype::Font,
+        character_size: f32,
+        label: &str,
+    ) -> Text<'a> {
+        Self::from_spans(
+            context,
+            vec![TextSpan::new(text, font, character_size)],
+            label,
+        )
+    }
+
+    /// Create a text mixing several styles
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `spans` - Styled runs of characters, displayed one after the other
+    /// * `label` - Debug name shown by GPU debuggers
+    pub fn from_spans(context: Ctx, spans: Vec<TextSpan<'a>>, label: &str) -> Text<'a> {
         let ctx = context.lock().unwrap();
-        let color = WHITE;
 
         let texture_size = wgpu::Extent3d {
             width: TEXTURE_WIDTH,
@@ -214,7 +1276,7 @@ impl<'a> Text<'a> {
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::R8Unorm,
             usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-            label: Some("Diffuse texture"),
+            label: Some(&format!("{label}-atlas")),
             view_formats: &[],
         });
         let diffuse_texture_view =
@@ -226,19 +1288,25 @@ impl<'a> Text<'a> {
             mag_filter: wgpu::FilterMode::Nearest,
             min_filter: wgpu::FilterMode::Nearest,
             mipmap_filter: wgpu::FilterMode::Nearest,
+            label: Some(&format!("{label}-sampler")),
             ..Default::default()
         });
 
-        let (vertices, bounds) = generate_vertices(
+        let mut atlas = GlyphAtlas::new();
+        let layout = layout_glyphs(
             &ctx.queue,
             &diffuse_texture,
-            font,
-            text,
-            character_size,
-            Vec2::default(),
-            (ctx.config.width as f32, ctx.config.height as f32),
-            &color,
+            &mut atlas,
+            &spans,
+            ctx.screen_size().0 as u32,
+            TextDirection::default(),
         );
+        let vertices = glyph_vertices(&layout, &spans, Vec2::default(), ctx.screen_size(), 1.);
+        let bounds = Rect {
+            width: layout.size.x,
+            height: layout.size.y,
+            ..Default::default()
+        };
 
         let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
             layout: &TEXT_BRUSH.get().unwrap().bind_group_layout,
@@ -252,31 +1320,44 @@ impl<'a> Text<'a> {
                     resource: wgpu::BindingResource::Sampler(&diffuse_sampler),
                 },
             ],
-            label: Some("diffuse_bind_group"),
+            label: Some(&format!("{label}-bind-group")),
         });
 
         let vertex_buffer = ctx
             .device
             .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Vertex buffer"),
+                label: Some(&format!("{label}-vb")),
                 contents: bytemuck::cast_slice(&vertices),
                 usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
             });
 
         Self {
-            text: text.to_string(),
-            character_size,
+            label: label.to_string(),
+            text: spans.iter().map(|span| span.content.as_str()).collect(),
+            spans,
             vertex_buffer,
             num_vertices: vertices.len() as _,
             bind_group,
             position: Vec2::default(),
             geometry_need_update: false,
-            vertices: Vec::new(),
+            vertices,
             texture: diffuse_texture,
-            font,
+            atlas,
+            cache: TextCache::default(),
+            last_layout: None,
             context: context.clone(),
             bounds,
-            color,
+            opacity: 1.,
+            direction: TextDirection::default(),
+            rendering: TextRendering::default(),
+            effects: SdfEffects::default(),
+            sdf: None,
+            shadow_vertices: 0,
+            auto_fit: None,
+            container: None,
+            transform: Transform::default(),
+            translation: None,
+            style: None,
         }
     }
 
@@ -284,73 +1365,455 @@ impl<'a> Text<'a> {
         self.bounds
     }
 
+    /// Returns the debug name of the text
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
     fn ensure_geometry_update(&mut self) {
         if !self.geometry_need_update {
             return;
         }
 
         self.geometry_need_update = false;
-        self.vertices.clear();
         self.bounds = Rect::default();
 
-        let ctx = self.context.lock().unwrap();
+        let context = self.context.clone();
+        let mut ctx = context.lock().unwrap();
 
-        let (vertices, bounds) = generate_vertices(
-            &ctx.queue,
-            &self.texture,
-            self.font,
-            &self.text,
-            self.character_size,
-            self.position,
-            (ctx.config.width as f32, ctx.config.height as f32),
-            &self.color,
-        );
-        self.vertices = vertices;
+        let (mut vertices, shadow_vertices, bounds) = match self.rendering {
+            TextRendering::Bitmap => {
+                let screen_size = ctx.screen_size();
+                let layout = self.layout_bitmap(&ctx.queue, screen_size.0 as u32);
+                let vertices = glyph_vertices(
+                    &layout,
+                    &self.spans,
+                    self.position,
+                    screen_size,
+                    self.opacity,
+                );
+                let bounds = Rect {
+                    x: self.position.x,
+                    y: self.position.y,
+                    width: layout.size.x,
+                    height: layout.size.y,
+                };
+
+                (vertices, 0, bounds)
+            }
+            TextRendering::Sdf => {
+                if let Some(sdf) = &self.sdf {
+                    ctx.queue.write_buffer(
+                        &sdf.fill_style,
+                        0,
+                        bytemuck::cast_slice(&[SdfStyle::fill(&self.effects, self.opacity)]),
+                    );
+                }
+
+                generate_sdf_vertices(
+                    &ctx.queue,
+                    &self.texture,
+                    &self.spans,
+                    self.position,
+                    ctx.screen_size(),
+                    self.opacity,
+                    self.direction,
+                    self.effects.shadow,
+                )
+            }
+        };
+        if !self.transform.is_identity() {
+            transform::transform_vertices(self.matrix(), &mut vertices, ctx.screen_size());
+        }
+        let previous = std::mem::replace(&mut self.vertices, vertices);
+        self.shadow_vertices = shadow_vertices;
         self.bounds = bounds;
         self.num_vertices = self.vertices.len() as _;
 
-        ctx.queue
-            .write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&self.vertices));
+        let contents: &[u8] = bytemuck::cast_slice(&self.vertices);
+        // The string may have grown since the buffer creation
+        if contents.len() as wgpu::BufferAddress > self.vertex_buffer.size() {
+            let buffer = ctx
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!("{}-vb", self.label)),
+                    contents,
+                    usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                });
+            ctx.retire(std::mem::replace(&mut self.vertex_buffer, buffer));
+        } else if let Some(range) = changed_range(&previous, &self.vertices) {
+            // Typing a character only rewrites the quads from the caret onwards
+            let offset = range.start * std::mem::size_of::<Vertex>();
+            ctx.queue.write_buffer(
+                &self.vertex_buffer,
+                offset as wgpu::BufferAddress,
+                bytemuck::cast_slice(&self.vertices[range]),
+            );
+        }
+    }
+
+    /// Returns the glyphs of the bitmap rendering, reusing a cached layout or the glyphs of the
+    /// previous string when possible
+    ///
+    /// # Arguments
+    ///
+    /// * `queue` - Queue the uploads are scheduled on
+    /// * `width` - Width the lines wrap at
+    fn layout_bitmap(&mut self, queue: &wgpu::Queue, width: u32) -> GlyphLayout {
+        let direction = self.direction;
+        let context = |atlas: &GlyphAtlas| LayoutContext {
+            width,
+            direction,
+            atlas_clears: atlas.clears,
+        };
+        if !self.cache.validate(context(&self.atlas)) {
+            self.last_layout = None;
+        }
+
+        let key = match self.spans.as_slice() {
+            [span] => Some(CacheKey::new(span)),
+            _ => None,
+        };
+        let mut layout = key.as_ref().and_then(|key| self.cache.get(key).cloned());
+        if let (true, Some(key), Some((previous_key, previous))) =
+            (layout.is_none(), &key, &self.last_layout)
+        {
+            let style = |key: &CacheKey| (key.font, key.size, key.letter_spacing);
+            if style(previous_key) == style(key) {
+                layout = relayout_suffix(
+                    queue,
+                    &self.texture,
+                    &mut self.atlas,
+                    &self.spans[0],
+                    &previous_key.text,
+                    previous,
+                    width,
+                    direction,
+                );
+            }
+        }
+
+        let layout = match layout {
+            Some(layout) => layout,
+            None => {
+                let layout = layout_glyphs(
+                    queue,
+                    &self.texture,
+                    &mut self.atlas,
+                    &self.spans,
+                    width,
+                    direction,
+                );
+                // The atlas may have been cleared to make room, the cached glyphs point to its
+                // former content
+                self.cache.validate(context(&self.atlas));
+
+                layout
+            }
+        };
+
+        self.last_layout = key.map(|key| {
+            self.cache.insert(key.clone(), layout.clone());
+            (key, layout.clone())
+        });
+
+        layout
+    }
+
+    /// Returns the displayed string
+    pub fn string(&self) -> &str {
+        &self.text
+    }
+
+    /// Set the displayed string, the style of the first span is applied to the whole string.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - New string to display
+    pub fn set_string(&mut self, text: &str) {
+        if self.text == text {
+            return;
+        }
+
+        self.spans.truncate(1);
+        self.spans[0].content = text.to_string();
+        self.text = text.to_string();
+
+        self.geometry_need_update = true;
+        self.fit();
+    }
+
+    /// Display a translated message, resolved again when the locale changes, see
+    /// [`crate::Context::set_locale`]
+    ///
+    /// # Arguments
+    ///
+    /// * `translation` - Key and arguments of the message, `None` to keep the displayed string
+    pub fn set_translation(&mut self, translation: Option<Translation>) {
+        self.translation = translation;
+        self.follow_locale();
+    }
+
+    pub fn translation(&self) -> Option<&Translation> {
+        self.translation.as_ref()
+    }
+
+    /// Resolve the translated message again if the locale changed, returns `true` if the
+    /// displayed string changed and the owner must lay it out again
+    pub fn follow_locale(&mut self) -> bool {
+        let Some(text) = self.translation.as_mut().and_then(Translation::changed) else {
+            return false;
+        };
+        if text == self.text {
+            return false;
+        }
+
+        self.set_string(&text);
+        self.ensure_geometry_update();
+
+        true
+    }
+
+    /// Follow a style shared with other texts, its font, size, color, spacing and outline
+    /// replace those of every span
+    ///
+    /// # Arguments
+    ///
+    /// * `style` - Shared style, `None` to keep the current look and stop following it
+    pub fn set_style(&mut self, style: Option<TextStyle>) {
+        self.style = style.map(Binding::new);
+        self.follow_style();
+    }
+
+    /// Apply the shared style again if it changed, returns `true` if the owner must lay the text
+    /// out again
+    pub fn follow_style(&mut self) -> bool {
+        let Some(style) = self.style.as_mut().and_then(Binding::changed) else {
+            return false;
+        };
+
+        for span in &mut self.spans {
+            span.font = style.font;
+            span.size = style.size;
+            span.color = style.color;
+            span.letter_spacing = style.letter_spacing;
+        }
+        self.effects.outline = style.outline;
+
+        self.geometry_need_update = true;
+        self.fit();
+        // The owner reads the new bounds to lay the text out
+        self.ensure_geometry_update();
+
+        true
+    }
+
+    pub fn spans(&self) -> &[TextSpan<'a>] {
+        &self.spans
+    }
+
+    /// Replace the styled runs of the text.
+    ///
+    /// # Arguments
+    ///
+    /// * `spans` - New spans, must not be empty
+    pub fn set_spans(&mut self, spans: Vec<TextSpan<'a>>) {
+        assert!(!spans.is_empty(), "a text needs at least one span");
+
+        self.text = spans.iter().map(|span| span.content.as_str()).collect();
+        self.spans = spans;
+
+        self.geometry_need_update = true;
+        self.fit();
     }
 
-    /// Set the fill color of the text.
+    /// Set the fill color of the whole text.
     ///
     /// # Arguments
     ///
     /// * `color` - New text color
     pub fn set_fill_color(&mut self, color: Color) {
-        self.color = color;
+        self.spans.iter_mut().for_each(|span| span.color = color);
 
-        self.vertices
+        // The drop shadow keeps its own color
+        let color = color.premultiplied(self.opacity);
+        self.vertices[self.shadow_vertices as usize..]
             .iter_mut()
-            .for_each(|vertex| vertex.color = color.into());
+            .for_each(|vertex| vertex.color = color);
 
         let ctx = self.context.lock().unwrap();
         ctx.queue
             .write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&self.vertices));
     }
 
-    /// Set the character size.
+    /// Set the opacity of the whole text, from 0 (transparent) to 1 (opaque)
+    ///
+    /// # Arguments
+    ///
+    /// * `opacity` - New text opacity
+    pub fn set_opacity(&mut self, opacity: f32) {
+        if self.opacity == opacity {
+            return;
+        }
+
+        self.opacity = opacity;
+        self.geometry_need_update = true;
+    }
+
+    pub fn direction(&self) -> TextDirection {
+        self.direction
+    }
+
+    /// Set the base direction of the text, which side a mixed-direction line starts from
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - New base direction
+    pub fn set_direction(&mut self, direction: TextDirection) {
+        if self.direction == direction {
+            return;
+        }
+
+        self.direction = direction;
+        self.geometry_need_update = true;
+    }
+
+    pub fn rendering(&self) -> TextRendering {
+        self.rendering
+    }
+
+    /// Switch between bitmap glyphs and distance field glyphs, the latter stay sharp when the
+    /// text is scaled or zoomed
+    ///
+    /// # Arguments
+    ///
+    /// * `rendering` - How glyphs are rasterized
+    pub fn set_rendering(&mut self, rendering: TextRendering) {
+        if self.rendering == rendering {
+            return;
+        }
+
+        if rendering == TextRendering::Sdf && self.sdf.is_none() {
+            let ctx = self.context.lock().unwrap();
+            self.sdf = Some(SdfBinding::new(&ctx.device, &self.texture, &self.label));
+        }
+        // Both renderings share the texture, distance fields overwrite the bitmaps
+        self.atlas.clear();
+
+        self.rendering = rendering;
+        self.geometry_need_update = true;
+    }
+
+    pub fn effects(&self) -> &SdfEffects {
+        &self.effects
+    }
+
+    /// Set the outline and drop shadow of the text, only drawn with [`TextRendering::Sdf`]
+    ///
+    /// # Arguments
+    ///
+    /// * `effects` - New effects
+    pub fn set_effects(&mut self, effects: SdfEffects) {
+        if self.effects == effects {
+            return;
+        }
+
+        self.effects = effects;
+        self.geometry_need_update = true;
+    }
+
+    /// Set the character size of the whole text.
     ///
     /// # Arguments
     ///
     /// * `character_size` - New text size
     pub fn set_character_size(&mut self, character_size: f32) {
-        self.character_size = character_size;
+        self.spans
+            .iter_mut()
+            .for_each(|span| span.size = character_size);
 
         self.geometry_need_update = true;
     }
+
+    /// Let the character size follow the room available, e.g. a button label whose translations
+    /// have very different lengths, the whole text takes one character size
+    ///
+    /// # Arguments
+    ///
+    /// * `auto_fit` - Range of the character size, `None` to keep the size set by the application
+    pub fn set_auto_fit(&mut self, auto_fit: Option<AutoFit>) {
+        self.auto_fit = auto_fit;
+        self.fit();
+    }
+
+    pub fn auto_fit(&self) -> Option<AutoFit> {
+        self.auto_fit
+    }
+
+    /// Set the room the auto-fitted text must fit in, must be called when the container is
+    /// resized, the text is fitted again when its string changes
+    ///
+    /// # Arguments
+    ///
+    /// * `container` - Width and height available, in pixels
+    pub fn set_container(&mut self, container: Vec2) {
+        if self.container == Some(container) {
+            return;
+        }
+
+        self.container = Some(container);
+        self.fit();
+    }
+
+    /// Change the character size until the string fits its container
+    fn fit(&mut self) {
+        let (Some(limits), Some(container)) = (self.auto_fit, self.container) else {
+            return;
+        };
+
+        for _ in 0..FIT_PASSES {
+            self.ensure_geometry_update();
+
+            let character_size = self.spans[0].size;
+            let measured = Vec2::new(self.bounds.width, self.bounds.height);
+            let fitted = fit_character_size(character_size, measured, container, limits);
+            if (fitted - character_size).abs() < 0.5 {
+                break;
+            }
+
+            self.set_character_size(fitted);
+        }
+        self.ensure_geometry_update();
+    }
 }
 
 impl<'a> Drawable for Text<'a> {
     fn draw<'b>(&'b mut self, render_pass: &mut wgpu::RenderPass<'b>) {
+        self.follow_style();
+        self.follow_locale();
         self.ensure_geometry_update();
 
-        render_pass.set_pipeline(TEXT_BRUSH.get().unwrap().render_pipeline());
-
-        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.insert_debug_marker(&self.label);
         render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-        render_pass.draw(0..self.num_vertices, 0..1);
+
+        match (&self.sdf, self.rendering) {
+            (Some(sdf), TextRendering::Sdf) => {
+                render_pass.set_pipeline(text_brush().sdf_render_pipeline());
+
+                if self.shadow_vertices > 0 {
+                    render_pass.set_bind_group(1, &sdf.shadow_bind_group, &[]);
+                    render_pass.draw(0..self.shadow_vertices, 0..1);
+                }
+                render_pass.set_bind_group(1, &sdf.fill_bind_group, &[]);
+                render_pass.draw(self.shadow_vertices..self.num_vertices, 0..1);
+            }
+            _ => {
+                render_pass.set_pipeline(text_brush().render_pipeline());
+                render_pass.set_bind_group(1, &self.bind_group, &[]);
+                render_pass.draw(0..self.num_vertices, 0..1);
+            }
+        }
+        debug::record_draw(self.num_vertices);
     }
 }
 
@@ -364,86 +1827,141 @@ impl<'a> Transformable for Text<'a> {
 
         self.geometry_need_update = true;
     }
+
+    fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+
+        self.geometry_need_update = true;
+    }
+
+    fn matrix(&self) -> Mat3 {
+        self.transform.matrix(self.position)
+    }
 }
 
 pub struct TextBrush {
     bind_group_layout: wgpu::BindGroupLayout,
     render_pipeline: wgpu::RenderPipeline,
+    /// Layout of the distance field atlas, its sampler and the style uniform
+    sdf_bind_group_layout: wgpu::BindGroupLayout,
+    sdf_render_pipeline: wgpu::RenderPipeline,
 }
 
 impl TextBrush {
-    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        blend: BlendMode,
+    ) -> Self {
         let shader = device.create_shader_module(wgpu::include_wgsl!("../shaders/text.wgsl"));
+        let sdf_shader =
+            device.create_shader_module(wgpu::include_wgsl!("../shaders/text_sdf.wgsl"));
 
-        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        multisampled: false,
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    // This should match the filterable field of the
-                    // corresponding Texture entry above.
-                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                    count: None,
+        let texture_entries = [
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
                 },
-            ],
-            label: Some("texture_bind_group_layout"),
-        });
-
-        let render_pipeline_layout =
-            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("Render pipeline layout"),
-                bind_group_layouts: &[&bind_group_layout],
-                push_constant_ranges: &[],
-            });
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Text render pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[Vertex::desc()],
+                count: None,
             },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                // cull_mode: Some(wgpu::Face::Back),
-                cull_mode: None,
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                // This should match the filterable field of the
+                // corresponding Texture entry above.
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
             },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
+        ];
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &texture_entries,
+            label: Some("Text bind group layout"),
         });
+        let sdf_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    texture_entries[0],
+                    texture_entries[1],
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+                label: Some("Text SDF bind group layout"),
+            });
+
+        let create_pipeline =
+            |label: &str, layout: &wgpu::BindGroupLayout, shader: &wgpu::ShaderModule| {
+                let render_pipeline_layout =
+                    device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                        label: Some(&format!("{label} layout")),
+                        bind_group_layouts: &[CAMERA_BIND_GROUP_LAYOUT.get().unwrap(), layout],
+                        push_constant_ranges: &[],
+                    });
+
+                device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some(label),
+                    layout: Some(&render_pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: shader,
+                        entry_point: "vs_main",
+                        buffers: &[Vertex::desc()],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: shader,
+                        entry_point: "fs_main",
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format,
+                            blend: Some(blend.state()),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: wgpu::PrimitiveState {
+                        topology: wgpu::PrimitiveTopology::TriangleList,
+                        strip_index_format: None,
+                        front_face: wgpu::FrontFace::Ccw,
+                        // cull_mode: Some(wgpu::Face::Back),
+                        cull_mode: None,
+                        polygon_mode: wgpu::PolygonMode::Fill,
+                        unclipped_depth: false,
+                        conservative: false,
+                    },
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState {
+                        count: sample_count,
+                        mask: !0,
+                        alpha_to_coverage_enabled: false,
+                    },
+                    multiview: None,
+                })
+            };
+        let render_pipeline = create_pipeline("Text render pipeline", &bind_group_layout, &shader);
+        let sdf_render_pipeline = create_pipeline(
+            "Text SDF render pipeline",
+            &sdf_bind_group_layout,
+            &sdf_shader,
+        );
 
         Self {
             render_pipeline,
             bind_group_layout,
+            sdf_bind_group_layout,
+            sdf_render_pipeline,
         }
     }
 
@@ -455,7 +1973,191 @@ impl TextBrush {
         &self.render_pipeline
     }
 
+    pub fn sdf_bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.sdf_bind_group_layout
+    }
+
+    pub fn sdf_render_pipeline(&self) -> &wgpu::RenderPipeline {
+        &self.sdf_render_pipeline
+    }
+
     pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
         render_pass.set_pipeline(&self.render_pipeline);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::{
+        changed_range, fit_character_size, reusable_glyphs, visual_runs, AutoFit, CacheKey,
+        GlyphLayout, LayoutContext, PlacedGlyph, Run, TextCache, TextDirection, CACHE_CAPACITY,
+    };
+    use crate::graphics::Vertex;
+
+    fn vertices(xs: &[f32]) -> Vec<Vertex> {
+        xs.iter()
+            .map(|x| Vertex {
+                position: [*x, 0.],
+                color: [1.; 4],
+                tex_coords: [0.; 2],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn only_changed_vertices_are_rewritten() {
+        let previous = vertices(&[0., 1., 2., 3.]);
+
+        assert_eq!(changed_range(&previous, &previous), None);
+        // Replaced in place, the end is untouched
+        assert_eq!(
+            changed_range(&previous, &vertices(&[0., 5., 2., 3.])),
+            Some(1..2)
+        );
+        // Appended
+        assert_eq!(
+            changed_range(&previous, &vertices(&[0., 1., 2., 3., 4.])),
+            Some(4..5)
+        );
+        // Inserted, the following vertices are shifted
+        assert_eq!(
+            changed_range(&previous, &vertices(&[0., 5., 1., 2., 3.])),
+            Some(1..5)
+        );
+        // Removed at the end, the buffer tail isn't drawn anymore
+        assert_eq!(changed_range(&previous, &vertices(&[0., 1.])), None);
+    }
+
+    fn glyphs(clusters: &[usize]) -> Vec<PlacedGlyph> {
+        clusters
+            .iter()
+            .map(|cluster| PlacedGlyph {
+                cluster: *cluster,
+                caret: *cluster as f32 * 10.,
+                bottom: 10.,
+                span: 0,
+                quad: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn glyphs_of_the_common_prefix_are_reused() {
+        let digits = glyphs(&[0, 1, 2, 3, 4]);
+
+        // The glyph before the first change may be kerned with it
+        assert_eq!(reusable_glyphs(&digits, "12:59", "12:60"), 2);
+        assert_eq!(reusable_glyphs(&digits[..2], "12", "123"), 1);
+        assert_eq!(reusable_glyphs(&digits[..3], "123", "12"), 1);
+        assert_eq!(reusable_glyphs(&digits[..3], "abc", "xbc"), 0);
+
+        // "ffi" is a single ligature glyph, it is shaped again with the changed "i"
+        let ligature = glyphs(&[0, 1, 4, 5]);
+        assert_eq!(reusable_glyphs(&ligature, "office", "offline"), 1);
+    }
+
+    #[test]
+    fn cache_keeps_the_latest_short_strings() {
+        let key = |text: &str| CacheKey {
+            text: text.to_string(),
+            font: 1,
+            size: 16f32.to_bits(),
+            letter_spacing: 0,
+        };
+        let context = LayoutContext {
+            width: 800,
+            direction: TextDirection::Auto,
+            atlas_clears: 0,
+        };
+        let mut cache = TextCache::default();
+
+        assert!(!cache.validate(context));
+        assert!(cache.validate(context));
+
+        cache.insert(key("60 FPS"), GlyphLayout::default());
+        cache.insert(
+            key("a string too long to be cached"),
+            GlyphLayout::default(),
+        );
+        assert!(cache.get(&key("60 FPS")).is_some());
+        assert!(cache.get(&key("a string too long to be cached")).is_none());
+
+        (0..CACHE_CAPACITY).for_each(|i| cache.insert(key(&i.to_string()), GlyphLayout::default()));
+        assert!(cache.get(&key("60 FPS")).is_none());
+        assert!(cache.get(&key("0")).is_some());
+
+        // Glyphs point to the atlas content, they are dropped with it
+        assert!(!cache.validate(LayoutContext {
+            atlas_clears: 1,
+            ..context
+        }));
+        assert!(cache.get(&key("0")).is_none());
+    }
+
+    #[test]
+    fn rtl_runs_are_reordered() {
+        // Latin, then two Hebrew words in their own spans
+        let line = "ab אב גד";
+        let spans = [0..3, 3..7, 7..line.len()];
+        let runs = visual_runs(line, &spans, TextDirection::LeftToRight);
+
+        assert_eq!(
+            runs,
+            vec![
+                Run {
+                    span: 0,
+                    range: 0..3,
+                    rtl: false
+                },
+                Run {
+                    span: 2,
+                    range: 7..line.len(),
+                    rtl: true
+                },
+                Run {
+                    span: 1,
+                    range: 3..7,
+                    rtl: true
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn auto_direction_follows_first_strong_character() {
+        let line = "שלום hi";
+        let runs = visual_runs(line, &[0..line.len()], TextDirection::Auto);
+
+        // The Latin word is displayed first, on the left of the Hebrew one
+        assert_eq!(runs.first().map(|run| &line[run.range.clone()]), Some("hi"));
+        assert!(runs.last().unwrap().rtl);
+    }
+
+    #[test]
+    fn character_size_fits_the_container_within_limits() {
+        let limits = AutoFit {
+            min_size: 10.,
+            max_size: 40.,
+        };
+        let container = Vec2::new(100., 50.);
+
+        // Too wide, shrunk by half
+        assert_eq!(
+            fit_character_size(30., Vec2::new(200., 30.), container, limits),
+            15.
+        );
+        // Short string, grown until the height is filled but not past the limit
+        assert_eq!(
+            fit_character_size(20., Vec2::new(40., 20.), container, limits),
+            40.
+        );
+        assert_eq!(
+            fit_character_size(30., Vec2::new(1000., 30.), container, limits),
+            10.
+        );
+        // Nothing to measure
+        assert_eq!(fit_character_size(30., Vec2::ZERO, container, limits), 30.);
+    }
+}
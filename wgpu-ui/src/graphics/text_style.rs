@@ -0,0 +1,86 @@
+//! Typography shared by many texts, e.g. the headings or the body of a whole application, edited
+//! in one place
+
+use rusttype::Font;
+
+use super::color::{Color, WHITE};
+use crate::ui::state::State;
+
+/// Font, size, color, spacing and outline of the texts using a style
+#[derive(Clone, Copy)]
+pub struct TextStyleProperties {
+    pub font: &'static Font<'static>,
+    pub size: f32,
+    pub color: Color,
+    /// Extra space after each glyph, in pixels, negative to tighten the text
+    pub letter_spacing: f32,
+    /// Thickness and color, only drawn with [`super::text::TextRendering::Sdf`]
+    pub outline: Option<(f32, Color)>,
+}
+
+impl TextStyleProperties {
+    pub fn new(font: &'static Font<'static>, size: f32) -> Self {
+        Self {
+            font,
+            size,
+            color: WHITE,
+            letter_spacing: 0.,
+            outline: None,
+        }
+    }
+}
+
+impl PartialEq for TextStyleProperties {
+    fn eq(&self, other: &Self) -> bool {
+        // Fonts are assets, the same font has the same address
+        std::ptr::eq(self.font, other.font)
+            && self.size == other.size
+            && self.color == other.color
+            && self.letter_spacing == other.letter_spacing
+            && self.outline == other.outline
+    }
+}
+
+impl std::fmt::Debug for TextStyleProperties {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TextStyleProperties")
+            .field("size", &self.size)
+            .field("color", &self.color)
+            .field("letter_spacing", &self.letter_spacing)
+            .field("outline", &self.outline)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Handle of a style, clones share the same properties and the texts using it follow its
+/// changes on their next frame, see [`super::text::Text::set_style`]
+pub type TextStyle = State<TextStyleProperties>;
+
+#[cfg(test)]
+mod tests {
+    use super::{TextStyle, TextStyleProperties};
+    use crate::graphics::color::Color;
+    use crate::ui::state::Binding;
+    use crate::ASSETS;
+
+    #[test]
+    fn every_text_using_a_style_follows_its_changes() {
+        let font = ASSETS.get_font("Roboto.ttf").unwrap();
+        let heading = TextStyle::new(TextStyleProperties::new(font, 24.));
+        let (mut title, mut subtitle) =
+            (Binding::new(heading.clone()), Binding::new(heading.clone()));
+        assert!(title.changed().is_some() && subtitle.changed().is_some());
+
+        heading.update(|style| style.color = Color::from((200, 30, 30)));
+        // Applied once by each text
+        assert_eq!(
+            title.changed().map(|style| style.color),
+            Some(Color::from((200, 30, 30)))
+        );
+        assert_eq!(title.changed(), None);
+        assert_eq!(subtitle.changed().map(|style| style.size), Some(24.));
+
+        heading.update(|style| style.size = 24.);
+        assert_eq!(title.changed(), None);
+    }
+}
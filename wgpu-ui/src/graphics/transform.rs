@@ -0,0 +1,150 @@
+//! Rotation and scale of the drawables and widgets around an origin, applied on top of their
+//! position
+
+use glam::{Mat3, Vec2};
+
+use super::Vertex;
+use crate::math::Rect;
+
+/// Rotation and scale applied around an origin
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    /// Clockwise rotation, in radians
+    pub rotation: f32,
+    pub scale: Vec2,
+    /// Point the object rotates and scales around, relative to its top-left corner
+    pub origin: Vec2,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            rotation: 0.,
+            scale: Vec2::ONE,
+            origin: Vec2::ZERO,
+        }
+    }
+}
+
+impl Transform {
+    pub fn is_identity(&self) -> bool {
+        self.rotation == 0. && self.scale == Vec2::ONE
+    }
+
+    /// Returns the matrix moving the points of an object at `position` where they are displayed
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - Position of the object, its top-left corner before the transform
+    pub fn matrix(&self, position: Vec2) -> Mat3 {
+        let pivot = position + self.origin;
+
+        // The y axis points down, so a positive angle turns clockwise on screen
+        Mat3::from_translation(pivot)
+            * Mat3::from_angle(self.rotation)
+            * Mat3::from_scale(self.scale)
+            * Mat3::from_translation(-pivot)
+    }
+}
+
+/// Returns the box enclosing `rect` once moved by `matrix`
+///
+/// # Arguments
+///
+/// * `matrix` - Transform of the object, see [`Transform::matrix`]
+/// * `rect` - Untransformed bounds
+pub fn transform_rect(matrix: Mat3, rect: Rect) -> Rect {
+    let corners = [
+        Vec2::new(rect.x, rect.y),
+        Vec2::new(rect.x, rect.y + rect.height),
+        Vec2::new(rect.x + rect.width, rect.y + rect.height),
+        Vec2::new(rect.x + rect.width, rect.y),
+    ]
+    .map(|corner| matrix.transform_point2(corner));
+    let min = corners.into_iter().reduce(Vec2::min).unwrap();
+    let max = corners.into_iter().reduce(Vec2::max).unwrap();
+
+    Rect {
+        x: min.x,
+        y: min.y,
+        width: max.x - min.x,
+        height: max.y - min.y,
+    }
+}
+
+/// Returns where `point` was before being moved by `matrix`, `None` if the object is scaled to
+/// nothing
+///
+/// # Arguments
+///
+/// * `matrix` - Transform of the object, see [`Transform::matrix`]
+/// * `point` - Displayed point, e.g. the cursor position
+pub fn untransform_point(matrix: Mat3, point: Vec2) -> Option<Vec2> {
+    if matrix.determinant() == 0. {
+        return None;
+    }
+
+    Some(matrix.inverse().transform_point2(point))
+}
+
+/// Move vertices already converted to clip space by `matrix`, which works in pixels
+///
+/// # Arguments
+///
+/// * `matrix` - Transform of the object, see [`Transform::matrix`]
+/// * `vertices` - Vertices to move
+/// * `screen_size` - Size of the UI space
+pub fn transform_vertices(matrix: Mat3, vertices: &mut [Vertex], screen_size: (f32, f32)) {
+    let (width, height) = screen_size;
+
+    for vertex in vertices {
+        let [x, y] = vertex.position;
+        let pixel = Vec2::new((x + 1.) / 2. * width, (1. - y) / 2. * height);
+        let moved = matrix.transform_point2(pixel);
+
+        vertex.position = crate::math::pixels_to_clip(moved.x, moved.y, width, height);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use glam::Vec2;
+
+    use super::{transform_rect, untransform_point, Transform};
+    use crate::math::Rect;
+
+    #[test]
+    fn rotating_around_the_center_keeps_the_hit_area_on_the_visible_shape() {
+        let rect = Rect {
+            x: 10.,
+            y: 10.,
+            width: 40.,
+            height: 20.,
+        };
+        let matrix = Transform {
+            rotation: FRAC_PI_2,
+            origin: Vec2::new(20., 10.),
+            ..Default::default()
+        }
+        .matrix(rect.position());
+
+        let bounds = transform_rect(matrix, rect);
+        assert!((bounds.x - 20.).abs() < 1e-4 && (bounds.y - 0.).abs() < 1e-4);
+        assert!((bounds.width - 20.).abs() < 1e-4 && (bounds.height - 40.).abs() < 1e-4);
+
+        // Above the unrotated shape, but on the rotated one
+        let local = untransform_point(matrix, Vec2::new(30., 5.)).unwrap();
+        assert!(rect.contains(local));
+        // On the unrotated shape, but no longer on the rotated one
+        let local = untransform_point(matrix, Vec2::new(12., 20.)).unwrap();
+        assert!(!rect.contains(local));
+
+        let flat = Transform {
+            scale: Vec2::new(0., 1.),
+            ..Default::default()
+        };
+        assert_eq!(untransform_point(flat.matrix(Vec2::ZERO), Vec2::ZERO), None);
+    }
+}
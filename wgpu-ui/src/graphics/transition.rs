@@ -0,0 +1,252 @@
+//! Animated passage from a scene to another, both rendered into textures then blended
+
+use glam::Vec2;
+
+use super::{render_texture::RenderTexture, Drawable, Transformable};
+use crate::math::Rect;
+use crate::Ctx;
+
+/// Side the incoming scene moves toward
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlideDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl SlideDirection {
+    fn vector(self) -> Vec2 {
+        match self {
+            Self::Left => Vec2::new(-1., 0.),
+            Self::Right => Vec2::new(1., 0.),
+            Self::Up => Vec2::new(0., -1.),
+            Self::Down => Vec2::new(0., 1.),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransitionEffect {
+    /// The incoming scene fades in over the outgoing one
+    #[default]
+    CrossFade,
+    /// The incoming scene pushes the outgoing one out of the screen
+    Slide(SlideDirection),
+    /// The incoming scene is revealed through an opening growing from the center of the screen
+    IrisWipe,
+}
+
+/// Region of a scene texture drawn, where and how opaque
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Placement {
+    source: Rect,
+    position: Vec2,
+    opacity: f32,
+}
+
+/// Returns the placements of the outgoing then the incoming scene
+///
+/// # Arguments
+///
+/// * `effect` - Blending of the scenes
+/// * `progress` - Eased progress of the transition, from 0 to 1
+/// * `screen_size` - Size of the UI space, the size of the textures
+fn placements(effect: TransitionEffect, progress: f32, screen_size: Vec2) -> [Placement; 2] {
+    let full = Rect {
+        x: 0.,
+        y: 0.,
+        width: screen_size.x,
+        height: screen_size.y,
+    };
+    let outgoing = Placement {
+        source: full,
+        position: Vec2::ZERO,
+        opacity: 1.,
+    };
+
+    match effect {
+        TransitionEffect::CrossFade => [
+            outgoing,
+            Placement {
+                opacity: progress,
+                ..outgoing
+            },
+        ],
+        TransitionEffect::Slide(direction) => {
+            let offset = direction.vector() * screen_size;
+
+            [
+                Placement {
+                    position: offset * progress,
+                    ..outgoing
+                },
+                Placement {
+                    position: offset * (progress - 1.),
+                    ..outgoing
+                },
+            ]
+        }
+        TransitionEffect::IrisWipe => {
+            let size = screen_size * progress;
+            let position = (screen_size - size) / 2.;
+
+            [
+                outgoing,
+                Placement {
+                    source: Rect {
+                        x: position.x,
+                        y: position.y,
+                        width: size.x,
+                        height: size.y,
+                    },
+                    position,
+                    opacity: 1.,
+                },
+            ]
+        }
+    }
+}
+
+/// Blends the outgoing scene into the incoming one over a duration
+///
+/// Both scenes are rendered each frame into their texture, through [`Transition::outgoing`]
+/// and [`Transition::incoming`], so they keep animating during the transition.
+pub struct Transition {
+    outgoing: RenderTexture,
+    incoming: RenderTexture,
+    effect: TransitionEffect,
+    /// Size of the UI space when the textures were created
+    screen_size: Vec2,
+    /// Duration of the transition, in seconds
+    duration: f32,
+    elapsed: f32,
+}
+
+impl Transition {
+    /// Create a transition starting with the outgoing scene fully shown
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `effect` - Blending of the scenes
+    /// * `duration` - Duration of the transition, in seconds
+    pub fn new(context: Ctx, effect: TransitionEffect, duration: f32) -> Self {
+        let screen_size = context.lock().unwrap().screen_size().into();
+        let mut transition = Self {
+            screen_size,
+            outgoing: RenderTexture::new(context.clone(), "Transition/outgoing"),
+            incoming: RenderTexture::new(context, "Transition/incoming"),
+            effect,
+            duration,
+            elapsed: 0.,
+        };
+        transition.apply();
+
+        transition
+    }
+
+    /// Returns the texture the outgoing scene is rendered into, see [`RenderTexture::begin`]
+    pub fn outgoing(&self) -> &RenderTexture {
+        &self.outgoing
+    }
+
+    /// Returns the texture the incoming scene is rendered into, see [`RenderTexture::begin`]
+    pub fn incoming(&self) -> &RenderTexture {
+        &self.incoming
+    }
+
+    /// Returns the elapsed fraction of the duration, from 0 to 1
+    pub fn progress(&self) -> f32 {
+        if self.duration <= 0. {
+            return 1.;
+        }
+
+        (self.elapsed / self.duration).min(1.)
+    }
+
+    /// Returns `true` once the incoming scene is fully shown and can be drawn on its own
+    pub fn is_done(&self) -> bool {
+        self.progress() >= 1.
+    }
+
+    /// Advance the transition
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Time elapsed since the last frame, in seconds
+    pub fn tick(&mut self, dt: f32) {
+        if self.is_done() {
+            return;
+        }
+
+        self.elapsed += dt;
+        self.apply();
+    }
+
+    fn apply(&mut self) {
+        let t = self.progress();
+        // Smoothstep, the scenes start and stop moving gently
+        let eased = t * t * (3. - 2. * t);
+        let [outgoing, incoming] = placements(self.effect, eased, self.screen_size);
+        for (texture, placement) in [
+            (&mut self.outgoing, outgoing),
+            (&mut self.incoming, incoming),
+        ] {
+            texture.set_source(placement.source);
+            texture.set_position(placement.position);
+            texture.set_opacity(placement.opacity);
+        }
+    }
+}
+
+impl Drawable for Transition {
+    fn draw<'a>(&'a mut self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.push_debug_group("Transition");
+        self.outgoing.draw(render_pass);
+        self.incoming.draw(render_pass);
+        render_pass.pop_debug_group();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::{placements, SlideDirection, TransitionEffect};
+
+    const SCREEN: Vec2 = Vec2::new(800., 600.);
+
+    #[test]
+    fn cross_fade_blends_the_incoming_scene() {
+        let [outgoing, incoming] = placements(TransitionEffect::CrossFade, 0.25, SCREEN);
+
+        assert_eq!(outgoing.opacity, 1.);
+        assert_eq!(incoming.opacity, 0.25);
+        assert_eq!(incoming.position, Vec2::ZERO);
+    }
+
+    #[test]
+    fn slide_pushes_the_outgoing_scene() {
+        let effect = TransitionEffect::Slide(SlideDirection::Left);
+        let [outgoing, incoming] = placements(effect, 0.5, SCREEN);
+
+        assert_eq!(outgoing.position, Vec2::new(-400., 0.));
+        assert_eq!(incoming.position, Vec2::new(400., 0.));
+
+        let [_, incoming] = placements(effect, 1., SCREEN);
+        assert_eq!(incoming.position, Vec2::ZERO);
+    }
+
+    #[test]
+    fn iris_opens_from_the_center() {
+        let [_, incoming] = placements(TransitionEffect::IrisWipe, 0.5, SCREEN);
+
+        assert_eq!(incoming.position, Vec2::new(200., 150.));
+        assert_eq!(incoming.source.position(), incoming.position);
+        assert_eq!(
+            (incoming.source.width, incoming.source.height),
+            (400., 300.)
+        );
+    }
+}
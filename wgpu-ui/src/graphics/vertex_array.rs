@@ -0,0 +1,311 @@
+use bytemuck::Zeroable;
+use glam::Vec2;
+use wgpu::util::DeviceExt;
+
+use super::{
+    color::{Color, WHITE},
+    Drawable, Transformable, Vertex,
+};
+use crate::{
+    debug,
+    math::{pixels_to_clip, Rect},
+    pipeline, Ctx,
+};
+
+/// How the points of a [`VertexArray`] are assembled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrimitiveType {
+    Points,
+    /// Every two points form a line
+    Lines,
+    /// Every point is linked to the previous one
+    LineStrip,
+    /// Every three points form a triangle
+    Triangles,
+    /// Every point forms a triangle with the two previous ones
+    TriangleStrip,
+}
+
+impl PrimitiveType {
+    pub const ALL: [PrimitiveType; 5] = [
+        Self::Points,
+        Self::Lines,
+        Self::LineStrip,
+        Self::Triangles,
+        Self::TriangleStrip,
+    ];
+
+    /// Returns the name of the pipeline drawing the primitive
+    pub fn pipeline_key(&self) -> &'static str {
+        match self {
+            Self::Points => "vertex-array/points",
+            Self::Lines => "vertex-array/lines",
+            Self::LineStrip => "vertex-array/line-strip",
+            Self::Triangles => "vertex-array/triangles",
+            Self::TriangleStrip => "vertex-array/triangle-strip",
+        }
+    }
+}
+
+impl From<PrimitiveType> for wgpu::PrimitiveTopology {
+    fn from(primitive: PrimitiveType) -> Self {
+        match primitive {
+            PrimitiveType::Points => Self::PointList,
+            PrimitiveType::Lines => Self::LineList,
+            PrimitiveType::LineStrip => Self::LineStrip,
+            PrimitiveType::Triangles => Self::TriangleList,
+            PrimitiveType::TriangleStrip => Self::TriangleStrip,
+        }
+    }
+}
+
+/// A point of a [`VertexArray`]
+#[derive(Debug, Clone, Copy)]
+pub struct Point {
+    /// Position in pixels, relative to the position of the array
+    pub position: Vec2,
+    pub color: Color,
+    pub tex_coords: Vec2,
+}
+
+impl Point {
+    pub fn new(position: Vec2, color: Color) -> Self {
+        Self {
+            position,
+            color,
+            tex_coords: Vec2::new(-1., -1.),
+        }
+    }
+}
+
+impl From<Vec2> for Point {
+    fn from(position: Vec2) -> Self {
+        Self::new(position, WHITE)
+    }
+}
+
+/// Returns the smallest rectangle containing every point
+///
+/// # Arguments
+///
+/// * `points` - Points to enclose
+pub fn points_bounds(points: &[Point]) -> Rect {
+    let Some(first) = points.first() else {
+        return Rect::default();
+    };
+
+    let (min, max) = points
+        .iter()
+        .fold((first.position, first.position), |(min, max), point| {
+            (min.min(point.position), max.max(point.position))
+        });
+
+    Rect {
+        x: min.x,
+        y: min.y,
+        width: max.x - min.x,
+        height: max.y - min.y,
+    }
+}
+
+/// Arbitrary geometry (charts, waveforms, custom shapes) built from user supplied points
+pub struct VertexArray {
+    context: Ctx,
+    label: String,
+    primitive: PrimitiveType,
+    points: Vec<Point>,
+    position: Vec2,
+    opacity: f32,
+    vertex_buffer: wgpu::Buffer,
+    /// Number of vertices the buffer can hold
+    capacity: usize,
+    /// Points changed since the last upload
+    need_update: bool,
+}
+
+impl VertexArray {
+    pub fn new(context: Ctx, primitive: PrimitiveType) -> Self {
+        Self::new_with_label(context, primitive, "VertexArray")
+    }
+
+    /// Create an empty array whose GPU resources are named after `label`
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `primitive` - How points are assembled
+    /// * `label` - Debug name shown by GPU debuggers
+    pub fn new_with_label(context: Ctx, primitive: PrimitiveType, label: &str) -> Self {
+        let vertex_buffer = Self::create_buffer(&context, label, 0);
+
+        Self {
+            context,
+            label: label.to_string(),
+            primitive,
+            points: Vec::new(),
+            position: Vec2::default(),
+            opacity: 1.,
+            vertex_buffer,
+            capacity: 0,
+            need_update: false,
+        }
+    }
+
+    fn create_buffer(context: &Ctx, label: &str, capacity: usize) -> wgpu::Buffer {
+        let ctx = context.lock().unwrap();
+
+        ctx.device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("{label}-vb")),
+                contents: bytemuck::cast_slice(&vec![Vertex::zeroed(); capacity.max(1)]),
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            })
+    }
+
+    pub fn primitive(&self) -> PrimitiveType {
+        self.primitive
+    }
+
+    pub fn set_primitive(&mut self, primitive: PrimitiveType) {
+        self.primitive = primitive;
+    }
+
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    /// Returns a point to edit it in place
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the point
+    pub fn point_mut(&mut self, index: usize) -> Option<&mut Point> {
+        self.need_update = true;
+
+        self.points.get_mut(index)
+    }
+
+    /// Add a point at the end of the array
+    pub fn push(&mut self, point: impl Into<Point>) {
+        self.points.push(point.into());
+        self.need_update = true;
+    }
+
+    /// Replace every point of the array
+    pub fn set_points(&mut self, points: Vec<Point>) {
+        self.points = points;
+        self.need_update = true;
+    }
+
+    /// Remove every point, the GPU buffer is kept for reuse
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.need_update = true;
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Returns the smallest rectangle containing every point, in the UI space
+    pub fn bounds(&self) -> Rect {
+        let bounds = points_bounds(&self.points);
+
+        Rect {
+            x: bounds.x + self.position.x,
+            y: bounds.y + self.position.y,
+            ..bounds
+        }
+    }
+
+    /// Set the opacity of the whole array, from 0 (transparent) to 1 (opaque)
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+        self.need_update = true;
+    }
+
+    fn ensure_update(&mut self) {
+        if !self.need_update {
+            return;
+        }
+
+        self.need_update = false;
+
+        if self.points.len() > self.capacity {
+            self.capacity = self.points.len().next_power_of_two();
+            let buffer = Self::create_buffer(&self.context, &self.label, self.capacity);
+            let previous = std::mem::replace(&mut self.vertex_buffer, buffer);
+            self.context.lock().unwrap().retire(previous);
+        }
+
+        let ctx = self.context.lock().unwrap();
+        let screen_size = ctx.screen_size();
+        let vertices = self
+            .points
+            .iter()
+            .map(|point| {
+                let position = self.position + point.position;
+
+                Vertex {
+                    position: pixels_to_clip(position.x, position.y, screen_size.0, screen_size.1),
+                    color: point.color.premultiplied(self.opacity),
+                    tex_coords: point.tex_coords.into(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        ctx.queue
+            .write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+    }
+}
+
+impl Transformable for VertexArray {
+    fn position(&self) -> &Vec2 {
+        &self.position
+    }
+
+    fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+        self.need_update = true;
+    }
+}
+
+impl Drawable for VertexArray {
+    fn draw<'b>(&'b mut self, render_pass: &mut wgpu::RenderPass<'b>) {
+        if self.points.is_empty() {
+            return;
+        }
+
+        self.ensure_update();
+
+        let count = self.points.len() as u32;
+        let size = std::mem::size_of::<Vertex>() as wgpu::BufferAddress * count as u64;
+
+        render_pass.insert_debug_marker(&self.label);
+        render_pass.set_pipeline(&pipeline(self.primitive.pipeline_key()).0);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..size));
+        render_pass.draw(0..count, 0..1);
+        debug::record_draw(count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{points_bounds, Point};
+
+    #[test]
+    fn bounds_enclose_every_point() {
+        let points =
+            [(10., 5.), (-2., 8.), (4., 20.)].map(|(x, y)| Point::from(glam::Vec2::new(x, y)));
+        let bounds = points_bounds(&points);
+
+        assert_eq!(
+            (bounds.x, bounds.y, bounds.width, bounds.height),
+            (-2., 5., 12., 15.)
+        );
+    }
+}
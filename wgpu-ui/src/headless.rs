@@ -0,0 +1,318 @@
+//! Rendering without a window into an offscreen texture, for golden-image tests on machines
+//! without a display
+
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Instant;
+
+use anyhow::{anyhow, bail};
+use image::RgbaImage;
+
+use crate::graphics::{layer::Layer, sprite_batch, Drawable};
+use crate::timer;
+use crate::ui::Ui;
+use crate::{create_index_buffer, init_pipelines, Context, Ctx, PIPELINES};
+
+/// Format of the offscreen texture, matching the layout of [`RgbaImage`]
+pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// Renders drawables and widgets like [`crate::run`] does, without creating a window nor a surface
+///
+/// Pipelines are created once per process for the first device, so a single headless renderer
+/// can exist and tests must share it.
+pub struct Headless {
+    context: Ctx,
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    index_buffer: wgpu::Buffer,
+    /// Content drawn through a movable camera
+    world: Layer<'static>,
+    /// Screen space layer the widgets are drawn in
+    overlay: Layer<'static>,
+    ui: Ui,
+}
+
+impl Headless {
+    /// Request a device without a compatible surface and create the offscreen texture
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Width of the rendered frames, in pixels
+    /// * `height` - Height of the rendered frames, in pixels
+    pub async fn new(width: u32, height: u32) -> anyhow::Result<Self> {
+        if PIPELINES.get().is_some() {
+            bail!("Pipelines were already created for another device");
+        }
+
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            dx12_shader_compiler: Default::default(),
+        });
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: None,
+                force_fallback_adapter: false,
+            })
+            .await
+            .ok_or_else(|| anyhow!("No graphics adapter available"))?;
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    features: wgpu::Features::empty(),
+                    limits: wgpu::Limits::default(),
+                    label: Some("Headless device"),
+                },
+                None,
+            )
+            .await?;
+
+        sprite_batch::set_gpu_culling(sprite_batch::supports_gpu_culling(&adapter));
+        init_pipelines(&device, FORMAT, 1);
+        let index_buffer = create_index_buffer(&device);
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Headless target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        // Only the size and format are read, nothing is configured
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: FORMAT,
+            width,
+            height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            view_formats: vec![],
+        };
+        let context = Arc::new(Mutex::new(Context::new(device, queue, config)));
+
+        Ok(Self {
+            world: Layer::new(context.clone(), "headless/world"),
+            overlay: Layer::new(context.clone(), "headless/ui"),
+            ui: Ui::new(context.clone()),
+            context,
+            texture,
+            view,
+            index_buffer,
+        })
+    }
+
+    /// Returns the rendering context, to create drawables and widgets
+    pub fn context(&self) -> Ctx {
+        self.context.clone()
+    }
+
+    /// Returns the layer drawn first, to add drawables or move its camera
+    pub fn world_mut(&mut self) -> &mut Layer<'static> {
+        &mut self.world
+    }
+
+    /// Returns the widgets drawn over the world
+    pub fn ui_mut(&mut self) -> &mut Ui {
+        &mut self.ui
+    }
+
+    /// Advance the widgets, e.g. to render an animation at a given time
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Time elapsed since the last frame, in seconds
+    pub fn tick(&mut self, dt: f32) {
+        self.context.lock().unwrap().maintain();
+        timer::run_due(&self.context, Instant::now());
+        self.ui.tick(dt);
+    }
+
+    /// Draw the world then the widgets into the offscreen texture
+    pub fn render(&mut self) {
+        let context = self.context.lock().unwrap();
+        let mut encoder = context
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Headless render encoder"),
+            });
+        let clear_color = context.clear_color;
+        drop(context);
+
+        self.world.prepare(&mut encoder);
+        self.overlay.prepare(&mut encoder);
+        self.ui.prepare(&mut encoder, &self.index_buffer);
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Headless pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(self.world.clear().unwrap_or(clear_color)),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+
+            self.world.draw(&mut render_pass);
+            self.overlay.draw(&mut render_pass);
+            self.ui.draw(&mut render_pass);
+        }
+
+        let mut context = self.context.lock().unwrap();
+        let context = &mut *context;
+        context.queue.submit(std::iter::once(encoder.finish()));
+        context.frames.submitted(&context.queue);
+    }
+
+    /// Render a frame and returns it once the GPU is done, blocking the thread meanwhile
+    pub fn capture(&mut self) -> anyhow::Result<RgbaImage> {
+        self.render();
+
+        let context = self.context.lock().unwrap();
+        let (width, height) = (context.config.width, context.config.height);
+        let pixels = read_texture(&context.device, &context.queue, &self.texture)?;
+
+        RgbaImage::from_raw(width, height, pixels)
+            .ok_or_else(|| anyhow!("Captured frame doesn't match its size"))
+    }
+}
+
+/// Returns the pixels of a texture once the GPU is done with it, blocking the thread meanwhile
+///
+/// # Arguments
+///
+/// * `device` - Device owning the texture
+/// * `queue` - Queue the copy is submitted to
+/// * `texture` - Texture of 4 bytes per pixel, created with [`wgpu::TextureUsages::COPY_SRC`]
+pub(crate) fn read_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> anyhow::Result<Vec<u8>> {
+    let (width, height) = (texture.width(), texture.height());
+    let bytes_per_row = padded_bytes_per_row(width);
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Headless readback"),
+        size: (bytes_per_row * height) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Headless capture encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (sender, receiver) = mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    device.poll(wgpu::Maintain::Wait);
+    receiver.recv()??;
+
+    let pixels = unpad_rows(&slice.get_mapped_range(), width, height, bytes_per_row);
+    buffer.unmap();
+
+    Ok(pixels)
+}
+
+/// Returns the size of a row of `width` pixels in a buffer copied from a texture, rows must be
+/// aligned to [`wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`]
+fn padded_bytes_per_row(width: u32) -> u32 {
+    let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+    (width * 4).div_ceil(alignment) * alignment
+}
+
+/// Returns the pixels of a copied texture without the padding at the end of each row
+///
+/// # Arguments
+///
+/// * `data` - Copied rows
+/// * `width` - Width of the texture, in pixels
+/// * `height` - Height of the texture, in pixels
+/// * `bytes_per_row` - Size of a padded row
+fn unpad_rows(data: &[u8], width: u32, height: u32, bytes_per_row: u32) -> Vec<u8> {
+    data.chunks(bytes_per_row as usize)
+        .take(height as usize)
+        .flat_map(|row| &row[..(width * 4) as usize])
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{padded_bytes_per_row, unpad_rows, Headless};
+    use crate::graphics::{
+        color::{BLACK, RED},
+        shape::{RectangleShape, Shape},
+        Transformable,
+    };
+
+    #[test]
+    fn rows_are_aligned_then_unpadded() {
+        assert_eq!(padded_bytes_per_row(1), 256);
+        assert_eq!(padded_bytes_per_row(64), 256);
+        assert_eq!(padded_bytes_per_row(65), 512);
+
+        let mut data = vec![0; 512];
+        data[..4].copy_from_slice(&[1, 2, 3, 4]);
+        data[256..260].copy_from_slice(&[5, 6, 7, 8]);
+
+        assert_eq!(unpad_rows(&data, 1, 2, 256), vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn captures_a_rectangle() {
+        let mut headless = match pollster::block_on(Headless::new(64, 64)) {
+            Ok(headless) => headless,
+            Err(e) => {
+                // CI machines without any adapter, not even a software one
+                eprintln!("Skipped, {e}");
+                return;
+            }
+        };
+
+        let mut rect = RectangleShape::new(headless.context(), (32., 32.).into());
+        rect.set_position((16., 16.).into());
+        rect.set_fill_color(RED);
+        headless.world_mut().add(Box::new(rect));
+        headless.context().lock().unwrap().set_clear_color(BLACK);
+
+        let image = headless.capture().unwrap();
+
+        assert_eq!(image.dimensions(), (64, 64));
+        assert_eq!(image.get_pixel(32, 32).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(4, 4).0, [0, 0, 0, 255]);
+    }
+}
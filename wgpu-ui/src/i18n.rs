@@ -0,0 +1,435 @@
+//! Translated strings, resolved from catalogs of the current locale and resolved again when it
+//! changes
+//!
+//! Catalogs are Fluent (`.ftl`) or gettext (`.po`) files limited to plain messages: Fluent
+//! attributes, terms and selectors and gettext plurals and contexts aren't supported. Both use the
+//! Fluent placeables, `{ $name }`, for the arguments.
+
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        RwLock,
+    },
+};
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use tracing::warn;
+
+use crate::ui::direction::{self, LayoutDirection};
+use crate::ASSETS;
+
+/// Languages written from right to left
+const RIGHT_TO_LEFT_LANGUAGES: &[&str] =
+    &["ar", "ckb", "dv", "fa", "he", "ps", "sd", "ug", "ur", "yi"];
+
+static LOCALE: Lazy<RwLock<String>> = Lazy::new(|| RwLock::new("en".to_string()));
+/// Incremented each time the locale changes
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Messages of a locale by their key
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Catalog {
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// Parse the messages of a Fluent resource
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Content of a `.ftl` file
+    pub fn from_fluent(source: &str) -> Result<Self> {
+        let mut messages = HashMap::new();
+        let mut current: Option<(String, String)> = None;
+
+        for (number, line) in source.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with([' ', '\t']) {
+                let Some((_, value)) = &mut current else {
+                    return Err(anyhow!(
+                        "line {}: indented text outside a message",
+                        number + 1
+                    ));
+                };
+                if trimmed.starts_with('.') {
+                    return Err(anyhow!("line {}: attributes are not supported", number + 1));
+                }
+                if !value.is_empty() {
+                    value.push('\n');
+                }
+                value.push_str(trimmed);
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(anyhow!("line {}: expected `key = value`", number + 1));
+            };
+            let key = key.trim();
+            if !key.starts_with(|c: char| c.is_ascii_alphabetic())
+                || !key
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(anyhow!("line {}: invalid message key {key}", number + 1));
+            }
+
+            messages.extend(current.take());
+            current = Some((key.to_string(), value.trim().to_string()));
+        }
+        messages.extend(current);
+
+        Ok(Self { messages })
+    }
+
+    /// Parse the translated messages of a gettext catalog, keyed by their `msgid`
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Content of a `.po` file
+    pub fn from_gettext(source: &str) -> Result<Self> {
+        let mut messages = HashMap::new();
+        let mut id: Option<String> = None;
+        let mut translation: Option<String> = None;
+
+        let mut flush = |id: &mut Option<String>, translation: &mut Option<String>| {
+            if let (Some(id), Some(translation)) = (id.take(), translation.take()) {
+                // The header has an empty id, untranslated messages an empty string
+                if !id.is_empty() && !translation.is_empty() {
+                    messages.insert(id, translation);
+                }
+            }
+        };
+
+        for (number, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: &str| anyhow!("line {}: {message}", number + 1);
+            if let Some(rest) = line.strip_prefix("msgid ") {
+                flush(&mut id, &mut translation);
+                id = Some(unquote(rest).ok_or_else(|| error("invalid string"))?);
+            } else if let Some(rest) = line.strip_prefix("msgstr ") {
+                translation = Some(unquote(rest).ok_or_else(|| error("invalid string"))?);
+            } else if line.starts_with('"') {
+                let continued = unquote(line).ok_or_else(|| error("invalid string"))?;
+                match (&mut id, &mut translation) {
+                    (_, Some(translation)) => translation.push_str(&continued),
+                    (Some(id), None) => id.push_str(&continued),
+                    _ => return Err(error("string outside a message")),
+                }
+            } else {
+                return Err(error("only msgid and msgstr are supported"));
+            }
+        }
+        flush(&mut id, &mut translation);
+
+        Ok(Self { messages })
+    }
+
+    /// Returns the message `key` with its placeables replaced by `args`, `None` if the catalog
+    /// doesn't translate it
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Key of the message
+    /// * `args` - Values of the `{ $name }` placeables
+    pub fn format(&self, key: &str, args: &[(String, String)]) -> Option<String> {
+        let message = self.messages.get(key)?;
+        let mut formatted = String::with_capacity(message.len());
+        let mut rest = message.as_str();
+
+        while let Some(start) = rest.find('{') {
+            let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+                break;
+            };
+            formatted.push_str(&rest[..start]);
+
+            let placeable = rest[start + 1..end].trim();
+            if let Some(name) = placeable.strip_prefix('$') {
+                match args.iter().find(|(arg, _)| arg == name) {
+                    Some((_, value)) => formatted.push_str(value),
+                    // Fluent shows the missing variables
+                    None => formatted.push_str(&format!("{{${name}}}")),
+                }
+            } else if let Some(literal) = placeable
+                .strip_prefix('"')
+                .and_then(|p| p.strip_suffix('"'))
+            {
+                formatted.push_str(literal);
+            } else {
+                formatted.push_str(&rest[start..=end]);
+            }
+            rest = &rest[end + 1..];
+        }
+        formatted.push_str(rest);
+
+        Some(formatted)
+    }
+}
+
+/// Returns the content of a quoted gettext string, `None` if it isn't quoted
+fn unquote(string: &str) -> Option<String> {
+    let inner = string.strip_prefix('"')?.strip_suffix('"')?;
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unquoted.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => unquoted.push('\n'),
+            't' => unquoted.push('\t'),
+            escaped => unquoted.push(escaped),
+        }
+    }
+
+    Some(unquoted)
+}
+
+/// Catalogs of every loaded locale
+#[derive(Debug, Default)]
+pub struct Translations {
+    catalogs: RwLock<HashMap<String, Catalog>>,
+}
+
+impl Translations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a catalog named after its locale, e.g. `fr.ftl` or `pt-BR.po`, merged with the
+    /// catalogs already loaded for that locale
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to a Fluent or gettext file
+    pub fn load(&self, path: &Path) -> Result<()> {
+        let source = std::fs::read_to_string(path)?;
+        let locale = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| anyhow!("invalid catalog name {}", path.display()))?;
+
+        let catalog = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("ftl") => Catalog::from_fluent(&source)?,
+            Some("po") => Catalog::from_gettext(&source)?,
+            _ => return Err(anyhow!("unknown catalog format {}", path.display())),
+        };
+        self.insert(locale, catalog);
+
+        Ok(())
+    }
+
+    /// Add the messages of `catalog` to the locale, replacing the messages with the same keys
+    ///
+    /// # Arguments
+    ///
+    /// * `locale` - Language tag, e.g. `fr` or `pt-BR`
+    /// * `catalog` - Translated messages
+    pub fn insert(&self, locale: &str, catalog: Catalog) {
+        self.catalogs
+            .write()
+            .unwrap()
+            .entry(locale.to_string())
+            .or_default()
+            .messages
+            .extend(catalog.messages);
+    }
+
+    /// Returns the message `key` of `locale`, looked up in the catalog of its language when the
+    /// region doesn't translate it, e.g. `fr` for `fr-CA`
+    ///
+    /// # Arguments
+    ///
+    /// * `locale` - Language tag
+    /// * `key` - Key of the message
+    /// * `args` - Values of the `{ $name }` placeables
+    pub fn format(&self, locale: &str, key: &str, args: &[(String, String)]) -> Option<String> {
+        let catalogs = self.catalogs.read().unwrap();
+
+        [locale, language(locale)]
+            .into_iter()
+            .find_map(|locale| catalogs.get(locale)?.format(key, args))
+    }
+}
+
+/// Returns the language subtag of a locale, `ar` for `ar-EG`
+fn language(locale: &str) -> &str {
+    locale.split(['-', '_']).next().unwrap_or(locale)
+}
+
+/// Returns the direction the widgets are laid out in for `locale`
+///
+/// # Arguments
+///
+/// * `locale` - Language tag
+pub fn locale_direction(locale: &str) -> LayoutDirection {
+    if RIGHT_TO_LEFT_LANGUAGES.contains(&language(locale)) {
+        LayoutDirection::RightToLeft
+    } else {
+        LayoutDirection::LeftToRight
+    }
+}
+
+/// Returns the language tag the texts are translated to
+pub fn locale() -> String {
+    LOCALE.read().unwrap().clone()
+}
+
+/// Translate the texts to `locale` and lay the widgets out in its direction, see
+/// [`crate::Context::set_locale`]
+///
+/// # Arguments
+///
+/// * `locale` - Language tag, e.g. `fr` or `ar-EG`
+pub fn set_locale(locale: &str) {
+    *LOCALE.write().unwrap() = locale.to_string();
+    direction::set_layout_direction(locale_direction(locale));
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Returns a counter incremented each time the locale changes
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::Relaxed)
+}
+
+/// Key of a message and its arguments, resolved again each time the locale changes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Translation {
+    key: String,
+    args: Vec<(String, String)>,
+    /// Locale generation of the last resolution, `None` until resolved
+    generation: Option<u64>,
+}
+
+impl Translation {
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            args: Vec::new(),
+            generation: None,
+        }
+    }
+
+    /// Set the value of a `{ $name }` placeable
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the placeable, without `$`
+    /// * `value` - Displayed value
+    pub fn arg(mut self, name: &str, value: impl ToString) -> Self {
+        self.args.retain(|(arg, _)| arg != name);
+        self.args.push((name.to_string(), value.to_string()));
+        self.generation = None;
+
+        self
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the message in the current locale, the key when no catalog translates it
+    pub fn resolve(&self) -> String {
+        let locale = locale();
+
+        ASSETS
+            .translations()
+            .format(&locale, &self.key, &self.args)
+            .unwrap_or_else(|| {
+                warn!("No {locale} translation of {}", self.key);
+                self.key.clone()
+            })
+    }
+
+    /// Returns the message if the locale changed since the last call, e.g. from each `tick` of
+    /// the widget
+    pub fn changed(&mut self) -> Option<String> {
+        let generation = generation();
+        if self.generation == Some(generation) {
+            return None;
+        }
+        self.generation = Some(generation);
+
+        Some(self.resolve())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{locale_direction, Catalog, Translations};
+    use crate::ui::direction::LayoutDirection;
+
+    fn args(args: &[(&str, &str)]) -> Vec<(String, String)> {
+        args.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn fluent_and_gettext_messages_are_formatted() {
+        let fluent = Catalog::from_fluent(
+            "# Menu\nsave = Enregistrer\ngreeting = Bonjour { $name } !\nabout =\n    Une ligne\n    Deux lignes\n",
+        )
+        .unwrap();
+        assert_eq!(fluent.format("save", &[]).as_deref(), Some("Enregistrer"));
+        assert_eq!(
+            fluent
+                .format("greeting", &args(&[("name", "Ana")]))
+                .as_deref(),
+            Some("Bonjour Ana !")
+        );
+        assert_eq!(
+            fluent.format("greeting", &[]).as_deref(),
+            Some("Bonjour {$name} !")
+        );
+        assert_eq!(
+            fluent.format("about", &[]).as_deref(),
+            Some("Une ligne\nDeux lignes")
+        );
+        assert!(Catalog::from_fluent("save = a\n    .tooltip = b").is_err());
+
+        let gettext = Catalog::from_gettext(
+            "msgid \"\"\nmsgstr \"Language: fr\\n\"\n\n# Menu\nmsgid \"save\"\nmsgstr \"\"\n\"Enre\"\n\"gistrer\"\n\nmsgid \"quit\"\nmsgstr \"\"\n",
+        )
+        .unwrap();
+        assert_eq!(gettext.format("save", &[]).as_deref(), Some("Enregistrer"));
+        // Untranslated
+        assert_eq!(gettext.format("quit", &[]), None);
+        assert_eq!(gettext.format("", &[]), None);
+    }
+
+    #[test]
+    fn regional_locales_fall_back_to_their_language() {
+        let translations = Translations::new();
+        translations.insert(
+            "fr",
+            Catalog::from_fluent("save = Enregistrer\ncar = Voiture").unwrap(),
+        );
+        translations.insert("fr-CA", Catalog::from_fluent("car = Char").unwrap());
+
+        assert_eq!(
+            translations.format("fr-CA", "car", &[]).as_deref(),
+            Some("Char")
+        );
+        assert_eq!(
+            translations.format("fr-CA", "save", &[]).as_deref(),
+            Some("Enregistrer")
+        );
+        assert_eq!(translations.format("de", "save", &[]), None);
+
+        assert_eq!(locale_direction("ar-EG"), LayoutDirection::RightToLeft);
+        assert_eq!(locale_direction("fr_CA"), LayoutDirection::LeftToRight);
+    }
+}
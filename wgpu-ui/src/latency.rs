@@ -0,0 +1,132 @@
+//! Trade-off between throughput and responsiveness, and measurement of the input latency
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Number of input to frame completion samples kept
+const SAMPLES: usize = 120;
+
+/// Number of frames the CPU may record ahead of the GPU
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FrameLatency {
+    /// A single frame in flight, inputs are shown one frame sooner at the cost of stalls when
+    /// the GPU is slower than the CPU, e.g. drawing apps or rhythm games
+    DoubleBuffered,
+    /// Two frames in flight, the CPU and the GPU work in parallel
+    #[default]
+    TripleBuffered,
+}
+
+impl FrameLatency {
+    /// Returns the maximum number of submitted frames the GPU hasn't completed
+    pub fn max_frames_in_flight(self) -> u64 {
+        match self {
+            Self::DoubleBuffered => 1,
+            Self::TripleBuffered => 2,
+        }
+    }
+}
+
+/// Statistics of the latest input latency samples
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LatencyStats {
+    pub average: Duration,
+    pub max: Duration,
+    /// Number of samples, zero until an input reached the GPU
+    pub samples: usize,
+}
+
+impl LatencyStats {
+    fn from_samples<'a>(samples: impl ExactSizeIterator<Item = &'a Duration>) -> Self {
+        let count = samples.len();
+        if count == 0 {
+            return Self::default();
+        }
+
+        let (total, max) = samples.fold((Duration::ZERO, Duration::ZERO), |(total, max), s| {
+            (total + *s, max.max(*s))
+        });
+
+        Self {
+            average: total / count as u32,
+            max,
+            samples: count,
+        }
+    }
+}
+
+/// Measures the time between an input and the completion by the GPU of the first frame
+/// reflecting it
+///
+/// The presentation by the compositor and the display come after and aren't measured, the
+/// samples are a lower bound of the latency perceived by the user.
+#[derive(Debug, Default)]
+pub struct LatencyMeter {
+    /// Oldest input not yet part of a submitted frame
+    pending: Option<Instant>,
+    /// Written from the queue callbacks
+    samples: Arc<Mutex<VecDeque<Duration>>>,
+}
+
+impl LatencyMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an input, the next submitted frame is the first one reflecting it
+    pub fn input(&mut self) {
+        self.pending.get_or_insert_with(Instant::now);
+    }
+
+    /// Close the frame being recorded, must be called right after its submission
+    ///
+    /// # Arguments
+    ///
+    /// * `queue` - Queue the frame was submitted to
+    pub fn submitted(&mut self, queue: &wgpu::Queue) {
+        let Some(input) = self.pending.take() else {
+            return;
+        };
+
+        let samples = self.samples.clone();
+        queue.on_submitted_work_done(move || {
+            let mut samples = samples.lock().unwrap();
+            if samples.len() == SAMPLES {
+                samples.pop_front();
+            }
+            samples.push_back(input.elapsed());
+        });
+    }
+
+    /// Returns the statistics of the latest samples
+    pub fn stats(&self) -> LatencyStats {
+        LatencyStats::from_samples(self.samples.lock().unwrap().iter())
+    }
+
+    /// Forget the samples, e.g. after changing the frame latency
+    pub fn reset(&mut self) {
+        self.samples.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::LatencyStats;
+
+    #[test]
+    fn stats_average_the_samples() {
+        let samples = [10, 30, 20].map(Duration::from_millis);
+        let stats = LatencyStats::from_samples(samples.iter());
+
+        assert_eq!(stats.average, Duration::from_millis(20));
+        assert_eq!(stats.max, Duration::from_millis(30));
+        assert_eq!(stats.samples, 3);
+        assert_eq!(
+            LatencyStats::from_samples([].iter()),
+            LatencyStats::default()
+        );
+    }
+}
@@ -1,38 +1,109 @@
+use accessibility::{Accessibility, Announcement, Priority};
+use accesskit_winit::ActionRequestEvent;
+use app_window::AppWindow;
 use assets::Assets;
-use graphics::{text::TextBrush, Transformable, Vertex};
+use clipboard::Clipboard;
+use debug::{DebugOverlay, FrameCapture, FrameStats, GpuTimer};
+use governor::{AnimationClock, FrameGovernor, QUALITY_LEVELS};
+use graphics::{
+    background::Background,
+    blend::{self, BlendMode},
+    color::Color,
+    color_blind::ColorBlindFilter,
+    deletion::{DeletionQueue, FrameFence, GpuResource},
+    layer::Layer,
+    msaa,
+    sprite_batch::{self, SpriteInstance},
+    text::TextBrush,
+    vertex_array::PrimitiveType,
+    Drawable, Transformable, Vertex,
+};
+use latency::{FrameLatency, LatencyMeter, LatencyStats};
+use math::{letterbox, Rect};
 use once_cell::sync::{Lazy, OnceCell};
+use power::{NextFrame, PowerMonitor, PowerSettings};
+use scene::SceneResources;
 use std::{
     cell::RefCell,
+    collections::HashMap,
     path::Path,
     rc::Rc,
     sync::{Arc, Mutex},
-    collections::HashMap,
+    time::{Duration, Instant},
 };
+use timer::{TimerId, Timers};
 use ui::{
     button::{Button, ButtonEvent},
     layout::Layout,
+    recording::Recording,
+    theme::{Density, ScrollbarStyle, Theme},
     Ui, Widget, WidgetId,
 };
+use viewport::Viewport;
 use wgpu::util::DeviceExt;
 use winit::{
     event::*,
-    event_loop::{ControlFlow, EventLoop},
-    window::{Window, WindowBuilder},
+    event_loop::EventLoopWindowTarget,
+    event_loop::{ControlFlow, EventLoopBuilder, EventLoopProxy},
+    window::{CursorIcon, Window, WindowBuilder, WindowId},
 };
 
+mod accessibility;
+mod app_window;
 mod assets;
+mod audio;
+mod clipboard;
+mod debug;
+mod export;
+mod governor;
 mod graphics;
+mod headless;
+mod i18n;
+mod latency;
 mod math;
+mod power;
+mod scene;
+mod timer;
 mod ui;
+mod viewport;
 
 const INDICES: &[u16] = &[0, 1, 3, 1, 2, 3];
+/// Inputs recorded with F9 and replayed with F10
+const RECORDING_PATH: &str = "recording.ron";
+
+type Pipelines = HashMap<String, (wgpu::RenderPipeline, Option<wgpu::BindGroupLayout>)>;
 
 static PIPELINES: OnceCell<HashMap<String, (wgpu::RenderPipeline, Option<wgpu::BindGroupLayout>)>> = OnceCell::new();
 static TEXT_BRUSH: OnceCell<TextBrush> = OnceCell::new();
+/// Pipelines drawn with while the frame governor turns multisampling off, only created when the
+/// main ones are multisampled
+static SINGLE_SAMPLE_PIPELINES: OnceCell<
+    HashMap<String, (wgpu::RenderPipeline, Option<wgpu::BindGroupLayout>)>,
+> = OnceCell::new();
+static SINGLE_SAMPLE_TEXT_BRUSH: OnceCell<TextBrush> = OnceCell::new();
+/// Pipelines and text brushes of the blend modes other than [`BlendMode::Alpha`], by mode and
+/// whether they are the single sampled ones
+static BLENDED_PIPELINES: OnceCell<HashMap<(BlendMode, bool), (Pipelines, TextBrush)>> =
+    OnceCell::new();
+static CAMERA_BIND_GROUP_LAYOUT: OnceCell<wgpu::BindGroupLayout> = OnceCell::new();
 static ASSETS: Lazy<Assets> = Lazy::new(|| {
     let mut assets = Assets::new();
     let _ = assets.load_font(Path::new("assets/Roboto.ttf"));
 
+    // Optional fonts used for characters missing from Roboto
+    for (priority, name) in [
+        "NotoSansSymbols2-Regular.ttf",
+        "NotoSansJP-Regular.ttf",
+        "NotoEmoji-Regular.ttf",
+    ]
+    .iter()
+    .enumerate()
+    {
+        if assets.load_font(&Path::new("assets").join(name)).is_ok() {
+            let _ = assets.add_fallback(name, priority as u32);
+        }
+    }
+
     assets
 });
 
@@ -41,21 +112,715 @@ pub struct Context {
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
     pub config: wgpu::SurfaceConfiguration,
+    pub clipboard: Clipboard,
+    pub clear_color: wgpu::Color,
+    pub scaling: Scaling,
+    /// Messages waiting to be spoken by screen readers
+    pub announcements: Vec<Announcement>,
+    pub frames: FrameFence,
+    /// Frames the CPU may record ahead of the GPU
+    pub frame_latency: FrameLatency,
+    /// Present modes supported by the surface, only the configured one when headless
+    pub present_modes: Vec<wgpu::PresentMode>,
+    /// Time between the inputs and the frames reflecting them
+    pub latency: LatencyMeter,
+    /// GPU resources replaced or removed while frames using them may still be in flight
+    pub deletion: DeletionQueue<GpuResource>,
+    /// Default look of the widgets created from now on
+    pub theme: Theme,
+    pub timers: Timers,
+    /// Textures and shaders preloaded for the scenes
+    pub resources: SceneResources,
+    /// Samples per pixel of the passes drawing with the pipelines, 1 without multisampling
+    pub sample_count: u32,
+    /// Degrades the optional visual features when the frames exceed their budget, `None` to
+    /// always draw at the full quality
+    pub governor: Option<FrameGovernor>,
+    /// Frame rates while the window is unfocused or hidden
+    pub power: PowerSettings,
+}
+
+/// Renderer options chosen when the window is created, the pipelines are built for them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RendererSettings {
+    /// Wanted samples per pixel to smooth the shape edges, lowered to what the adapter supports
+    pub sample_count: u32,
+    /// Present mode to start with, the first one supported by the surface if `None`, see
+    /// [`Context::set_present_mode`] to change it afterwards
+    pub present_mode: Option<wgpu::PresentMode>,
+    /// Blend in an sRGB surface format, colors are written as linear values otherwise
+    pub srgb: bool,
+}
+
+impl Default for RendererSettings {
+    fn default() -> Self {
+        Self {
+            sample_count: 4,
+            present_mode: None,
+            srgb: true,
+        }
+    }
+}
+
+/// How the UI coordinates map to the window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scaling {
+    /// One UI unit is one physical pixel, the UI space follows the window size
+    Window,
+    /// The UI is laid out at a fixed logical resolution, scaled to the window while preserving
+    /// its aspect ratio, remaining space is filled with the clear color
+    Letterbox { width: u32, height: u32 },
+}
+
+impl Context {
+    /// Create the context of a target whose size and format are described by `config`
+    ///
+    /// # Arguments
+    ///
+    /// * `device` - Device the drawables are created with
+    /// * `queue` - Queue of the device
+    /// * `config` - Configuration of the surface, or of the offscreen texture when headless
+    pub fn new(
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        config: wgpu::SurfaceConfiguration,
+    ) -> Self {
+        Self {
+            device,
+            queue,
+            present_modes: vec![config.present_mode],
+            config,
+            clipboard: Clipboard::new(),
+            clear_color: wgpu::Color::BLACK,
+            scaling: Scaling::Window,
+            announcements: Vec::new(),
+            frames: FrameFence::new(),
+            frame_latency: FrameLatency::default(),
+            latency: LatencyMeter::new(),
+            deletion: DeletionQueue::new(),
+            theme: Theme::default(),
+            timers: Timers::new(),
+            resources: SceneResources::new(),
+            sample_count: 1,
+            governor: None,
+            power: PowerSettings::default(),
+        }
+    }
+
+    /// Returns the clipboard shared by the application and its widgets
+    pub fn clipboard(&mut self) -> &mut Clipboard {
+        &mut self.clipboard
+    }
+
+    /// Ask screen readers to speak `message`, for dynamic changes (toasts, validation errors, ..)
+    /// which aren't tied to the focused widget
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - Text to speak
+    /// * `priority` - Whether the message waits for the current speech or interrupts it
+    pub fn announce(&mut self, message: &str, priority: Priority) {
+        self.announcements.push(Announcement {
+            message: message.to_string(),
+            priority,
+        });
+    }
+
+    /// Destroy `resource` once the frames which may still use it are completed, rather than
+    /// dropping it mid-frame
+    ///
+    /// # Arguments
+    ///
+    /// * `resource` - Buffer, texture, view or bind group no longer used
+    pub fn retire(&mut self, resource: impl Into<GpuResource>) {
+        let frame = self.frames.current();
+        self.deletion.retire(frame, resource.into());
+    }
+
+    /// Poll the device for completed frames and destroy the resources they were holding
+    pub fn maintain(&mut self) {
+        self.device.poll(wgpu::Maintain::Poll);
+
+        let completed = self.frames.completed();
+        self.deletion.destroy(completed);
+    }
+
+    /// Wait for the GPU while the maximum number of frames in flight is reached, must be called
+    /// before acquiring the texture of the next frame
+    pub fn throttle(&mut self) {
+        if self.frames.in_flight() >= self.frame_latency.max_frames_in_flight() {
+            // Waits for every submission, the frames in flight are all completed afterwards
+            self.device.poll(wgpu::Maintain::Wait);
+        }
+    }
+
+    /// Set how many frames the CPU may record ahead of the GPU
+    ///
+    /// # Arguments
+    ///
+    /// * `latency` - Double buffered for responsiveness, triple buffered for throughput
+    pub fn set_frame_latency(&mut self, latency: FrameLatency) {
+        self.frame_latency = latency;
+        self.latency.reset();
+    }
+
+    /// Change how frames are presented, returns `false` if the surface doesn't support `mode`
+    ///
+    /// `Fifo` waits for the vertical blank, `Mailbox` replaces the queued frame with a newer one
+    /// and `Immediate` presents at once, tearing but with the lowest latency.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - Present mode, applied from the next frame
+    pub fn set_present_mode(&mut self, mode: wgpu::PresentMode) -> bool {
+        if !self.present_modes.contains(&mode) {
+            return false;
+        }
+
+        self.config.present_mode = mode;
+        self.latency.reset();
+
+        true
+    }
+
+    /// Keep the frames within `budget` by degrading the shadow blur, multisampling and
+    /// animation rate while they take longer, `None` to go back to the full quality
+    ///
+    /// # Arguments
+    ///
+    /// * `budget` - Target frame time, e.g. 16 ms to keep up with a 60 Hz display
+    pub fn set_frame_budget(&mut self, budget: Option<Duration>) {
+        self.governor = budget.map(FrameGovernor::new);
+        governor::set_quality(QUALITY_LEVELS[0]);
+    }
+
+    /// Translate the texts bound to a translation key to `locale`, the widgets are laid out in
+    /// its direction from the next frame
+    ///
+    /// # Arguments
+    ///
+    /// * `locale` - Language tag of the loaded catalogs, e.g. `fr` or `ar-EG`
+    pub fn set_locale(&mut self, locale: &str) {
+        i18n::set_locale(locale);
+    }
+
+    /// Returns the time between the latest inputs and the completion of the frames reflecting
+    /// them
+    pub fn input_latency(&self) -> LatencyStats {
+        self.latency.stats()
+    }
+
+    /// Run `callback` once after `delay`, e.g. to show a tooltip
+    ///
+    /// Timers are checked once per frame, the callback runs without the context being locked.
+    ///
+    /// # Arguments
+    ///
+    /// * `delay` - Time before the callback runs
+    /// * `callback` - Function to run
+    pub fn set_timeout(
+        &mut self,
+        delay: Duration,
+        callback: impl FnMut() + Send + 'static,
+    ) -> TimerId {
+        self.timers.schedule(Instant::now() + delay, None, callback)
+    }
+
+    /// Run `callback` every `period`, e.g. to refresh displayed data
+    ///
+    /// # Arguments
+    ///
+    /// * `period` - Time between two runs, the first one included
+    /// * `callback` - Function to run
+    pub fn set_interval(
+        &mut self,
+        period: Duration,
+        callback: impl FnMut() + Send + 'static,
+    ) -> TimerId {
+        self.timers
+            .schedule(Instant::now() + period, Some(period), callback)
+    }
+
+    /// Cancel a timeout or an interval, returns `false` if it already ran or was cancelled
+    pub fn clear_timer(&mut self, id: TimerId) -> bool {
+        self.timers.cancel(id)
+    }
+
+    /// Switch every widget created from now on to the sizes of `density`
+    ///
+    /// # Arguments
+    ///
+    /// * `density` - Compact for dense desktop tools, comfortable for touch screens
+    pub fn set_density(&mut self, density: Density) {
+        self.theme.density = density;
+    }
+
+    /// Switch the scrollable widgets created from now on to classic or overlay scrollbars
+    ///
+    /// # Arguments
+    ///
+    /// * `style` - Overlay scrollbars fade out while the content isn't scrolled
+    pub fn set_scrollbar_style(&mut self, style: ScrollbarStyle) {
+        self.theme.scrollbars = style;
+    }
+
+    /// Set the color the surface is cleared with before drawing a frame
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - Clear color
+    pub fn set_clear_color(&mut self, color: Color) {
+        self.clear_color = color.into();
+    }
+
+    /// Returns the size of the UI space, widgets are positioned within it
+    pub fn screen_size(&self) -> (f32, f32) {
+        match self.scaling {
+            Scaling::Window => (self.config.width as f32, self.config.height as f32),
+            Scaling::Letterbox { width, height } => (width as f32, height as f32),
+        }
+    }
+
+    /// Returns the area of the surface the UI is rendered to, in physical pixels
+    pub fn viewport(&self) -> Rect {
+        let window = glam::Vec2::new(self.config.width as f32, self.config.height as f32);
+
+        match self.scaling {
+            Scaling::Window => Rect {
+                x: 0.,
+                y: 0.,
+                width: window.x,
+                height: window.y,
+            },
+            Scaling::Letterbox { .. } => letterbox(window, self.screen_size().into()),
+        }
+    }
+
+    /// Convert a position in the window to the UI space
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - Position in physical pixels, relative to the window
+    pub fn window_to_ui(&self, position: glam::Vec2) -> glam::Vec2 {
+        let viewport = self.viewport();
+        let (width, height) = self.screen_size();
+
+        glam::Vec2 {
+            x: (position.x - viewport.x) * width / viewport.width,
+            y: (position.y - viewport.y) * height / viewport.height,
+        }
+    }
 }
 
 pub type Ctx = Arc<Mutex<Context>>;
 
+/// Create the pipelines, layouts and text brush shared by every drawable, once per process
+///
+/// # Arguments
+///
+/// * `device` - Device the drawables are created with
+/// * `format` - Format of the textures rendered to
+/// * `sample_count` - Samples per pixel of the passes, see [`msaa::create_view`]
+fn init_pipelines(device: &wgpu::Device, format: wgpu::TextureFormat, sample_count: u32) {
+    let alpha = BlendMode::Alpha;
+    let _ = PIPELINES.set(create_pipelines(device, format, sample_count, alpha));
+    let _ = TEXT_BRUSH.set(TextBrush::new(device, format, sample_count, alpha));
+
+    // Bind groups created for the main pipelines stay valid with these, their layouts are the
+    // same
+    if sample_count > 1 {
+        let _ = SINGLE_SAMPLE_PIPELINES.set(create_pipelines(device, format, 1, alpha));
+        let _ = SINGLE_SAMPLE_TEXT_BRUSH.set(TextBrush::new(device, format, 1, alpha));
+    }
+
+    let create_set = |count, blend| {
+        (
+            create_pipelines(device, format, count, blend),
+            TextBrush::new(device, format, count, blend),
+        )
+    };
+    let mut blended = HashMap::new();
+    // The alpha blended pipelines are the main ones
+    for blend in BlendMode::ALL.into_iter().skip(1) {
+        blended.insert((blend, false), create_set(sample_count, blend));
+        if sample_count > 1 {
+            blended.insert((blend, true), create_set(1, blend));
+        }
+    }
+    let _ = BLENDED_PIPELINES.set(blended);
+}
+
+/// Returns the pipelines and text brush of the blend mode set by [`blend::set_blend_mode`],
+/// `None` for the alpha blended ones
+fn blended_pipelines() -> Option<&'static (Pipelines, TextBrush)> {
+    let single_sample = SINGLE_SAMPLE_PIPELINES.get().is_some() && !msaa::enabled();
+
+    match blend::blend_mode() {
+        BlendMode::Alpha => None,
+        blend => BLENDED_PIPELINES.get()?.get(&(blend, single_sample)),
+    }
+}
+
+/// Returns the pipeline drawing `key` in the passes of the current frame and the layout of its
+/// second bind group
+///
+/// # Arguments
+///
+/// * `key` - Name of the pipeline, e.g. `std` or `textured`
+fn pipeline(key: &str) -> &'static (wgpu::RenderPipeline, Option<wgpu::BindGroupLayout>) {
+    if let Some((pipelines, _)) = blended_pipelines() {
+        return pipelines.get(key).unwrap();
+    }
+
+    let pipelines = match SINGLE_SAMPLE_PIPELINES.get() {
+        Some(pipelines) if !msaa::enabled() => pipelines,
+        _ => PIPELINES.get().unwrap(),
+    };
+
+    pipelines.get(key).unwrap()
+}
+
+/// Returns the text brush drawing in the passes of the current frame
+fn text_brush() -> &'static TextBrush {
+    if let Some((_, brush)) = blended_pipelines() {
+        return brush;
+    }
+
+    match SINGLE_SAMPLE_TEXT_BRUSH.get() {
+        Some(brush) if !msaa::enabled() => brush,
+        _ => TEXT_BRUSH.get().unwrap(),
+    }
+}
+
+/// Create the pipelines of the drawables for passes with `sample_count` samples per pixel
+///
+/// # Arguments
+///
+/// * `device` - Device the drawables are created with
+/// * `format` - Format of the textures rendered to
+/// * `sample_count` - Samples per pixel of the passes
+/// * `blend` - How the drawables are combined with the colors beneath
+fn create_pipelines(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    blend: BlendMode,
+) -> Pipelines {
+    CAMERA_BIND_GROUP_LAYOUT.get_or_init(|| {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("Camera bind group layout"),
+        })
+    });
+
+    let shader = device.create_shader_module(wgpu::include_wgsl!("shaders/shader.wgsl"));
+
+    let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Shape pipeline layout"),
+        bind_group_layouts: &[CAMERA_BIND_GROUP_LAYOUT.get().unwrap()],
+        push_constant_ranges: &[],
+    });
+    let create_pipeline_with_fragment = |label: &str,
+                                         layout: &wgpu::PipelineLayout,
+                                         shader: &wgpu::ShaderModule,
+                                         fragment_entry_point: &str,
+                                         topology,
+                                         cull_mode| {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: fragment_entry_point,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(blend.state()),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        })
+    };
+    let create_pipeline_with = |label: &str,
+                                layout: &wgpu::PipelineLayout,
+                                shader: &wgpu::ShaderModule,
+                                topology,
+                                cull_mode| {
+        create_pipeline_with_fragment(label, layout, shader, "fs_main", topology, cull_mode)
+    };
+    let create_pipeline = |label: &str, topology, cull_mode| {
+        create_pipeline_with(label, &render_pipeline_layout, &shader, topology, cull_mode)
+    };
+    let render_pipeline = create_pipeline(
+        "Shape pipeline",
+        wgpu::PrimitiveTopology::TriangleList,
+        Some(wgpu::Face::Back),
+    );
+
+    let mut render_pipelines = HashMap::new();
+    render_pipelines.insert("std".to_string(), (render_pipeline, None));
+    // User geometry, the winding is unknown so nothing is culled
+    for primitive in PrimitiveType::ALL {
+        render_pipelines.insert(
+            primitive.pipeline_key().to_string(),
+            (
+                create_pipeline(primitive.pipeline_key(), primitive.into(), None),
+                None,
+            ),
+        );
+    }
+
+    // Render textures, their texture and sampler are bound to the second group
+    let texture_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("Texture bind group layout"),
+        });
+    let texture_shader = device.create_shader_module(wgpu::include_wgsl!("shaders/texture.wgsl"));
+    let texture_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Texture pipeline layout"),
+        bind_group_layouts: &[
+            CAMERA_BIND_GROUP_LAYOUT.get().unwrap(),
+            &texture_bind_group_layout,
+        ],
+        push_constant_ranges: &[],
+    });
+    // Drop shadows, the size and blur of their rectangle are bound to the second group
+    let shadow_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("Shadow bind group layout"),
+        });
+    let shadow_shader = device.create_shader_module(wgpu::include_wgsl!("shaders/shadow.wgsl"));
+    let shadow_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Shadow pipeline layout"),
+        bind_group_layouts: &[
+            CAMERA_BIND_GROUP_LAYOUT.get().unwrap(),
+            &shadow_bind_group_layout,
+        ],
+        push_constant_ranges: &[],
+    });
+    render_pipelines.insert(
+        "shadow".to_string(),
+        (
+            create_pipeline_with(
+                "Shadow pipeline",
+                &shadow_pipeline_layout,
+                &shadow_shader,
+                wgpu::PrimitiveTopology::TriangleList,
+                Some(wgpu::Face::Back),
+            ),
+            Some(shadow_bind_group_layout),
+        ),
+    );
+
+    render_pipelines.insert(
+        "textured".to_string(),
+        (
+            create_pipeline_with(
+                "Texture pipeline",
+                &texture_pipeline_layout,
+                &texture_shader,
+                wgpu::PrimitiveTopology::TriangleList,
+                Some(wgpu::Face::Back),
+            ),
+            Some(texture_bind_group_layout),
+        ),
+    );
+    // Snapshots of the disabled widgets, bound with the groups of the textured pipeline
+    render_pipelines.insert(
+        "textured_grayscale".to_string(),
+        (
+            create_pipeline_with_fragment(
+                "Grayscale texture pipeline",
+                &texture_pipeline_layout,
+                &texture_shader,
+                "fs_grayscale",
+                wgpu::PrimitiveTopology::TriangleList,
+                Some(wgpu::Face::Back),
+            ),
+            None,
+        ),
+    );
+
+    // Sprite batches, one instance per sprite, the size of the screen is bound to the second group
+    let sprite_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("Sprite bind group layout"),
+        });
+    let sprite_shader = device.create_shader_module(wgpu::include_wgsl!("shaders/sprite.wgsl"));
+    let sprite_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Sprite pipeline layout"),
+        bind_group_layouts: &[
+            CAMERA_BIND_GROUP_LAYOUT.get().unwrap(),
+            &sprite_bind_group_layout,
+        ],
+        push_constant_ranges: &[],
+    });
+    let sprite_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Sprite pipeline"),
+        layout: Some(&sprite_pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &sprite_shader,
+            entry_point: "vs_main",
+            buffers: &[SpriteInstance::desc()],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &sprite_shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(blend.state()),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+    });
+    render_pipelines.insert(
+        "sprite".to_string(),
+        (sprite_pipeline, Some(sprite_bind_group_layout)),
+    );
+
+    render_pipelines
+}
+
+/// Returns the index buffer of the quads, bound before drawing
+fn create_index_buffer(device: &wgpu::Device) -> wgpu::Buffer {
+    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Quad index buffer"),
+        contents: bytemuck::cast_slice(INDICES),
+        usage: wgpu::BufferUsages::INDEX,
+    })
+}
+
 struct State {
     context: Arc<Mutex<Context>>,
+    /// Kept to create the surfaces of additional windows
+    instance: wgpu::Instance,
+    adapter: wgpu::Adapter,
     surface: wgpu::Surface,
+    /// Windows opened besides the main one
+    windows: HashMap<WindowId, AppWindow>,
     index_buffer: wgpu::Buffer,
+    background: Background,
+    /// Game content, drawn through a movable camera
+    world: Layer<'static>,
+    /// Screen space layer the widgets are drawn in
+    overlay: Layer<'static>,
     ui: Ui,
+    /// Split views drawn between the world and the Ui, e.g. `viewport::grid(2, 2)` for a 4-way
+    /// editor, the last one is the topmost
+    viewports: Vec<Viewport>,
+    /// Viewport under the cursor, receiving the inputs
+    hovered_viewport: Option<usize>,
+    /// Cursor last shown over the main window
+    cursor: CursorIcon,
+    /// Present mode the surface is configured with
+    present_mode: wgpu::PresentMode,
+    /// Multisampled texture the passes draw into, resolved into the surface
+    msaa: Option<wgpu::TextureView>,
+    capture: FrameCapture,
+    last_frame: Instant,
+    /// CPU time of the last frame, in seconds
+    frame_time: f32,
+    /// Time gathered for the animations while the governor updates them every few frames
+    animations: AnimationClock,
+    stats_overlay: DebugOverlay,
+    gpu_timer: Option<GpuTimer>,
+    color_blind: ColorBlindFilter,
+    accessibility: Accessibility,
+    /// Paces the frames while the window is in the background
+    power: PowerMonitor,
     // btn_id: WidgetId,
     // window_id: WidgetId,
 }
 
 impl State {
-    async fn new(window: &Window) -> State {
+    async fn new(
+        window: &Window,
+        proxy: EventLoopProxy<ActionRequestEvent>,
+        settings: RendererSettings,
+    ) -> State {
         let size = window.inner_size();
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
             backends: wgpu::Backends::all(),
@@ -73,11 +838,12 @@ impl State {
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
-                    features: wgpu::Features::empty(),
+                    // Only used to time frames in the debug overlay
+                    features: adapter.features() & wgpu::Features::TIMESTAMP_QUERY,
                     limits: wgpu::Limits::default(),
-                    label: None,
+                    label: Some("Device"),
                 },
-                None,
+                debug::trace_path().as_deref(),
             )
             .await
             .unwrap();
@@ -86,83 +852,47 @@ impl State {
             .formats
             .iter()
             .copied()
-            .find(|f| f.is_srgb())
+            .find(|f| f.is_srgb() == settings.srgb)
             .unwrap_or(surface_caps.formats[0]);
+        let present_mode = settings
+            .present_mode
+            .filter(|mode| surface_caps.present_modes.contains(mode))
+            .unwrap_or(surface_caps.present_modes[0]);
+        let sample_count =
+            msaa::adapter_sample_count(&adapter, surface_format, settings.sample_count);
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             format: surface_format,
             width: size.width,
             height: size.height,
-            present_mode: surface_caps.present_modes[0],
+            present_mode,
             alpha_mode: surface_caps.alpha_modes[0],
             view_formats: vec![],
         };
         surface.configure(&device, &config);
 
-        let shader = device.create_shader_module(wgpu::include_wgsl!("shaders/shader.wgsl"));
+        sprite_batch::set_gpu_culling(sprite_batch::supports_gpu_culling(&adapter));
+        init_pipelines(&device, config.format, sample_count);
+        let index_buffer = create_index_buffer(&device);
 
-        let render_pipeline_layout =
-            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("Render pipeline layout"),
-                bind_group_layouts: &[],
-                push_constant_ranges: &[],
-            });
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[Vertex::desc()],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-        });
+        let msaa = msaa::create_view(
+            &device,
+            config.format,
+            (config.width, config.height),
+            sample_count,
+            "Multisampled target",
+        );
+        let mut context = Context::new(device, queue, config);
+        context.present_modes = surface_caps.present_modes;
+        context.sample_count = sample_count;
+        let context = Arc::new(Mutex::new(context));
 
-        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Index buffer"),
-            contents: bytemuck::cast_slice(INDICES),
-            usage: wgpu::BufferUsages::INDEX,
-        });
-
-        let text_brush = TextBrush::new(&device, config.format);
-
-        let _ = TEXT_BRUSH.set(text_brush);
-
-        let mut render_pipelines = HashMap::new();
-        render_pipelines.insert("std".to_string(), (render_pipeline, None));
-        let _ = PIPELINES.set(render_pipelines);
-
-        let context = Arc::new(Mutex::new(Context {
-            config,
-            device,
-            queue
-        }));
+        let gpu_timer = {
+            let context = context.lock().unwrap();
+            GpuTimer::new(&context.device, &context.queue)
+        };
 
-        let mut ui = Ui::new();
+        let mut ui = Ui::new(context.clone());
         // let mut btn = Button::new("Lorem ipsum", context.clone());
         // btn.set_position(glam::Vec2 { x: 0., y: 200. });
         // btn.set_paddings((10., 20., 20., 10.).into());
@@ -186,17 +916,167 @@ impl State {
         ui.add(Box::new(layout));
 
         Self {
+            instance,
+            adapter,
             surface,
+            windows: HashMap::new(),
             index_buffer,
+            background: Background::new(context.clone()),
+            world: Layer::new(context.clone(), "world"),
+            overlay: Layer::new(context.clone(), "ui"),
             ui,
+            viewports: Vec::new(),
+            hovered_viewport: None,
+            cursor: CursorIcon::Default,
+            present_mode,
+            msaa,
+            stats_overlay: DebugOverlay::new(context.clone()),
+            gpu_timer,
+            color_blind: ColorBlindFilter::new(context.clone()),
             context,
+            capture: FrameCapture::new(),
+            last_frame: Instant::now(),
+            frame_time: 0.,
+            animations: AnimationClock::default(),
+            accessibility: Accessibility::new(window, proxy),
+            power: PowerMonitor::new(),
             // btn_id,
             // window_id,
         }
     }
 
+    /// Forward an event to the viewport under the cursor, positions are converted to its space
+    // `CursorMoved::modifiers` is deprecated but still has to be forwarded
+    #[allow(deprecated)]
+    fn route_to_viewport(&mut self, event: &WindowEvent) {
+        if self.viewports.is_empty() {
+            return;
+        }
+
+        let context = self.context.lock().unwrap();
+        let (window, screen_size) = (context.viewport(), context.screen_size());
+        drop(context);
+
+        let viewport_at = |viewports: &[Viewport], position: glam::Vec2| {
+            viewports
+                .iter()
+                .rposition(|viewport| viewport.rect(window).contains(position))
+        };
+
+        match event {
+            WindowEvent::CursorMoved {
+                device_id,
+                position,
+                modifiers,
+            } => {
+                let position = glam::Vec2::new(position.x as f32, position.y as f32);
+                let hovered = viewport_at(&self.viewports, position);
+
+                if hovered != self.hovered_viewport {
+                    if let Some(previous) = self.hovered_viewport {
+                        self.viewports[previous].process_events(&WindowEvent::CursorLeft {
+                            device_id: *device_id,
+                        });
+                    }
+                    self.hovered_viewport = hovered;
+                }
+
+                if let Some(viewport) = hovered.map(|index| &mut self.viewports[index]) {
+                    let local = viewport.window_to_local(window, screen_size, position);
+                    viewport.process_events(&WindowEvent::CursorMoved {
+                        device_id: *device_id,
+                        position: (local.x as f64, local.y as f64).into(),
+                        modifiers: *modifiers,
+                    });
+                }
+            }
+            WindowEvent::Touch(touch) => {
+                let location = glam::Vec2::new(touch.location.x as f32, touch.location.y as f32);
+
+                if let Some(index) = viewport_at(&self.viewports, location) {
+                    let viewport = &mut self.viewports[index];
+                    let local = viewport.window_to_local(window, screen_size, location);
+                    viewport.process_events(&WindowEvent::Touch(Touch {
+                        location: (local.x as f64, local.y as f64).into(),
+                        ..*touch
+                    }));
+                }
+            }
+            _ => {
+                if let Some(index) = self.hovered_viewport {
+                    self.viewports[index].process_events(event);
+                }
+            }
+        }
+    }
+
+    /// Open an additional window, sharing the device, pipelines and assets of the main one
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - Event loop the window is created on
+    /// * `title` - Title of the window
+    fn open_window<T>(&mut self, target: &EventLoopWindowTarget<T>, title: &str) -> WindowId {
+        let window = WindowBuilder::new()
+            .with_title(title)
+            .build(target)
+            .unwrap();
+        let window = AppWindow::new(self.context.clone(), &self.instance, &self.adapter, window);
+        let id = window.id();
+        self.windows.insert(id, window);
+
+        id
+    }
+
+    // `CursorMoved::modifiers` is deprecated but still has to be forwarded
+    #[allow(deprecated)]
     fn input(&mut self, event: &WindowEvent) -> bool {
-        self.ui.process_events(event);
+        if matches!(
+            event,
+            WindowEvent::KeyboardInput { .. }
+                | WindowEvent::ReceivedCharacter(_)
+                | WindowEvent::MouseInput { .. }
+                | WindowEvent::MouseWheel { .. }
+                | WindowEvent::CursorMoved { .. }
+                | WindowEvent::Touch(_)
+        ) {
+            self.context.lock().unwrap().latency.input();
+        }
+
+        self.route_to_viewport(event);
+
+        match event {
+            WindowEvent::CursorMoved {
+                device_id,
+                position,
+                modifiers,
+            } => {
+                let position = self
+                    .context
+                    .lock()
+                    .unwrap()
+                    .window_to_ui((position.x as f32, position.y as f32).into());
+
+                self.ui.process_events(&WindowEvent::CursorMoved {
+                    device_id: *device_id,
+                    position: (position.x as f64, position.y as f64).into(),
+                    modifiers: *modifiers,
+                });
+            }
+            WindowEvent::Touch(touch) => {
+                let location = self
+                    .context
+                    .lock()
+                    .unwrap()
+                    .window_to_ui((touch.location.x as f32, touch.location.y as f32).into());
+
+                self.ui.process_events(&WindowEvent::Touch(Touch {
+                    location: (location.x as f64, location.y as f64).into(),
+                    ..*touch
+                }));
+            }
+            _ => self.ui.process_events(event),
+        }
 
         // let visible = Rc::new(RefCell::new(false));
 
@@ -221,10 +1101,97 @@ impl State {
             context.config.width = new_size.width;
             context.config.height = new_size.height;
             self.surface.configure(&context.device, &context.config);
+            let screen_size = context.screen_size();
+            let msaa = msaa::create_view(
+                &context.device,
+                context.config.format,
+                (new_size.width, new_size.height),
+                context.sample_count,
+                "Multisampled target",
+            );
+            // The previous frame may still draw into the old texture
+            if let Some(previous) = std::mem::replace(&mut self.msaa, msaa) {
+                context.retire(previous);
+            }
+            drop(context);
+
+            self.background.resize(screen_size.into());
+            self.color_blind.resize();
+            self.world.update();
+            self.overlay.update();
+            self.viewports
+                .iter_mut()
+                .for_each(|viewport| viewport.update());
+            self.windows.values_mut().for_each(|window| window.update());
+        }
+    }
+
+    fn update(&mut self) {
+        let now = Instant::now();
+        let dt = (now - self.last_frame).as_secs_f32();
+        self.last_frame = now;
+        self.frame_time = dt;
+
+        {
+            let mut context = self.context.lock().unwrap();
+            context.maintain();
+
+            // Frames paced in the background would be mistaken for slow ones
+            let paced = self.power.frame(&context.power, now);
+            if let Some(quality) = context
+                .governor
+                .as_mut()
+                .filter(|_| paced)
+                .and_then(|governor| governor.frame(dt))
+            {
+                tracing::info!("Frame budget: quality changed to {quality:?}");
+                governor::set_quality(quality);
+            }
+        }
+        timer::run_due(&self.context, now);
+
+        if let Some(dt) = self.animations.tick(dt, governor::quality().animation_step) {
+            self.ui.tick(dt);
+            self.viewports
+                .iter_mut()
+                .for_each(|viewport| viewport.tick(dt));
+            self.windows.values_mut().for_each(|window| window.tick(dt));
+        }
+
+        let announcements = std::mem::take(&mut self.context.lock().unwrap().announcements);
+        announcements
+            .into_iter()
+            .for_each(|announcement| self.accessibility.announce(announcement));
+        self.accessibility.update(&self.ui);
+    }
+
+    /// Show the cursor wanted by the topmost hovered widget, the default one over nothing
+    fn update_cursor(&mut self, window: &Window) {
+        let cursor = self
+            .ui
+            .cursor_icon()
+            .or_else(|| {
+                self.hovered_viewport
+                    .and_then(|index| self.viewports[index].cursor_icon())
+            })
+            .unwrap_or_default();
+
+        if cursor != self.cursor {
+            window.set_cursor_icon(cursor);
+            self.cursor = cursor;
         }
     }
 
     fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        {
+            let mut context = self.context.lock().unwrap();
+            if context.config.present_mode != self.present_mode {
+                self.present_mode = context.config.present_mode;
+                self.surface.configure(&context.device, &context.config);
+            }
+            context.throttle();
+        }
+
         let output = self.surface.get_current_texture()?;
         let view = output
             .texture
@@ -232,57 +1199,157 @@ impl State {
 
         let context = self.context.lock().unwrap();
 
+        self.capture.begin(&context.device);
+
         let mut encoder = context
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Render Encoder"),
             });
+        let clear_color = context.clear_color;
+        let viewport = context.viewport();
         drop(context);
 
+        if let Some(gpu_timer) = &self.gpu_timer {
+            gpu_timer.begin(&mut encoder);
+        }
+
+        self.world.prepare(&mut encoder);
+        self.overlay.prepare(&mut encoder);
+        self.ui.prepare(&mut encoder, &self.index_buffer);
+        for viewport in &mut self.viewports {
+            viewport.prepare(&mut encoder, &self.index_buffer);
+        }
+
+        // Passes draw into the filter's texture while a color blindness simulation is active
+        let target = self.color_blind.target().unwrap_or(&view);
+
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.0,
-                            g: 0.0,
-                            b: 0.0,
-                            a: 1.0,
-                        }),
-                        store: true,
+                label: Some("World pass"),
+                color_attachments: &[Some(msaa::attachment(
+                    self.msaa.as_ref(),
+                    target,
+                    wgpu::LoadOp::Clear(self.world.clear().unwrap_or(clear_color)),
+                ))],
+                depth_stencil_attachment: None,
+            });
+
+            render_pass.set_viewport(
+                viewport.x,
+                viewport.y,
+                viewport.width,
+                viewport.height,
+                0.,
+                1.,
+            );
+            render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+
+            // The background stays in screen space
+            render_pass.push_debug_group("Background");
+            self.overlay.bind(&mut render_pass);
+            self.background.draw(&mut render_pass);
+            render_pass.pop_debug_group();
+
+            render_pass.push_debug_group("World");
+            self.world.draw(&mut render_pass);
+            render_pass.pop_debug_group();
+
+            if !self.viewports.is_empty() {
+                render_pass.push_debug_group("Viewports");
+                for split in &mut self.viewports {
+                    split.draw(&mut render_pass, viewport);
+                }
+                render_pass.pop_debug_group();
+            }
+        }
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Ui pass"),
+                color_attachments: &[Some(msaa::attachment(
+                    self.msaa.as_ref(),
+                    target,
+                    match self.overlay.clear() {
+                        Some(color) => wgpu::LoadOp::Clear(color),
+                        None => wgpu::LoadOp::Load,
                     },
-                })],
+                ))],
                 depth_stencil_attachment: None,
             });
 
+            render_pass.set_viewport(
+                viewport.x,
+                viewport.y,
+                viewport.width,
+                viewport.height,
+                0.,
+                1.,
+            );
             render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
 
             // self.ui.draw(&mut render_pass, &self.render_pipeline);
+            render_pass.push_debug_group("Ui");
+            self.overlay.draw(&mut render_pass);
             self.ui.draw(&mut render_pass);
+            render_pass.pop_debug_group();
+
+            render_pass.push_debug_group("Debug overlay");
+            self.stats_overlay.draw(&mut render_pass);
+            render_pass.pop_debug_group();
         }
 
-        let context = self.context.lock().unwrap();
+        self.color_blind.apply(&mut encoder, &view);
+
+        if let Some(gpu_timer) = &mut self.gpu_timer {
+            gpu_timer.end(&mut encoder);
+        }
+
+        let mut context = self.context.lock().unwrap();
+        let context = &mut *context;
         context.queue.submit(std::iter::once(encoder.finish()));
+        context.frames.submitted(&context.queue);
+        context.latency.submitted(&context.queue);
+        self.capture.end(&context.device);
+
+        let mut stats = FrameStats::collect(self.frame_time);
+        if let Some(gpu_timer) = &mut self.gpu_timer {
+            gpu_timer.submitted();
+            stats.gpu_time = gpu_timer.read(&context.device);
+        }
+        drop(context);
         output.present();
 
+        self.stats_overlay.update(&stats);
+
         Ok(())
     }
 }
 
 pub async fn run() {
-    let event_loop = EventLoop::new();
-    let window = WindowBuilder::new().build(&event_loop).unwrap();
+    run_with(RendererSettings::default()).await
+}
+
+/// Same as [`run`], with the renderer configured by `settings`
+pub async fn run_with(settings: RendererSettings) {
+    let event_loop = EventLoopBuilder::<ActionRequestEvent>::with_user_event().build();
+    // The window must stay hidden until the accessibility adapter is created
+    let window = WindowBuilder::new()
+        .with_visible(false)
+        .build(&event_loop)
+        .unwrap();
     window.set_title("Wgpu Basic UI");
-    let mut state = State::new(&window).await;
+    let mut state = State::new(&window, event_loop.create_proxy(), settings).await;
+    window.set_visible(true);
 
-    event_loop.run(move |event, _, control_flow| match event {
+    event_loop.run(move |event, target, control_flow| match event {
         Event::WindowEvent {
             ref event,
             window_id,
         } if window_id == window.id() => {
+            state.accessibility.process_event(&window, event);
+            state.power.process_event(event);
+
             if !state.input(event) {
                 match event {
                     WindowEvent::CloseRequested
@@ -295,6 +1362,83 @@ pub async fn run() {
                             },
                         ..
                     } => *control_flow = ControlFlow::Exit,
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::F12),
+                                ..
+                            },
+                        ..
+                    } => state.capture.request(),
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::F3),
+                                ..
+                            },
+                        ..
+                    } => state.stats_overlay.toggle(),
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::F2),
+                                ..
+                            },
+                        ..
+                    } => {
+                        state.open_window(target, "Wgpu Basic UI");
+                    }
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::F4),
+                                ..
+                            },
+                        ..
+                    } => {
+                        state.color_blind.cycle();
+                        tracing::info!(
+                            "Color blindness simulation: {:?}",
+                            state.color_blind.deficiency()
+                        );
+                    }
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::F9),
+                                ..
+                            },
+                        ..
+                    } => match state.ui.stop_recording() {
+                        Some(recording) => match recording.save(Path::new(RECORDING_PATH)) {
+                            Ok(()) => tracing::info!(
+                                "Saved {} inputs to {RECORDING_PATH}",
+                                recording.events.len()
+                            ),
+                            Err(e) => tracing::warn!("Couldn't save the recording: {e}"),
+                        },
+                        None => {
+                            tracing::info!("Recording inputs, press F9 again to stop");
+                            state.ui.start_recording();
+                        }
+                    },
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::F10),
+                                ..
+                            },
+                        ..
+                    } => match Recording::load(Path::new(RECORDING_PATH)) {
+                        Ok(recording) => state.ui.play(recording),
+                        Err(e) => tracing::warn!("Couldn't load {RECORDING_PATH}: {e}"),
+                    },
                     WindowEvent::Resized(physical_size) => {
                         state.resize(*physical_size);
                     }
@@ -306,7 +1450,39 @@ pub async fn run() {
                 }
             }
         }
+        // Additional windows, closing one doesn't quit the application
+        Event::WindowEvent {
+            event: WindowEvent::CloseRequested,
+            window_id,
+        } if state.windows.contains_key(&window_id) => {
+            state.windows.remove(&window_id);
+        }
+        Event::WindowEvent {
+            ref event,
+            window_id,
+        } => {
+            if let Some(window) = state.windows.get_mut(&window_id) {
+                state.power.wake();
+                window.process_event(event);
+            }
+        }
+        Event::RedrawRequested(window_id) if state.windows.contains_key(&window_id) => {
+            let window = state.windows.get_mut(&window_id).unwrap();
+
+            match window.render(&state.index_buffer) {
+                Ok(_) => {}
+                Err(wgpu::SurfaceError::Lost) => {
+                    let size = window.window().inner_size();
+                    window.resize(size);
+                }
+                Err(wgpu::SurfaceError::OutOfMemory) => *control_flow = ControlFlow::Exit,
+                Err(e) => eprintln!("{:?}", e),
+            }
+        }
         Event::RedrawRequested(window_id) if window_id == window.id() => {
+            state.update();
+            state.update_cursor(&window);
+
             match state.render() {
                 Ok(_) => {}
                 // Reconfigure the surface if lost
@@ -325,10 +1501,31 @@ pub async fn run() {
                 Err(e) => eprintln!("{:?}", e),
             }
         }
+        Event::UserEvent(ActionRequestEvent { request, .. }) => {
+            tracing::debug!("Unhandled accessibility action {:?}", request.action);
+        }
         Event::MainEventsCleared => {
-            // RedrawRequested will only trigger once, unless we manually
-            // request it.
-            window.request_redraw();
+            let (settings, timer) = {
+                let context = state.context.lock().unwrap();
+
+                (context.power, context.timers.next_deadline())
+            };
+
+            match state.power.next_frame(&settings, timer, Instant::now()) {
+                NextFrame::Now => {
+                    control_flow.set_poll();
+                    // RedrawRequested will only trigger once, unless we manually
+                    // request it.
+                    window.request_redraw();
+                    state
+                        .windows
+                        .values()
+                        .for_each(|window| window.window().request_redraw());
+                }
+                // Idle in the background until the next frame or an event wakes the loop up
+                NextFrame::At(at) => control_flow.set_wait_until(at),
+                NextFrame::Wait => control_flow.set_wait(),
+            }
         }
         _ => {}
     });
@@ -8,7 +8,7 @@ pub fn pixels_to_texture_coord(x: f32, y: f32, width: f32, height: f32) -> [f32;
     [x / width, y / height]
 }
 
-#[derive(Copy, Clone, Default, Debug)]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
 pub struct Rect {
     pub x: f32,
     pub y: f32,
@@ -38,11 +38,43 @@ impl Rect {
     }
 }
 
+/// Returns the largest rectangle of `logical` aspect ratio centered in `window`
+///
+/// # Arguments
+///
+/// * `window` - Size of the window, in physical pixels
+/// * `logical` - Fixed logical resolution
+pub fn letterbox(window: Vec2, logical: Vec2) -> Rect {
+    let scale = (window.x / logical.x).min(window.y / logical.y);
+    let (width, height) = (logical.x * scale, logical.y * scale);
+
+    Rect {
+        x: (window.x - width) / 2.,
+        y: (window.y - height) / 2.,
+        width,
+        height,
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Rect;
+    use super::{letterbox, Rect};
     use glam::Vec2;
 
+    #[test]
+    fn letterbox_adds_vertical_bars() {
+        let viewport = letterbox(Vec2::new(1920., 720.), Vec2::new(1280., 720.));
+        assert_eq!(viewport.position(), Vec2::new(320., 0.));
+        assert_eq!((viewport.width, viewport.height), (1280., 720.));
+    }
+
+    #[test]
+    fn letterbox_adds_horizontal_bars() {
+        let viewport = letterbox(Vec2::new(640., 480.), Vec2::new(1280., 720.));
+        assert_eq!(viewport.position(), Vec2::new(0., 60.));
+        assert_eq!((viewport.width, viewport.height), (640., 360.));
+    }
+
     #[test]
     fn rect_contains_point() {
         let rect = Rect {
@@ -0,0 +1,217 @@
+//! Frame rate of the application in the background, lowered or paused while the window is
+//! unfocused or hidden so that tool applications don't keep the GPU busy for nothing
+
+use std::time::{Duration, Instant};
+
+use winit::event::WindowEvent;
+
+/// Frame rate while the window is in the background
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IdleRate {
+    /// As many frames as in the foreground
+    Full,
+    /// At most this many frames per second
+    Limited(f32),
+    /// No frame until an input, a timer or the window coming back to the foreground
+    Paused,
+}
+
+/// Frame rates of an application, see [`crate::Context::power`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowerSettings {
+    /// Rate while another window has the focus
+    pub unfocused: IdleRate,
+    /// Rate while the window is minimized or covered by other windows
+    pub occluded: IdleRate,
+}
+
+impl Default for PowerSettings {
+    fn default() -> Self {
+        Self {
+            unfocused: IdleRate::Limited(10.),
+            occluded: IdleRate::Paused,
+        }
+    }
+}
+
+/// When to draw the next frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NextFrame {
+    Now,
+    /// Wait for events until then
+    At(Instant),
+    /// Wait for events
+    Wait,
+}
+
+/// Returns when to draw the next frame
+///
+/// # Arguments
+///
+/// * `rate` - Current frame rate
+/// * `last_frame` - Time of the previous frame, `None` before the first one
+/// * `timer` - Deadline of the next timer, timers run on frames
+/// * `now` - Current time
+pub fn schedule(
+    rate: IdleRate,
+    last_frame: Option<Instant>,
+    timer: Option<Instant>,
+    now: Instant,
+) -> NextFrame {
+    let at = match (rate, last_frame) {
+        (IdleRate::Full, _) | (_, None) => return NextFrame::Now,
+        (IdleRate::Limited(fps), Some(last_frame)) => {
+            Some(last_frame + Duration::from_secs_f32(1. / fps.max(0.01)))
+        }
+        (IdleRate::Paused, _) => None,
+    };
+
+    match [at, timer].into_iter().flatten().min() {
+        Some(at) if at <= now => NextFrame::Now,
+        Some(at) => NextFrame::At(at),
+        None => NextFrame::Wait,
+    }
+}
+
+/// Follows whether the window is in the background and paces the frames accordingly
+#[derive(Debug, Clone)]
+pub struct PowerMonitor {
+    focused: bool,
+    occluded: bool,
+    minimized: bool,
+    /// An input arrived since the last frame, it is shown right away
+    woken: bool,
+    last_frame: Option<Instant>,
+    /// The previous frame was drawn in the foreground
+    was_full_rate: bool,
+}
+
+impl Default for PowerMonitor {
+    fn default() -> Self {
+        Self {
+            focused: true,
+            occluded: false,
+            minimized: false,
+            woken: false,
+            last_frame: None,
+            was_full_rate: true,
+        }
+    }
+}
+
+impl PowerMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Follow the focus and visibility of the window, inputs wake it up
+    pub fn process_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::Focused(focused) => {
+                self.focused = *focused;
+                self.woken = true;
+            }
+            WindowEvent::Occluded(occluded) => {
+                self.occluded = *occluded;
+                self.woken = true;
+            }
+            WindowEvent::Resized(size) => {
+                self.minimized = size.width == 0 || size.height == 0;
+                self.woken = true;
+            }
+            WindowEvent::KeyboardInput { .. }
+            | WindowEvent::ReceivedCharacter(_)
+            | WindowEvent::MouseInput { .. }
+            | WindowEvent::MouseWheel { .. }
+            | WindowEvent::CursorMoved { .. }
+            | WindowEvent::Touch(_)
+            | WindowEvent::DroppedFile(_)
+            | WindowEvent::HoveredFile(_) => self.woken = true,
+            _ => {}
+        }
+    }
+
+    /// Draw the next frame right away, e.g. after an input to another window
+    pub fn wake(&mut self) {
+        self.woken = true;
+    }
+
+    /// Returns the frame rate the window is drawn at
+    pub fn rate(&self, settings: &PowerSettings) -> IdleRate {
+        if self.occluded || self.minimized {
+            settings.occluded
+        } else if !self.focused {
+            settings.unfocused
+        } else {
+            IdleRate::Full
+        }
+    }
+
+    /// Returns when to draw the next frame
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - Frame rates of the application
+    /// * `timer` - Deadline of the next timer
+    /// * `now` - Current time
+    pub fn next_frame(
+        &self,
+        settings: &PowerSettings,
+        timer: Option<Instant>,
+        now: Instant,
+    ) -> NextFrame {
+        if self.woken {
+            return NextFrame::Now;
+        }
+
+        schedule(self.rate(settings), self.last_frame, timer, now)
+    }
+
+    /// Record a frame, returns `true` if it and the previous one were drawn in the foreground,
+    /// their interval being the frame time rather than the idle pace
+    pub fn frame(&mut self, settings: &PowerSettings, now: Instant) -> bool {
+        let full_rate = self.rate(settings) == IdleRate::Full;
+        let paced = full_rate && self.was_full_rate;
+
+        self.last_frame = Some(now);
+        self.woken = false;
+        self.was_full_rate = full_rate;
+
+        paced
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{schedule, IdleRate, NextFrame};
+
+    #[test]
+    fn idle_frames_wait_for_their_interval_or_a_timer() {
+        let now = Instant::now();
+        let last = Some(now - Duration::from_millis(40));
+        let limited = IdleRate::Limited(8.);
+
+        assert_eq!(schedule(IdleRate::Full, last, None, now), NextFrame::Now);
+        assert_eq!(
+            schedule(limited, last, None, now),
+            NextFrame::At(now + Duration::from_millis(85))
+        );
+        assert_eq!(
+            schedule(limited, Some(now - Duration::from_millis(150)), None, now),
+            NextFrame::Now
+        );
+
+        let timer = now + Duration::from_millis(20);
+        assert_eq!(
+            schedule(limited, last, Some(timer), now),
+            NextFrame::At(timer)
+        );
+        assert_eq!(schedule(IdleRate::Paused, last, None, now), NextFrame::Wait);
+        assert_eq!(
+            schedule(IdleRate::Paused, last, Some(timer), now),
+            NextFrame::At(timer)
+        );
+    }
+}
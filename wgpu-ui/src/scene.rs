@@ -0,0 +1,300 @@
+//! Resources needed by a scene, preloaded before entering it and released once left
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::{Context, Ctx, ASSETS};
+
+/// Files of the `assets` directory a scene needs
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct SceneManifest {
+    pub name: String,
+    #[serde(default)]
+    pub fonts: Vec<String>,
+    /// Images uploaded as textures
+    #[serde(default)]
+    pub textures: Vec<String>,
+    /// WGSL sources compiled into shader modules
+    #[serde(default)]
+    pub shaders: Vec<String>,
+}
+
+impl SceneManifest {
+    pub fn from_ron(source: &str) -> Result<Self> {
+        Ok(ron::from_str(source)?)
+    }
+
+    pub fn from_json(source: &str) -> Result<Self> {
+        Ok(serde_json::from_str(source)?)
+    }
+
+    /// Returns every resource of the manifest
+    fn resources(&self) -> Vec<Resource> {
+        let fonts = self.fonts.iter().cloned().map(Resource::Font);
+        let textures = self.textures.iter().cloned().map(Resource::Texture);
+        let shaders = self.shaders.iter().cloned().map(Resource::Shader);
+
+        fonts.chain(textures).chain(shaders).collect()
+    }
+}
+
+/// A file listed by a manifest
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Resource {
+    Font(String),
+    Texture(String),
+    Shader(String),
+}
+
+/// Textures and shaders of the scenes, fonts are held by the assets
+#[derive(Debug, Default)]
+pub struct SceneResources {
+    textures: HashMap<String, Arc<wgpu::Texture>>,
+    shaders: HashMap<String, Arc<wgpu::ShaderModule>>,
+}
+
+impl SceneResources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a preloaded texture
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - File name listed by the manifest
+    pub fn texture(&self, name: &str) -> Option<Arc<wgpu::Texture>> {
+        self.textures.get(name).cloned()
+    }
+
+    /// Returns a preloaded shader module
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - File name listed by the manifest
+    pub fn shader(&self, name: &str) -> Option<Arc<wgpu::ShaderModule>> {
+        self.shaders.get(name).cloned()
+    }
+
+    fn contains(&self, resource: &Resource) -> bool {
+        match resource {
+            Resource::Font(name) => ASSETS.get_font(name).is_some(),
+            Resource::Texture(name) => self.textures.contains_key(name),
+            Resource::Shader(name) => self.shaders.contains_key(name),
+        }
+    }
+}
+
+/// Load a resource of the `assets` directory
+///
+/// # Arguments
+///
+/// * `context` - Context the textures and shaders are created with and stored in
+/// * `resource` - Resource to load
+fn load(context: &mut Context, resource: &Resource) -> Result<()> {
+    let assets = Path::new("assets");
+
+    match resource {
+        Resource::Font(name) => ASSETS.preload_font(&assets.join(name))?,
+        Resource::Texture(name) => {
+            let image = image::open(assets.join(name))?.to_rgba8();
+            let size = wgpu::Extent3d {
+                width: image.width(),
+                height: image.height(),
+                depth_or_array_layers: 1,
+            };
+            let texture = context.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(name),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            });
+            context.queue.write_texture(
+                texture.as_image_copy(),
+                &image,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * size.width),
+                    rows_per_image: Some(size.height),
+                },
+                size,
+            );
+
+            context
+                .resources
+                .textures
+                .insert(name.clone(), Arc::new(texture));
+        }
+        Resource::Shader(name) => {
+            let source = std::fs::read_to_string(assets.join(name))?;
+            let module = context
+                .device
+                .create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some(name),
+                    source: wgpu::ShaderSource::Wgsl(source.into()),
+                });
+
+            context
+                .resources
+                .shaders
+                .insert(name.clone(), Arc::new(module));
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the job loading the resources of `manifest` which aren't loaded yet
+///
+/// # Arguments
+///
+/// * `context` - Context holding the loaded resources
+/// * `manifest` - Scene about to be entered
+pub fn preload(context: &Ctx, manifest: &SceneManifest) -> Preloading {
+    let context = context.lock().unwrap();
+    let pending = manifest
+        .resources()
+        .into_iter()
+        .filter(|resource| !context.resources.contains(resource))
+        .collect();
+
+    Preloading {
+        manifest: manifest.clone(),
+        pending,
+        done: 0,
+        failed: Vec::new(),
+    }
+}
+
+/// Resources being loaded by [`preload`], a few per frame to report the progress
+#[derive(Debug)]
+pub struct Preloading {
+    manifest: SceneManifest,
+    pending: Vec<Resource>,
+    done: usize,
+    failed: Vec<(Resource, String)>,
+}
+
+impl Preloading {
+    /// Returns the scene being loaded
+    pub fn manifest(&self) -> &SceneManifest {
+        &self.manifest
+    }
+
+    /// Returns the number of resources to load
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done == self.pending.len()
+    }
+
+    /// Returns the loaded fraction of the resources, from 0 to 1
+    pub fn progress(&self) -> f32 {
+        if self.pending.is_empty() {
+            return 1.;
+        }
+
+        self.done as f32 / self.pending.len() as f32
+    }
+
+    /// Returns the resources which couldn't be loaded, with the reason
+    pub fn failed(&self) -> &[(Resource, String)] {
+        &self.failed
+    }
+
+    /// Load the next resources and returns the progress, e.g. the value of a progress bar
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Context holding the loaded resources
+    /// * `count` - Maximum number of resources loaded by this call
+    pub fn step(&mut self, context: &Ctx, count: usize) -> f32 {
+        let end = self.done.saturating_add(count).min(self.pending.len());
+        let mut context = context.lock().unwrap();
+
+        for resource in &self.pending[self.done..end] {
+            if let Err(e) = load(&mut context, resource) {
+                tracing::warn!(
+                    "Couldn't preload {resource:?} of {}: {e}",
+                    self.manifest.name
+                );
+                self.failed.push((resource.clone(), e.to_string()));
+            }
+        }
+        self.done = end;
+
+        self.progress()
+    }
+
+    /// Load the remaining resources at once
+    pub fn finish(&mut self, context: &Ctx) {
+        self.step(context, usize::MAX);
+    }
+
+    /// Release the textures and shaders not listed by the loaded scene, once the previous scene
+    /// is left
+    ///
+    /// Fonts stay loaded, texts borrow them until the program exits.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Context holding the loaded resources
+    pub fn enter(self, context: &Ctx) {
+        let mut context = context.lock().unwrap();
+        let context = &mut *context;
+
+        let resources = &mut context.resources;
+        resources
+            .shaders
+            .retain(|name, _| self.manifest.shaders.contains(name));
+
+        let (kept, released): (HashMap<_, _>, Vec<_>) = std::mem::take(&mut resources.textures)
+            .into_iter()
+            .partition(|(name, _)| self.manifest.textures.contains(name));
+        resources.textures = kept;
+
+        for (_, texture) in released {
+            // Textures still used elsewhere are freed once their last handle is dropped
+            if let Ok(texture) = Arc::try_unwrap(texture) {
+                context.retire(texture);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Resource, SceneManifest};
+
+    #[test]
+    fn manifests_list_their_resources() {
+        let manifest = SceneManifest::from_ron(
+            r#"(name: "menu", fonts: ["Roboto.ttf"], textures: ["logo.png"])"#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.name, "menu");
+        assert!(manifest.shaders.is_empty());
+        assert_eq!(
+            manifest.resources(),
+            vec![
+                Resource::Font(String::from("Roboto.ttf")),
+                Resource::Texture(String::from("logo.png")),
+            ]
+        );
+    }
+}
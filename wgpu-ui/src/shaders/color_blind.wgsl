@@ -0,0 +1,39 @@
+struct FilterUniform {
+    rows: array<vec4<f32>, 3>,
+};
+
+@group(0) @binding(0)
+var t_frame: texture_2d<f32>;
+@group(0) @binding(1)
+var s_frame: sampler;
+@group(0) @binding(2)
+var<uniform> simulation: FilterUniform;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>
+};
+
+// Single triangle covering the screen
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    out.clip_position = vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+    out.tex_coords = uv;
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // The target has the surface format, sampled colors are already linear
+    let color = textureSample(t_frame, s_frame, in.tex_coords).rgb;
+    let simulated = vec3<f32>(
+        dot(simulation.rows[0].xyz, color),
+        dot(simulation.rows[1].xyz, color),
+        dot(simulation.rows[2].xyz, color),
+    );
+
+    return vec4<f32>(clamp(simulated, vec3<f32>(0.0), vec3<f32>(1.0)), 1.0);
+}
@@ -0,0 +1,63 @@
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct ShadowUniform {
+    // Half size of the rectangle casting the shadow, in pixels
+    half_size: vec2<f32>,
+    // Standard deviation of the gaussian blur, in pixels
+    sigma: f32,
+    _padding: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> shadow: ShadowUniform;
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    // Offset from the center of the shadow, in pixels
+    @location(2) tex_coords: vec2<f32>
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) offset: vec2<f32>
+};
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.color = model.color;
+    out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0, 1.0);
+    out.offset = model.tex_coords;
+
+    return out;
+}
+
+// Approximation of the error function, precise enough for blurs
+fn erf(x: vec2<f32>) -> vec2<f32> {
+    let s = sign(x);
+    let a = abs(x);
+    var r = 1.0 + (0.278393 + (0.230389 + 0.078108 * (a * a)) * a) * a;
+    r = r * r;
+
+    return s - s / (r * r);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // A rectangle convolved with a gaussian is separable, each axis is the difference of two
+    // error functions
+    let scale = 1.0 / (sqrt(2.0) * max(shadow.sigma, 0.001));
+    let coverage = 0.5 * (erf((in.offset + shadow.half_size) * scale) - erf((in.offset - shadow.half_size) * scale));
+
+    // Colors are premultiplied by their alpha
+    return in.color * coverage.x * coverage.y;
+}
@@ -0,0 +1,58 @@
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct Params {
+    view_min: vec2<f32>,
+    view_max: vec2<f32>,
+    screen_size: vec2<f32>,
+    count: u32,
+    _padding: u32,
+};
+
+@group(1) @binding(0)
+var<uniform> params: Params;
+
+struct InstanceInput {
+    @location(0) position: vec2<f32>,
+    @location(1) size: vec2<f32>,
+    @location(2) color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) vertex_index: u32,
+    instance: InstanceInput,
+) -> VertexOutput {
+    // Same corners as the rectangles, indexed by the quad index buffer
+    var corners = array<vec2<f32>, 4>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(1.0, 0.0),
+    );
+    let pixel = instance.position + corners[vertex_index] * instance.size;
+    let clip = vec2<f32>(
+        2.0 * pixel.x / params.screen_size.x - 1.0,
+        1.0 - 2.0 * pixel.y / params.screen_size.y,
+    );
+
+    var out: VertexOutput;
+    out.color = instance.color;
+    out.clip_position = camera.view_proj * vec4<f32>(clip, 1.0, 1.0);
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
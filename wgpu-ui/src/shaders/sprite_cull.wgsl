@@ -0,0 +1,48 @@
+struct Sprite {
+    position: vec2<f32>,
+    size: vec2<f32>,
+    color: vec4<f32>,
+};
+
+struct Params {
+    view_min: vec2<f32>,
+    view_max: vec2<f32>,
+    screen_size: vec2<f32>,
+    count: u32,
+    _padding: u32,
+};
+
+// Arguments of `draw_indexed_indirect`, the instance count is the number of visible sprites
+struct DrawArgs {
+    index_count: u32,
+    instance_count: atomic<u32>,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+@group(0) @binding(1)
+var<storage, read> sprites: array<Sprite>;
+@group(0) @binding(2)
+var<storage, read_write> visible: array<Sprite>;
+@group(0) @binding(3)
+var<storage, read_write> args: DrawArgs;
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= params.count {
+        return;
+    }
+
+    let sprite = sprites[id.x];
+    let end = sprite.position + sprite.size;
+    if any(end < params.view_min) || any(sprite.position > params.view_max) {
+        return;
+    }
+
+    // Visible sprites are packed at the start of the buffer, in no particular order
+    let slot = atomicAdd(&args.instance_count, 1u);
+    visible[slot] = sprite;
+}
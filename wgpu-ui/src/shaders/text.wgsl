@@ -1,18 +1,25 @@
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
 struct VertexInput {
     @location(0) position: vec2<f32>,
-    @location(1) color: vec3<f32>,
+    @location(1) color: vec4<f32>,
     @location(2) tex_coords: vec2<f32>
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
-    @location(0) color: vec3<f32>,
+    @location(0) color: vec4<f32>,
     @location(1) tex_coords: vec2<f32>
 };
 
-@group(0) @binding(0)
+@group(1) @binding(0)
 var t_diffuse: texture_2d<f32>;
-@group(0) @binding(1)
+@group(1) @binding(1)
 var s_diffuse: sampler;
 
 @vertex
@@ -21,7 +28,7 @@ fn vs_main(
 ) -> VertexOutput {
     var out: VertexOutput;
     out.color = model.color;
-    out.clip_position = vec4<f32>(model.position, 1.0, 1.0);
+    out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0, 1.0);
     out.tex_coords = model.tex_coords;
 
     return out;
@@ -29,5 +36,6 @@ fn vs_main(
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return vec4<f32>(in.color, 1.0) * vec4<f32>(1.0, 1.0, 1.0, textureSample(t_diffuse, s_diffuse, in.tex_coords).r);
+    // Colors are premultiplied by their alpha, so is the coverage
+    return in.color * textureSample(t_diffuse, s_diffuse, in.tex_coords).r;
 }
\ No newline at end of file
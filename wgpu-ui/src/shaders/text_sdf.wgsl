@@ -0,0 +1,58 @@
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) tex_coords: vec2<f32>
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) tex_coords: vec2<f32>
+};
+
+struct SdfStyle {
+    outline_color: vec4<f32>,
+    outline_width: f32,
+    softness: f32,
+};
+
+@group(1) @binding(0)
+var t_field: texture_2d<f32>;
+@group(1) @binding(1)
+var s_field: sampler;
+@group(1) @binding(2)
+var<uniform> style: SdfStyle;
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.color = model.color;
+    out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0, 1.0);
+    out.tex_coords = model.tex_coords;
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // 0.5 is the outline of the glyph, higher values are inside
+    let distance = textureSample(t_field, s_field, in.tex_coords).r;
+    // Antialias over about one screen pixel, whatever the scale
+    let width = max(fwidth(distance) * 0.5, 0.0001) + style.softness;
+
+    let fill = smoothstep(0.5 - width, 0.5 + width, distance);
+    let edge = 0.5 - style.outline_width;
+    let outline = smoothstep(edge - width, edge + width, distance);
+
+    // Colors are premultiplied by their alpha
+    return in.color * fill + style.outline_color * max(outline - fill, 0.0);
+}
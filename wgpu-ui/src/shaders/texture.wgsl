@@ -0,0 +1,51 @@
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) tex_coords: vec2<f32>
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) tex_coords: vec2<f32>
+};
+
+@group(1) @binding(0)
+var t_diffuse: texture_2d<f32>;
+@group(1) @binding(1)
+var s_diffuse: sampler;
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.color = model.color;
+    out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0, 1.0);
+    out.tex_coords = model.tex_coords;
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Both the texture and the tint are premultiplied by their alpha
+    return in.color * textureSample(t_diffuse, s_diffuse, in.tex_coords);
+}
+
+// Drawn without colors, e.g. the snapshots of disabled widgets
+@fragment
+fn fs_grayscale(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = in.color * textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    // Luma of the premultiplied channels, which stays premultiplied
+    let luma = dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+
+    return vec4<f32>(vec3<f32>(luma), color.a);
+}
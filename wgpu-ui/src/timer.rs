@@ -0,0 +1,213 @@
+//! Callbacks run after a delay or periodically, from the update of each frame
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use crate::Ctx;
+
+type Callback = Box<dyn FnMut() + Send>;
+
+/// Handle of a timer, to cancel it with [`crate::Context::clear_timer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(u64);
+
+struct Timer {
+    id: TimerId,
+    deadline: Instant,
+    /// Period of an interval, `None` for a timeout
+    period: Option<Duration>,
+    callback: Callback,
+}
+
+/// Pending timeouts and intervals
+#[derive(Default)]
+pub struct Timers {
+    next_id: u64,
+    timers: Vec<Timer>,
+    /// Timers taken out of the list while their callback runs
+    running: HashSet<TimerId>,
+    /// Running timers cancelled by their own or another callback
+    cancelled: HashSet<TimerId>,
+}
+
+impl std::fmt::Debug for Timers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Timers")
+            .field("pending", &self.timers.len())
+            .finish()
+    }
+}
+
+impl Timers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Schedule `callback`, returns the handle cancelling it
+    ///
+    /// # Arguments
+    ///
+    /// * `deadline` - First time the callback runs
+    /// * `period` - Delay between two runs, `None` to run it once
+    /// * `callback` - Function to run
+    pub fn schedule(
+        &mut self,
+        deadline: Instant,
+        period: Option<Duration>,
+        callback: impl FnMut() + Send + 'static,
+    ) -> TimerId {
+        self.next_id += 1;
+        let id = TimerId(self.next_id);
+
+        self.timers.push(Timer {
+            id,
+            deadline,
+            period,
+            callback: Box::new(callback),
+        });
+
+        id
+    }
+
+    /// Cancel a timer, returns `false` if it already ran or was cancelled
+    pub fn cancel(&mut self, id: TimerId) -> bool {
+        let count = self.timers.len();
+        self.timers.retain(|timer| timer.id != id);
+
+        if self.timers.len() < count {
+            return true;
+        }
+
+        // A running interval mustn't be rescheduled afterward
+        self.running.contains(&id) && self.cancelled.insert(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.timers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    /// Returns the earliest deadline of the pending timers
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.timers.iter().map(|timer| timer.deadline).min()
+    }
+
+    /// Returns the timers whose deadline is reached, removing them from the list
+    fn take_due(&mut self, now: Instant) -> Vec<Timer> {
+        let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.timers)
+            .into_iter()
+            .partition(|timer| timer.deadline <= now);
+        self.timers = pending;
+        self.running = due.iter().map(|timer| timer.id).collect();
+
+        due
+    }
+
+    /// Put back the intervals which ran, unless cancelled meanwhile
+    fn reschedule(&mut self, ran: Vec<Timer>, now: Instant) {
+        for mut timer in ran {
+            let Some(period) = timer.period else {
+                continue;
+            };
+            if self.cancelled.contains(&timer.id) {
+                continue;
+            }
+
+            timer.deadline += period;
+            // Missed runs are skipped rather than run in a burst
+            if timer.deadline <= now {
+                timer.deadline = now + period;
+            }
+            self.timers.push(timer);
+        }
+
+        self.running.clear();
+        self.cancelled.clear();
+    }
+}
+
+/// Run the callbacks of the due timers, must be called once per frame
+///
+/// Callbacks run without the context being locked, so they can use it, e.g. to schedule other
+/// timers.
+///
+/// # Arguments
+///
+/// * `context` - Context holding the timers
+/// * `now` - Current time
+pub fn run_due(context: &Ctx, now: Instant) {
+    let mut due = context.lock().unwrap().timers.take_due(now);
+    if due.is_empty() {
+        return;
+    }
+
+    due.sort_by_key(|timer| timer.deadline);
+    due.iter_mut().for_each(|timer| (timer.callback)());
+
+    context.lock().unwrap().timers.reschedule(due, now);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+    use std::time::{Duration, Instant};
+
+    use super::Timers;
+
+    fn counter() -> (Arc<AtomicU32>, impl FnMut() + Send + 'static) {
+        let count = Arc::new(AtomicU32::new(0));
+        let shared = count.clone();
+
+        (count, move || {
+            shared.fetch_add(1, Ordering::Relaxed);
+        })
+    }
+
+    fn run(timers: &mut Timers, now: Instant) {
+        let mut due = timers.take_due(now);
+        due.iter_mut().for_each(|timer| (timer.callback)());
+        timers.reschedule(due, now);
+    }
+
+    #[test]
+    fn timeouts_run_once() {
+        let start = Instant::now();
+        let mut timers = Timers::new();
+        let (count, callback) = counter();
+        timers.schedule(start + Duration::from_millis(100), None, callback);
+
+        run(&mut timers, start + Duration::from_millis(50));
+        assert_eq!(count.load(Ordering::Relaxed), 0);
+
+        run(&mut timers, start + Duration::from_millis(100));
+        run(&mut timers, start + Duration::from_millis(200));
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+        assert!(timers.is_empty());
+    }
+
+    #[test]
+    fn intervals_repeat_until_cancelled() {
+        let start = Instant::now();
+        let period = Duration::from_millis(100);
+        let mut timers = Timers::new();
+        let (count, callback) = counter();
+        let id = timers.schedule(start + period, Some(period), callback);
+
+        run(&mut timers, start + period);
+        run(&mut timers, start + period * 2);
+        // Late frame, the missed run is skipped
+        run(&mut timers, start + period * 5);
+        assert_eq!(count.load(Ordering::Relaxed), 3);
+
+        assert!(timers.cancel(id));
+        assert!(!timers.cancel(id));
+        run(&mut timers, start + period * 10);
+        assert_eq!(count.load(Ordering::Relaxed), 3);
+    }
+}
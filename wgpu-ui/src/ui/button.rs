@@ -1,26 +1,56 @@
-use super::{Widget, WidgetEvent};
+use super::{
+    gesture::LONG_PRESS_DURATION,
+    hit::HitShape,
+    state::{Binding, State},
+    theme::ButtonStyle,
+    EventQueue, Opacity, Uid, Widget, WidgetEvent,
+};
+use crate::accessibility::{AccessNode, Role, Semantics};
+use crate::audio::{self, SoundStyle};
+use crate::graphics::shadow::Shadow;
 use crate::graphics::shape::{RectangleShape, Shape};
-use crate::graphics::text::Text;
+use crate::graphics::text::{AutoFit, Text};
+use crate::graphics::text_style::TextStyle;
+use crate::graphics::transform::{self, Transform};
 use crate::graphics::{
-    color::{BLUE, GREEN, RED},
+    color::{Color, BLUE, GREEN, RED},
     Drawable, Transformable,
 };
+use crate::i18n::Translation;
+use crate::math::Rect;
 use crate::Ctx;
 use crate::ASSETS;
 use glam::{Vec2, Vec4};
 use wgpu::RenderPass;
-use winit::event::{ElementState, MouseButton, WindowEvent};
+use winit::event::{ElementState, KeyboardInput, MouseButton, VirtualKeyCode, WindowEvent};
+use winit::window::CursorIcon;
+
+/// Maximum time between two clicks making a double click, in seconds
+const DOUBLE_CLICK_TIME: f32 = 0.4;
 
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum ButtonEvent {
+    /// Left button pressed and released over the button, or Enter/Space while focused
     Click,
     Hover,
+    /// Second click shortly after a first one, which was emitted as a `Click`
+    DoubleClick,
+    /// Right button pressed over the button
+    RightClick,
+    /// Left button held over the button, no `Click` follows its release
+    LongPress,
+    /// Left button released after being pressed over the button, wherever the cursor is
+    Released,
 }
 
 impl From<u32> for ButtonEvent {
     fn from(value: u32) -> Self {
         match value {
             0 => Self::Click,
+            2 => Self::DoubleClick,
+            3 => Self::RightClick,
+            4 => Self::LongPress,
+            5 => Self::Released,
             _ => Self::Hover,
         }
     }
@@ -28,20 +58,170 @@ impl From<u32> for ButtonEvent {
 
 impl WidgetEvent for ButtonEvent {}
 
+/// Times the presses of the left button, turning them into clicks, double clicks and long
+/// presses
+#[derive(Debug, Clone, PartialEq)]
+struct PressTimer {
+    double_click_time: f32,
+    long_press_duration: f32,
+    /// Time the button has been held, `None` while released
+    held: Option<f32>,
+    /// The current press already emitted a long press
+    long_pressed: bool,
+    /// Time since the last click, `None` once too old to make a double click
+    since_click: Option<f32>,
+}
+
+impl Default for PressTimer {
+    fn default() -> Self {
+        Self {
+            double_click_time: DOUBLE_CLICK_TIME,
+            long_press_duration: LONG_PRESS_DURATION,
+            held: None,
+            long_pressed: false,
+            since_click: None,
+        }
+    }
+}
+
+impl PressTimer {
+    fn is_pressed(&self) -> bool {
+        self.held.is_some()
+    }
+
+    fn press(&mut self) {
+        self.held = Some(0.);
+        self.long_pressed = false;
+    }
+
+    /// Returns the events of releasing the button, none if it wasn't pressed
+    ///
+    /// # Arguments
+    ///
+    /// * `inside` - The cursor is over the button
+    fn release(&mut self, inside: bool) -> Vec<ButtonEvent> {
+        if self.held.take().is_none() {
+            return Vec::new();
+        }
+
+        let mut events = vec![ButtonEvent::Released];
+        if inside && !self.long_pressed {
+            events.push(ButtonEvent::Click);
+            match self.since_click.take() {
+                Some(_) => events.push(ButtonEvent::DoubleClick),
+                None => self.since_click = Some(0.),
+            }
+        }
+
+        events
+    }
+
+    /// Advance the timers, returns a long press once the button is held long enough
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Time elapsed since the last frame, in seconds
+    fn tick(&mut self, dt: f32) -> Option<ButtonEvent> {
+        self.since_click = self
+            .since_click
+            .map(|since| since + dt)
+            .filter(|since| *since <= self.double_click_time);
+
+        let held = self.held.as_mut()?;
+        *held += dt;
+        if self.long_pressed || *held < self.long_press_duration {
+            return None;
+        }
+        self.long_pressed = true;
+
+        Some(ButtonEvent::LongPress)
+    }
+}
+
+/// Progress of the background toward the hovered and pressed states, from 0 to 1
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct Feedback {
+    hover: f32,
+    press: f32,
+}
+
+impl Feedback {
+    /// Move toward the state of the button, returns `true` if the feedback changed
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Time elapsed since the last frame, in seconds
+    /// * `duration` - Time to go from a state to another, 0 to change instantly
+    /// * `hovered` - The cursor is over the button
+    /// * `pressed` - The left button is held on the button
+    fn tick(&mut self, dt: f32, duration: f32, hovered: bool, pressed: bool) -> bool {
+        let step = if duration > 0. { dt / duration } else { 1. };
+        let approach = |value: f32, target: bool| {
+            if target {
+                (value + step).min(1.)
+            } else {
+                (value - step).max(0.)
+            }
+        };
+
+        let previous = *self;
+        self.hover = approach(self.hover, hovered);
+        self.press = approach(self.press, pressed);
+
+        *self != previous
+    }
+
+    /// Returns the eased progress toward the hovered and pressed states
+    fn eased(&self) -> (f32, f32) {
+        let smoothstep = |t: f32| t * t * (3. - 2. * t);
+
+        (smoothstep(self.hover), smoothstep(self.press))
+    }
+
+    /// Returns the scale of the background and the paddings added to it
+    fn geometry(&self, style: &ButtonStyle) -> (f32, Vec4) {
+        let (hover, press) = self.eased();
+        let lerp = |from: f32, to: f32, t: f32| from + (to - from) * t;
+
+        let scale = lerp(lerp(1., style.hover_scale, hover), style.press_scale, press);
+        let paddings = (style.hover_paddings * hover).lerp(style.press_paddings, press);
+
+        (scale, paddings)
+    }
+}
+
 pub struct Button<'a> {
     rect: RectangleShape,
     label: Text<'a>,
     position: Vec2,
     mouse_position: Vec2,
     paddings: Vec4,
-    events: Vec<ButtonEvent>,
+    events: EventQueue<ButtonEvent>,
     visible: bool,
-    size: Vec2
+    uid: Uid,
+    opacity: Opacity,
+    size: Vec2,
+    hit_shape: HitShape,
+    /// State the text follows
+    label_binding: Option<Binding<String>>,
+    elevation: f32,
+    /// Enter and Space click the button while it has the keyboard focus
+    focused: bool,
+    presses: PressTimer,
+    /// Rotation and scale of the background and label together
+    transform: Transform,
+    sounds: SoundStyle,
+    /// The cursor is over the button
+    hovered: bool,
+    /// Disabled buttons ignore the inputs
+    enabled: bool,
+    style: ButtonStyle,
+    feedback: Feedback,
 }
 
 impl<'a> Transformable for Button<'a> {
     fn position(&self) -> &Vec2 {
-        self.rect.position()
+        &self.position
     }
 
     fn set_position(&mut self, position: Vec2) {
@@ -51,81 +231,296 @@ impl<'a> Transformable for Button<'a> {
 
         self.update();
     }
+
+    fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+
+        self.update();
+    }
 }
 
 impl<'a> Button<'a> {
     pub fn new(text: &str, context: Ctx) -> Button<'a> {
         let position = Vec2::default();
+        let theme = context.lock().unwrap().theme;
+        let metrics = theme.metrics();
 
-        let label = Text::new(
+        let label = Text::new_with_label(
             context.clone(),
             text,
             ASSETS.get_font("Roboto.ttf").unwrap(),
-            30.,
+            metrics.font_size,
+            &format!("Button#{text}/label"),
         );
         let label_bounds = label.bounds();
 
-        let mut rect = RectangleShape::new(
+        let mut rect = RectangleShape::new_with_label(
             context.clone(),
             (label_bounds.width, label_bounds.height).into(),
+            &format!("Button#{text}/background"),
         );
         rect.set_position(position);
+        rect.set_shadow(Shadow::elevation(theme.elevations.button));
 
-        Self {
+        let mut button = Self {
             rect,
             position,
             label,
             mouse_position: Default::default(),
-            paddings: (0., 0., 0., 0.).into(),
-            events: Vec::new(),
+            paddings: metrics.paddings(),
+            events: EventQueue::new(),
             visible: true,
-            size: Default::default()
-        }
+            uid: Uid::new(),
+            opacity: Opacity::default(),
+            size: Default::default(),
+            hit_shape: HitShape::default(),
+            label_binding: None,
+            elevation: theme.elevations.button,
+            focused: false,
+            presses: PressTimer::default(),
+            transform: Transform::default(),
+            sounds: theme.sounds,
+            hovered: false,
+            enabled: true,
+            style: theme.buttons,
+            feedback: Feedback::default(),
+        };
+        button.update();
+
+        button
+    }
+
+    /// Create a button labelled with a translated message, resolved again when the locale
+    /// changes
+    ///
+    /// # Arguments
+    ///
+    /// * `translation` - Key and arguments of the label
+    /// * `context` - Rendering context
+    pub fn translated(translation: Translation, context: Ctx) -> Button<'a> {
+        let mut button = Self::new(translation.key(), context);
+        button.label.set_translation(Some(translation));
+        button.update();
+
+        button
+    }
+
+    /// Style the label with a style shared with other texts, see [`Text::set_style`]
+    ///
+    /// # Arguments
+    ///
+    /// * `style` - Shared style, `None` to stop following it
+    pub fn set_label_style(&mut self, style: Option<TextStyle>) {
+        self.label.set_style(style);
+        self.update();
     }
 
     pub fn set_character_size(&mut self, character_size: f32) {
         self.label.set_character_size(character_size);
     }
 
+    /// Shrink or grow the label to fit the size given to the button, e.g. by a layout, so
+    /// translations of any length fit
+    ///
+    /// # Arguments
+    ///
+    /// * `auto_fit` - Range of the character size, `None` to keep the theme size
+    pub fn set_auto_fit(&mut self, auto_fit: Option<AutoFit>) {
+        self.label.set_auto_fit(auto_fit);
+
+        self.update();
+    }
+
+    /// Set the maximum time between two clicks making a double click
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - Time in seconds, 0.4 by default
+    pub fn set_double_click_time(&mut self, time: f32) {
+        self.presses.double_click_time = time;
+    }
+
+    /// Set the time the left button must be held to emit a long press
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - Time in seconds, the same as touch long presses by default
+    pub fn set_long_press_duration(&mut self, duration: f32) {
+        self.presses.long_press_duration = duration;
+    }
+
+    /// Set the sounds played when the button is hovered and clicked
+    ///
+    /// # Arguments
+    ///
+    /// * `sounds` - Names of sounds loaded in the bank of the assets
+    pub fn set_sounds(&mut self, sounds: SoundStyle) {
+        self.sounds = sounds;
+    }
+
     pub fn set_paddings(&mut self, paddings: Vec4) {
         self.paddings = paddings;
 
         self.update();
     }
-}
 
-impl<'a> Widget for Button<'a> {
-    fn set_visibility(&mut self, visibility: bool) {
-        self.visible = visibility;
+    /// Set how the background reacts to the pointer, e.g. [`ButtonStyle::instant`] to only
+    /// change its color
+    ///
+    /// # Arguments
+    ///
+    /// * `style` - Scales, paddings and duration of the hover and press feedback
+    pub fn set_style(&mut self, style: ButtonStyle) {
+        self.style = style;
+        self.place_background();
+    }
+
+    pub fn style(&self) -> &ButtonStyle {
+        &self.style
+    }
+
+    /// Set the area reacting to the pointer, e.g. [`HitShape::Ellipse`] for round buttons
+    ///
+    /// # Arguments
+    ///
+    /// * `hit_shape` - Shape fitted to the button bounds
+    pub fn set_hit_shape(&mut self, hit_shape: HitShape) {
+        self.hit_shape = hit_shape;
+    }
+
+    /// Raise the button above the background, it casts a shadow growing with its elevation
+    ///
+    /// # Arguments
+    ///
+    /// * `elevation` - Height above the background in pixels, 0 for a flat button
+    pub fn set_elevation(&mut self, elevation: f32) {
+        self.elevation = elevation;
+        self.rect.set_shadow(Shadow::elevation(elevation));
+    }
+
+    pub fn elevation(&self) -> f32 {
+        self.elevation
+    }
+
+    /// Display the text held by `state`, its changes are displayed on the next frame
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - State holding the text
+    pub fn bind_label(&mut self, state: State<String>) {
+        self.label_binding = Some(Binding::new(state));
+        self.tick(0.);
+    }
+
+    /// Name the button after its background when it has no text, e.g. an icon-only button
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - Action spoken by screen readers, e.g. `Close`
+    pub fn set_semantic_label(&mut self, label: &str) {
+        self.rect
+            .set_semantics(Some(Semantics::new(Role::Button, label)));
     }
 
-    fn visible(&self) -> bool {
-        self.visible
+    /// Returns the text of the button, or its semantic label for an icon-only button
+    fn name(&self) -> &str {
+        match (self.label.string(), self.rect.semantics()) {
+            ("", Some(semantics)) => semantics.label.as_str(),
+            (text, _) => text,
+        }
     }
 
+    /// Returns the color of the background while neither hovered nor pressed
+    fn idle_color(&self) -> Color {
+        if self.focused {
+            BLUE
+        } else {
+            RED
+        }
+    }
+
+    /// Move the feedback toward the hovered and pressed states, returns `true` if it changed
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Time elapsed since the last frame, in seconds
+    fn animate(&mut self, dt: f32) -> bool {
+        let pressed = self.presses.is_pressed();
+
+        self.feedback
+            .tick(dt, self.style.duration, self.hovered, pressed)
+    }
+
+    /// Follow a change of state, right away or over the duration of the style
+    fn refresh_feedback(&mut self) {
+        self.animate(0.);
+        self.place_background();
+        self.apply_color();
+    }
+
+    /// Place the background around the label, grown or shrunk by the feedback
+    fn place_background(&mut self) {
+        let (scale, paddings) = self.feedback.geometry(&self.style);
+        let center = self.position + self.size / 2.;
+        let corner = self.position - Vec2::new(paddings.x, paddings.y);
+        let size = self.size + Vec2::new(paddings.x + paddings.w, paddings.y + paddings.z);
+
+        // Scaled around the center of the button, its layout size is kept
+        let position = center + (corner - center) * scale;
+        self.rect.set_position(position);
+        self.rect.set_size(size * scale);
+        // Turns around the origin of the button, like the label
+        self.rect.set_transform(Transform {
+            origin: self.position + self.transform.origin - position,
+            ..self.transform
+        });
+    }
+
+    /// Color the background after the feedback, pressed over hovered over idle
+    fn apply_color(&mut self) {
+        let (hover, press) = self.feedback.eased();
+        let color = self.idle_color().mix(GREEN, hover).mix(BLUE, press);
+
+        self.rect.set_fill_color(color);
+    }
+
+    fn apply_opacity(&mut self) {
+        let opacity = self.opacity.effective();
+        self.rect.set_opacity(opacity);
+        self.label.set_opacity(opacity);
+    }
+
+    fn emit(&mut self, event: ButtonEvent) {
+        if event == ButtonEvent::Click {
+            audio::play_optional(self.sounds.on_click_sound);
+        }
+
+        self.events.push(event);
+    }
+}
+
+impl<'a> Widget for Button<'a> {
+    impl_widget_state!();
+    impl_widget_opacity!();
+    impl_widget_events!();
+
     fn size(&self) -> &Vec2 {
-        self.rect.size()
+        &self.size
     }
 
     fn set_size(&mut self, size: Vec2) {
+        // The given size is the room of the label, the paddings are added around it
+        self.label.set_container(size);
+
         let mut size = size;
         size.x += self.paddings.x + self.paddings.w;
         size.y += self.paddings.y + self.paddings.z;
         self.size = size;
-        self.rect.set_size(size);
-    }
-
-    fn events(&mut self, event_handler: Box<dyn Fn(u32)>) {
-        self.events.drain(..).for_each(|e| event_handler(e as u32));
-    }
-
-    fn emitted(&mut self, event: u32) -> bool {
-        !self
-            .events
-            .drain(..)
-            .filter(|e| *e as u32 == event)
-            .collect::<Vec<_>>()
-            .is_empty()
+        self.place_background();
     }
 
     fn update(&mut self) {
@@ -135,7 +530,7 @@ impl<'a> Widget for Button<'a> {
             x: label_bounds.width + self.paddings.x + self.paddings.w,
             y: label_bounds.height + self.paddings.y + self.paddings.z,
         };
-        self.rect.set_size(size);
+        self.size = size;
         // self.rect.set_size(self.size);
 
         let label_position = Vec2 {
@@ -147,40 +542,163 @@ impl<'a> Widget for Button<'a> {
         //     y: self.position.y + (self.size.y.ceil() - label_bounds.height) / 2.,
         // };
         self.label.set_position(label_position);
+
+        // Both turn around the origin of the button
+        self.label.set_transform(Transform {
+            origin: self.position + self.transform.origin - label_position,
+            ..self.transform
+        });
+        self.place_background();
+    }
+
+    fn debug_name(&self) -> String {
+        format!("Button#{}", self.name())
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.visible && self.enabled
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+        self.apply_color();
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.hovered = false;
+            self.focused = false;
+            self.presses = PressTimer::default();
+            self.refresh_feedback();
+        }
+    }
+
+    fn is_hovered(&self) -> bool {
+        self.hovered
+    }
+
+    fn accessibility(&self) -> AccessNode {
+        let mut node = AccessNode::new(Role::Button, self.bounds()).with_name(self.name());
+        node.focused = self.focused;
+
+        node
+    }
+
+    fn hit_test(&self, point: Vec2) -> bool {
+        // Tested against the untransformed shape, where the point was before the transform, at
+        // its layout size so that the feedback doesn't move the edges under the cursor
+        let shape = Rect {
+            x: self.position.x,
+            y: self.position.y,
+            width: self.size.x,
+            height: self.size.y,
+        };
+        transform::untransform_point(self.matrix(), point)
+            .is_some_and(|point| self.hit_shape.contains(shape, point))
+    }
+
+    fn tick(&mut self, dt: f32) {
+        if let Some(event) = self.presses.tick(dt) {
+            self.events.push(event);
+        }
+
+        if self.animate(dt) {
+            self.place_background();
+            self.apply_color();
+        }
+
+        if let Some(text) = self.label_binding.as_mut().and_then(Binding::changed) {
+            self.label.set_string(&text);
+            self.update();
+        }
+
+        let translated = self.label.follow_locale();
+        if self.label.follow_style() || translated {
+            self.update();
+        }
+    }
+
+    fn cursor(&self, _point: Vec2) -> Option<CursorIcon> {
+        self.enabled.then_some(CursorIcon::Hand)
     }
 
     fn process_events(&mut self, event: &WindowEvent) {
-        let bounds = self.rect.bounds();
+        if !self.enabled {
+            return;
+        }
 
         match event {
             WindowEvent::CursorMoved { position, .. } => {
                 let (x, y) = (position.x as f32, position.y as f32);
                 self.mouse_position = (x.round(), y.round()).into();
 
-                if bounds.contains(self.mouse_position) {
-                    self.rect.set_fill_color(GREEN);
+                let hovered = self.hit_test(self.mouse_position);
+                if hovered && !self.hovered {
+                    audio::play_optional(self.sounds.on_hover_sound);
+                }
+                let changed = hovered != self.hovered;
+                self.hovered = hovered;
+
+                // Stays pressed until released, even once the cursor left
+                if hovered && !self.presses.is_pressed() {
                     self.events.push(ButtonEvent::Hover);
-                } else {
-                    self.rect.set_fill_color(RED);
+                }
+                if changed {
+                    self.refresh_feedback();
+                }
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } => {
+                let hit = self.hit_test(self.mouse_position);
+                if self.focused {
+                    // Clicking elsewhere moves the focus away
+                    self.focused = hit;
+                    self.apply_color();
+                }
+
+                if hit {
+                    self.presses.press();
+                    self.refresh_feedback();
                 }
             }
             WindowEvent::MouseInput {
-                state,
+                state: ElementState::Released,
                 button: MouseButton::Left,
                 ..
             } => {
-                if state == &ElementState::Pressed && bounds.contains(self.mouse_position) {
-                    match *state {
-                        ElementState::Pressed => {
-                            self.events.push(ButtonEvent::Click);
-                            self.rect.set_fill_color(BLUE);
-                        }
-                        ElementState::Released => {
-                            self.rect.set_fill_color(RED);
-                        }
-                    }
+                let hit = self.hit_test(self.mouse_position);
+                let events = self.presses.release(hit);
+                if !events.is_empty() {
+                    events.into_iter().for_each(|event| self.emit(event));
+                    self.refresh_feedback();
                 }
             }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Right,
+                ..
+            } if self.hit_test(self.mouse_position) => self.events.push(ButtonEvent::RightClick),
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(VirtualKeyCode::Return | VirtualKeyCode::Space),
+                        ..
+                    },
+                ..
+            } if self.focused => self.emit(ButtonEvent::Click),
             _ => {}
         }
     }
@@ -193,3 +711,82 @@ impl<'a> Drawable for Button<'a> {
         self.label.draw(render_pass);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec4;
+
+    use super::{ButtonEvent, Feedback, PressTimer};
+    use crate::ui::theme::ButtonStyle;
+
+    #[test]
+    fn presses_make_clicks_double_clicks_and_long_presses() {
+        let mut presses = PressTimer::default();
+
+        presses.press();
+        assert_eq!(
+            presses.release(true),
+            [ButtonEvent::Released, ButtonEvent::Click]
+        );
+        presses.tick(0.1);
+        presses.press();
+        assert_eq!(
+            presses.release(true),
+            [
+                ButtonEvent::Released,
+                ButtonEvent::Click,
+                ButtonEvent::DoubleClick
+            ]
+        );
+
+        // Released outside, no click
+        presses.press();
+        assert_eq!(presses.release(false), [ButtonEvent::Released]);
+        assert!(presses.release(true).is_empty());
+
+        // Too slow for a double click
+        presses.tick(1.);
+        presses.press();
+        presses.release(true);
+        presses.tick(1.);
+        presses.press();
+        assert_eq!(
+            presses.release(true),
+            [ButtonEvent::Released, ButtonEvent::Click]
+        );
+
+        presses.tick(1.);
+        presses.press();
+        assert_eq!(presses.tick(0.3), None);
+        assert_eq!(presses.tick(0.3), Some(ButtonEvent::LongPress));
+        assert_eq!(presses.tick(0.3), None);
+        assert_eq!(presses.release(true), [ButtonEvent::Released]);
+    }
+
+    #[test]
+    fn feedback_eases_toward_the_pressed_look() {
+        let style = ButtonStyle {
+            hover_scale: 1.5,
+            press_scale: 0.5,
+            hover_paddings: Vec4::splat(2.),
+            press_paddings: Vec4::ZERO,
+            duration: 0.25,
+        };
+        let mut feedback = Feedback::default();
+        assert_eq!(feedback.geometry(&style), (1., Vec4::ZERO));
+
+        assert!(feedback.tick(0.125, style.duration, true, false));
+        assert_eq!(feedback.geometry(&style), (1.25, Vec4::splat(1.)));
+        feedback.tick(0.125, style.duration, true, false);
+        assert_eq!(feedback.geometry(&style), (1.5, Vec4::splat(2.)));
+        assert!(!feedback.tick(0.125, style.duration, true, false));
+
+        // Pressed over hovered
+        feedback.tick(1., style.duration, true, true);
+        assert_eq!(feedback.geometry(&style), (0.5, Vec4::ZERO));
+
+        // Instant without a duration
+        feedback.tick(0., 0., false, false);
+        assert_eq!(feedback, Feedback::default());
+    }
+}
@@ -0,0 +1,433 @@
+use glam::Vec2;
+use wgpu::RenderPass;
+use winit::event::{ElementState, KeyboardInput, MouseButton, VirtualKeyCode, WindowEvent};
+use winit::window::CursorIcon;
+
+use super::{direction::layout_direction, EventQueue, Opacity, Uid, Widget, WidgetEvent};
+use crate::accessibility::{AccessNode, Role};
+use crate::audio::{self, SoundStyle};
+use crate::graphics::shape::{RectangleShape, Shape};
+use crate::graphics::text::Text;
+use crate::graphics::text_style::TextStyle;
+use crate::graphics::{
+    color::{Color, BLUE, WHITE},
+    Drawable, Transformable,
+};
+use crate::i18n::Translation;
+use crate::{Ctx, ASSETS};
+
+/// Space between the box and the label
+const LABEL_SPACING: f32 = 6.;
+
+/// State of a check box, indeterminate when it sums up a mixed selection (tree, table, ..)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CheckState {
+    #[default]
+    Unchecked,
+    Checked,
+    Indeterminate,
+}
+
+impl CheckState {
+    /// Returns the state reached by clicking the box
+    ///
+    /// # Arguments
+    ///
+    /// * `tri_state` - The user can select the indeterminate state, otherwise it's only set by
+    ///   the application and clicking it checks the box
+    pub fn next(self, tri_state: bool) -> Self {
+        match (self, tri_state) {
+            (Self::Unchecked, _) => Self::Checked,
+            (Self::Checked, true) => Self::Indeterminate,
+            (Self::Checked, false) | (Self::Indeterminate, true) => Self::Unchecked,
+            (Self::Indeterminate, false) => Self::Checked,
+        }
+    }
+}
+
+/// Emitted when the user changes the state, one variant per state
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum CheckBoxEvent {
+    Unchecked,
+    Checked,
+    Indeterminate,
+}
+
+impl From<CheckState> for CheckBoxEvent {
+    fn from(state: CheckState) -> Self {
+        match state {
+            CheckState::Unchecked => Self::Unchecked,
+            CheckState::Checked => Self::Checked,
+            CheckState::Indeterminate => Self::Indeterminate,
+        }
+    }
+}
+
+impl WidgetEvent for CheckBoxEvent {}
+
+/// Box toggled by a click or the space bar, followed by a label
+pub struct CheckBox<'a> {
+    frame: RectangleShape,
+    /// Filled square when checked, horizontal bar when indeterminate
+    mark: RectangleShape,
+    label: Text<'a>,
+    state: CheckState,
+    tri_state: bool,
+    position: Vec2,
+    size: Vec2,
+    mouse_position: Vec2,
+    focused: bool,
+    visible: bool,
+    uid: Uid,
+    opacity: Opacity,
+    events: EventQueue<CheckBoxEvent>,
+    sounds: SoundStyle,
+    /// The cursor is over the box or its label
+    hovered: bool,
+    /// Disabled boxes ignore the inputs
+    enabled: bool,
+}
+
+impl<'a> CheckBox<'a> {
+    /// Create an unchecked box whose size follows the theme
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `text` - Text of the label
+    pub fn new(context: Ctx, text: &str) -> Self {
+        let theme = context.lock().unwrap().theme;
+        let metrics = theme.metrics();
+        let box_size = Vec2::splat(metrics.control_height * 0.6);
+
+        let mut frame = RectangleShape::new_with_label(
+            context.clone(),
+            box_size,
+            &format!("CheckBox#{text}/frame"),
+        );
+        frame.set_fill_color(Color::from((60, 60, 60)));
+
+        let mut mark = RectangleShape::new_with_label(
+            context.clone(),
+            Vec2::ZERO,
+            &format!("CheckBox#{text}/mark"),
+        );
+        mark.set_fill_color(WHITE);
+
+        let mut label = Text::new_with_label(
+            context,
+            text,
+            ASSETS.get_font("Roboto.ttf").unwrap(),
+            metrics.small_font_size,
+            &format!("CheckBox#{text}/label"),
+        );
+        label.set_fill_color(WHITE);
+
+        let mut check_box = Self {
+            frame,
+            mark,
+            label,
+            state: CheckState::default(),
+            tri_state: false,
+            position: Vec2::default(),
+            size: Vec2::ZERO,
+            mouse_position: Vec2::default(),
+            focused: false,
+            visible: true,
+            uid: Uid::new(),
+            opacity: Opacity::default(),
+            events: EventQueue::new(),
+            sounds: theme.sounds,
+            hovered: false,
+            enabled: true,
+        };
+        check_box.fit_label();
+
+        check_box
+    }
+
+    /// Create a check box labelled with a translated message, resolved again when the locale
+    /// changes
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `translation` - Key and arguments of the label
+    pub fn translated(context: Ctx, translation: Translation) -> Self {
+        let mut check_box = Self::new(context, translation.key());
+        check_box.label.set_translation(Some(translation));
+        check_box.fit_label();
+
+        check_box
+    }
+
+    pub fn state(&self) -> CheckState {
+        self.state
+    }
+
+    pub fn is_checked(&self) -> bool {
+        self.state == CheckState::Checked
+    }
+
+    /// Set the state without emitting an event, e.g. the indeterminate state of a parent whose
+    /// children are partly checked
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - New state
+    pub fn set_state(&mut self, state: CheckState) {
+        self.state = state;
+
+        self.update();
+    }
+
+    /// Let the user cycle through the indeterminate state, which is skipped by default
+    ///
+    /// # Arguments
+    ///
+    /// * `tri_state` - Include the indeterminate state in the cycle
+    pub fn set_tri_state(&mut self, tri_state: bool) {
+        self.tri_state = tri_state;
+    }
+
+    /// Set the sounds played when the box is hovered and toggled
+    ///
+    /// # Arguments
+    ///
+    /// * `sounds` - Names of sounds loaded in the bank of the assets
+    pub fn set_sounds(&mut self, sounds: SoundStyle) {
+        self.sounds = sounds;
+    }
+
+    fn toggle(&mut self) {
+        audio::play_optional(self.sounds.on_click_sound);
+        self.state = self.state.next(self.tri_state);
+        self.events.push(self.state.into());
+
+        self.update();
+    }
+
+    /// Style the label with a style shared with other texts, see [`Text::set_style`]
+    ///
+    /// # Arguments
+    ///
+    /// * `style` - Shared style, `None` to stop following it
+    pub fn set_label_style(&mut self, style: Option<TextStyle>) {
+        self.label.set_style(style);
+        self.fit_label();
+    }
+
+    /// Size the check box after its label
+    fn fit_label(&mut self) {
+        let box_size = *self.frame.size();
+        let label_bounds = self.label.bounds();
+        self.size = Vec2::new(
+            box_size.x + LABEL_SPACING + label_bounds.width,
+            box_size.y.max(label_bounds.height),
+        );
+
+        self.update();
+    }
+
+    fn apply_opacity(&mut self) {
+        let opacity = self.opacity.effective();
+        self.frame.set_opacity(opacity);
+        self.mark.set_opacity(opacity);
+        self.label.set_opacity(opacity);
+    }
+}
+
+impl<'a> Transformable for CheckBox<'a> {
+    fn position(&self) -> &Vec2 {
+        &self.position
+    }
+
+    fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+
+        self.update();
+    }
+}
+
+impl<'a> Widget for CheckBox<'a> {
+    impl_widget_state!();
+    impl_widget_opacity!();
+    impl_widget_events!();
+
+    fn process_events(&mut self, event: &WindowEvent) {
+        if !self.enabled {
+            return;
+        }
+
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.mouse_position = Vec2::new(position.x as f32, position.y as f32);
+
+                let hovered = self.hit_test(self.mouse_position);
+                if hovered && !self.hovered {
+                    audio::play_optional(self.sounds.on_hover_sound);
+                }
+                self.hovered = hovered;
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } => {
+                let hit = self.hit_test(self.mouse_position);
+                if hit {
+                    self.toggle();
+                }
+                self.set_focused(hit);
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(VirtualKeyCode::Space),
+                        ..
+                    },
+                ..
+            } if self.focused => self.toggle(),
+            _ => {}
+        }
+    }
+
+    fn update(&mut self) {
+        // The box is on the start side, followed by the label
+        let direction = layout_direction();
+        let box_size = *self.frame.size();
+        let box_position = Vec2::new(
+            self.position.x + direction.place(self.size.x, 0., box_size.x),
+            self.position.y + (self.size.y - box_size.y) / 2.,
+        );
+        self.frame.set_position(box_position);
+
+        let mark_size = match self.state {
+            CheckState::Unchecked => Vec2::ZERO,
+            CheckState::Checked => box_size * 0.6,
+            CheckState::Indeterminate => Vec2::new(box_size.x * 0.6, box_size.y * 0.2),
+        };
+        self.mark.set_size(mark_size);
+        self.mark
+            .set_position(box_position + (box_size - mark_size) / 2.);
+
+        let label_bounds = self.label.bounds();
+        self.label.set_position(Vec2::new(
+            self.position.x
+                + direction.place(self.size.x, box_size.x + LABEL_SPACING, label_bounds.width),
+            self.position.y + (self.size.y - label_bounds.height) / 2.,
+        ));
+    }
+
+    fn size(&self) -> &Vec2 {
+        &self.size
+    }
+
+    fn tick(&mut self, _dt: f32) {
+        let translated = self.label.follow_locale();
+        if self.label.follow_style() || translated {
+            self.fit_label();
+        }
+    }
+
+    fn debug_name(&self) -> String {
+        format!("CheckBox#{}", self.label.string())
+    }
+
+    fn cursor(&self, _point: Vec2) -> Option<CursorIcon> {
+        Some(CursorIcon::Hand)
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.visible && self.enabled
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.hovered = false;
+            self.set_focused(false);
+        }
+    }
+
+    fn is_hovered(&self) -> bool {
+        self.hovered
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+        self.frame.set_fill_color(if focused {
+            BLUE
+        } else {
+            Color::from((60, 60, 60))
+        });
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    fn accessibility(&self) -> AccessNode {
+        let mut node =
+            AccessNode::new(Role::CheckBox, self.bounds()).with_name(self.label.string());
+        node.checked = Some(self.state);
+        node.focused = self.focused;
+
+        node
+    }
+}
+
+impl<'a> Drawable for CheckBox<'a> {
+    fn draw<'b>(&'b mut self, render_pass: &mut RenderPass<'b>) {
+        if !self.visible {
+            return;
+        }
+
+        self.frame.draw(render_pass);
+        if self.state != CheckState::Unchecked {
+            self.mark.draw(render_pass);
+        }
+        self.label.draw(render_pass);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CheckState;
+
+    #[test]
+    fn indeterminate_is_skipped_unless_tri_state() {
+        let cycle = |tri_state| {
+            let mut state = CheckState::Unchecked;
+            (0..3)
+                .map(|_| {
+                    state = state.next(tri_state);
+                    state
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            cycle(false),
+            [
+                CheckState::Checked,
+                CheckState::Unchecked,
+                CheckState::Checked
+            ]
+        );
+        assert_eq!(
+            cycle(true),
+            [
+                CheckState::Checked,
+                CheckState::Indeterminate,
+                CheckState::Unchecked
+            ]
+        );
+        assert_eq!(CheckState::Indeterminate.next(false), CheckState::Checked);
+    }
+}
@@ -0,0 +1,89 @@
+use glam::Vec2;
+use winit::window::CursorIcon;
+
+use super::Widget;
+use crate::math::Rect;
+
+/// Returns the cursor of the deepest visible widget containing `point`, `None` if the subtree
+/// isn't hovered
+///
+/// Hovered widgets without a preference let their parent decide, e.g. a panel showing resize
+/// arrows on its edges.
+///
+/// # Arguments
+///
+/// * `widget` - Root of the searched subtree
+/// * `point` - Position of the pointer
+pub fn cursor_at(widget: &dyn Widget, point: Vec2) -> Option<CursorIcon> {
+    if !widget.visible() || !widget.hit_test(point) {
+        return None;
+    }
+
+    let child = widget
+        .children()
+        .into_iter()
+        // Children drawn last are on top
+        .rev()
+        .find_map(|child| cursor_at(child, point));
+
+    match child {
+        Some(CursorIcon::Default) | None => {
+            Some(widget.cursor(point).unwrap_or(CursorIcon::Default))
+        }
+        child => child,
+    }
+}
+
+/// Returns the resize arrow matching the edge or corner of `bounds` under `point`, `None` away
+/// from the edges
+///
+/// # Arguments
+///
+/// * `bounds` - Bounds of the resizable widget
+/// * `point` - Position of the pointer
+/// * `thickness` - Width of the grabbable band along the edges, in pixels
+pub fn edge_cursor(bounds: Rect, point: Vec2, thickness: f32) -> Option<CursorIcon> {
+    if !bounds.contains(point) {
+        return None;
+    }
+
+    let left = point.x - bounds.x <= thickness;
+    let right = bounds.x + bounds.width - point.x <= thickness;
+    let top = point.y - bounds.y <= thickness;
+    let bottom = bounds.y + bounds.height - point.y <= thickness;
+
+    match (left || right, top || bottom) {
+        (true, true) if left == top => Some(CursorIcon::NwseResize),
+        (true, true) => Some(CursorIcon::NeswResize),
+        (true, false) => Some(CursorIcon::EwResize),
+        (false, true) => Some(CursorIcon::NsResize),
+        (false, false) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+    use winit::window::CursorIcon;
+
+    use super::edge_cursor;
+    use crate::math::Rect;
+
+    #[test]
+    fn edges_show_resize_arrows() {
+        let bounds = Rect {
+            x: 0.,
+            y: 0.,
+            width: 100.,
+            height: 50.,
+        };
+        let at = |x, y| edge_cursor(bounds, Vec2::new(x, y), 4.);
+
+        assert_eq!(at(50., 25.), None);
+        assert_eq!(at(1., 25.), Some(CursorIcon::EwResize));
+        assert_eq!(at(50., 48.), Some(CursorIcon::NsResize));
+        assert_eq!(at(99., 49.), Some(CursorIcon::NwseResize));
+        assert_eq!(at(99., 1.), Some(CursorIcon::NeswResize));
+        assert_eq!(at(150., 25.), None);
+    }
+}
@@ -0,0 +1,400 @@
+use glam::Vec2;
+use winit::event::{ElementState, MouseButton, WindowEvent};
+use winit::window::CursorIcon;
+
+use super::button::{Button, ButtonEvent};
+use super::direction::layout_direction;
+use super::theme::Metrics;
+use super::{EventQueue, Opacity, Uid, Widget, WidgetEvent};
+use crate::accessibility::{AccessNode, Role};
+use crate::graphics::shadow::{DropShadow, Shadow};
+use crate::graphics::shape::{RectangleShape, Shape};
+use crate::graphics::text::Text;
+use crate::graphics::{
+    color::{Color, BLUE},
+    Drawable, Transformable,
+};
+use crate::math::Rect;
+use crate::{Ctx, ASSETS};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DialogEvent {
+    /// Dismissed through the close button
+    Closed,
+    /// Validated through the confirm button
+    Confirmed,
+}
+
+impl WidgetEvent for DialogEvent {}
+
+/// Window-in-window with a title bar, a close button and a confirm button
+///
+/// A modal dialog dims the widgets beneath it and keeps them from receiving inputs while it is
+/// visible, the dialog hides itself once closed or confirmed.
+pub struct Dialog<'a> {
+    title: Text<'a>,
+    titlebar: RectangleShape,
+    body: RectangleShape,
+    close_btn: Button<'a>,
+    confirm_btn: Button<'a>,
+    content: Option<Box<dyn Widget>>,
+    position: Vec2,
+    mouse_position: Vec2,
+    /// Cursor position relative to the dialog while dragged by the title bar
+    click_position: Option<Vec2>,
+    draggable: bool,
+    modal: bool,
+    visible: bool,
+    uid: Uid,
+    opacity: Opacity,
+    events: EventQueue<DialogEvent>,
+    size: Vec2,
+    /// Title bar height and body margin, from the theme
+    metrics: Metrics,
+    /// Shadow cast by the title bar and the body together
+    shadow: Option<DropShadow>,
+    elevation: f32,
+    context: Ctx,
+}
+
+impl<'a> Dialog<'a> {
+    /// Create a non-modal, draggable dialog
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `title` - Text of the title bar
+    /// * `size` - Size of the body, under the title bar
+    pub fn new(context: Ctx, title: &str, size: Vec2) -> Self {
+        let theme = context.lock().unwrap().theme;
+        let metrics = theme.metrics();
+
+        let mut titlebar = RectangleShape::new_with_label(
+            context.clone(),
+            (size.x, metrics.titlebar_height).into(),
+            &format!("Dialog#{title}/titlebar"),
+        );
+        titlebar.set_fill_color(BLUE);
+
+        let mut body =
+            RectangleShape::new_with_label(context.clone(), size, &format!("Dialog#{title}/body"));
+        body.set_fill_color(Color::from((60, 60, 60)));
+
+        let mut close_btn = Button::new("x", context.clone());
+        close_btn.set_character_size(metrics.small_font_size);
+        // Lies on the title bar
+        close_btn.set_elevation(0.);
+
+        let mut confirm_btn = Button::new("OK", context.clone());
+        confirm_btn.set_character_size(metrics.small_font_size + 2.);
+
+        let mut dialog = Self {
+            title: Text::new_with_label(
+                context.clone(),
+                title,
+                ASSETS.get_font("Roboto.ttf").unwrap(),
+                metrics.small_font_size,
+                &format!("Dialog#{title}/title"),
+            ),
+            titlebar,
+            body,
+            close_btn,
+            confirm_btn,
+            content: None,
+            position: Vec2::default(),
+            mouse_position: Vec2::default(),
+            click_position: None,
+            draggable: true,
+            modal: false,
+            visible: true,
+            uid: Uid::new(),
+            opacity: Opacity::default(),
+            events: EventQueue::new(),
+            size: size + Vec2::new(0., metrics.titlebar_height),
+            metrics,
+            shadow: None,
+            elevation: 0.,
+            context,
+        };
+        dialog.set_elevation(theme.elevations.dialog);
+        dialog.set_position(Vec2::default());
+
+        dialog
+    }
+
+    /// Dim the widgets beneath the dialog and block their inputs while it is visible
+    pub fn modal(mut self) -> Self {
+        self.modal = true;
+
+        self
+    }
+
+    /// Raise the dialog above the widgets beneath it, it casts a shadow growing with its
+    /// elevation
+    ///
+    /// # Arguments
+    ///
+    /// * `elevation` - Height above the background in pixels, 0 for no shadow
+    pub fn set_elevation(&mut self, elevation: f32) {
+        self.elevation = elevation;
+
+        let Some(shadow) = Shadow::elevation(elevation) else {
+            self.shadow = None;
+            return;
+        };
+        match &mut self.shadow {
+            Some(drop_shadow) => drop_shadow.set_shadow(shadow),
+            None => {
+                let mut drop_shadow = DropShadow::new_with_label(
+                    self.context.clone(),
+                    shadow,
+                    &format!("Dialog#{}/shadow", self.title.string()),
+                );
+                drop_shadow.set_bounds(self.bounds());
+                drop_shadow.set_opacity(self.opacity.effective());
+                self.shadow = Some(drop_shadow);
+            }
+        }
+    }
+
+    pub fn elevation(&self) -> f32 {
+        self.elevation
+    }
+
+    /// Allow or forbid moving the dialog by its title bar
+    pub fn set_draggable(&mut self, draggable: bool) {
+        self.draggable = draggable;
+
+        if !draggable {
+            self.click_position = None;
+        }
+    }
+
+    /// Set the widget displayed in the body, replacing the previous one
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - Widget placed in the top-left corner of the body
+    pub fn set_content(&mut self, mut content: Box<dyn Widget>) {
+        content.set_inherited_opacity(self.opacity.effective());
+        self.content = Some(content);
+
+        self.set_position(self.position);
+    }
+
+    fn close(&mut self, event: DialogEvent) {
+        self.set_visibility(false);
+        self.click_position = None;
+        self.events.push(event);
+    }
+
+    fn apply_opacity(&mut self) {
+        let opacity = self.opacity.effective();
+        self.titlebar.set_opacity(opacity);
+        self.body.set_opacity(opacity);
+        self.title.set_opacity(opacity);
+        if let Some(shadow) = &mut self.shadow {
+            shadow.set_opacity(opacity);
+        }
+        self.close_btn.set_inherited_opacity(opacity);
+        self.confirm_btn.set_inherited_opacity(opacity);
+
+        if let Some(content) = &mut self.content {
+            content.set_inherited_opacity(opacity);
+        }
+    }
+}
+
+impl<'a> Widget for Dialog<'a> {
+    impl_widget_state!();
+    impl_widget_opacity!();
+    impl_widget_events!();
+
+    fn process_events(&mut self, event: &WindowEvent) {
+        if !self.visible {
+            return;
+        }
+
+        self.close_btn.process_events(event);
+        self.confirm_btn.process_events(event);
+
+        if self.close_btn.emitted(ButtonEvent::Click as u32) {
+            self.close(DialogEvent::Closed);
+            return;
+        }
+        if self.confirm_btn.emitted(ButtonEvent::Click as u32) {
+            self.close(DialogEvent::Confirmed);
+            return;
+        }
+
+        if let Some(content) = &mut self.content {
+            content.process_events(event);
+        }
+
+        match event {
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => match state {
+                ElementState::Pressed => {
+                    if self.draggable && self.titlebar.bounds().contains(self.mouse_position) {
+                        self.click_position = Some(self.mouse_position - self.position);
+                    }
+                }
+                ElementState::Released => self.click_position = None,
+            },
+            WindowEvent::CursorMoved { position, .. } => {
+                self.mouse_position = (position.x as f32, position.y as f32).into();
+
+                if let Some(click_position) = self.click_position {
+                    self.set_position(self.mouse_position - click_position);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn is_modal(&self) -> bool {
+        self.modal && self.visible
+    }
+
+    fn is_focus_scope(&self) -> bool {
+        self.visible
+    }
+
+    fn size(&self) -> &Vec2 {
+        &self.size
+    }
+
+    fn children(&self) -> Vec<&dyn Widget> {
+        let mut children: Vec<&dyn Widget> = vec![&self.close_btn, &self.confirm_btn];
+        if let Some(content) = &self.content {
+            children.push(content.as_ref());
+        }
+
+        children
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        let mut children: Vec<&mut dyn Widget> = vec![&mut self.close_btn, &mut self.confirm_btn];
+        if let Some(content) = &mut self.content {
+            children.push(content.as_mut());
+        }
+
+        children
+    }
+
+    fn on_mount(&mut self, context: Ctx) {
+        self.children_mut()
+            .into_iter()
+            .for_each(|child| child.on_mount(context.clone()));
+    }
+
+    fn on_unmount(&mut self, context: Ctx) {
+        self.children_mut()
+            .into_iter()
+            .for_each(|child| child.on_unmount(context.clone()));
+    }
+
+    fn tick(&mut self, dt: f32) {
+        self.children_mut()
+            .into_iter()
+            .for_each(|child| child.tick(dt));
+    }
+
+    fn debug_name(&self) -> String {
+        format!("Dialog#{}", self.title.string())
+    }
+
+    fn accessibility(&self) -> AccessNode {
+        AccessNode::new(Role::Dialog, self.bounds()).with_name(self.title.string())
+    }
+
+    fn cursor(&self, point: Vec2) -> Option<CursorIcon> {
+        (self.draggable && self.titlebar.bounds().contains(point)).then_some(CursorIcon::Move)
+    }
+}
+
+impl<'a> Transformable for Dialog<'a> {
+    fn position(&self) -> &Vec2 {
+        &self.position
+    }
+
+    fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+        self.titlebar.set_position(position);
+        if let Some(shadow) = &mut self.shadow {
+            shadow.set_bounds(Rect {
+                x: position.x,
+                y: position.y,
+                width: self.size.x,
+                height: self.size.y,
+            });
+        }
+
+        // The title is on the start side, the close button on the end side
+        let direction = layout_direction();
+        let title_bounds = self.title.bounds();
+        self.title.set_position(Vec2 {
+            x: position.x + direction.place(self.size.x, 5., title_bounds.width),
+            y: position.y + (self.metrics.titlebar_height - title_bounds.height) / 2.,
+        });
+
+        let close_btn_size = *self.close_btn.size();
+        self.close_btn.set_position(Vec2 {
+            x: position.x
+                + direction.place(
+                    self.size.x,
+                    self.size.x - close_btn_size.x - 5.,
+                    close_btn_size.x,
+                ),
+            y: position.y + (self.metrics.titlebar_height - close_btn_size.y) / 2.,
+        });
+
+        let body_position = position + Vec2::new(0., self.metrics.titlebar_height);
+        self.body.set_position(body_position);
+
+        let margin = self.metrics.margin;
+        let confirm_btn_size = *self.confirm_btn.size();
+        self.confirm_btn.set_position(Vec2 {
+            x: position.x
+                + direction.place(
+                    self.size.x,
+                    self.size.x - confirm_btn_size.x - margin,
+                    confirm_btn_size.x,
+                ),
+            y: position.y + self.size.y - confirm_btn_size.y - margin,
+        });
+
+        if let Some(content) = &mut self.content {
+            let content_width = content.size().x;
+            content.set_position(Vec2 {
+                x: body_position.x + direction.place(self.size.x, margin, content_width),
+                y: body_position.y + margin,
+            });
+        }
+    }
+}
+
+impl<'a> Drawable for Dialog<'a> {
+    fn draw<'b>(&'b mut self, render_pass: &mut wgpu::RenderPass<'b>) {
+        if !self.visible {
+            return;
+        }
+
+        if let Some(shadow) = &mut self.shadow {
+            shadow.draw(render_pass);
+        }
+        self.titlebar.draw(render_pass);
+        self.body.draw(render_pass);
+        self.title.draw(render_pass);
+        self.close_btn.draw(render_pass);
+
+        if let Some(content) = &mut self.content {
+            content.draw(render_pass);
+        }
+
+        self.confirm_btn.draw(render_pass);
+    }
+}
@@ -0,0 +1,87 @@
+//! Layout direction of the widgets, mirrored horizontally for right-to-left languages
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use glam::Vec4;
+
+static RIGHT_TO_LEFT: AtomicBool = AtomicBool::new(false);
+
+/// Side the widgets start from: rows, paddings, icons and progress fills
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LayoutDirection {
+    #[default]
+    LeftToRight,
+    /// Arabic, Hebrew, .. layouts, the start side is on the right
+    RightToLeft,
+}
+
+/// Returns the direction the widgets are laid out in
+pub fn layout_direction() -> LayoutDirection {
+    if RIGHT_TO_LEFT.load(Ordering::Relaxed) {
+        LayoutDirection::RightToLeft
+    } else {
+        LayoutDirection::LeftToRight
+    }
+}
+
+/// Set the direction of every widget, they must be updated to follow it, see
+/// [`super::Ui::set_layout_direction`]
+///
+/// # Arguments
+///
+/// * `direction` - New direction
+pub fn set_layout_direction(direction: LayoutDirection) {
+    RIGHT_TO_LEFT.store(direction == LayoutDirection::RightToLeft, Ordering::Relaxed);
+}
+
+impl LayoutDirection {
+    pub fn is_right_to_left(self) -> bool {
+        self == Self::RightToLeft
+    }
+
+    /// Returns the horizontal offset, from the left edge of a container, of an element placed
+    /// `offset` away from the start edge
+    ///
+    /// # Arguments
+    ///
+    /// * `container_width` - Width of the container
+    /// * `offset` - Distance between the start edge of the container and the element
+    /// * `width` - Width of the element
+    pub fn place(self, container_width: f32, offset: f32, width: f32) -> f32 {
+        match self {
+            Self::LeftToRight => offset,
+            Self::RightToLeft => container_width - offset - width,
+        }
+    }
+
+    /// Returns the left, top, bottom and right paddings of start, top, bottom and end paddings
+    ///
+    /// # Arguments
+    ///
+    /// * `paddings` - Start, top, bottom and end paddings
+    pub fn paddings(self, paddings: Vec4) -> Vec4 {
+        match self {
+            Self::LeftToRight => paddings,
+            Self::RightToLeft => Vec4::new(paddings.w, paddings.y, paddings.z, paddings.x),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec4;
+
+    use super::LayoutDirection;
+
+    #[test]
+    fn right_to_left_mirrors_the_placement() {
+        let (ltr, rtl) = (LayoutDirection::LeftToRight, LayoutDirection::RightToLeft);
+
+        assert_eq!(ltr.place(100., 10., 30.), 10.);
+        assert_eq!(rtl.place(100., 10., 30.), 60.);
+
+        let paddings = Vec4::new(1., 2., 3., 4.);
+        assert_eq!(ltr.paddings(paddings), paddings);
+        assert_eq!(rtl.paddings(paddings), Vec4::new(4., 2., 3., 1.));
+    }
+}
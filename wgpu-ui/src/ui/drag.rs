@@ -0,0 +1,169 @@
+use std::{
+    any::Any,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use glam::Vec2;
+
+use super::{Uid, Widget};
+
+/// Distance the cursor has to travel with the button held before a drag starts, in pixels
+pub const DRAG_THRESHOLD: f32 = 5.;
+
+/// Opacity of the snapshot following the cursor during a drag
+pub const GHOST_OPACITY: f32 = 0.6;
+
+/// Data carried by a drag, of any type
+#[derive(Clone)]
+pub struct DragPayload(Rc<dyn Any>);
+
+impl DragPayload {
+    pub fn new<T: Any>(value: T) -> Self {
+        Self(Rc::new(value))
+    }
+
+    /// Returns the payload if it is a `T`
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+
+    pub fn is<T: Any>(&self) -> bool {
+        self.0.is::<T>()
+    }
+}
+
+impl std::fmt::Debug for DragPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DragPayload").finish_non_exhaustive()
+    }
+}
+
+/// A payload dropped on a widget
+#[derive(Debug, Clone)]
+pub struct Dropped {
+    pub source: Uid,
+    pub target: Uid,
+    pub payload: DragPayload,
+}
+
+/// Files dragged from the OS over the window, the payload of the drops coming from outside
+///
+/// Drop targets accept them like any payload, see [`Widget::accepts_drop`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilesDropped(pub Vec<PathBuf>);
+
+impl FilesDropped {
+    /// Returns `true` if every file has one of `extensions`, whatever their case
+    ///
+    /// # Arguments
+    ///
+    /// * `extensions` - Accepted extensions without their dot, e.g. `["png", "jpg"]`
+    pub fn all_with_extension(&self, extensions: &[&str]) -> bool {
+        let accepted = |path: &Path| {
+            path.extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| {
+                    extensions
+                        .iter()
+                        .any(|accepted| accepted.eq_ignore_ascii_case(extension))
+                })
+        };
+
+        !self.0.is_empty() && self.0.iter().all(|path| accepted(path))
+    }
+}
+
+/// Files dropped from the OS, on a widget accepting them or on the window
+#[derive(Debug, Clone)]
+pub struct FileDrop {
+    /// Widget the files were dropped on, `None` when no widget accepted them
+    pub target: Option<Uid>,
+    pub files: Vec<PathBuf>,
+}
+
+/// Returns the deepest visible widget containing `point`
+///
+/// # Arguments
+///
+/// * `widget` - Root of the searched subtree
+/// * `point` - Position to test
+pub fn widget_at(widget: &dyn Widget, point: Vec2) -> Option<Uid> {
+    if !widget.visible() || !widget.hit_test(point) {
+        return None;
+    }
+
+    widget
+        .children()
+        .into_iter()
+        // Children drawn last are on top
+        .rev()
+        .find_map(|child| widget_at(child, point))
+        .or(Some(widget.uid()))
+}
+
+/// Returns the deepest visible widget containing `point` and accepting `payload`
+///
+/// # Arguments
+///
+/// * `widget` - Root of the searched subtree
+/// * `point` - Position to test
+/// * `payload` - Dragged payload
+pub fn drop_target(widget: &dyn Widget, point: Vec2, payload: &DragPayload) -> Option<Uid> {
+    if !widget.visible() || !widget.hit_test(point) {
+        return None;
+    }
+
+    widget
+        .children()
+        .into_iter()
+        .rev()
+        .find_map(|child| drop_target(child, point, payload))
+        .or_else(|| widget.accepts_drop(payload).then(|| widget.uid()))
+}
+
+/// Returns the widget identified by `uid` in a subtree
+///
+/// # Arguments
+///
+/// * `widget` - Root of the searched subtree
+/// * `uid` - Identity of the widget
+pub fn find_mut(widget: &mut dyn Widget, uid: Uid) -> Option<&mut dyn Widget> {
+    if widget.uid() == uid {
+        return Some(widget);
+    }
+
+    for child in widget.children_mut() {
+        if let Some(found) = find_mut(child, uid) {
+            return Some(found);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{DragPayload, FilesDropped};
+
+    #[test]
+    fn payload_is_typed() {
+        let payload = DragPayload::new(42u32);
+
+        assert!(payload.is::<u32>());
+        assert_eq!(payload.get::<u32>(), Some(&42));
+        assert_eq!(payload.get::<String>(), None);
+    }
+
+    #[test]
+    fn dropped_files_are_filtered_by_extension() {
+        let files = |names: &[&str]| FilesDropped(names.iter().map(PathBuf::from).collect());
+
+        assert!(files(&["photo.PNG", "scan.jpg"]).all_with_extension(&["png", "jpg"]));
+        assert!(!files(&["photo.png", "notes.txt"]).all_with_extension(&["png", "jpg"]));
+        assert!(!files(&["Makefile"]).all_with_extension(&["png"]));
+        assert!(!files(&[]).all_with_extension(&["png"]));
+    }
+}
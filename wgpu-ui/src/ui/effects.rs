@@ -0,0 +1,272 @@
+//! Effects attached to widgets and driven by their state: grays while disabled, a glow while
+//! hovered or focused and a shake when their content turns invalid
+
+use std::f32::consts::TAU;
+
+use glam::Vec2;
+use wgpu::RenderPass;
+
+use super::Widget;
+use crate::graphics::{
+    blend::{self, BlendMode},
+    color::Color,
+    render_texture::RenderTexture,
+    shadow::{DropShadow, Shadow},
+    Drawable,
+};
+use crate::math::Rect;
+use crate::Ctx;
+
+/// Time the glow takes to appear or disappear, in seconds
+const GLOW_FADE: f32 = 0.15;
+/// Room around the widget kept in its snapshot, for its shadow
+const SNAPSHOT_MARGIN: f32 = 16.;
+
+/// Light around a widget, added to the colors beneath
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glow {
+    pub color: Color,
+    /// Distance the glow spreads over, in pixels
+    pub radius: f32,
+    /// Opacity of the glow along the edges of the widget, from 0 to 1
+    pub intensity: f32,
+}
+
+impl Default for Glow {
+    fn default() -> Self {
+        Self {
+            color: Color::from((90, 160, 255)),
+            radius: 12.,
+            intensity: 0.8,
+        }
+    }
+}
+
+/// Horizontal shake, fading out over its duration
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shake {
+    /// Largest offset, in pixels
+    pub amplitude: f32,
+    /// Back and forth moves per second
+    pub frequency: f32,
+    /// In seconds
+    pub duration: f32,
+}
+
+impl Default for Shake {
+    fn default() -> Self {
+        Self {
+            amplitude: 8.,
+            frequency: 12.,
+            duration: 0.4,
+        }
+    }
+}
+
+/// Returns the horizontal offset of a shaken widget
+///
+/// # Arguments
+///
+/// * `shake` - Look of the shake
+/// * `elapsed` - Time since the shake started, in seconds
+pub fn shake_offset(shake: &Shake, elapsed: f32) -> f32 {
+    if elapsed >= shake.duration || shake.duration <= 0. {
+        return 0.;
+    }
+
+    let decay = 1. - elapsed / shake.duration;
+    shake.amplitude * decay * (TAU * shake.frequency * elapsed).sin()
+}
+
+/// Effects of a widget, see [`super::Ui::set_effects`]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct WidgetEffects {
+    /// Drawn in shades of gray while [`Widget::is_enabled`] is `false`
+    pub grayscale_disabled: bool,
+    /// Shown while the widget is hovered or focused
+    pub glow: Option<Glow>,
+    /// Played each time [`Widget::is_invalid`] turns `true`
+    pub shake: Option<Shake>,
+}
+
+impl WidgetEffects {
+    /// Returns the usual effects of an interactive widget
+    pub fn interactive() -> Self {
+        Self {
+            grayscale_disabled: true,
+            glow: Some(Glow::default()),
+            shake: Some(Shake::default()),
+        }
+    }
+}
+
+/// Effects of a widget and their progress
+pub struct EffectState {
+    context: Ctx,
+    label: String,
+    effects: WidgetEffects,
+    glow: Option<DropShadow>,
+    /// Opacity of the glow, from 0 to 1
+    glow_strength: f32,
+    /// Time since the shake started, `None` while still
+    shaking: Option<f32>,
+    /// Offset the shake moved the widget by
+    shake_offset: f32,
+    /// The widget was invalid on the last frame
+    was_invalid: bool,
+    /// Drawn instead of the widget while it is disabled
+    snapshot: Option<RenderTexture>,
+}
+
+impl EffectState {
+    /// Create the resources of the effects
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `effects` - Attached effects
+    /// * `label` - Debug name of the GPU resources, e.g. `Button#save/effects`
+    pub fn new(context: Ctx, effects: WidgetEffects, label: &str) -> Self {
+        let glow = effects.glow.map(|glow| {
+            let mut shadow = DropShadow::new_with_label(
+                context.clone(),
+                Shadow {
+                    offset: Vec2::ZERO,
+                    blur: glow.radius,
+                    color: glow.color,
+                    opacity: glow.intensity,
+                },
+                &format!("{label}/glow"),
+            );
+            shadow.set_opacity(0.);
+
+            shadow
+        });
+
+        Self {
+            context,
+            label: label.to_string(),
+            effects,
+            glow,
+            glow_strength: 0.,
+            shaking: None,
+            shake_offset: 0.,
+            was_invalid: false,
+            snapshot: None,
+        }
+    }
+
+    pub fn effects(&self) -> &WidgetEffects {
+        &self.effects
+    }
+
+    /// Returns the snapshot to render the widget into this frame, while it is drawn in grays
+    pub fn snapshot_mut(&mut self) -> Option<&mut RenderTexture> {
+        self.snapshot.as_mut()
+    }
+
+    /// Follow the state of the widget, the shake moves it
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Time elapsed since the last frame, in seconds
+    /// * `widget` - Widget the effects are attached to
+    pub fn tick(&mut self, dt: f32, widget: &mut dyn Widget) {
+        let bounds = widget.bounds();
+
+        if let Some(glow) = &mut self.glow {
+            let lit = widget.is_enabled() && (widget.is_hovered() || widget.is_focused());
+            let step = dt / GLOW_FADE;
+            self.glow_strength = if lit {
+                (self.glow_strength + step).min(1.)
+            } else {
+                (self.glow_strength - step).max(0.)
+            };
+
+            glow.set_bounds(bounds);
+            glow.set_opacity(self.glow_strength * widget.opacity());
+        }
+
+        let invalid = widget.is_invalid();
+        if invalid && !self.was_invalid && self.effects.shake.is_some() {
+            self.shaking = Some(0.);
+        }
+        self.was_invalid = invalid;
+
+        if let (Some(elapsed), Some(shake)) = (&mut self.shaking, self.effects.shake) {
+            *elapsed += dt;
+            let offset = shake_offset(&shake, *elapsed);
+            let position = *widget.position();
+            widget.set_position(position + Vec2::new(offset - self.shake_offset, 0.));
+            self.shake_offset = offset;
+
+            if *elapsed >= shake.duration {
+                self.shaking = None;
+            }
+        }
+
+        let grayscale = self.effects.grayscale_disabled && !widget.is_enabled();
+        if !grayscale {
+            self.snapshot = None;
+            return;
+        }
+
+        let snapshot = self.snapshot.get_or_insert_with(|| {
+            let mut snapshot =
+                RenderTexture::new(self.context.clone(), &format!("{}/grayscale", self.label));
+            snapshot.set_grayscale(true);
+
+            snapshot
+        });
+        let source = Rect {
+            x: bounds.x - SNAPSHOT_MARGIN,
+            y: bounds.y - SNAPSHOT_MARGIN,
+            width: bounds.width + 2. * SNAPSHOT_MARGIN,
+            height: bounds.height + 2. * SNAPSHOT_MARGIN,
+        };
+        snapshot.set_source(source);
+        snapshot.set_position(source.position());
+    }
+
+    /// Draw the glow then the widget, or its snapshot in grays
+    ///
+    /// # Arguments
+    ///
+    /// * `widget` - Widget the effects are attached to
+    /// * `render_pass` - Pass drawing the Ui
+    pub fn draw<'a>(&'a mut self, widget: &'a mut dyn Widget, render_pass: &mut RenderPass<'a>) {
+        let lit = self.glow_strength > 0.;
+        if let Some(glow) = self.glow.as_mut().filter(|_| lit) {
+            let blend = blend::blend_mode();
+            blend::set_blend_mode(BlendMode::Additive);
+            glow.draw(render_pass);
+            blend::set_blend_mode(blend);
+        }
+
+        match &mut self.snapshot {
+            Some(snapshot) => snapshot.draw(render_pass),
+            None => widget.draw(render_pass),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{shake_offset, Shake};
+
+    #[test]
+    fn shakes_fade_out_and_stop_in_place() {
+        let shake = Shake {
+            amplitude: 10.,
+            frequency: 1.,
+            duration: 1.,
+        };
+
+        assert_eq!(shake_offset(&shake, 0.), 0.);
+        // Peaks a quarter period in, already faded by a quarter
+        assert!((shake_offset(&shake, 0.25) - 7.5).abs() < 1e-4);
+        assert!((shake_offset(&shake, 0.75) + 2.5).abs() < 1e-4);
+        assert_eq!(shake_offset(&shake, 1.), 0.);
+        assert_eq!(shake_offset(&shake, 3.), 0.);
+    }
+}
@@ -0,0 +1,327 @@
+//! Fixed-capacity containers, used instead of heap growing ones when the `fixed-capacity`
+//! feature is enabled.
+
+use std::ops::RangeFull;
+
+use glam::{Vec2, Vec4};
+use tracing::warn;
+
+use super::{
+    direction::layout_direction,
+    gesture::Gesture,
+    layout::{fit_size, stack, Direction},
+    Opacity, Uid, Widget,
+};
+use crate::graphics::{Drawable, Transformable};
+use crate::Ctx;
+
+/// Vector storing up to `N` elements inline
+pub struct FixedVec<T, const N: usize> {
+    items: [Option<T>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> Default for FixedVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> FixedVec<T, N> {
+    pub fn new() -> Self {
+        Self {
+            items: std::array::from_fn(|_| None),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Append an element, giving it back if the vector is full
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Element to append
+    pub fn try_push(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+
+        self.items[self.len] = Some(value);
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Append an element, the element is dropped if the vector is full
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Element to append
+    pub fn push(&mut self, value: T) {
+        if self.try_push(value).is_err() {
+            warn!("FixedVec capacity ({N}) exceeded, element dropped");
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.items[..self.len].iter().flatten()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.items[..self.len].iter_mut().flatten()
+    }
+
+    /// Remove every element, mirrors `Vec::drain(..)`
+    pub fn drain(&mut self, _range: RangeFull) -> impl Iterator<Item = T> + '_ {
+        let len = std::mem::take(&mut self.len);
+
+        self.items[..len].iter_mut().filter_map(Option::take)
+    }
+}
+
+/// Layout whose children are stored inline, up to `N` of them
+pub struct FixedLayout<const N: usize> {
+    direction: Direction,
+    widgets: FixedVec<Box<dyn Widget>, N>,
+    position: Vec2,
+    visible: bool,
+    uid: Uid,
+    opacity: Opacity,
+    size: Vec2,
+    spacing: f32,
+    paddings: Vec4,
+    /// Size computed from the children after each layout
+    fit_content: bool,
+    context: Option<Ctx>,
+}
+
+impl<const N: usize> FixedLayout<N> {
+    pub fn new(direction: Direction) -> Self {
+        Self {
+            direction,
+            widgets: FixedVec::new(),
+            position: Default::default(),
+            visible: true,
+            uid: Uid::new(),
+            opacity: Opacity::default(),
+            size: Default::default(),
+            spacing: 3.,
+            paddings: Vec4::ZERO,
+            fit_content: false,
+            context: None,
+        }
+    }
+
+    /// Add a child, giving it back if the layout is full
+    ///
+    /// # Arguments
+    ///
+    /// * `widget` - Child to add
+    pub fn add_widget(&mut self, mut widget: Box<dyn Widget>) -> Result<(), Box<dyn Widget>> {
+        if self.widgets.is_full() {
+            return Err(widget);
+        }
+
+        if let Some(context) = &self.context {
+            widget.on_mount(context.clone());
+        }
+        widget.set_inherited_opacity(self.opacity.effective());
+        self.widgets.push(widget);
+
+        self.update();
+
+        Ok(())
+    }
+
+    /// Size the layout from its children plus paddings, see [`super::layout::Layout::fit_content`]
+    pub fn fit_content(mut self) -> Self {
+        self.fit_content = true;
+        self.update();
+
+        self
+    }
+
+    /// Set the space kept between the layout edges and its children
+    ///
+    /// # Arguments
+    ///
+    /// * `paddings` - Start, top, bottom and end paddings
+    pub fn set_paddings(&mut self, paddings: Vec4) {
+        self.paddings = paddings;
+
+        self.update();
+    }
+
+    pub fn set_spacing(&mut self, spacing: f32) {
+        self.spacing = spacing;
+
+        self.update();
+    }
+
+    fn apply_opacity(&mut self) {
+        let opacity = self.opacity.effective();
+        self.widgets
+            .iter_mut()
+            .for_each(|widget| widget.set_inherited_opacity(opacity));
+    }
+}
+
+impl<const N: usize> Transformable for FixedLayout<N> {
+    fn position(&self) -> &Vec2 {
+        &self.position
+    }
+
+    fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+
+        self.update();
+    }
+}
+
+impl<const N: usize> Drawable for FixedLayout<N> {
+    fn draw<'b>(&'b mut self, render_pass: &mut wgpu::RenderPass<'b>) {
+        self.widgets
+            .iter_mut()
+            .for_each(|widget| widget.draw(render_pass));
+    }
+}
+
+impl<const N: usize> Widget for FixedLayout<N> {
+    impl_widget_state!();
+    impl_widget_opacity!();
+
+    fn process_events(&mut self, event: &winit::event::WindowEvent) {
+        self.widgets
+            .iter_mut()
+            .for_each(|widget| widget.process_events(event));
+    }
+
+    fn process_gesture(&mut self, gesture: &Gesture) {
+        self.widgets
+            .iter_mut()
+            .for_each(|widget| widget.process_gesture(gesture));
+    }
+
+    fn update(&mut self) {
+        if self.fit_content {
+            self.widgets.iter_mut().for_each(|widget| widget.update());
+        }
+
+        let biggest_dimensions = self
+            .widgets
+            .iter()
+            .fold(Vec2::default(), |biggest, widget| {
+                biggest.max(*widget.size())
+            });
+
+        let layout_direction = layout_direction();
+        let paddings = layout_direction.paddings(self.paddings);
+        let width = if self.fit_content {
+            0.
+        } else {
+            self.size.x - paddings.x - paddings.w
+        };
+        let positions = stack(
+            &self.direction,
+            layout_direction,
+            self.position + Vec2::new(paddings.x, paddings.y),
+            width,
+            self.widgets.len(),
+            biggest_dimensions,
+            self.spacing,
+        );
+        self.widgets
+            .iter_mut()
+            .zip(positions)
+            .for_each(|(widget, position)| {
+                widget.set_size(biggest_dimensions);
+                widget.set_position(position);
+                widget.update();
+            });
+
+        if self.fit_content {
+            self.size = fit_size(
+                self.position,
+                self.widgets.iter().map(|widget| widget.bounds()),
+                paddings,
+            );
+        }
+    }
+
+    fn on_mount(&mut self, context: Ctx) {
+        self.widgets
+            .iter_mut()
+            .for_each(|widget| widget.on_mount(context.clone()));
+
+        self.context = Some(context);
+    }
+
+    fn on_unmount(&mut self, context: Ctx) {
+        self.context = None;
+
+        self.widgets
+            .iter_mut()
+            .for_each(|widget| widget.on_unmount(context.clone()));
+    }
+
+    fn tick(&mut self, dt: f32) {
+        self.widgets.iter_mut().for_each(|widget| widget.tick(dt));
+    }
+
+    fn size(&self) -> &Vec2 {
+        &self.size
+    }
+
+    fn set_size(&mut self, size: Vec2) {
+        self.size = size;
+    }
+
+    fn children(&self) -> Vec<&dyn Widget> {
+        self.widgets.iter().map(|widget| widget.as_ref()).collect()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        self.widgets
+            .iter_mut()
+            .map(|widget| widget.as_mut() as &mut dyn Widget)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FixedVec;
+
+    #[test]
+    fn push_until_full() {
+        let mut vec = FixedVec::<u32, 2>::new();
+        assert!(vec.try_push(1).is_ok());
+        assert!(vec.try_push(2).is_ok());
+        assert_eq!(vec.try_push(3), Err(3));
+        assert_eq!(vec.iter().copied().collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn drain_empties() {
+        let mut vec = FixedVec::<u32, 4>::new();
+        vec.push(1);
+        vec.push(2);
+
+        assert_eq!(vec.drain(..).collect::<Vec<_>>(), vec![1, 2]);
+        assert!(vec.is_empty());
+
+        vec.push(3);
+        assert_eq!(vec.iter().copied().collect::<Vec<_>>(), vec![3]);
+    }
+}
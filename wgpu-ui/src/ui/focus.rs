@@ -0,0 +1,164 @@
+//! Keyboard focus, moved with Tab and trapped within the open dialogs and popups
+
+use super::{Uid, Widget};
+
+/// Returns the visible widgets taking the focus, in the order Tab reaches them
+///
+/// # Arguments
+///
+/// * `widget` - Root of the searched subtree
+pub fn focusable(widget: &dyn Widget) -> Vec<Uid> {
+    if !widget.visible() {
+        return Vec::new();
+    }
+
+    let mut uids = Vec::new();
+    if widget.is_focusable() {
+        uids.push(widget.uid());
+    }
+    for child in widget.children() {
+        uids.extend(focusable(child));
+    }
+
+    uids
+}
+
+/// Returns the focused widgets of the subtree, usually one at most
+///
+/// # Arguments
+///
+/// * `widget` - Root of the searched subtree
+pub fn focused(widget: &dyn Widget) -> Vec<Uid> {
+    let mut uids = Vec::new();
+    if widget.is_focused() {
+        uids.push(widget.uid());
+    }
+    for child in widget.children() {
+        uids.extend(focused(child));
+    }
+
+    uids
+}
+
+/// Returns the widget after `current` in `candidates`, wrapping around
+///
+/// # Arguments
+///
+/// * `candidates` - Focusable widgets, in Tab order
+/// * `current` - Focused widget, the first or last candidate is returned if it isn't one of them
+/// * `backward` - Move to the previous widget, as Shift+Tab does
+pub fn next(candidates: &[Uid], current: Option<Uid>, backward: bool) -> Option<Uid> {
+    let count = candidates.len();
+    if count == 0 {
+        return None;
+    }
+
+    let index = current.and_then(|uid| candidates.iter().position(|other| *other == uid));
+    let index = match (index, backward) {
+        (Some(index), false) => (index + 1) % count,
+        (Some(index), true) => (index + count - 1) % count,
+        (None, false) => 0,
+        (None, true) => count - 1,
+    };
+
+    Some(candidates[index])
+}
+
+/// Change of the innermost focus scope
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeChange {
+    /// A scope opened, its first widget takes the focus
+    Entered(Uid),
+    /// The innermost scope closed, the focus goes back to the widget focused before it opened
+    Left(Option<Uid>),
+}
+
+/// Open focus scopes, innermost last, with the widget focused before each one opened
+#[derive(Debug, Default)]
+pub struct FocusScopes {
+    scopes: Vec<(Uid, Option<Uid>)>,
+}
+
+impl FocusScopes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the innermost open scope, Tab cycles within it
+    pub fn current(&self) -> Option<Uid> {
+        self.scopes.last().map(|(scope, _)| *scope)
+    }
+
+    /// Follow the scopes opened and closed since the last call
+    ///
+    /// # Arguments
+    ///
+    /// * `open` - Scopes currently open, back to front
+    /// * `focused` - Widget currently focused
+    pub fn sync(&mut self, open: &[Uid], focused: Option<Uid>) -> Option<ScopeChange> {
+        let mut restored = None;
+        while let Some((scope, previous)) = self.scopes.last().copied() {
+            if open.contains(&scope) {
+                break;
+            }
+            self.scopes.pop();
+            restored = Some(previous);
+        }
+
+        let opened = open
+            .iter()
+            .filter(|scope| !self.scopes.iter().any(|(other, _)| other == *scope))
+            .copied()
+            .collect::<Vec<_>>();
+        // Closing a scope while opening another one hands the focus of the closed one over
+        let mut previous = restored.unwrap_or(focused);
+        for scope in &opened {
+            self.scopes.push((*scope, previous));
+            previous = None;
+        }
+
+        match (opened.last(), restored) {
+            (Some(scope), _) => Some(ScopeChange::Entered(*scope)),
+            (None, Some(previous)) => Some(ScopeChange::Left(previous)),
+            (None, None) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{next, FocusScopes, ScopeChange};
+    use crate::ui::Uid;
+
+    #[test]
+    fn tab_wraps_around() {
+        let [a, b, c] = [Uid::new(), Uid::new(), Uid::new()];
+        let candidates = [a, b, c];
+
+        assert_eq!(next(&candidates, None, false), Some(a));
+        assert_eq!(next(&candidates, Some(c), false), Some(a));
+        assert_eq!(next(&candidates, Some(a), true), Some(c));
+        assert_eq!(next(&[], Some(a), false), None);
+    }
+
+    #[test]
+    fn closing_a_scope_restores_the_focus() {
+        let (input, dialog, popup) = (Uid::new(), Uid::new(), Uid::new());
+        let mut scopes = FocusScopes::new();
+
+        assert_eq!(
+            scopes.sync(&[dialog], Some(input)),
+            Some(ScopeChange::Entered(dialog))
+        );
+        assert_eq!(
+            scopes.sync(&[dialog, popup], None),
+            Some(ScopeChange::Entered(popup))
+        );
+        assert_eq!(scopes.sync(&[dialog, popup], None), None);
+        assert_eq!(scopes.current(), Some(popup));
+
+        assert_eq!(scopes.sync(&[dialog], None), Some(ScopeChange::Left(None)));
+        assert_eq!(scopes.sync(&[], None), Some(ScopeChange::Left(Some(input))));
+        assert_eq!(scopes.current(), None);
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::BTreeMap;
+
+use glam::Vec2;
+use winit::event::TouchPhase;
+
+/// Minimum distance a finger has to travel before a touch becomes a drag, in pixels
+const DRAG_THRESHOLD: f32 = 10.;
+/// Time a finger has to stay still to trigger a long press, in seconds
+pub const LONG_PRESS_DURATION: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// A short touch without movement
+    Tap(Vec2),
+    /// A touch held without movement
+    LongPress(Vec2),
+    /// A single finger moving, `delta` is the movement since the previous drag gesture
+    Drag { position: Vec2, delta: Vec2 },
+    /// The finger of a drag was lifted
+    DragEnd(Vec2),
+    /// Two fingers moving, `scale` is relative to their distance when the second one touched
+    Pinch { center: Vec2, scale: f32 },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TouchPoint {
+    start: Vec2,
+    position: Vec2,
+    elapsed: f32,
+}
+
+/// Turns raw touch events into gestures
+#[derive(Debug, Default)]
+pub struct GestureRecognizer {
+    touches: BTreeMap<u64, TouchPoint>,
+    dragging: bool,
+    long_pressed: bool,
+    /// Set when more than one finger took part in the current interaction
+    multi_touch: bool,
+    pinch_distance: Option<f32>,
+}
+
+impl GestureRecognizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of fingers currently touching the screen
+    pub fn touch_count(&self) -> usize {
+        self.touches.len()
+    }
+
+    /// Feed a touch event, returns the recognized gestures
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Identifier of the finger
+    /// * `phase` - Phase of the touch
+    /// * `position` - Position of the finger
+    pub fn touch(&mut self, id: u64, phase: TouchPhase, position: Vec2) -> Vec<Gesture> {
+        let mut gestures = Vec::new();
+
+        match phase {
+            TouchPhase::Started => {
+                if self.touches.is_empty() {
+                    self.dragging = false;
+                    self.long_pressed = false;
+                    self.multi_touch = false;
+                }
+
+                self.touches.insert(
+                    id,
+                    TouchPoint {
+                        start: position,
+                        position,
+                        elapsed: 0.,
+                    },
+                );
+
+                if self.touches.len() == 2 {
+                    self.multi_touch = true;
+                    self.pinch_distance = Some(self.distance());
+                }
+            }
+            TouchPhase::Moved => {
+                let Some(touch) = self.touches.get_mut(&id) else {
+                    return gestures;
+                };
+                let delta = position - touch.position;
+                touch.position = position;
+                let travelled = touch.start.distance(position);
+
+                match self.touches.len() {
+                    1 if !self.multi_touch => {
+                        if self.dragging || travelled > DRAG_THRESHOLD {
+                            self.dragging = true;
+                            gestures.push(Gesture::Drag { position, delta });
+                        }
+                    }
+                    2 => {
+                        if let Some(start_distance) = self.pinch_distance {
+                            if start_distance > 0. {
+                                gestures.push(Gesture::Pinch {
+                                    center: self.center(),
+                                    scale: self.distance() / start_distance,
+                                });
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                if self.touches.remove(&id).is_none() {
+                    return gestures;
+                }
+
+                if phase == TouchPhase::Ended && self.touches.is_empty() && !self.multi_touch {
+                    if self.dragging {
+                        gestures.push(Gesture::DragEnd(position));
+                    } else if !self.long_pressed {
+                        gestures.push(Gesture::Tap(position));
+                    }
+                }
+
+                if self.touches.len() < 2 {
+                    self.pinch_distance = None;
+                }
+            }
+        }
+
+        gestures
+    }
+
+    /// Advance timers, returns the recognized gestures
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Time elapsed since the last frame, in seconds
+    pub fn tick(&mut self, dt: f32) -> Vec<Gesture> {
+        if self.touches.len() != 1 || self.dragging || self.long_pressed || self.multi_touch {
+            return Vec::new();
+        }
+
+        let touch = self.touches.values_mut().next().unwrap();
+        touch.elapsed += dt;
+
+        if touch.elapsed >= LONG_PRESS_DURATION {
+            self.long_pressed = true;
+            return vec![Gesture::LongPress(touch.position)];
+        }
+
+        Vec::new()
+    }
+
+    fn distance(&self) -> f32 {
+        let mut points = self.touches.values();
+        match (points.next(), points.next()) {
+            (Some(a), Some(b)) => a.position.distance(b.position),
+            _ => 0.,
+        }
+    }
+
+    fn center(&self) -> Vec2 {
+        let sum = self
+            .touches
+            .values()
+            .fold(Vec2::ZERO, |sum, touch| sum + touch.position);
+
+        sum / self.touches.len().max(1) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Gesture, GestureRecognizer};
+    use glam::Vec2;
+    use winit::event::TouchPhase;
+
+    #[test]
+    fn short_touch_is_a_tap() {
+        let mut recognizer = GestureRecognizer::new();
+        let position = Vec2::new(10., 10.);
+
+        assert!(recognizer
+            .touch(0, TouchPhase::Started, position)
+            .is_empty());
+        assert!(recognizer.tick(0.1).is_empty());
+        assert_eq!(
+            recognizer.touch(0, TouchPhase::Ended, position),
+            vec![Gesture::Tap(position)]
+        );
+    }
+
+    #[test]
+    fn held_touch_is_a_long_press() {
+        let mut recognizer = GestureRecognizer::new();
+        let position = Vec2::new(10., 10.);
+
+        recognizer.touch(0, TouchPhase::Started, position);
+        assert_eq!(recognizer.tick(0.6), vec![Gesture::LongPress(position)]);
+        // Lifting the finger after a long press is not a tap
+        assert!(recognizer.touch(0, TouchPhase::Ended, position).is_empty());
+    }
+
+    #[test]
+    fn moving_touch_is_a_drag() {
+        let mut recognizer = GestureRecognizer::new();
+
+        recognizer.touch(0, TouchPhase::Started, Vec2::ZERO);
+        assert!(recognizer
+            .touch(0, TouchPhase::Moved, Vec2::new(5., 0.))
+            .is_empty());
+        assert_eq!(
+            recognizer.touch(0, TouchPhase::Moved, Vec2::new(20., 0.)),
+            vec![Gesture::Drag {
+                position: Vec2::new(20., 0.),
+                delta: Vec2::new(15., 0.)
+            }]
+        );
+        assert_eq!(
+            recognizer.touch(0, TouchPhase::Ended, Vec2::new(20., 0.)),
+            vec![Gesture::DragEnd(Vec2::new(20., 0.))]
+        );
+    }
+
+    #[test]
+    fn two_fingers_pinch() {
+        let mut recognizer = GestureRecognizer::new();
+
+        recognizer.touch(0, TouchPhase::Started, Vec2::new(0., 0.));
+        recognizer.touch(1, TouchPhase::Started, Vec2::new(10., 0.));
+        assert_eq!(
+            recognizer.touch(1, TouchPhase::Moved, Vec2::new(20., 0.)),
+            vec![Gesture::Pinch {
+                center: Vec2::new(10., 0.),
+                scale: 2.
+            }]
+        );
+        recognizer.touch(1, TouchPhase::Ended, Vec2::new(20., 0.));
+        assert!(recognizer
+            .touch(0, TouchPhase::Ended, Vec2::new(0., 0.))
+            .is_empty());
+    }
+}
@@ -0,0 +1,267 @@
+//! Syntax highlighting of a [`super::text_area::TextArea`], the integration point of syntect,
+//! tree-sitter or a hand written lexer
+//!
+//! Lines are highlighted one after the other, each starting from the state the previous one
+//! ended in, e.g. inside a block comment. Edited lines are highlighted again, along with the
+//! following ones until a line ends in the state it ended in before.
+
+use std::ops::Range;
+
+use super::text_buffer::TextBuffer;
+use crate::graphics::color::Color;
+
+/// Look of a highlighted range
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HighlightStyle {
+    pub color: Color,
+}
+
+/// Range of a line and its style
+#[derive(Debug, Clone, PartialEq)]
+pub struct Highlight {
+    /// Byte offsets in the line, ranges must be sorted and not overlap
+    pub range: Range<usize>,
+    pub style: HighlightStyle,
+}
+
+/// Returns the runs of `line` and their color, the bytes out of the highlights being drawn in
+/// `default`
+///
+/// # Arguments
+///
+/// * `line` - Highlighted line
+/// * `highlights` - Sorted ranges of the line, those out of it or splitting a character are
+///   skipped
+/// * `default` - Color of the text
+pub fn runs(line: &str, highlights: &[Highlight], default: Color) -> Vec<(Range<usize>, Color)> {
+    let mut runs = Vec::new();
+    let mut at = 0;
+
+    for highlight in highlights {
+        let Range { start, end } = highlight.range;
+        let inside = at <= start && start < end && end <= line.len();
+        if !inside || !line.is_char_boundary(start) || !line.is_char_boundary(end) {
+            continue;
+        }
+
+        if start > at {
+            runs.push((at..start, default));
+        }
+        runs.push((start..end, highlight.style.color));
+        at = end;
+    }
+
+    if at < line.len() || runs.is_empty() {
+        runs.push((at..line.len(), default));
+    }
+
+    runs
+}
+
+/// Maps the bytes of each line to styles
+pub trait Highlighter {
+    /// State carried from a line to the next, e.g. the stack of open scopes
+    type State: Clone + PartialEq + Default;
+
+    /// Returns the styled ranges of `line`, the bytes out of them keep the default style
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - Line to highlight, without its line break
+    /// * `state` - State at the start of the line, to update to the state at its end
+    fn highlight_line(&mut self, line: &str, state: &mut Self::State) -> Vec<Highlight>;
+}
+
+/// Highlights of a text kept up to date by a [`HighlightCache`], without its state type
+pub trait Highlighting {
+    /// Forget the highlights of edited lines
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - First edited line
+    /// * `removed` - Number of lines the edit replaced, starting at `first`
+    /// * `inserted` - Number of lines replacing them
+    fn edited(&mut self, first: usize, removed: usize, inserted: usize);
+
+    /// Highlight the lines before `end` which are not up to date, returns the range of the lines
+    /// highlighted again
+    fn refresh(&mut self, buffer: &dyn TextBuffer, end: usize) -> Range<usize>;
+
+    /// Returns the highlights of `line`, empty until it is refreshed
+    fn highlights(&self, line: usize) -> &[Highlight];
+}
+
+#[derive(Debug, Clone)]
+struct Line<S> {
+    /// State at the end of the line
+    state: S,
+    highlights: Vec<Highlight>,
+}
+
+/// Highlights of each line and the state each one ends in, `None` once edited
+pub struct HighlightCache<H: Highlighter> {
+    highlighter: H,
+    lines: Vec<Option<Line<H::State>>>,
+}
+
+impl<H: Highlighter> HighlightCache<H> {
+    pub fn new(highlighter: H) -> Self {
+        Self {
+            highlighter,
+            lines: Vec::new(),
+        }
+    }
+
+    pub fn highlighter(&self) -> &H {
+        &self.highlighter
+    }
+}
+
+impl<H: Highlighter> Highlighting for HighlightCache<H> {
+    fn edited(&mut self, first: usize, removed: usize, inserted: usize) {
+        let start = first.min(self.lines.len());
+        let end = (first + removed).min(self.lines.len());
+
+        self.lines
+            .splice(start..end, std::iter::repeat_with(|| None).take(inserted));
+    }
+
+    fn refresh(&mut self, buffer: &dyn TextBuffer, end: usize) -> Range<usize> {
+        let end = end.min(buffer.len_lines());
+        self.lines.resize_with(buffer.len_lines(), || None);
+
+        let Some(start) = self.lines[..end].iter().position(Option::is_none) else {
+            return end..end;
+        };
+
+        let mut state = start
+            .checked_sub(1)
+            .and_then(|previous| self.lines[previous].as_ref())
+            .map(|line| line.state.clone())
+            .unwrap_or_default();
+        let mut index = start;
+
+        while index < end {
+            let highlights = self
+                .highlighter
+                .highlight_line(&buffer.line(index), &mut state);
+            let previous = self.lines[index].replace(Line {
+                state: state.clone(),
+                highlights,
+            });
+            index += 1;
+
+            // Past the edit, lines ending in the same state as before are still up to date
+            let unchanged = previous.is_some_and(|line| line.state == state);
+            if unchanged {
+                match self.lines[index..end].iter().position(Option::is_none) {
+                    Some(next) => {
+                        index += next;
+                        state = self.lines[index - 1].as_ref().unwrap().state.clone();
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        start..index
+    }
+
+    fn highlights(&self, line: usize) -> &[Highlight] {
+        self.lines
+            .get(line)
+            .and_then(Option::as_ref)
+            .map_or(&[][..], |line| line.highlights.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Highlight, HighlightCache, HighlightStyle, Highlighter, Highlighting};
+    use crate::graphics::color::WHITE;
+    use crate::ui::text_buffer::{RopeBuffer, TextBuffer};
+
+    /// Highlights `/* .. */` comments, counting the lines it is asked for
+    #[derive(Default)]
+    struct Comments {
+        highlighted: usize,
+    }
+
+    impl Highlighter for Comments {
+        /// Inside a comment
+        type State = bool;
+
+        fn highlight_line(&mut self, line: &str, comment: &mut bool) -> Vec<Highlight> {
+            self.highlighted += 1;
+            let mut highlights = Vec::new();
+            let mut start = (*comment).then_some(0);
+            let mut at = 0;
+
+            while at < line.len() {
+                let rest = &line[at..];
+                match start {
+                    None if rest.starts_with("/*") => start = Some(at),
+                    Some(from) if rest.starts_with("*/") => {
+                        highlights.push(Highlight {
+                            range: from..at + 2,
+                            style: HighlightStyle { color: WHITE },
+                        });
+                        start = None;
+                        at += 1;
+                    }
+                    _ => {}
+                }
+                at += 1;
+            }
+
+            if let Some(from) = start {
+                highlights.push(Highlight {
+                    range: from..line.len(),
+                    style: HighlightStyle { color: WHITE },
+                });
+            }
+            *comment = start.is_some();
+
+            highlights
+        }
+    }
+
+    fn ranges(cache: &HighlightCache<Comments>, line: usize) -> Vec<std::ops::Range<usize>> {
+        cache
+            .highlights(line)
+            .iter()
+            .map(|highlight| highlight.range.clone())
+            .collect()
+    }
+
+    #[test]
+    fn only_edited_lines_are_highlighted_again() {
+        let mut buffer = RopeBuffer::new("a /* b\nc */ d\ne\nf\n");
+        let mut cache = HighlightCache::new(Comments::default());
+
+        assert_eq!(cache.refresh(&buffer, 3), 0..3);
+        assert_eq!(ranges(&cache, 0), [2..6]);
+        assert_eq!(ranges(&cache, 1), [0..4]);
+        assert_eq!(cache.refresh(&buffer, 3), 3..3);
+        assert_eq!(cache.refresh(&buffer, 5), 3..5);
+
+        // Same state at the end of the line, the next ones are kept
+        buffer.insert(16, "x");
+        cache.edited(3, 1, 1);
+        assert_eq!(cache.refresh(&buffer, 5), 3..4);
+        assert_eq!(cache.highlighter().highlighted, 6);
+
+        // Opening a comment highlights the following lines again
+        buffer.insert(14, "/*");
+        cache.edited(2, 1, 1);
+        assert_eq!(cache.refresh(&buffer, 5), 2..5);
+        assert_eq!(ranges(&cache, 3), [0..2]);
+
+        // A line break splits the edited line in two
+        buffer.insert(0, "\n");
+        cache.edited(0, 1, 2);
+        assert_eq!(cache.refresh(&buffer, 6), 0..3);
+        assert_eq!(ranges(&cache, 1), [2..6]);
+        assert_eq!(ranges(&cache, 4), [0..2]);
+    }
+}
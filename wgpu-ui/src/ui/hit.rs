@@ -0,0 +1,138 @@
+//! Hit shapes, deciding which points of its bounds a widget reacts to
+
+use glam::Vec2;
+
+use crate::math::Rect;
+
+/// Area of a widget reacting to the pointer, fitted to the widget bounds
+pub enum HitShape {
+    /// The whole bounds
+    Rect,
+    /// Ellipse inscribed in the bounds, a circle for square widgets such as knobs
+    Ellipse,
+    /// Alpha mask stretched over the bounds, points whose alpha is above `threshold` are hit
+    Mask {
+        width: u32,
+        height: u32,
+        /// One alpha value per pixel, rows first
+        alpha: Vec<u8>,
+        threshold: u8,
+    },
+    /// Custom test, called with the point relative to the top-left corner of the bounds and the
+    /// size of the bounds
+    Custom(Box<dyn Fn(Vec2, Vec2) -> bool>),
+}
+
+impl Default for HitShape {
+    fn default() -> Self {
+        Self::Rect
+    }
+}
+
+impl std::fmt::Debug for HitShape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rect => write!(f, "Rect"),
+            Self::Ellipse => write!(f, "Ellipse"),
+            Self::Mask { width, height, .. } => write!(f, "Mask({width}x{height})"),
+            Self::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+impl HitShape {
+    /// Create a mask from the alpha channel of an RGBA8 image, e.g. the sprite drawn by the widget
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Image width, in pixels
+    /// * `height` - Image height, in pixels
+    /// * `rgba` - Image pixels, rows first
+    /// * `threshold` - Minimum alpha of a hit pixel
+    pub fn from_rgba(width: u32, height: u32, rgba: &[u8], threshold: u8) -> Self {
+        Self::Mask {
+            width,
+            height,
+            alpha: rgba.chunks_exact(4).map(|pixel| pixel[3]).collect(),
+            threshold,
+        }
+    }
+
+    /// Returns `true` if `point` hits the shape fitted to `bounds`
+    ///
+    /// # Arguments
+    ///
+    /// * `bounds` - Bounds of the widget
+    /// * `point` - Position to test, in the UI space
+    pub fn contains(&self, bounds: Rect, point: Vec2) -> bool {
+        if !bounds.contains(point) {
+            return false;
+        }
+
+        let local = point - bounds.position();
+        let size = Vec2::new(bounds.width, bounds.height);
+
+        match self {
+            Self::Rect => true,
+            Self::Ellipse => {
+                let radii = size / 2.;
+                if radii.x <= 0. || radii.y <= 0. {
+                    return false;
+                }
+
+                ((local - radii) / radii).length_squared() <= 1.
+            }
+            Self::Mask {
+                width,
+                height,
+                alpha,
+                threshold,
+            } => {
+                if size.x <= 0. || size.y <= 0. {
+                    return false;
+                }
+
+                let x = ((local.x / size.x * *width as f32) as u32).min(width.saturating_sub(1));
+                let y = ((local.y / size.y * *height as f32) as u32).min(height.saturating_sub(1));
+
+                alpha
+                    .get((y * width + x) as usize)
+                    .map_or(false, |alpha| alpha > threshold)
+            }
+            Self::Custom(test) => test(local, size),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HitShape;
+    use crate::math::Rect;
+
+    const BOUNDS: Rect = Rect {
+        x: 10.,
+        y: 10.,
+        width: 20.,
+        height: 20.,
+    };
+
+    #[test]
+    fn ellipse_excludes_corners() {
+        assert!(HitShape::Ellipse.contains(BOUNDS, (20., 20.).into()));
+        assert!(HitShape::Ellipse.contains(BOUNDS, (20., 11.).into()));
+        assert!(!HitShape::Ellipse.contains(BOUNDS, (11., 11.).into()));
+        assert!(HitShape::Rect.contains(BOUNDS, (11., 11.).into()));
+    }
+
+    #[test]
+    fn mask_is_stretched_over_bounds() {
+        // 2x2 mask, only the bottom-right pixel is opaque
+        let rgba = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 255];
+        let mask = HitShape::from_rgba(2, 2, &rgba, 127);
+
+        assert!(!mask.contains(BOUNDS, (15., 15.).into()));
+        assert!(mask.contains(BOUNDS, (25., 25.).into()));
+        assert!(mask.contains(BOUNDS, (30., 30.).into()));
+        assert!(!mask.contains(BOUNDS, (35., 35.).into()));
+    }
+}
@@ -0,0 +1,136 @@
+use glam::{Mat3, Vec2};
+use wgpu::RenderPass;
+use winit::event::WindowEvent;
+
+use super::{Opacity, Uid, Widget};
+use crate::accessibility::{AccessNode, Role, Semantics};
+use crate::graphics::shape::{RectangleShape, Shape};
+use crate::graphics::{color::Color, transform::Transform, Drawable, Transformable};
+use crate::Ctx;
+
+/// Graphic without text (icon, status dot, ..), described to screen readers by its semantics
+pub struct Icon {
+    shape: RectangleShape,
+    visible: bool,
+    uid: Uid,
+    opacity: Opacity,
+}
+
+impl Icon {
+    /// Create an icon exposed as `semantics`
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `size` - Icon size
+    /// * `semantics` - Role and label spoken in place of the graphic
+    pub fn new(context: Ctx, size: Vec2, semantics: Semantics) -> Self {
+        let mut shape =
+            RectangleShape::new_with_label(context, size, &format!("Icon#{}", semantics.label));
+        shape.set_semantics(Some(semantics));
+
+        Self {
+            shape,
+            visible: true,
+            uid: Uid::new(),
+            opacity: Opacity::default(),
+        }
+    }
+
+    /// Create a status dot, e.g. green for `Connected`
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `size` - Diameter of the dot
+    /// * `color` - Color of the dot
+    /// * `label` - State the color stands for
+    pub fn status(context: Ctx, size: f32, color: Color, label: &str) -> Self {
+        let mut icon = Self::new(
+            context,
+            Vec2::splat(size),
+            Semantics::new(Role::Status, label),
+        );
+        icon.shape.set_fill_color(color);
+
+        icon
+    }
+
+    pub fn set_fill_color(&mut self, color: Color) {
+        self.shape.set_fill_color(color);
+    }
+
+    /// Change what the icon stands for, e.g. when a status changes
+    pub fn set_semantics(&mut self, semantics: Semantics) {
+        self.shape.set_semantics(Some(semantics));
+    }
+
+    pub fn semantics(&self) -> Option<&Semantics> {
+        self.shape.semantics()
+    }
+
+    fn apply_opacity(&mut self) {
+        self.shape.set_opacity(self.opacity.effective());
+    }
+}
+
+impl Transformable for Icon {
+    fn position(&self) -> &Vec2 {
+        self.shape.position()
+    }
+
+    fn set_position(&mut self, position: Vec2) {
+        self.shape.set_position(position);
+    }
+
+    fn transform(&self) -> Transform {
+        self.shape.transform()
+    }
+
+    fn set_transform(&mut self, transform: Transform) {
+        self.shape.set_transform(transform);
+    }
+
+    fn matrix(&self) -> Mat3 {
+        self.shape.matrix()
+    }
+}
+
+impl Widget for Icon {
+    impl_widget_state!();
+    impl_widget_opacity!();
+
+    fn process_events(&mut self, _event: &WindowEvent) {}
+
+    fn size(&self) -> &Vec2 {
+        self.shape.size()
+    }
+
+    fn set_size(&mut self, size: Vec2) {
+        self.shape.set_size(size);
+    }
+
+    fn debug_name(&self) -> String {
+        match self.shape.semantics() {
+            Some(semantics) => format!("Icon#{}", semantics.label),
+            None => String::from("Icon"),
+        }
+    }
+
+    fn accessibility(&self) -> AccessNode {
+        match self.shape.semantics() {
+            Some(semantics) => semantics.node(self.bounds()),
+            None => AccessNode::new(Role::Image, self.bounds()),
+        }
+    }
+}
+
+impl Drawable for Icon {
+    fn draw<'b>(&'b mut self, render_pass: &mut RenderPass<'b>) {
+        if !self.visible {
+            return;
+        }
+
+        self.shape.draw(render_pass);
+    }
+}
@@ -0,0 +1,516 @@
+//! Picture decoded off the main thread, drawn with a fit mode once uploaded
+
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+
+use glam::Vec2;
+use wgpu::util::DeviceExt;
+use wgpu::RenderPass;
+use winit::event::WindowEvent;
+
+use super::{Opacity, Uid, Widget};
+use crate::accessibility::{AccessNode, Role};
+use crate::graphics::shape::{RectangleShape, Shape};
+use crate::graphics::{
+    color::{Color, WHITE},
+    Drawable, Transformable, Vertex,
+};
+use crate::math::{pixels_to_clip, Rect};
+use crate::{debug, pipeline, Ctx, PIPELINES};
+
+/// Where the picture comes from
+#[derive(Debug, Clone)]
+pub enum ImageSource {
+    /// File of the `assets` directory, reused if a scene already preloaded it
+    Asset(String),
+    /// Encoded file content (PNG, JPEG), e.g. downloaded or embedded with `include_bytes!`
+    Bytes(Arc<[u8]>),
+}
+
+impl ImageSource {
+    fn name(&self) -> &str {
+        match self {
+            Self::Asset(name) => name,
+            Self::Bytes(_) => "bytes",
+        }
+    }
+
+    /// Read and decode the picture, blocking
+    fn decode(&self) -> anyhow::Result<image::RgbaImage> {
+        let image = match self {
+            Self::Asset(name) => image::open(Path::new("assets").join(name))?,
+            Self::Bytes(bytes) => image::load_from_memory(bytes)?,
+        };
+
+        Ok(image.to_rgba8())
+    }
+}
+
+/// How the picture is scaled into the bounds of the widget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Fit {
+    /// Stretched to the bounds, the aspect ratio is lost
+    Fill,
+    /// As big as possible while fully visible, centered
+    #[default]
+    Contain,
+    /// Covers the bounds, the overflowing part of the picture is cropped
+    Cover,
+    /// Same as `Contain`, but never enlarged past its own size
+    ScaleDown,
+}
+
+/// Region of the bounds the picture is drawn in, and region of the picture shown
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Placement {
+    /// Offset and size in the bounds, in pixels
+    target: Rect,
+    /// Region of the picture, in texture coordinates
+    source: Rect,
+}
+
+/// Returns where a picture is drawn in the bounds of the widget
+///
+/// # Arguments
+///
+/// * `fit` - Fit mode
+/// * `image` - Size of the picture, in pixels
+/// * `bounds` - Size of the widget
+fn place(fit: Fit, image: Vec2, bounds: Vec2) -> Placement {
+    let whole = Rect {
+        x: 0.,
+        y: 0.,
+        width: 1.,
+        height: 1.,
+    };
+    let centered = |size: Vec2| Rect {
+        x: (bounds.x - size.x) / 2.,
+        y: (bounds.y - size.y) / 2.,
+        width: size.x,
+        height: size.y,
+    };
+    if image.x <= 0. || image.y <= 0. {
+        return Placement {
+            target: centered(Vec2::ZERO),
+            source: whole,
+        };
+    }
+
+    let contain = (bounds.x / image.x).min(bounds.y / image.y);
+    match fit {
+        Fit::Fill => Placement {
+            target: centered(bounds),
+            source: whole,
+        },
+        Fit::Contain => Placement {
+            target: centered(image * contain),
+            source: whole,
+        },
+        Fit::ScaleDown => Placement {
+            target: centered(image * contain.min(1.)),
+            source: whole,
+        },
+        Fit::Cover => {
+            let scale = (bounds.x / image.x).max(bounds.y / image.y);
+            // Part of the picture falling in the bounds, relative to its size
+            let shown = bounds / (image * scale);
+
+            Placement {
+                target: centered(bounds),
+                source: Rect {
+                    x: (1. - shown.x) / 2.,
+                    y: (1. - shown.y) / 2.,
+                    width: shown.x,
+                    height: shown.y,
+                },
+            }
+        }
+    }
+}
+
+/// Picture uploaded to the GPU
+struct Picture {
+    size: Vec2,
+    bind_group: wgpu::BindGroup,
+    vertex_buffer: wgpu::Buffer,
+}
+
+/// Picture loaded from an asset or bytes, a placeholder is shown while it is decoded
+///
+/// Until [`Widget::set_size`] is called, the widget takes the size of the picture, see
+/// [`Widget::intrinsic_size`].
+pub struct Image {
+    context: Ctx,
+    source: ImageSource,
+    fit: Fit,
+    /// Shown while loading, or if the picture can't be decoded
+    placeholder: RectangleShape,
+    /// Decoded picture, sent by the loading thread
+    loading: Option<Receiver<anyhow::Result<image::RgbaImage>>>,
+    picture: Option<Picture>,
+    position: Vec2,
+    size: Vec2,
+    /// The size was set by the application or a layout, it no longer follows the picture
+    sized: bool,
+    description: Option<String>,
+    visible: bool,
+    uid: Uid,
+    opacity: Opacity,
+}
+
+impl Image {
+    /// Create an image and start loading its picture
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `source` - Asset or bytes of the picture
+    pub fn new(context: Ctx, source: ImageSource) -> Self {
+        let placeholder_size = Vec2::splat(context.lock().unwrap().theme.metrics().control_height);
+        let mut placeholder = RectangleShape::new_with_label(
+            context.clone(),
+            placeholder_size,
+            &format!("Image#{}/placeholder", source.name()),
+        );
+        placeholder.set_fill_color(Color::from((60, 60, 60)));
+
+        let mut image = Self {
+            context,
+            source,
+            fit: Fit::default(),
+            placeholder,
+            loading: None,
+            picture: None,
+            position: Vec2::default(),
+            size: placeholder_size,
+            sized: false,
+            description: None,
+            visible: true,
+            uid: Uid::new(),
+            opacity: Opacity::default(),
+        };
+        image.load();
+
+        image
+    }
+
+    pub fn fit(&self) -> Fit {
+        self.fit
+    }
+
+    pub fn set_fit(&mut self, fit: Fit) {
+        self.fit = fit;
+        self.update();
+    }
+
+    /// Returns `true` while the picture is decoded
+    pub fn is_loading(&self) -> bool {
+        self.loading.is_some()
+    }
+
+    /// Describe the picture to screen readers, decorative images have no description
+    ///
+    /// # Arguments
+    ///
+    /// * `description` - Alternative text
+    pub fn set_description(&mut self, description: Option<&str>) {
+        self.description = description.map(str::to_string);
+    }
+
+    /// Load another picture, the placeholder is shown until it is decoded
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Asset or bytes of the picture
+    pub fn set_source(&mut self, source: ImageSource) {
+        self.source = source;
+        self.load();
+    }
+
+    fn load(&mut self) {
+        if let Some(picture) = self.picture.take() {
+            let mut ctx = self.context.lock().unwrap();
+            ctx.retire(picture.bind_group);
+            ctx.retire(picture.vertex_buffer);
+        }
+
+        // Scenes upload their textures while preloading, no need to decode them again
+        if let ImageSource::Asset(name) = &self.source {
+            let texture = self.context.lock().unwrap().resources.texture(name);
+            if let Some(texture) = texture {
+                self.loading = None;
+                self.show(&texture);
+                return;
+            }
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        let source = self.source.clone();
+        std::thread::spawn(move || {
+            // The image may be dropped before its picture is decoded
+            let _ = sender.send(source.decode());
+        });
+        self.loading = Some(receiver);
+    }
+
+    /// Upload the decoded picture
+    fn upload(&mut self, image: &image::RgbaImage) {
+        let ctx = self.context.lock().unwrap();
+        let size = wgpu::Extent3d {
+            width: image.width(),
+            height: image.height(),
+            depth_or_array_layers: 1,
+        };
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(&format!("Image#{}", self.source.name())),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        ctx.queue.write_texture(
+            texture.as_image_copy(),
+            image,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * size.width),
+                rows_per_image: Some(size.height),
+            },
+            size,
+        );
+        drop(ctx);
+
+        self.show(&texture);
+    }
+
+    /// Draw `texture` in place of the placeholder
+    fn show(&mut self, texture: &wgpu::Texture) {
+        let ctx = self.context.lock().unwrap();
+        let label = format!("Image#{}", self.source.name());
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = ctx.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some(&format!("{label}/sampler")),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&format!("{label}/bind-group")),
+            layout: PIPELINES
+                .get()
+                .unwrap()
+                .get("textured")
+                .unwrap()
+                .1
+                .as_ref()
+                .unwrap(),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+        let vertex_buffer = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("{label}-vb")),
+                contents: bytemuck::cast_slice(
+                    &[Vertex {
+                        position: [0., 0.],
+                        color: WHITE.premultiplied(1.),
+                        tex_coords: [0., 0.],
+                    }; 4],
+                ),
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            });
+        drop(ctx);
+
+        let picture_size = Vec2::new(texture.width() as f32, texture.height() as f32);
+        self.picture = Some(Picture {
+            size: picture_size,
+            bind_group,
+            vertex_buffer,
+        });
+        if !self.sized {
+            self.size = picture_size;
+        }
+
+        self.update();
+    }
+
+    fn apply_opacity(&mut self) {
+        self.placeholder.set_opacity(self.opacity.effective());
+        self.update();
+    }
+}
+
+impl Transformable for Image {
+    fn position(&self) -> &Vec2 {
+        &self.position
+    }
+
+    fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+        self.update();
+    }
+}
+
+impl Widget for Image {
+    impl_widget_state!();
+    impl_widget_opacity!();
+
+    fn process_events(&mut self, _event: &WindowEvent) {}
+
+    fn tick(&mut self, _dt: f32) {
+        let Some(loading) = &self.loading else {
+            return;
+        };
+
+        match loading.try_recv() {
+            Ok(Ok(image)) => {
+                self.loading = None;
+                self.upload(&image);
+            }
+            Ok(Err(error)) => {
+                tracing::warn!("can't load image {}: {error}", self.source.name());
+                self.loading = None;
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => self.loading = None,
+        }
+    }
+
+    fn update(&mut self) {
+        self.placeholder.set_size(self.size);
+        self.placeholder.set_position(self.position);
+
+        let Some(picture) = &self.picture else {
+            return;
+        };
+
+        let ctx = self.context.lock().unwrap();
+        let (width, height) = ctx.screen_size();
+        let placement = place(self.fit, picture.size, self.size);
+        let color = WHITE.premultiplied(self.opacity.effective());
+
+        // Top-left, bottom-left, bottom-right and top-right, as rectangles are indexed
+        let vertices = [(0., 0.), (0., 1.), (1., 1.), (1., 0.)].map(|(x, y)| {
+            let target = self.position
+                + placement.target.position()
+                + Vec2::new(x * placement.target.width, y * placement.target.height);
+            let source = placement.source.position()
+                + Vec2::new(x * placement.source.width, y * placement.source.height);
+
+            Vertex {
+                position: pixels_to_clip(target.x, target.y, width, height),
+                color,
+                tex_coords: source.into(),
+            }
+        });
+
+        ctx.queue
+            .write_buffer(&picture.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+    }
+
+    fn size(&self) -> &Vec2 {
+        &self.size
+    }
+
+    fn set_size(&mut self, size: Vec2) {
+        self.size = size;
+        self.sized = true;
+        self.update();
+    }
+
+    fn intrinsic_size(&self) -> Option<Vec2> {
+        self.picture.as_ref().map(|picture| picture.size)
+    }
+
+    fn debug_name(&self) -> String {
+        format!("Image#{}", self.source.name())
+    }
+
+    fn accessibility(&self) -> AccessNode {
+        let node = AccessNode::new(Role::Image, self.bounds());
+
+        match &self.description {
+            Some(description) => node.with_name(description),
+            None => node,
+        }
+    }
+}
+
+impl Drawable for Image {
+    fn draw<'b>(&'b mut self, render_pass: &mut RenderPass<'b>) {
+        if !self.visible {
+            return;
+        }
+
+        let Some(picture) = &self.picture else {
+            self.placeholder.draw(render_pass);
+            return;
+        };
+
+        render_pass.insert_debug_marker(&format!("Image#{}", self.source.name()));
+        render_pass.set_pipeline(&pipeline("textured").0);
+        render_pass.set_bind_group(1, &picture.bind_group, &[]);
+
+        render_pass.set_vertex_buffer(0, picture.vertex_buffer.slice(..));
+        render_pass.draw_indexed(0..6, 0, 0..1);
+        debug::record_draw(4);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::{place, Fit};
+    use crate::math::Rect;
+
+    #[test]
+    fn fit_modes_place_the_picture() {
+        let image = Vec2::new(200., 100.);
+        let bounds = Vec2::new(100., 100.);
+
+        let contain = place(Fit::Contain, image, bounds);
+        assert_eq!(
+            contain.target,
+            Rect {
+                x: 0.,
+                y: 25.,
+                width: 100.,
+                height: 50.,
+            }
+        );
+
+        let cover = place(Fit::Cover, image, bounds);
+        assert_eq!(cover.target.width, 100.);
+        assert_eq!(
+            cover.source,
+            Rect {
+                x: 0.25,
+                y: 0.,
+                width: 0.5,
+                height: 1.,
+            }
+        );
+
+        // Smaller pictures keep their size
+        let scale_down = place(Fit::ScaleDown, Vec2::new(20., 10.), bounds);
+        assert_eq!(scale_down.target.width, 20.);
+        assert_eq!(scale_down.target.x, 40.);
+
+        assert_eq!(place(Fit::Fill, image, bounds).target.height, 100.);
+    }
+}
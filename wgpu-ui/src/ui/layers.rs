@@ -0,0 +1,112 @@
+//! Named groups of top level widgets drawn one after the other, each with its own blend mode and
+//! visibility, e.g. to hide every debug widget at once
+
+use std::collections::HashMap;
+
+use super::WidgetId;
+use crate::graphics::blend::BlendMode;
+
+/// Group of widgets, drawn in the order of [`WidgetLayer::ALL`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum WidgetLayer {
+    Background,
+    #[default]
+    Content,
+    /// Tooltips, toasts, ..
+    Overlay,
+    /// Inspectors and statistics, above everything else
+    Debug,
+}
+
+impl WidgetLayer {
+    pub const ALL: [WidgetLayer; 4] = [Self::Background, Self::Content, Self::Overlay, Self::Debug];
+}
+
+/// How the widgets of a layer are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayerSettings {
+    pub blend: BlendMode,
+    /// Hidden layers are neither drawn nor receive inputs
+    pub visible: bool,
+}
+
+impl Default for LayerSettings {
+    fn default() -> Self {
+        Self {
+            blend: BlendMode::Alpha,
+            visible: true,
+        }
+    }
+}
+
+/// Layer of each widget and the settings of each layer
+#[derive(Debug, Default)]
+pub struct WidgetLayers {
+    /// Widgets outside the content layer
+    layers: HashMap<WidgetId, WidgetLayer>,
+    settings: [LayerSettings; 4],
+}
+
+impl WidgetLayers {
+    pub fn layer(&self, id: WidgetId) -> WidgetLayer {
+        self.layers.get(&id).copied().unwrap_or_default()
+    }
+
+    pub fn set_layer(&mut self, id: WidgetId, layer: WidgetLayer) {
+        match layer {
+            WidgetLayer::Content => self.layers.remove(&id),
+            layer => self.layers.insert(id, layer),
+        };
+    }
+
+    pub fn remove(&mut self, id: WidgetId) {
+        self.layers.remove(&id);
+    }
+
+    pub fn settings(&self, layer: WidgetLayer) -> LayerSettings {
+        self.settings[layer as usize]
+    }
+
+    pub fn settings_mut(&mut self, layer: WidgetLayer) -> &mut LayerSettings {
+        &mut self.settings[layer as usize]
+    }
+
+    /// Returns `true` if the layer of the widget is visible
+    pub fn is_visible(&self, id: WidgetId) -> bool {
+        self.settings(self.layer(id)).visible
+    }
+
+    /// Sort a back-to-front order by layer, the widgets of a layer keep their order
+    ///
+    /// # Arguments
+    ///
+    /// * `order` - Identifiers of the widgets, back to front
+    pub fn sort(&self, order: &mut [WidgetId]) {
+        order.sort_by_key(|id| self.layer(*id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WidgetLayer, WidgetLayers};
+
+    #[test]
+    fn layers_are_drawn_in_order_keeping_the_order_of_their_widgets() {
+        let mut layers = WidgetLayers::default();
+        layers.set_layer(1, WidgetLayer::Debug);
+        layers.set_layer(2, WidgetLayer::Overlay);
+        layers.set_layer(4, WidgetLayer::Background);
+        layers.set_layer(5, WidgetLayer::Overlay);
+
+        let mut order = [1, 2, 3, 4, 5, 6];
+        layers.sort(&mut order);
+        assert_eq!(order, [4, 3, 6, 2, 5, 1]);
+
+        layers.settings_mut(WidgetLayer::Debug).visible = false;
+        assert!(!layers.is_visible(1));
+        assert!(layers.is_visible(3));
+
+        layers.set_layer(1, WidgetLayer::Content);
+        assert!(layers.is_visible(1));
+    }
+}
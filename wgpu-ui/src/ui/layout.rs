@@ -1,9 +1,77 @@
 use std::collections::BTreeMap;
 
-use glam::Vec2;
+use glam::{Vec2, Vec4};
 
-use super::{Widget, WidgetId};
+use super::{
+    direction::{layout_direction, LayoutDirection},
+    gesture::Gesture,
+    Opacity, Uid, Widget, WidgetId,
+};
 use crate::graphics::{Drawable, Transformable};
+use crate::math::Rect;
+use crate::Ctx;
+
+/// Returns the size of a container at `origin` enclosing `children`
+///
+/// # Arguments
+///
+/// * `origin` - Position of the container
+/// * `children` - Bounds of the children, in the UI space
+/// * `paddings` - Space kept around the children: left, top, bottom and right
+pub fn fit_size(origin: Vec2, children: impl IntoIterator<Item = Rect>, paddings: Vec4) -> Vec2 {
+    let far_corner = children.into_iter().fold(
+        origin + Vec2::new(paddings.x, paddings.y),
+        |corner, bounds| corner.max(Vec2::new(bounds.x + bounds.width, bounds.y + bounds.height)),
+    );
+
+    far_corner - origin + Vec2::new(paddings.w, paddings.z)
+}
+
+/// Returns the positions of the children of a layout, in order, mirrored in right-to-left
+/// layouts
+///
+/// # Arguments
+///
+/// * `direction` - Direction the children are stacked in
+/// * `layout_direction` - Side the children start from
+/// * `origin` - Top-left corner of the room inside the paddings
+/// * `width` - Width of the room inside the paddings, widened to the children if smaller
+/// * `count` - Number of children
+/// * `child` - Size of every child
+/// * `spacing` - Space between the children
+pub fn stack(
+    direction: &Direction,
+    layout_direction: LayoutDirection,
+    origin: Vec2,
+    width: f32,
+    count: usize,
+    child: Vec2,
+    spacing: f32,
+) -> Vec<Vec2> {
+    // Offsets from the start edge
+    let offsets = (0..count)
+        .map(|i| match direction {
+            Direction::Horizontal => Vec2 {
+                x: (child.x + spacing) * i as f32,
+                y: spacing,
+            },
+            Direction::Vertical => Vec2 {
+                x: spacing,
+                y: (child.y + spacing) * i as f32,
+            },
+        })
+        .collect::<Vec<_>>();
+    let width = offsets
+        .iter()
+        .fold(width, |width, offset| width.max(offset.x + child.x));
+
+    offsets
+        .into_iter()
+        .map(|offset| {
+            origin + Vec2::new(layout_direction.place(width, offset.x, child.x), offset.y)
+        })
+        .collect()
+}
 
 #[derive(Debug)]
 pub enum Direction {
@@ -16,9 +84,16 @@ pub struct Layout {
     widgets: BTreeMap<WidgetId, Box<dyn Widget>>,
     position: Vec2,
     visible: bool,
+    uid: Uid,
+    opacity: Opacity,
     size: Vec2,
     spacing: f32,
     counter: u16,
+    paddings: Vec4,
+    /// Size computed from the children after each layout
+    fit_content: bool,
+    /// Set while the layout is mounted, children added meanwhile are mounted right away
+    context: Option<Ctx>,
 }
 
 impl Layout {
@@ -28,19 +103,50 @@ impl Layout {
             widgets: BTreeMap::new(),
             position: Default::default(),
             visible: true,
+            uid: Uid::new(),
+            opacity: Opacity::default(),
             size: Default::default(),
             spacing: 3.,
             counter: 0,
+            paddings: Vec4::ZERO,
+            fit_content: false,
+            context: None,
         }
     }
 
-    pub fn add_widget(&mut self, widget: Box<dyn Widget>) {
+    pub fn add_widget(&mut self, mut widget: Box<dyn Widget>) {
+        if let Some(context) = &self.context {
+            widget.on_mount(context.clone());
+        }
+        widget.set_inherited_opacity(self.opacity.effective());
+
         self.counter += 1;
         self.widgets.insert(self.counter, widget);
 
         self.update();
     }
 
+    /// Size the layout from its children plus paddings, nested fitting containers are fitted
+    /// first
+    pub fn fit_content(mut self) -> Self {
+        self.fit_content = true;
+        self.update();
+
+        self
+    }
+
+    /// Set the space kept between the layout edges and its children
+    ///
+    /// # Arguments
+    ///
+    /// * `paddings` - Start, top, bottom and end paddings, the start is on the right in
+    ///   right-to-left layouts
+    pub fn set_paddings(&mut self, paddings: Vec4) {
+        self.paddings = paddings;
+
+        self.update();
+    }
+
     pub fn direction(&self) -> &Direction {
         &self.direction
     }
@@ -56,6 +162,13 @@ impl Layout {
 
         self.update();
     }
+
+    fn apply_opacity(&mut self) {
+        let opacity = self.opacity.effective();
+        self.widgets
+            .values_mut()
+            .for_each(|widget| widget.set_inherited_opacity(opacity));
+    }
 }
 
 impl Transformable for Layout {
@@ -72,13 +185,18 @@ impl Transformable for Layout {
 
 impl Drawable for Layout {
     fn draw<'b>(&'b mut self, render_pass: &mut wgpu::RenderPass<'b>) {
-        self.widgets
-            .iter_mut()
-            .for_each(|(_, widget)| widget.draw(render_pass));
+        self.widgets.iter_mut().for_each(|(_, widget)| {
+            render_pass.push_debug_group(&widget.debug_name());
+            widget.draw(render_pass);
+            render_pass.pop_debug_group();
+        });
     }
 }
 
 impl Widget for Layout {
+    impl_widget_state!();
+    impl_widget_opacity!();
+
     fn process_events(&mut self, event: &winit::event::WindowEvent) {
         self.widgets
             .iter_mut()
@@ -86,10 +204,17 @@ impl Widget for Layout {
     }
 
     fn update(&mut self) {
+        if self.fit_content {
+            // Children fitting their own content must know their size before being measured
+            self.widgets
+                .iter_mut()
+                .for_each(|(_, widget)| widget.update());
+        }
+
         let mut biggest_dimensions = Vec2::default();
 
         self.widgets.iter().for_each(|(_, widget)| {
-            let size = widget.size();
+            let size = widget.intrinsic_size().unwrap_or(*widget.size());
 
             if size.x > biggest_dimensions.x {
                 biggest_dimensions.x = size.x;
@@ -102,41 +227,157 @@ impl Widget for Layout {
 
         println!("\nBiggest widget in layout {biggest_dimensions:?}\n");
 
-        self.widgets
-            .iter_mut()
-            .enumerate()
-            .for_each(|(i, (_, widget))| {
-                widget.set_size(biggest_dimensions);
+        let layout_direction = layout_direction();
+        let paddings = layout_direction.paddings(self.paddings);
+        let width = if self.fit_content {
+            0.
+        } else {
+            self.size.x - paddings.x - paddings.w
+        };
+        let positions = stack(
+            &self.direction,
+            layout_direction,
+            self.position + Vec2::new(paddings.x, paddings.y),
+            width,
+            self.widgets.len(),
+            biggest_dimensions,
+            self.spacing,
+        );
 
-                let position = match self.direction {
-                    Direction::Horizontal => Vec2 {
-                        x: (biggest_dimensions.x + self.spacing) * i as f32,
-                        y: self.spacing,
-                    },
-                    _ => Vec2 {
-                        x: self.spacing,
-                        y: (biggest_dimensions.y + self.spacing) * i as f32,
-                    },
-                } + self.position;
+        self.widgets.iter_mut().zip(positions).enumerate().for_each(
+            |(i, ((_, widget), position))| {
+                widget.set_size(biggest_dimensions);
                 println!("{i} New position: {position:?}");
 
                 widget.set_position(position);
-            });
+            },
+        );
 
         self.widgets
             .iter_mut()
             .for_each(|(_, widget)| widget.update());
+
+        if self.fit_content {
+            self.size = fit_size(
+                self.position,
+                self.widgets.values().map(|widget| widget.bounds()),
+                paddings,
+            );
+        }
+    }
+
+    fn process_gesture(&mut self, gesture: &Gesture) {
+        self.widgets
+            .iter_mut()
+            .for_each(|(_, widget)| widget.process_gesture(gesture));
     }
 
-    fn set_visibility(&mut self, visible: bool) {
-        self.visible = visible;
+    fn on_mount(&mut self, context: Ctx) {
+        self.widgets
+            .iter_mut()
+            .for_each(|(_, widget)| widget.on_mount(context.clone()));
+
+        self.context = Some(context);
     }
 
-    fn visible(&self) -> bool {
-        self.visible
+    fn on_unmount(&mut self, context: Ctx) {
+        self.context = None;
+
+        self.widgets
+            .iter_mut()
+            .for_each(|(_, widget)| widget.on_unmount(context.clone()));
+    }
+
+    fn tick(&mut self, dt: f32) {
+        self.widgets
+            .iter_mut()
+            .for_each(|(_, widget)| widget.tick(dt));
     }
 
     fn size(&self) -> &Vec2 {
         &self.size
     }
+
+    fn set_size(&mut self, size: Vec2) {
+        self.size = size;
+    }
+
+    fn debug_name(&self) -> String {
+        format!("Layout#{:?}", self.direction)
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        self.widgets
+            .values_mut()
+            .map(|widget| widget.as_mut() as &mut dyn Widget)
+            .collect()
+    }
+
+    fn children(&self) -> Vec<&dyn Widget> {
+        self.widgets
+            .values()
+            .map(|widget| widget.as_ref())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Vec2, Vec4};
+
+    use super::{fit_size, stack, Direction};
+    use crate::math::Rect;
+    use crate::ui::direction::LayoutDirection;
+
+    #[test]
+    fn fit_size_encloses_children_and_paddings() {
+        let children = [
+            Rect {
+                x: 15.,
+                y: 12.,
+                width: 30.,
+                height: 10.,
+            },
+            Rect {
+                x: 15.,
+                y: 25.,
+                width: 20.,
+                height: 10.,
+            },
+        ];
+        let size = fit_size(Vec2::new(10., 10.), children, Vec4::new(5., 2., 3., 4.));
+
+        assert_eq!(size, Vec2::new(39., 28.));
+    }
+
+    #[test]
+    fn fit_size_of_empty_container_is_paddings() {
+        let size = fit_size(Vec2::ZERO, [], Vec4::new(5., 2., 3., 4.));
+
+        assert_eq!(size, Vec2::new(9., 5.));
+    }
+
+    #[test]
+    fn right_to_left_rows_start_on_the_right() {
+        let row = |layout_direction| {
+            stack(
+                &Direction::Horizontal,
+                layout_direction,
+                Vec2::new(10., 0.),
+                0.,
+                3,
+                Vec2::new(20., 10.),
+                5.,
+            )
+        };
+
+        assert_eq!(
+            row(LayoutDirection::LeftToRight),
+            [Vec2::new(10., 5.), Vec2::new(35., 5.), Vec2::new(60., 5.)]
+        );
+        assert_eq!(
+            row(LayoutDirection::RightToLeft),
+            [Vec2::new(60., 5.), Vec2::new(35., 5.), Vec2::new(10., 5.)]
+        );
+    }
 }
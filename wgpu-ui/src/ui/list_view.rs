@@ -0,0 +1,573 @@
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use glam::Vec2;
+use wgpu::RenderPass;
+use winit::event::{ElementState, ModifiersState, MouseButton, MouseScrollDelta, WindowEvent};
+
+use super::{
+    gesture::Gesture, scrollbar::Scrollbar, EventQueue, Opacity, Uid, Widget, WidgetEvent,
+};
+use crate::accessibility::{AccessNode, Role};
+use crate::graphics::shape::{RectangleShape, Shape};
+use crate::graphics::{color::Color, Drawable, Transformable};
+use crate::Ctx;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ListViewEvent {
+    /// Indices of the selected items, in ascending order
+    SelectionChanged(Vec<usize>),
+}
+
+impl ListViewEvent {
+    /// Returns the number identifying the kind of the event, as passed to [`Widget::events`]
+    pub fn id(&self) -> u32 {
+        match self {
+            Self::SelectionChanged(_) => 0,
+        }
+    }
+}
+
+impl WidgetEvent for ListViewEvent {}
+
+/// Items displayed by a [`ListView`], rows are built on demand then bound to the visible items
+pub struct ItemProvider<W> {
+    count: usize,
+    build: Box<dyn FnMut() -> W>,
+    bind: Box<dyn FnMut(&mut W, usize)>,
+}
+
+impl<W> ItemProvider<W> {
+    /// Create a provider of `count` items
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - Number of items
+    /// * `build` - Creates an empty row
+    /// * `bind` - Displays an item in a row, rows are bound to other items while scrolling
+    pub fn new(
+        count: usize,
+        build: impl FnMut() -> W + 'static,
+        bind: impl FnMut(&mut W, usize) + 'static,
+    ) -> Self {
+        Self {
+            count,
+            build: Box::new(build),
+            bind: Box::new(bind),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+struct Row<W> {
+    widget: W,
+    /// Item displayed by the row, `None` while it waits to be recycled
+    item: Option<usize>,
+    /// Drawn behind the row while its item is selected
+    highlight: RectangleShape,
+}
+
+/// Returns the items displayed by a list scrolled to `first`
+///
+/// # Arguments
+///
+/// * `count` - Number of items
+/// * `first` - First displayed item
+/// * `capacity` - Number of rows fitting in the list
+fn visible_items(count: usize, first: usize, capacity: usize) -> Range<usize> {
+    first.min(count)..first.saturating_add(capacity).min(count)
+}
+
+/// Returns the selection after a click on `item`
+///
+/// # Arguments
+///
+/// * `selection` - Selected items
+/// * `anchor` - Item selections are extended from, the last one clicked without shift
+/// * `item` - Clicked item
+/// * `modifiers` - Held keys, ctrl toggles the item and shift selects from the anchor
+fn select(
+    selection: &BTreeSet<usize>,
+    anchor: Option<usize>,
+    item: usize,
+    modifiers: ModifiersState,
+) -> BTreeSet<usize> {
+    if modifiers.shift() {
+        let anchor = anchor.unwrap_or(item);
+
+        (anchor.min(item)..=anchor.max(item)).collect()
+    } else if modifiers.ctrl() {
+        let mut selection = selection.clone();
+        if !selection.remove(&item) {
+            selection.insert(item);
+        }
+
+        selection
+    } else {
+        BTreeSet::from([item])
+    }
+}
+
+/// Scrollable list of any number of items
+///
+/// Only the rows of the visible items exist, the rows of the items scrolled out are bound to the
+/// items scrolled in. The list scrolls by whole rows, so that no row is partly visible.
+pub struct ListView<W: Widget> {
+    context: Ctx,
+    background: RectangleShape,
+    provider: ItemProvider<W>,
+    /// Built up to the number of visible items, then recycled
+    rows: Vec<Row<W>>,
+    row_height: f32,
+    /// First displayed item
+    first: usize,
+    /// Scrolling not amounting to a whole row yet, in rows
+    scrolled: f32,
+    /// Classic or overlay, following the theme
+    scrollbar: Scrollbar,
+    selection: BTreeSet<usize>,
+    /// Item shift-clicks extend the selection from
+    anchor: Option<usize>,
+    position: Vec2,
+    size: Vec2,
+    mouse_position: Vec2,
+    modifiers: ModifiersState,
+    /// Rows built while the list is mounted are mounted right away
+    mounted: bool,
+    visible: bool,
+    uid: Uid,
+    opacity: Opacity,
+    events: EventQueue<ListViewEvent>,
+}
+
+impl<W: Widget> ListView<W> {
+    /// Create a list showing the items of `provider`
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `size` - Size of the list, the number of rows fitting in its height are built
+    /// * `row_height` - Height of each row
+    /// * `provider` - Displayed items
+    pub fn new(context: Ctx, size: Vec2, row_height: f32, provider: ItemProvider<W>) -> Self {
+        let mut background =
+            RectangleShape::new_with_label(context.clone(), size, "ListView/background");
+        background.set_fill_color(Color::from((30, 30, 30)));
+        let style = context.lock().unwrap().theme.scrollbars;
+        let scrollbar = Scrollbar::new(context.clone(), style, "ListView/scrollbar");
+
+        let mut list = Self {
+            context,
+            background,
+            provider,
+            rows: Vec::new(),
+            row_height,
+            first: 0,
+            scrolled: 0.,
+            scrollbar,
+            selection: BTreeSet::new(),
+            anchor: None,
+            position: Default::default(),
+            size,
+            mouse_position: Default::default(),
+            modifiers: ModifiersState::empty(),
+            mounted: false,
+            visible: true,
+            uid: Uid::new(),
+            opacity: Opacity::default(),
+            events: EventQueue::new(),
+        };
+        list.update();
+
+        list
+    }
+
+    /// Returns the selected items, in ascending order
+    pub fn selection(&self) -> Vec<usize> {
+        self.selection.iter().copied().collect()
+    }
+
+    /// Replace the selected items
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - Indices of the items to select, those out of the list are ignored
+    pub fn set_selection(&mut self, items: impl IntoIterator<Item = usize>) {
+        let count = self.provider.count;
+        self.change_selection(items.into_iter().filter(|item| *item < count).collect());
+    }
+
+    /// Returns the events emitted since the last call, with their payload
+    pub fn take_events(&mut self) -> Vec<ListViewEvent> {
+        self.events.drain(..).collect()
+    }
+
+    /// Change the number of items, e.g. once the dataset changed, every visible row is bound again
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - New number of items
+    pub fn set_count(&mut self, count: usize) {
+        self.provider.count = count;
+        self.first = self.first.min(self.last_first());
+
+        let selection = self.selection.range(..count).copied().collect();
+        self.change_selection(selection);
+        self.refresh();
+    }
+
+    /// Bind the visible rows again, e.g. once the displayed items changed
+    pub fn refresh(&mut self) {
+        self.rows.iter_mut().for_each(|row| row.item = None);
+
+        self.update();
+    }
+
+    /// Scroll the least needed for `item` to be visible
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - Index of the item to show
+    pub fn scroll_to(&mut self, item: usize) {
+        let capacity = self.capacity();
+
+        if item < self.first {
+            self.first = item;
+        } else if item >= self.first + capacity {
+            self.first = item + 1 - capacity;
+        }
+        self.first = self.first.min(self.last_first());
+
+        self.update();
+    }
+
+    /// Returns the number of rows fitting in the list
+    fn capacity(&self) -> usize {
+        ((self.size.y / self.row_height) as usize).max(1)
+    }
+
+    /// Returns the first displayed item once scrolled to the end
+    fn last_first(&self) -> usize {
+        self.provider.count.saturating_sub(self.capacity())
+    }
+
+    /// Returns the item displayed at `point`, `None` outside of the list or under its last item
+    fn item_at(&self, point: Vec2) -> Option<usize> {
+        if !self.bounds().contains(point) {
+            return None;
+        }
+
+        let row = ((point.y - self.position.y) / self.row_height) as usize;
+        visible_items(self.provider.count, self.first, self.capacity()).nth(row)
+    }
+
+    /// Scroll by a number of rows, positive toward the end of the list
+    fn scroll(&mut self, rows: f32) {
+        self.scrolled += rows;
+        let whole = self.scrolled.trunc();
+        self.scrolled -= whole;
+
+        let first = (self.first as i64 + whole as i64).clamp(0, self.last_first() as i64) as usize;
+        if first != self.first {
+            self.first = first;
+            self.update();
+        }
+    }
+
+    fn change_selection(&mut self, selection: BTreeSet<usize>) {
+        if selection == self.selection {
+            return;
+        }
+
+        self.selection = selection;
+        self.events
+            .push(ListViewEvent::SelectionChanged(self.selection()));
+    }
+
+    /// Rows displaying an item
+    fn shown_rows(&self) -> impl Iterator<Item = &Row<W>> {
+        self.rows.iter().filter(|row| row.item.is_some())
+    }
+
+    fn shown_rows_mut(&mut self) -> impl Iterator<Item = &mut Row<W>> {
+        self.rows.iter_mut().filter(|row| row.item.is_some())
+    }
+
+    fn apply_opacity(&mut self) {
+        let opacity = self.opacity.effective();
+        self.background.set_opacity(opacity);
+        self.scrollbar.set_opacity(opacity);
+
+        self.rows.iter_mut().for_each(|row| {
+            row.highlight.set_opacity(opacity);
+            row.widget.set_inherited_opacity(opacity);
+        });
+    }
+}
+
+impl<W: Widget> Transformable for ListView<W> {
+    fn position(&self) -> &Vec2 {
+        &self.position
+    }
+
+    fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+
+        self.update();
+    }
+}
+
+impl<W: Widget> Widget for ListView<W> {
+    impl_widget_state!();
+    impl_widget_opacity!();
+
+    fn events(&mut self, event_handler: Box<dyn Fn(u32)>) {
+        self.events.drain(..).for_each(|e| event_handler(e.id()));
+    }
+
+    fn emitted(&mut self, event: u32) -> bool {
+        // Every event is drained, even after a match
+        self.events.drain(..).filter(|e| e.id() == event).count() > 0
+    }
+
+    fn process_events(&mut self, event: &WindowEvent) {
+        // Rows may hold interactive widgets, e.g. a delete button
+        self.shown_rows_mut()
+            .for_each(|row| row.widget.process_events(event));
+
+        if let Some(offset) = self.scrollbar.process_events(event) {
+            let first = (offset * self.provider.count as f32).round() as usize;
+            self.first = first.min(self.last_first());
+            self.scrolled = 0.;
+            self.update();
+        }
+
+        match event {
+            WindowEvent::ModifiersChanged(modifiers) => self.modifiers = *modifiers,
+            WindowEvent::CursorMoved { position, .. } => {
+                self.mouse_position = (position.x as f32, position.y as f32).into();
+            }
+            WindowEvent::MouseWheel { delta, .. }
+                if self.bounds().contains(self.mouse_position) =>
+            {
+                let rows = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => -y,
+                    MouseScrollDelta::PixelDelta(position) => -position.y as f32 / self.row_height,
+                };
+                self.scroll(rows);
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } if !self.scrollbar.contains(self.mouse_position) => {
+                if let Some(item) = self.item_at(self.mouse_position) {
+                    let selection = select(&self.selection, self.anchor, item, self.modifiers);
+                    if !self.modifiers.shift() {
+                        self.anchor = Some(item);
+                    }
+                    self.change_selection(selection);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn process_gesture(&mut self, gesture: &Gesture) {
+        self.shown_rows_mut()
+            .for_each(|row| row.widget.process_gesture(gesture));
+
+        if let Gesture::Drag { position, delta } = gesture {
+            if self.bounds().contains(*position) {
+                // Content follows the finger
+                self.scroll(-delta.y / self.row_height);
+            }
+        }
+    }
+
+    fn update(&mut self) {
+        self.background.set_position(self.position);
+        self.background.set_size(self.size);
+
+        let count = self.provider.count.max(1) as f32;
+        self.scrollbar
+            .set_range(self.capacity() as f32 / count, self.first as f32 / count);
+        self.scrollbar.set_bounds(self.bounds());
+
+        let items = visible_items(self.provider.count, self.first, self.capacity());
+        let opacity = self.opacity.effective();
+
+        while self.rows.len() < items.len() {
+            let mut widget = (self.provider.build)();
+            if self.mounted {
+                widget.on_mount(self.context.clone());
+            }
+            widget.set_inherited_opacity(opacity);
+
+            let mut highlight = RectangleShape::new_with_label(
+                self.context.clone(),
+                Vec2::ZERO,
+                &format!("ListView/row{}/highlight", self.rows.len()),
+            );
+            highlight.set_fill_color(Color::from((50, 90, 160)));
+            highlight.set_opacity(opacity);
+
+            self.rows.push(Row {
+                widget,
+                item: None,
+                highlight,
+            });
+        }
+
+        // Rows of the items still visible keep them, the others are recycled
+        self.rows
+            .iter_mut()
+            .filter(|row| row.item.is_some_and(|item| !items.contains(&item)))
+            .for_each(|row| row.item = None);
+        for item in items {
+            if self.rows.iter().any(|row| row.item == Some(item)) {
+                continue;
+            }
+
+            if let Some(row) = self.rows.iter_mut().find(|row| row.item.is_none()) {
+                (self.provider.bind)(&mut row.widget, item);
+                row.item = Some(item);
+            }
+        }
+
+        // Classic scrollbars take their width from the rows
+        let size = Vec2::new(
+            self.size.x - self.scrollbar.reserved_width(),
+            self.row_height,
+        );
+        for row in &mut self.rows {
+            let Some(item) = row.item else {
+                continue;
+            };
+
+            let position =
+                self.position + Vec2::new(0., (item - self.first) as f32 * self.row_height);
+            row.highlight.set_position(position);
+            row.highlight.set_size(size);
+            row.widget.set_size(size);
+            row.widget.set_position(position);
+            row.widget.update();
+        }
+    }
+
+    fn on_mount(&mut self, context: Ctx) {
+        self.rows
+            .iter_mut()
+            .for_each(|row| row.widget.on_mount(context.clone()));
+
+        self.mounted = true;
+    }
+
+    fn on_unmount(&mut self, context: Ctx) {
+        self.mounted = false;
+
+        self.rows
+            .iter_mut()
+            .for_each(|row| row.widget.on_unmount(context.clone()));
+    }
+
+    fn tick(&mut self, dt: f32) {
+        self.shown_rows_mut().for_each(|row| row.widget.tick(dt));
+        self.scrollbar.tick(dt);
+    }
+
+    fn size(&self) -> &Vec2 {
+        &self.size
+    }
+
+    fn set_size(&mut self, size: Vec2) {
+        self.size = size;
+        self.first = self.first.min(self.last_first());
+
+        self.update();
+    }
+
+    fn debug_name(&self) -> String {
+        String::from("ListView")
+    }
+
+    fn children(&self) -> Vec<&dyn Widget> {
+        self.shown_rows()
+            .map(|row| &row.widget as &dyn Widget)
+            .collect()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        self.shown_rows_mut()
+            .map(|row| &mut row.widget as &mut dyn Widget)
+            .collect()
+    }
+
+    fn accessibility(&self) -> AccessNode {
+        AccessNode::new(Role::List, self.bounds())
+    }
+}
+
+impl<W: Widget> Drawable for ListView<W> {
+    fn draw<'b>(&'b mut self, render_pass: &mut RenderPass<'b>) {
+        self.background.draw(render_pass);
+
+        let selection = &self.selection;
+        for row in self.rows.iter_mut() {
+            let Some(item) = row.item else {
+                continue;
+            };
+
+            if selection.contains(&item) {
+                row.highlight.draw(render_pass);
+            }
+            render_pass.push_debug_group(&row.widget.debug_name());
+            row.widget.draw(render_pass);
+            render_pass.pop_debug_group();
+        }
+
+        self.scrollbar.draw(render_pass);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use winit::event::ModifiersState;
+
+    use super::{select, visible_items};
+
+    #[test]
+    fn only_visible_items_are_displayed() {
+        assert_eq!(visible_items(10_000, 0, 12), 0..12);
+        assert_eq!(visible_items(10_000, 500, 12), 500..512);
+        assert_eq!(visible_items(5, 0, 12), 0..5);
+        assert_eq!(visible_items(5, 8, 12), 5..5);
+    }
+
+    #[test]
+    fn modifiers_extend_the_selection() {
+        let selection = BTreeSet::from([2]);
+
+        assert_eq!(
+            select(&selection, Some(2), 5, ModifiersState::empty()),
+            BTreeSet::from([5])
+        );
+        assert_eq!(
+            select(&selection, Some(2), 5, ModifiersState::CTRL),
+            BTreeSet::from([2, 5])
+        );
+        assert_eq!(
+            select(&selection, Some(2), 2, ModifiersState::CTRL),
+            BTreeSet::new()
+        );
+        assert_eq!(
+            select(&selection, Some(5), 2, ModifiersState::SHIFT),
+            BTreeSet::from([2, 3, 4, 5])
+        );
+    }
+}
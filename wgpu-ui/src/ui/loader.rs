@@ -0,0 +1,328 @@
+use std::{collections::HashMap, path::Path};
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    button::Button,
+    dialog::Dialog,
+    layout::{Direction, Layout},
+    progress_bar::ProgressBar,
+    spinner::Spinner,
+    text_input::TextInput,
+    window::Window,
+    Uid, Widget,
+};
+use crate::Ctx;
+
+/// Value of a widget property
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Property {
+    Bool(bool),
+    Number(f32),
+    Text(String),
+    List(Vec<f32>),
+}
+
+/// Serialized description of a widget and its children
+#[derive(Debug, Clone, Deserialize)]
+pub struct WidgetDescription {
+    /// Name of the widget type in the registry, e.g. `Button`
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Name used to retrieve the built widget
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub position: Option<(f32, f32)>,
+    #[serde(default)]
+    pub size: Option<(f32, f32)>,
+    /// Type specific properties (text, paddings, spacing, ..)
+    #[serde(default)]
+    pub properties: HashMap<String, Property>,
+    #[serde(default)]
+    pub children: Vec<WidgetDescription>,
+}
+
+impl WidgetDescription {
+    pub fn from_ron(source: &str) -> Result<Self> {
+        Ok(ron::from_str(source)?)
+    }
+
+    pub fn from_json(source: &str) -> Result<Self> {
+        Ok(serde_json::from_str(source)?)
+    }
+
+    /// Returns a text property
+    pub fn text(&self, name: &str) -> Option<&str> {
+        match self.properties.get(name) {
+            Some(Property::Text(text)) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Returns a numeric property
+    pub fn number(&self, name: &str) -> Option<f32> {
+        match self.properties.get(name) {
+            Some(Property::Number(number)) => Some(*number),
+            _ => None,
+        }
+    }
+
+    /// Returns a list of numbers property
+    pub fn list(&self, name: &str) -> Option<&[f32]> {
+        match self.properties.get(name) {
+            Some(Property::List(list)) => Some(list),
+            _ => None,
+        }
+    }
+}
+
+/// Builds a widget from its description, children are already built
+pub type WidgetConstructor =
+    Box<dyn Fn(&WidgetDescription, Ctx, Vec<Box<dyn Widget>>) -> Result<Box<dyn Widget>>>;
+
+/// Result of a load: the root widget and the identity of every named widget
+pub struct LoadedUi {
+    pub root: Box<dyn Widget>,
+    pub ids: HashMap<String, Uid>,
+}
+
+/// Maps widget type names to their constructors
+pub struct WidgetRegistry {
+    constructors: HashMap<String, WidgetConstructor>,
+}
+
+fn no_children(description: &WidgetDescription, children: &[Box<dyn Widget>]) -> Result<()> {
+    if !children.is_empty() {
+        bail!("{} widgets can't have children", description.kind);
+    }
+
+    Ok(())
+}
+
+impl WidgetRegistry {
+    /// Registry knowing about the widgets of the crate
+    pub fn new() -> Self {
+        let mut registry = Self {
+            constructors: HashMap::new(),
+        };
+
+        registry.register("Button", |description, context, children| {
+            no_children(description, &children)?;
+            let mut button = Button::new(description.text("text").unwrap_or_default(), context);
+            if let Some(size) = description.number("character_size") {
+                button.set_character_size(size);
+            }
+            if let Some(&[x, y, z, w]) = description.list("paddings") {
+                button.set_paddings((x, y, z, w).into());
+            }
+
+            Ok(Box::new(button))
+        });
+        registry.register("Layout", |description, context, children| {
+            let direction = match description.text("direction") {
+                Some("horizontal") => Direction::Horizontal,
+                _ => Direction::Vertical,
+            };
+            let mut layout = Layout::new(direction);
+            let spacing = context.lock().unwrap().theme.metrics().spacing;
+            layout.set_spacing(description.number("spacing").unwrap_or(spacing));
+            children
+                .into_iter()
+                .for_each(|child| layout.add_widget(child));
+
+            Ok(Box::new(layout))
+        });
+        registry.register("Window", |description, context, children| {
+            no_children(description, &children)?;
+
+            Ok(Box::new(Window::new(
+                context,
+                description.text("title").unwrap_or_default(),
+            )))
+        });
+        registry.register("Dialog", |description, context, mut children| {
+            if children.len() > 1 {
+                bail!(
+                    "{} holds a single content widget, got {}",
+                    description.kind,
+                    children.len()
+                );
+            }
+
+            let size = description.size.unwrap_or((300., 150.));
+            let mut dialog = Dialog::new(
+                context,
+                description.text("title").unwrap_or_default(),
+                size.into(),
+            );
+            if let Some(Property::Bool(true)) = description.properties.get("modal") {
+                dialog = dialog.modal();
+            }
+            if let Some(Property::Bool(draggable)) = description.properties.get("draggable") {
+                dialog.set_draggable(*draggable);
+            }
+            if let Some(content) = children.pop() {
+                dialog.set_content(content);
+            }
+
+            Ok(Box::new(dialog))
+        });
+        registry.register("ProgressBar", |description, context, children| {
+            no_children(description, &children)?;
+            let height = context.lock().unwrap().theme.metrics().bar_height;
+            let size = description.size.unwrap_or((200., height));
+            let mut progress_bar = ProgressBar::new(context.clone(), size.into());
+            if let Some(Property::Bool(true)) = description.properties.get("label") {
+                progress_bar = progress_bar.with_label(context);
+            }
+            if let Some(value) = description.number("value") {
+                progress_bar.set_value(value);
+            }
+
+            Ok(Box::new(progress_bar))
+        });
+        registry.register("Spinner", |description, context, children| {
+            no_children(description, &children)?;
+
+            Ok(Box::new(Spinner::new(
+                context,
+                description.number("radius").unwrap_or(16.),
+            )))
+        });
+        registry.register("TextInput", |description, context, children| {
+            no_children(description, &children)?;
+            let height = context.lock().unwrap().theme.metrics().control_height;
+            let size = description.size.unwrap_or((200., height));
+            let mut input = TextInput::new(context, size.into());
+            if let Some(value) = description.text("value") {
+                input.set_value(value);
+            }
+
+            Ok(Box::new(input))
+        });
+
+        registry
+    }
+
+    /// Register a constructor, replacing any previous one with the same name
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Value of the `type` field of descriptions built by `constructor`
+    /// * `constructor` - Builds the widget
+    pub fn register<F>(&mut self, name: &str, constructor: F)
+    where
+        F: Fn(&WidgetDescription, Ctx, Vec<Box<dyn Widget>>) -> Result<Box<dyn Widget>> + 'static,
+    {
+        self.constructors
+            .insert(name.to_string(), Box::new(constructor));
+    }
+
+    /// Build the widget tree of a description
+    ///
+    /// # Arguments
+    ///
+    /// * `description` - Root of the tree
+    /// * `context` - Context given to the constructors
+    pub fn build(&self, description: &WidgetDescription, context: Ctx) -> Result<LoadedUi> {
+        let mut ids = HashMap::new();
+        let root = self.build_widget(description, context, &mut ids)?;
+
+        Ok(LoadedUi { root, ids })
+    }
+
+    /// Load and build a `.ron` or `.json` description file
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the description file
+    /// * `context` - Context given to the constructors
+    pub fn load(&self, path: &Path, context: Ctx) -> Result<LoadedUi> {
+        let source = std::fs::read_to_string(path)?;
+        let description = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => WidgetDescription::from_json(&source)?,
+            _ => WidgetDescription::from_ron(&source)?,
+        };
+
+        self.build(&description, context)
+    }
+
+    fn build_widget(
+        &self,
+        description: &WidgetDescription,
+        context: Ctx,
+        ids: &mut HashMap<String, Uid>,
+    ) -> Result<Box<dyn Widget>> {
+        let constructor = self
+            .constructors
+            .get(&description.kind)
+            .ok_or_else(|| anyhow!("unknown widget type {}", description.kind))?;
+
+        let children = description
+            .children
+            .iter()
+            .map(|child| self.build_widget(child, context.clone(), ids))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut widget = constructor(description, context, children)?;
+        if let Some(size) = description.size {
+            widget.set_size(size.into());
+        }
+        if let Some(position) = description.position {
+            widget.set_position(position.into());
+        }
+        if let Some(id) = &description.id {
+            ids.insert(id.clone(), widget.uid());
+        }
+
+        Ok(widget)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Property, WidgetDescription};
+
+    #[test]
+    fn parse_ron_description() {
+        let description = WidgetDescription::from_ron(
+            r#"(
+                type: "Layout",
+                position: Some((100., 100.)),
+                properties: { "spacing": 20., "direction": "horizontal" },
+                children: [
+                    (type: "Button", id: Some("save"), properties: { "text": "Save" }),
+                ],
+            )"#,
+        )
+        .unwrap();
+
+        assert_eq!(description.kind, "Layout");
+        assert_eq!(description.position, Some((100., 100.)));
+        assert_eq!(description.number("spacing"), Some(20.));
+        assert_eq!(description.text("direction"), Some("horizontal"));
+        assert_eq!(description.children[0].id.as_deref(), Some("save"));
+        assert_eq!(description.children[0].text("text"), Some("Save"));
+    }
+
+    #[test]
+    fn parse_json_description() {
+        let description = WidgetDescription::from_json(
+            r#"{
+                "type": "Button",
+                "properties": { "text": "Ok", "paddings": [10, 20, 10, 20] }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(description.kind, "Button");
+        assert_eq!(
+            description.properties.get("paddings"),
+            Some(&Property::List(vec![10., 20., 10., 20.]))
+        );
+        assert!(description.children.is_empty());
+    }
+}
@@ -0,0 +1,199 @@
+use glam::Vec2;
+use wgpu::RenderPass;
+use winit::event::WindowEvent;
+
+use super::{progress_bar::ProgressBar, EventQueue, Opacity, Uid, Widget, WidgetEvent};
+use crate::accessibility::{AccessNode, Role};
+use crate::graphics::shape::{RectangleShape, Shape};
+use crate::graphics::text::Text;
+use crate::graphics::{
+    color::{Color, WHITE},
+    Drawable, Transformable,
+};
+use crate::scene::Preloading;
+use crate::{Ctx, ASSETS};
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum LoadingScreenEvent {
+    /// Every resource of the scene was loaded and the previous scene ones released
+    Loaded,
+}
+
+impl WidgetEvent for LoadingScreenEvent {}
+
+/// Screen shown during a scene transition, loading the resources of the next scene a few per
+/// frame while its progress bar follows
+pub struct LoadingScreen<'a> {
+    context: Ctx,
+    background: RectangleShape,
+    title: Text<'a>,
+    progress_bar: ProgressBar<'a>,
+    /// Taken once the scene is entered
+    preloading: Option<Preloading>,
+    /// Resources loaded per frame
+    budget: usize,
+    position: Vec2,
+    size: Vec2,
+    visible: bool,
+    uid: Uid,
+    opacity: Opacity,
+    events: EventQueue<LoadingScreenEvent>,
+}
+
+impl<'a> LoadingScreen<'a> {
+    /// Create a screen loading the resources of `preloading`, see [`crate::scene::preload`]
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `size` - Size of the screen, usually the UI space
+    /// * `preloading` - Resources of the next scene
+    pub fn new(context: Ctx, size: Vec2, preloading: Preloading) -> Self {
+        let metrics = context.lock().unwrap().theme.metrics();
+
+        let mut background =
+            RectangleShape::new_with_label(context.clone(), size, "LoadingScreen/background");
+        background.set_fill_color(Color::from((20, 20, 20)));
+
+        let mut title = Text::new_with_label(
+            context.clone(),
+            &format!("Loading {}", preloading.manifest().name),
+            ASSETS.get_font("Roboto.ttf").unwrap(),
+            metrics.font_size,
+            "LoadingScreen/title",
+        );
+        title.set_fill_color(WHITE);
+
+        let progress_bar =
+            ProgressBar::new(context.clone(), Vec2::new(size.x / 2., metrics.bar_height))
+                .with_label(context.clone());
+
+        let mut screen = Self {
+            context,
+            background,
+            title,
+            progress_bar,
+            preloading: Some(preloading),
+            budget: 1,
+            position: Default::default(),
+            size,
+            visible: true,
+            uid: Uid::new(),
+            opacity: Opacity::default(),
+            events: EventQueue::new(),
+        };
+        screen.update();
+
+        screen
+    }
+
+    /// Set the number of resources loaded per frame, more load faster but stall the frames
+    ///
+    /// # Arguments
+    ///
+    /// * `budget` - Resources loaded per frame, at least one
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget.max(1);
+    }
+
+    /// Returns `true` once the next scene can be entered
+    pub fn is_loaded(&self) -> bool {
+        self.preloading.is_none()
+    }
+
+    fn apply_opacity(&mut self) {
+        let opacity = self.opacity.effective();
+        self.background.set_opacity(opacity);
+        self.title.set_opacity(opacity);
+        self.progress_bar.set_inherited_opacity(opacity);
+    }
+}
+
+impl<'a> Transformable for LoadingScreen<'a> {
+    fn position(&self) -> &Vec2 {
+        &self.position
+    }
+
+    fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+
+        self.update();
+    }
+}
+
+impl<'a> Widget for LoadingScreen<'a> {
+    impl_widget_state!();
+    impl_widget_opacity!();
+    impl_widget_events!();
+
+    fn process_events(&mut self, _event: &WindowEvent) {}
+
+    fn update(&mut self) {
+        self.background.set_position(self.position);
+
+        let center = self.position + self.size / 2.;
+        let title_bounds = self.title.bounds();
+        let bar_size = *self.progress_bar.size();
+        self.title.set_position(Vec2 {
+            x: center.x - title_bounds.width / 2.,
+            y: center.y - title_bounds.height - bar_size.y,
+        });
+        self.progress_bar.set_position(Vec2 {
+            x: center.x - bar_size.x / 2.,
+            y: center.y,
+        });
+    }
+
+    fn tick(&mut self, dt: f32) {
+        if let Some(preloading) = &mut self.preloading {
+            let progress = preloading.step(&self.context, self.budget);
+            self.progress_bar.set_value(progress);
+
+            if preloading.is_done() {
+                self.preloading.take().unwrap().enter(&self.context);
+                self.events.push(LoadingScreenEvent::Loaded);
+            }
+        }
+
+        self.progress_bar.tick(dt);
+    }
+
+    fn size(&self) -> &Vec2 {
+        &self.size
+    }
+
+    fn set_size(&mut self, size: Vec2) {
+        self.size = size;
+        self.background.set_size(size);
+
+        self.update();
+    }
+
+    fn debug_name(&self) -> String {
+        String::from("LoadingScreen")
+    }
+
+    fn is_modal(&self) -> bool {
+        self.visible
+    }
+
+    fn children(&self) -> Vec<&dyn Widget> {
+        vec![&self.progress_bar]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        vec![&mut self.progress_bar]
+    }
+
+    fn accessibility(&self) -> AccessNode {
+        AccessNode::new(Role::Group, self.bounds()).with_name(self.title.string())
+    }
+}
+
+impl<'a> Drawable for LoadingScreen<'a> {
+    fn draw<'b>(&'b mut self, render_pass: &mut RenderPass<'b>) {
+        self.background.draw(render_pass);
+        self.title.draw(render_pass);
+        self.progress_bar.draw(render_pass);
+    }
+}
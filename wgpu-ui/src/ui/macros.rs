@@ -0,0 +1,82 @@
+//! Declarative macros generating the trivial parts of `Widget` implementations, to be
+//! invoked inside an `impl Widget for ..` block.
+
+/// Implements `set_visibility`, `visible` and `uid` on top of the `visible: bool` and
+/// `uid: Uid` fields of the widget
+macro_rules! impl_widget_state {
+    () => {
+        fn set_visibility(&mut self, visible: bool) {
+            self.visible = visible;
+        }
+
+        fn visible(&self) -> bool {
+            self.visible
+        }
+
+        fn uid(&self) -> $crate::ui::Uid {
+            self.uid
+        }
+    };
+}
+
+/// Implements `opacity`, `set_opacity` and `set_inherited_opacity` on top of the
+/// `opacity: Opacity` field of the widget, which must provide an `apply_opacity` method pushing
+/// the effective opacity to its drawables and children
+macro_rules! impl_widget_opacity {
+    () => {
+        fn opacity(&self) -> f32 {
+            self.opacity.own
+        }
+
+        fn set_opacity(&mut self, opacity: f32) {
+            self.opacity.own = opacity.clamp(0., 1.);
+            self.apply_opacity();
+        }
+
+        fn set_inherited_opacity(&mut self, opacity: f32) {
+            self.opacity.inherited = opacity;
+            self.apply_opacity();
+        }
+    };
+}
+
+/// Implements `events` and `emitted` on top of the `events: Vec<E>` field of the widget,
+/// `E` being a field-less event enum
+macro_rules! impl_widget_events {
+    () => {
+        fn events(&mut self, event_handler: Box<dyn Fn(u32)>) {
+            self.events.drain(..).for_each(|e| event_handler(e as u32));
+        }
+
+        fn emitted(&mut self, event: u32) -> bool {
+            // Every event is drained, even after a match
+            self.events.drain(..).filter(|e| *e as u32 == event).count() > 0
+        }
+    };
+}
+
+/// Implements `children`, `children_mut`, `on_mount`, `on_unmount` and `tick` by forwarding them to the listed
+/// child widget fields
+macro_rules! impl_widget_children {
+    ($($child:ident),+ $(,)?) => {
+        fn children(&self) -> Vec<&dyn $crate::ui::Widget> {
+            vec![$(&self.$child as &dyn $crate::ui::Widget),+]
+        }
+
+        fn children_mut(&mut self) -> Vec<&mut dyn $crate::ui::Widget> {
+            vec![$(&mut self.$child as &mut dyn $crate::ui::Widget),+]
+        }
+
+        fn on_mount(&mut self, context: $crate::Ctx) {
+            $(self.$child.on_mount(context.clone());)+
+        }
+
+        fn on_unmount(&mut self, context: $crate::Ctx) {
+            $(self.$child.on_unmount(context.clone());)+
+        }
+
+        fn tick(&mut self, dt: f32) {
+            $(self.$child.tick(dt);)+
+        }
+    };
+}
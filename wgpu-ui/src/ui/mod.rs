@@ -1,53 +1,1248 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use crate::graphics::{Drawable, Transformable};
+use crate::accessibility::{AccessNode, Role};
+use crate::export::{self, ExportOptions};
+use crate::graphics::{
+    blend::{self, BlendMode},
+    color::{Color, BLACK},
+    deletion::DeletionQueue,
+    layer::Layer,
+    render_texture::RenderTexture,
+    shape::{RectangleShape, Shape},
+    transform, Drawable, Transformable,
+};
+use crate::i18n;
+use crate::math::Rect;
+use crate::Ctx;
+use anyhow::anyhow;
 use glam::Vec2;
+use image::RgbaImage;
 use wgpu::RenderPass;
-use winit::event::WindowEvent;
+use winit::event::{
+    ElementState, KeyboardInput, ModifiersState, MouseButton, Touch, TouchPhase, VirtualKeyCode,
+    WindowEvent,
+};
+use winit::window::CursorIcon;
+
+use direction::LayoutDirection;
+use drag::{DragPayload, Dropped, FileDrop, FilesDropped};
+use effects::{EffectState, WidgetEffects};
+use focus::{FocusScopes, ScopeChange};
+use gesture::{Gesture, GestureRecognizer};
+use layers::{LayerSettings, WidgetLayer, WidgetLayers};
+use motion::PathAnimation;
+use pose::{Pose, PoseConstraints, SnapTarget};
+use recording::{Playback, RecordedEvent, Recorder, Recording};
+
+#[macro_use]
+mod macros;
 
 pub mod button;
+pub mod check_box;
+pub mod cursor;
+pub mod dialog;
+pub mod direction;
+pub mod drag;
+pub mod effects;
+#[cfg(feature = "fixed-capacity")]
+pub mod fixed;
+pub mod focus;
+pub mod gesture;
+pub mod highlight;
+pub mod hit;
+pub mod icon;
+pub mod image;
+pub mod layers;
 pub mod layout;
+pub mod list_view;
+pub mod loader;
+pub mod loading_screen;
+pub mod motion;
+pub mod pose;
+pub mod progress_bar;
+pub mod recording;
+pub mod scrollbar;
+pub mod spinner;
+pub mod state;
+pub mod text_area;
+pub mod text_buffer;
+pub mod text_input;
+pub mod theme;
+pub mod timeline;
 pub mod window;
 
 pub type WidgetId = u16;
 
+/// Queue of the events emitted by a widget
+#[cfg(not(feature = "fixed-capacity"))]
+pub type EventQueue<E> = Vec<E>;
+
+/// Queue of the events emitted by a widget, stored inline
+#[cfg(feature = "fixed-capacity")]
+pub type EventQueue<E> = fixed::FixedVec<E, 8>;
+
+/// Opacity of a widget, faded along with its parents
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Opacity {
+    /// Opacity set on the widget itself
+    pub own: f32,
+    /// Effective opacity of the parent
+    pub inherited: f32,
+}
+
+impl Default for Opacity {
+    fn default() -> Self {
+        Self {
+            own: 1.,
+            inherited: 1.,
+        }
+    }
+}
+
+impl Opacity {
+    /// Returns the opacity the widget is drawn with
+    pub fn effective(&self) -> f32 {
+        self.own * self.inherited
+    }
+}
+
+/// Identity of a widget, unique across the whole application and stable for the widget lifetime
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Uid(u64);
+
+impl Uid {
+    /// Identity reserved for the root of the widget tree
+    pub const ROOT: Uid = Uid(1);
+
+    pub fn new() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(2);
+
+        Self(COUNTER.fetch_add(1, Ordering::Relaxed))
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0
+    }
+}
+
 pub struct Ui {
-    widgets: HashMap<WidgetId, Box<dyn Widget>>,
+    context: Ctx,
+    widgets: BTreeMap<WidgetId, Box<dyn Widget>>,
+    /// Back-to-front draw order, sorted by layer, the last widget receives inputs first
+    order: Vec<WidgetId>,
+    layers: WidgetLayers,
+    /// Drawn under the topmost modal widget
+    dim: RectangleShape,
     counter: u16,
+    gestures: GestureRecognizer,
+    mouse_position: Vec2,
+    /// The pointer is over the area of the Ui
+    hovered: bool,
+    /// Position of the last left button press, while the button is held
+    pressed_at: Option<Vec2>,
+    drag: Option<Drag>,
+    /// Identity camera the drag ghost snapshot is rendered through
+    snapshot_camera: Layer<'static>,
+    drops: Vec<Dropped>,
+    /// Files dragged from the OS over the window
+    file_drag: Option<FileDrag>,
+    file_drops: Vec<FileDrop>,
+    /// Drawn over the widget the dragged files would be dropped on
+    drop_highlight: RectangleShape,
+    /// Deleted widgets, kept alive until the frames drawing them are completed
+    retired: DeletionQueue<Box<dyn Widget>>,
+    recorder: Option<Recorder>,
+    playback: Option<Playback>,
+    /// Widget receiving the keyboard inputs
+    focused: Option<Uid>,
+    focus_scopes: FocusScopes,
+    modifiers: ModifiersState,
+    /// Locale generation the widgets are laid out for, see [`i18n::generation`]
+    locale: u64,
+    /// Widgets moving along a path
+    motions: Vec<(WidgetId, PathAnimation)>,
+    /// Effects attached to the widgets
+    effects: BTreeMap<WidgetId, EffectState>,
+    /// Constraints of the floating panels and what they snapped to
+    poses: BTreeMap<WidgetId, Pose>,
+}
+
+/// Files dragged from the OS, winit reports them one event per file
+#[derive(Default)]
+struct FileDrag {
+    files: Vec<PathBuf>,
+    /// Drop target currently hovered
+    target: Option<Uid>,
+    /// The files are being dropped, they are delivered on the next tick
+    dropped: bool,
+}
+
+/// Drag in progress
+struct Drag {
+    source: Uid,
+    payload: DragPayload,
+    /// Translucent snapshot of the dragged widget, following the cursor
+    ghost: RenderTexture,
+    /// The snapshot has to be rendered before the ghost is drawn
+    snapshot_pending: bool,
+    /// Position of the cursor relative to the ghost
+    grab_offset: Vec2,
+    /// Drop target currently hovered
+    target: Option<Uid>,
 }
 
 impl Ui {
-    pub fn new() -> Self {
+    pub fn new(context: Ctx) -> Self {
+        let mut dim = RectangleShape::new_with_label(
+            context.clone(),
+            context.lock().unwrap().screen_size().into(),
+            "Ui/modal-dim",
+        );
+        dim.set_fill_color(BLACK);
+        dim.set_opacity(0.5);
+        let mut drop_highlight =
+            RectangleShape::new_with_label(context.clone(), Vec2::ZERO, "Ui/drop-highlight");
+        drop_highlight.set_fill_color(Color(66, 133, 244));
+        drop_highlight.set_opacity(0.25);
+
         Self {
-            widgets: HashMap::new(),
+            context,
+            widgets: BTreeMap::new(),
+            order: Vec::new(),
+            layers: WidgetLayers::default(),
+            dim,
             counter: 0,
+            gestures: GestureRecognizer::new(),
+            mouse_position: Vec2::ZERO,
+            hovered: false,
+            pressed_at: None,
+            drag: None,
+            snapshot_camera: Layer::new(context.clone(), "Ui/snapshot"),
+            drops: Vec::new(),
+            file_drag: None,
+            file_drops: Vec::new(),
+            drop_highlight,
+            retired: DeletionQueue::new(),
+            recorder: None,
+            playback: None,
+            focused: None,
+            focus_scopes: FocusScopes::new(),
+            modifiers: ModifiersState::empty(),
+            locale: i18n::generation(),
+            motions: Vec::new(),
+            effects: BTreeMap::new(),
+            poses: BTreeMap::new(),
         }
     }
 
-    pub fn add(&mut self, widget: Box<dyn Widget>) -> WidgetId {
+    pub fn add(&mut self, mut widget: Box<dyn Widget>) -> WidgetId {
+        widget.on_mount(self.context.clone());
+
         self.counter += 1;
         self.widgets.insert(self.counter, widget);
+        self.order.push(self.counter);
+        self.layers.sort(&mut self.order);
 
         self.counter
     }
 
+    /// Add a widget to a layer other than the content one
+    ///
+    /// # Arguments
+    ///
+    /// * `widget` - Widget to add
+    /// * `layer` - Group the widget is drawn with
+    pub fn add_to_layer(&mut self, widget: Box<dyn Widget>, layer: WidgetLayer) -> WidgetId {
+        let id = self.add(widget);
+        self.set_widget_layer(id, layer);
+
+        id
+    }
+
+    /// Move a widget to another layer, above the widgets already in it
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Identifier returned by [`Ui::add`]
+    /// * `layer` - Group the widget is drawn with
+    pub fn set_widget_layer(&mut self, id: WidgetId, layer: WidgetLayer) {
+        self.layers.set_layer(id, layer);
+        self.raise(id);
+    }
+
+    /// Move a widget along a path, replacing its running path animation
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Identifier returned by [`Ui::add`]
+    /// * `animation` - Path followed by the origin of the widget and its duration
+    pub fn animate_along(&mut self, id: WidgetId, animation: PathAnimation) {
+        self.motions.retain(|(other, _)| *other != id);
+        self.motions.push((id, animation));
+    }
+
+    /// Stop moving a widget along its path, it stays where it is
+    pub fn stop_motion(&mut self, id: WidgetId) {
+        self.motions.retain(|(other, _)| *other != id);
+    }
+
+    /// Attach effects to a widget, replacing its previous ones
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Identifier returned by [`Ui::add`]
+    /// * `effects` - Effects following the state of the widget
+    pub fn set_effects(&mut self, id: WidgetId, effects: WidgetEffects) {
+        let Some(widget) = self.widgets.get(&id) else {
+            return;
+        };
+        let label = format!("{}/effects", widget.debug_name());
+
+        self.effects
+            .insert(id, EffectState::new(self.context.clone(), effects, &label));
+    }
+
+    /// Detach the effects of a widget
+    pub fn clear_effects(&mut self, id: WidgetId) {
+        self.effects.remove(&id);
+    }
+
+    /// Constrain where a floating widget, e.g. a window or a dialog, may be
+    ///
+    /// The widget is kept inside the window and snaps to its edges or to the other constrained
+    /// widgets while dragged. It then stays against what it snapped to, following a resized
+    /// window or a moved panel, until dragged away. Moving it any other way, e.g. from the
+    /// application or by an animation, releases its snaps.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Identifier returned by [`Ui::add`]
+    /// * `constraints` - Replace the previous ones, releasing the snaps
+    pub fn set_pose_constraints(&mut self, id: WidgetId, constraints: PoseConstraints) {
+        if self.widgets.contains_key(&id) {
+            self.poses.insert(id, Pose::new(constraints));
+        }
+    }
+
+    /// Let a widget go anywhere, the widgets snapped to it are released
+    pub fn clear_pose_constraints(&mut self, id: WidgetId) {
+        self.poses.remove(&id);
+    }
+
+    pub fn layer_settings(&self, layer: WidgetLayer) -> LayerSettings {
+        self.layers.settings(layer)
+    }
+
+    /// Show or hide every widget of a layer, hidden widgets don't receive inputs
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - Group of widgets
+    /// * `visible` - `false` to hide them
+    pub fn set_layer_visible(&mut self, layer: WidgetLayer, visible: bool) {
+        self.layers.settings_mut(layer).visible = visible;
+    }
+
+    /// Set how the widgets of a layer are combined with what is drawn beneath them
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - Group of widgets
+    /// * `blend` - Blend mode of their drawables
+    pub fn set_layer_blend(&mut self, layer: WidgetLayer, blend: BlendMode) {
+        self.layers.settings_mut(layer).blend = blend;
+    }
+
+    /// Remove a widget from the tree, the widget is unmounted before being returned
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Identifier returned by [`Ui::add`]
+    pub fn remove(&mut self, id: WidgetId) -> Option<Box<dyn Widget>> {
+        let mut widget = self.widgets.remove(&id)?;
+        self.order.retain(|other| *other != id);
+        self.layers.remove(id);
+        self.effects.remove(&id);
+        self.poses.remove(&id);
+        widget.on_unmount(self.context.clone());
+
+        Some(widget)
+    }
+
+    /// Remove a widget and destroy it once the frames which may still draw it are completed
+    ///
+    /// Unlike [`Ui::remove`], the widget can be deleted while a frame is being recorded.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Id of the widget to delete
+    pub fn delete(&mut self, id: WidgetId) -> bool {
+        let Some(widget) = self.remove(id) else {
+            return false;
+        };
+
+        let frame = self.context.lock().unwrap().frames.current();
+        self.retired.retire(frame, widget);
+
+        true
+    }
+
     pub fn get(&mut self, id: WidgetId) -> Option<&mut Box<dyn Widget>> {
         self.widgets.get_mut(&id)
     }
 
+    /// Draw a widget above every other one of its layer
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Identifier returned by [`Ui::add`]
+    pub fn raise(&mut self, id: WidgetId) {
+        if let Some(index) = self.order.iter().position(|other| *other == id) {
+            let id = self.order.remove(index);
+            self.order.push(id);
+            self.layers.sort(&mut self.order);
+        }
+    }
+
+    /// Returns the top level widgets, back to front
+    pub fn widgets(&self) -> Vec<&dyn Widget> {
+        self.order
+            .iter()
+            .map(|id| self.widgets[id].as_ref())
+            .collect()
+    }
+
+    /// Returns the index in the draw order of the topmost visible modal widget
+    fn modal_index(&self) -> Option<usize> {
+        self.order
+            .iter()
+            .rposition(|id| self.layers.is_visible(*id) && self.widgets[id].is_modal())
+    }
+
+    /// Returns the widgets receiving inputs back to front, those under a modal widget are blocked
+    fn interactive(&self) -> Vec<WidgetId> {
+        self.order[self.modal_index().unwrap_or(0)..]
+            .iter()
+            .copied()
+            .filter(|id| self.layers.is_visible(*id))
+            .collect()
+    }
+
+    /// Returns the cursor wanted by the topmost widget under the pointer, `None` when no widget
+    /// is hovered so the content beneath decides
+    pub fn cursor_icon(&self) -> Option<CursorIcon> {
+        if !self.hovered {
+            return None;
+        }
+        if self.drag.is_some() {
+            return Some(CursorIcon::Grabbing);
+        }
+
+        self.interactive()
+            .into_iter()
+            // Topmost widget first
+            .rev()
+            .find_map(|id| cursor::cursor_at(self.widgets[&id].as_ref(), self.mouse_position))
+    }
+
+    /// Returns the widget identified by `uid`, searching the whole tree
+    pub fn find(&mut self, uid: Uid) -> Option<&mut dyn Widget> {
+        self.widgets
+            .values_mut()
+            .find_map(|widget| drag::find_mut(widget.as_mut(), uid))
+    }
+
+    /// Render a widget and its children into an image of any resolution, e.g. to export a chart
+    /// at print quality
+    ///
+    /// # Arguments
+    ///
+    /// * `uid` - Widget to render, at any depth
+    /// * `options` - Resolution and look of the image
+    pub fn export(&mut self, uid: Uid, options: &ExportOptions) -> anyhow::Result<RgbaImage> {
+        let context = self.context.clone();
+        let widget = self
+            .find(uid)
+            .ok_or_else(|| anyhow!("No widget {uid:?} to export"))?;
+
+        export::render(&context, widget, options)
+    }
+
+    /// Returns the payloads dropped since the last call
+    pub fn drain_drops(&mut self) -> Vec<Dropped> {
+        self.drops.drain(..).collect()
+    }
+
+    /// Returns the files dropped from the OS since the last call, the widgets accepting them
+    /// already received them as a [`FilesDropped`] payload
+    pub fn drain_file_drops(&mut self) -> Vec<FileDrop> {
+        self.file_drops.drain(..).collect()
+    }
+
+    /// Lay the whole tree out again, containers fitting their content are resized bottom-up
+    pub fn update_layout(&mut self) {
+        self.widgets.values_mut().for_each(|widget| widget.update());
+    }
+
+    /// Mirror the widgets for right-to-left languages, or back, see [`direction`]
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - Side the widgets start from
+    pub fn set_layout_direction(&mut self, direction: LayoutDirection) {
+        direction::set_layout_direction(direction);
+        self.relayout();
+    }
+
+    fn relayout(&mut self) {
+        // Placing the widgets at their position again lays their content out in the new direction
+        self.widgets.values_mut().for_each(|widget| {
+            let position = *widget.position();
+            widget.set_position(position);
+            widget.update();
+        });
+    }
+
     pub fn process_events(&mut self, event: &WindowEvent) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(event);
+        }
+
+        match event {
+            WindowEvent::ModifiersChanged(modifiers) => self.modifiers = *modifiers,
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(VirtualKeyCode::Tab),
+                        ..
+                    },
+                ..
+            } => {
+                // Tab moves the focus rather than reaching the widgets
+                self.focus_next(self.modifiers.shift());
+                return;
+            }
+            _ => {}
+        }
+
+        if let WindowEvent::Touch(touch) = event {
+            self.process_touch(touch);
+        }
+
+        self.dispatch(event);
+
+        if let WindowEvent::MouseInput {
+            state: ElementState::Pressed,
+            ..
+        } = event
+        {
+            self.follow_click_focus();
+        }
+    }
+
+    /// Returns the widget receiving the keyboard inputs
+    pub fn focused(&self) -> Option<Uid> {
+        self.focused
+    }
+
+    /// Give the keyboard focus to a widget, the previously focused one loses it
+    ///
+    /// # Arguments
+    ///
+    /// * `uid` - Widget to focus, `None` to clear the focus
+    pub fn focus(&mut self, uid: Option<Uid>) {
+        if let Some(widget) = self.focused.and_then(|previous| self.find(previous)) {
+            widget.set_focused(false);
+        }
+
+        let focused = uid.filter(|uid| match self.find(*uid) {
+            Some(widget) => {
+                widget.set_focused(true);
+                true
+            }
+            None => false,
+        });
+        self.focused = focused;
+    }
+
+    /// Move the focus to the next focusable widget, within the innermost open focus scope
+    ///
+    /// # Arguments
+    ///
+    /// * `backward` - Move to the previous widget, as Shift+Tab does
+    pub fn focus_next(&mut self, backward: bool) {
+        let candidates = self.focus_candidates();
+
+        self.focus(focus::next(&candidates, self.focused, backward));
+    }
+
+    /// Returns the widgets Tab cycles through, those of the innermost open scope if any
+    fn focus_candidates(&mut self) -> Vec<Uid> {
+        if let Some(scope) = self.focus_scopes.current() {
+            if let Some(widget) = self.find(scope) {
+                return focus::focusable(widget);
+            }
+        }
+
+        self.interactive()
+            .into_iter()
+            .flat_map(|id| focus::focusable(self.widgets[&id].as_ref()))
+            .collect()
+    }
+
+    /// Follow the widgets focusing themselves when clicked, e.g. text inputs
+    fn follow_click_focus(&mut self) {
+        let focused = self
+            .widgets
+            .values()
+            .flat_map(|widget| focus::focused(widget.as_ref()))
+            .collect::<Vec<_>>();
+        // The widget which just focused itself rather than the one which kept the focus
+        let clicked = focused
+            .iter()
+            .find(|uid| Some(**uid) != self.focused)
+            .or(focused.first())
+            .copied();
+
+        if clicked != self.focused {
+            self.focus(clicked);
+        }
+    }
+
+    /// Trap the focus in the focus scopes opened since the last frame, and give it back once
+    /// they close
+    fn sync_focus_scopes(&mut self) {
+        let open = self
+            .order
+            .iter()
+            .map(|id| self.widgets[id].as_ref())
+            .filter(|widget| widget.visible() && widget.is_focus_scope())
+            .map(|widget| widget.uid())
+            .collect::<Vec<_>>();
+
+        match self.focus_scopes.sync(&open, self.focused) {
+            Some(ScopeChange::Entered(scope)) => {
+                let first = self
+                    .find(scope)
+                    .and_then(|widget| focus::focusable(widget).into_iter().next());
+                self.focus(first);
+            }
+            Some(ScopeChange::Left(previous)) => self.focus(previous),
+            None => {}
+        }
+    }
+
+    /// Start recording the inputs, replacing the recording in progress
+    pub fn start_recording(&mut self) {
+        self.recorder = Some(Recorder::new());
+    }
+
+    /// Stop recording, returns `None` if no recording was in progress
+    pub fn stop_recording(&mut self) -> Option<Recording> {
+        self.recorder.take().map(Recorder::finish)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Replay the inputs of `recording` from the next frames, as if they were received again
+    ///
+    /// # Arguments
+    ///
+    /// * `recording` - Inputs to replay, their positions in the UI space
+    pub fn play(&mut self, recording: Recording) {
+        self.playback = Some(Playback::new(recording));
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playback.is_some()
+    }
+
+    /// Process synthetic inputs right away, e.g. from an automated test
+    pub fn inject(&mut self, events: impl IntoIterator<Item = RecordedEvent>) {
+        for event in events {
+            self.process_events(&event.to_window_event());
+        }
+    }
+
+    /// Click with the left button at `position`
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - Position of the click, in the UI space
+    pub fn click_at(&mut self, position: Vec2) {
+        self.inject(recording::click(position));
+    }
+
+    /// Type `text` into the focused widget
+    pub fn type_text(&mut self, text: &str) {
+        self.inject(recording::typing(text));
+    }
+
+    fn dispatch(&mut self, event: &WindowEvent) {
+        self.process_drag(event);
+        self.process_file_drag(event);
+
+        // Widgets moving with the cursor are dragged by their titlebar
+        let dragged = matches!(event, WindowEvent::CursorMoved { .. })
+            .then(|| self.posed_positions())
+            .unwrap_or_default();
+
+        for id in self.interactive() {
+            if let Some(widget) = self.widgets.get_mut(&id) {
+                widget.process_events(event);
+            }
+        }
+
+        self.snap_dragged(dragged);
+    }
+
+    fn screen_rect(&self) -> Rect {
+        let (width, height) = self.context.lock().unwrap().screen_size();
+
+        Rect {
+            x: 0.,
+            y: 0.,
+            width,
+            height,
+        }
+    }
+
+    /// Returns the positions of the constrained widgets
+    fn posed_positions(&self) -> Vec<(WidgetId, Vec2)> {
+        self.poses
+            .keys()
+            .filter_map(|id| Some((*id, *self.widgets.get(id)?.position())))
+            .collect()
+    }
+
+    /// Returns the bounds of the visible constrained widgets `id` may snap to, those snapped to
+    /// it left out
+    fn snap_panels(&self, id: WidgetId) -> Vec<(WidgetId, Rect)> {
+        self.poses
+            .iter()
+            .filter(|(other, pose)| {
+                **other != id
+                    && !pose
+                        .snaps
+                        .iter()
+                        .flatten()
+                        .any(|snap| snap.target == SnapTarget::Panel(id))
+            })
+            .filter_map(|(other, _)| {
+                let widget = self.widgets.get(other)?;
+                widget.visible().then(|| (*other, widget.bounds()))
+            })
+            .collect()
+    }
+
+    /// Move a widget so that its bounds go from `from` to `to`, returns its new position
+    fn move_posed(&mut self, id: WidgetId, from: Rect, to: Rect) -> Option<Vec2> {
+        let widget = self.widgets.get_mut(&id)?;
+        let offset = Vec2::new(to.x - from.x, to.y - from.y);
+        if offset != Vec2::ZERO {
+            let position = *widget.position() + offset;
+            widget.set_position(position);
+        }
+
+        Some(*widget.position())
+    }
+
+    /// Snap the constrained widgets which moved since `before`
+    ///
+    /// # Arguments
+    ///
+    /// * `before` - Positions of the constrained widgets before the event
+    fn snap_dragged(&mut self, before: Vec<(WidgetId, Vec2)>) {
+        if before.is_empty() {
+            return;
+        }
+        let screen = self.screen_rect();
+
+        for (id, position) in before {
+            let Some(widget) = self.widgets.get(&id) else {
+                continue;
+            };
+            if *widget.position() == position {
+                continue;
+            }
+
+            let rect = widget.bounds();
+            let panels = self.snap_panels(id);
+            let constraints = self.poses[&id].constraints;
+            let (mut snapped, snaps) = pose::snap(rect, screen, &panels, &constraints);
+            if constraints.keep_on_screen {
+                snapped = pose::keep_on_screen(snapped, screen);
+            }
+
+            let position = self.move_posed(id, rect, snapped);
+            if let Some(pose) = self.poses.get_mut(&id) {
+                pose.snaps = snaps;
+                pose.position = position;
+            }
+        }
+    }
+
+    /// Keep the constrained widgets against what they snapped to and inside the window
+    fn apply_poses(&mut self) {
+        let screen = self.screen_rect();
+        let ids: Vec<_> = self.poses.keys().copied().collect();
+
+        for id in ids {
+            let Some(widget) = self.widgets.get(&id) else {
+                continue;
+            };
+            let rect = widget.bounds();
+            let pose = &self.poses[&id];
+            let constraints = pose.constraints;
+            // Moved by someone else than the Ui, what it snapped to no longer holds
+            let moved = pose
+                .position
+                .is_some_and(|position| position != *widget.position());
+            let snaps = if moved { [None, None] } else { pose.snaps };
+
+            let widgets = &self.widgets;
+            let (mut placed, snaps) = pose::follow(rect, snaps, &|target| match target {
+                SnapTarget::Screen => Some(screen),
+                SnapTarget::Panel(other) => widgets
+                    .get(&other)
+                    .filter(|widget| widget.visible())
+                    .map(|widget| widget.bounds()),
+            });
+            if constraints.keep_on_screen {
+                placed = pose::keep_on_screen(placed, screen);
+            }
+
+            let position = self.move_posed(id, rect, placed);
+            if let Some(pose) = self.poses.get_mut(&id) {
+                pose.snaps = snaps;
+                pose.position = position;
+            }
+        }
+    }
+
+    /// Start, move and drop drags, drags are routed across the whole tree
+    fn process_drag(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.mouse_position = Vec2::new(position.x as f32, position.y as f32);
+                self.hovered = true;
+
+                if self.drag.is_some() {
+                    self.move_drag();
+                } else if let Some(pressed_at) = self.pressed_at {
+                    if pressed_at.distance(self.mouse_position) > drag::DRAG_THRESHOLD {
+                        self.pressed_at = None;
+                        self.start_drag(pressed_at);
+                    }
+                }
+            }
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => match state {
+                ElementState::Pressed => self.pressed_at = Some(self.mouse_position),
+                ElementState::Released => {
+                    self.pressed_at = None;
+                    self.end_drag();
+                }
+            },
+            WindowEvent::CursorLeft { .. } => self.hovered = false,
+            _ => {}
+        }
+    }
+
+    fn start_drag(&mut self, pressed_at: Vec2) {
+        let Some(source) = self
+            .interactive()
+            .into_iter()
+            // Topmost widget first
+            .rev()
+            .find_map(|id| drag::widget_at(self.widgets[&id].as_ref(), pressed_at))
+        else {
+            return;
+        };
+        let context = self.context.clone();
+        let Some(widget) = self.find(source) else {
+            return;
+        };
+        let Some(payload) = widget.drag_payload() else {
+            return;
+        };
+
+        let bounds = widget.bounds();
+        let mut ghost = RenderTexture::new(context, &format!("{}/drag-ghost", widget.debug_name()));
+        ghost.set_source(bounds);
+        ghost.set_opacity(drag::GHOST_OPACITY);
+
+        self.drag = Some(Drag {
+            source,
+            payload,
+            ghost,
+            snapshot_pending: true,
+            grab_offset: pressed_at - bounds.position(),
+            target: None,
+        });
+        self.move_drag();
+    }
+
+    fn move_drag(&mut self) {
+        let Some(drag) = &mut self.drag else {
+            return;
+        };
+        drag.ghost
+            .set_position(self.mouse_position - drag.grab_offset);
+
+        let (payload, previous) = (drag.payload.clone(), drag.target);
+        let target = self.interactive().into_iter().rev().find_map(|id| {
+            drag::drop_target(self.widgets[&id].as_ref(), self.mouse_position, &payload)
+        });
+
+        if target != previous {
+            if let Some(widget) = previous.and_then(|uid| self.find(uid)) {
+                widget.drag_hover(false);
+            }
+            if let Some(widget) = target.and_then(|uid| self.find(uid)) {
+                widget.drag_hover(true);
+            }
+            if let Some(drag) = &mut self.drag {
+                drag.target = target;
+            }
+        }
+    }
+
+    fn end_drag(&mut self) {
+        let Some(drag) = self.drag.take() else {
+            return;
+        };
+        let Some(target) = drag.target else {
+            return;
+        };
+
+        if let Some(widget) = self.find(target) {
+            widget.drag_hover(false);
+            widget.drop_payload(drag.payload.clone());
+        }
+
+        self.drops.push(Dropped {
+            source: drag.source,
+            target,
+            payload: drag.payload,
+        });
+    }
+
+    /// Follow the files dragged from the OS, they are routed like drags to the widgets accepting
+    /// a [`FilesDropped`] payload
+    fn process_file_drag(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::HoveredFile(path) => {
+                let drag = self.file_drag.get_or_insert_with(FileDrag::default);
+                drag.files.push(path.clone());
+                self.move_file_drag();
+            }
+            WindowEvent::DroppedFile(path) => {
+                let drag = self.file_drag.get_or_insert_with(FileDrag::default);
+                // The dropped files are reported again, without the hovered ones
+                if !drag.dropped {
+                    drag.dropped = true;
+                    drag.files.clear();
+                }
+                drag.files.push(path.clone());
+            }
+            WindowEvent::HoveredFileCancelled => {
+                let target = self.file_drag.take().and_then(|drag| drag.target);
+                if let Some(widget) = target.and_then(|uid| self.find(uid)) {
+                    widget.drag_hover(false);
+                }
+            }
+            // Not reported by every platform while files are dragged
+            WindowEvent::CursorMoved { .. } if self.file_drag.is_some() => self.move_file_drag(),
+            _ => {}
+        }
+    }
+
+    fn move_file_drag(&mut self) {
+        let Some(drag) = &self.file_drag else {
+            return;
+        };
+        let (payload, previous) = (
+            DragPayload::new(FilesDropped(drag.files.clone())),
+            drag.target,
+        );
+        let target = self.interactive().into_iter().rev().find_map(|id| {
+            drag::drop_target(self.widgets[&id].as_ref(), self.mouse_position, &payload)
+        });
+
+        if target != previous {
+            if let Some(widget) = previous.and_then(|uid| self.find(uid)) {
+                widget.drag_hover(false);
+            }
+            if let Some(widget) = target.and_then(|uid| self.find(uid)) {
+                widget.drag_hover(true);
+            }
+            if let Some(drag) = &mut self.file_drag {
+                drag.target = target;
+            }
+        }
+    }
+
+    /// Deliver the files dropped since the last frame, once every file was reported
+    fn end_file_drag(&mut self) {
+        if !self.file_drag.as_ref().is_some_and(|drag| drag.dropped) {
+            return;
+        }
+        self.move_file_drag();
+        let Some(drag) = self.file_drag.take() else {
+            return;
+        };
+
+        if let Some(widget) = drag.target.and_then(|uid| self.find(uid)) {
+            widget.drag_hover(false);
+            widget.drop_payload(DragPayload::new(FilesDropped(drag.files.clone())));
+        }
+
+        self.file_drops.push(FileDrop {
+            target: drag.target,
+            files: drag.files,
+        });
+    }
+
+    /// Recognize gestures and emulate the mouse with the first finger, so taps act as clicks
+    // `modifiers` is deprecated but still has to be filled
+    #[allow(deprecated)]
+    fn process_touch(&mut self, touch: &Touch) {
+        let position = Vec2::new(touch.location.x as f32, touch.location.y as f32);
+        let gestures = self.gestures.touch(touch.id, touch.phase, position);
+        let primary = self.gestures.touch_count() <= 1;
+
+        if primary {
+            let cursor_moved = WindowEvent::CursorMoved {
+                device_id: touch.device_id,
+                position: touch.location,
+                modifiers: Default::default(),
+            };
+            let mouse_input = |state| WindowEvent::MouseInput {
+                device_id: touch.device_id,
+                state,
+                button: MouseButton::Left,
+                modifiers: Default::default(),
+            };
+
+            let events = match touch.phase {
+                TouchPhase::Started => vec![cursor_moved, mouse_input(ElementState::Pressed)],
+                TouchPhase::Moved => vec![cursor_moved],
+                TouchPhase::Ended | TouchPhase::Cancelled => {
+                    vec![cursor_moved, mouse_input(ElementState::Released)]
+                }
+            };
+
+            events.iter().for_each(|event| self.dispatch(event));
+        }
+
+        self.dispatch_gestures(gestures);
+    }
+
+    fn dispatch_gestures(&mut self, gestures: Vec<Gesture>) {
+        let interactive = self.interactive();
+
+        gestures.iter().for_each(|gesture| {
+            for id in &interactive {
+                if let Some(widget) = self.widgets.get_mut(id) {
+                    widget.process_gesture(gesture);
+                }
+            }
+        });
+    }
+
+    /// Advance the per-frame logic of every widget
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Time elapsed since the last frame, in seconds
+    pub fn tick(&mut self, dt: f32) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.tick(dt);
+        }
+        if let Some(playback) = &mut self.playback {
+            let events = playback.advance(dt);
+            if playback.is_done() {
+                self.playback = None;
+            }
+            self.inject(events);
+        }
+
+        self.sync_focus_scopes();
+        self.end_file_drag();
+
+        let gestures = self.gestures.tick(dt);
+        self.dispatch_gestures(gestures);
+
+        let completed = self.context.lock().unwrap().frames.completed();
+        self.retired.collect(completed);
+
         self.widgets
             .iter_mut()
-            .for_each(|(_, widget)| widget.process_events(event));
+            .for_each(|(_, widget)| widget.tick(dt));
+
+        self.motions.retain_mut(|(id, animation)| {
+            let Some(widget) = self.widgets.get_mut(id) else {
+                return false;
+            };
+            animation.tick(dt, widget.as_mut());
+
+            !animation.is_done()
+        });
+
+        // Before the effects, which shake widgets around their position
+        self.apply_poses();
+
+        for (id, effects) in &mut self.effects {
+            if let Some(widget) = self.widgets.get_mut(id) {
+                effects.tick(dt, widget.as_mut());
+            }
+        }
+
+        // The labels were translated by their tick, the containers fit them in the direction of
+        // the new locale
+        let locale = i18n::generation();
+        if self.locale != locale {
+            self.locale = locale;
+            self.relayout();
+        }
+    }
+
+    /// Render the snapshot of a drag that just started and of the widgets drawn in grays, must be
+    /// recorded before the pass drawing the Ui
+    ///
+    /// The dragged widget is drawn once into the ghost texture, the ghost then follows the cursor
+    /// without drawing the widget again.
+    ///
+    /// # Arguments
+    ///
+    /// * `encoder` - Encoder recording the frame
+    /// * `index_buffer` - Index buffer of the quads
+    pub fn prepare<'a>(
+        &'a mut self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        index_buffer: &'a wgpu::Buffer,
+    ) {
+        for (id, effects) in &mut self.effects {
+            let (Some(snapshot), Some(widget)) = (effects.snapshot_mut(), self.widgets.get_mut(id))
+            else {
+                continue;
+            };
+
+            let mut render_pass = snapshot.begin(encoder);
+            render_pass.push_debug_group("Grayscale snapshot");
+            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+            self.snapshot_camera.bind(&mut render_pass);
+            widget.draw(&mut render_pass);
+            render_pass.pop_debug_group();
+        }
+
+        let Some(drag) = &mut self.drag else {
+            return;
+        };
+        if !drag.snapshot_pending {
+            return;
+        }
+        drag.snapshot_pending = false;
+
+        let source = drag.source;
+        let Some(widget) = self
+            .widgets
+            .values_mut()
+            .find_map(|widget| drag::find_mut(widget.as_mut(), source))
+        else {
+            return;
+        };
+
+        let mut render_pass = drag.ghost.begin(encoder);
+        render_pass.push_debug_group("Drag ghost snapshot");
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        self.snapshot_camera.bind(&mut render_pass);
+        widget.draw(&mut render_pass);
+        render_pass.pop_debug_group();
     }
 
     pub fn draw<'a>(
         &'a mut self,
         render_pass: &mut RenderPass<'a>,
     ) {
-        self.widgets.iter_mut().for_each(|(_, widget)| {
-            widget.draw(render_pass);
-        });
+        // Bounds of the widget the dragged files would be dropped on
+        let target = self.file_drag.as_ref().and_then(|drag| drag.target);
+        let highlight = target
+            .and_then(|uid| self.find(uid))
+            .map(|widget| widget.bounds());
+        if let Some(bounds) = highlight {
+            self.drop_highlight.set_position(bounds.position());
+            self.drop_highlight
+                .set_size(Vec2::new(bounds.width, bounds.height));
+        }
+
+        let modal = self.modal_index();
+        if modal.is_some() {
+            let screen_size = self.context.lock().unwrap().screen_size();
+            self.dim.set_size(screen_size.into());
+        }
+
+        let mut dim = modal.map(|_| &mut self.dim);
+        let order = &self.order;
+        let layers = &self.layers;
+        let mut widgets = self.widgets.iter_mut().collect::<Vec<_>>();
+        let mut effects = self.effects.iter_mut().collect::<BTreeMap<_, _>>();
+        widgets.sort_by_key(|(id, _)| order.iter().position(|other| other == *id));
+        // Blend mode of the drawables recorded last
+        let mut blend = None;
+
+        for (index, (id, widget)) in widgets.into_iter().enumerate() {
+            // The dim is drawn once, right under the modal widget
+            if Some(index) == modal {
+                if let Some(dim) = dim.take() {
+                    blend = Some(BlendMode::Alpha);
+                    blend::set_blend_mode(BlendMode::Alpha);
+
+                    render_pass.push_debug_group("Modal dim");
+                    dim.draw(render_pass);
+                    render_pass.pop_debug_group();
+                }
+            }
+
+            let settings = layers.settings(layers.layer(*id));
+            if !settings.visible {
+                continue;
+            }
+            if blend != Some(settings.blend) {
+                blend = Some(settings.blend);
+                blend::set_blend_mode(settings.blend);
+            }
+
+            render_pass.push_debug_group(&widget.debug_name());
+            match effects.remove(id) {
+                Some(effects) => effects.draw(widget.as_mut(), render_pass),
+                None => widget.draw(render_pass),
+            }
+            render_pass.pop_debug_group();
+        }
+        blend::set_blend_mode(BlendMode::Alpha);
+
+        // Overlay, drawn above every widget
+        if highlight.is_some() {
+            render_pass.push_debug_group("Drop highlight");
+            self.drop_highlight.draw(render_pass);
+            render_pass.pop_debug_group();
+        }
+
+        if let Some(drag) = self.drag.as_mut().filter(|drag| !drag.snapshot_pending) {
+            render_pass.push_debug_group("Drag ghost");
+            drag.ghost.draw(render_pass);
+            render_pass.pop_debug_group();
+        }
     }
 }
 
@@ -56,6 +1251,13 @@ pub trait WidgetEvent {}
 pub trait Widget: Drawable + Transformable {
     fn process_events(&mut self, event: &WindowEvent);
 
+    /// Handle a gesture recognized from touch inputs (taps also reach widgets as mouse clicks)
+    ///
+    /// # Arguments
+    ///
+    /// * `gesture` - Recognized gesture
+    fn process_gesture(&mut self, _gesture: &Gesture) {}
+
     fn events(&mut self, _event_handler: Box<dyn Fn(u32)>) {}
 
     fn emitted(&mut self, _event: u32) -> bool {
@@ -64,9 +1266,180 @@ pub trait Widget: Drawable + Transformable {
 
     fn update(&mut self) {}
 
+    /// Called once the widget is attached to the tree, before its first frame
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context, to allocate GPU resources
+    fn on_mount(&mut self, _context: Ctx) {}
+
+    /// Called once the widget is detached from the tree
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context, to release GPU resources
+    fn on_unmount(&mut self, _context: Ctx) {}
+
+    /// Per-frame logic (animations, ..)
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Time elapsed since the last frame, in seconds
+    fn tick(&mut self, _dt: f32) {}
+
     fn set_visibility(&mut self, visible: bool);
     fn visible(&self) -> bool;
 
+    /// Returns `true` while the widget blocks the inputs of the widgets beneath it
+    fn is_modal(&self) -> bool {
+        false
+    }
+
+    /// Returns `true` if the widget can receive the keyboard focus, reached with Tab
+    fn is_focusable(&self) -> bool {
+        false
+    }
+
+    /// Give (`true`) or take (`false`) the keyboard focus, called by the Ui
+    fn set_focused(&mut self, _focused: bool) {}
+
+    fn is_focused(&self) -> bool {
+        false
+    }
+
+    /// Returns `false` while the widget ignores the inputs, e.g. a button whose action is
+    /// unavailable
+    fn is_enabled(&self) -> bool {
+        true
+    }
+
+    /// Enable or disable the widget, ignored by widgets which can't be disabled
+    fn set_enabled(&mut self, _enabled: bool) {}
+
+    /// Returns `true` while the cursor is over the widget
+    fn is_hovered(&self) -> bool {
+        false
+    }
+
+    /// Returns `true` while the content of the widget fails its validation, e.g. a malformed
+    /// email address
+    fn is_invalid(&self) -> bool {
+        false
+    }
+
+    /// Returns `true` if Tab cycles only within the widget while it is visible, e.g. dialogs and
+    /// popups, the focus goes back to the previously focused widget once it is hidden or removed
+    fn is_focus_scope(&self) -> bool {
+        self.is_modal()
+    }
+
+    /// Returns the opacity set on the widget, from 0 (transparent) to 1 (opaque)
+    fn opacity(&self) -> f32 {
+        1.
+    }
+
+    /// Set the opacity of the widget, its children are faded along
+    ///
+    /// # Arguments
+    ///
+    /// * `opacity` - New opacity, from 0 (transparent) to 1 (opaque)
+    fn set_opacity(&mut self, _opacity: f32) {}
+
+    /// Called by the parent when its effective opacity changes
+    ///
+    /// # Arguments
+    ///
+    /// * `opacity` - Effective opacity of the parent
+    fn set_inherited_opacity(&mut self, _opacity: f32) {}
+
     fn size(&self) -> &Vec2;
     fn set_size(&mut self, size: Vec2) {}
+
+    /// Returns the size the content would like to have, e.g. the dimensions of a picture,
+    /// layouts measure the widget with it rather than its current size
+    fn intrinsic_size(&self) -> Option<Vec2> {
+        None
+    }
+
+    /// Name of the widget shown by GPU debuggers, e.g. `Button#save`
+    fn debug_name(&self) -> String {
+        String::from("Widget")
+    }
+
+    /// Identity of the widget
+    fn uid(&self) -> Uid;
+
+    /// Widgets owned by this one
+    fn children(&self) -> Vec<&dyn Widget> {
+        Vec::new()
+    }
+
+    /// Widgets owned by this one, mutable
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        Vec::new()
+    }
+
+    /// Returns the payload carried when the user starts dragging this widget, `None` if the
+    /// widget can't be dragged
+    fn drag_payload(&mut self) -> Option<DragPayload> {
+        None
+    }
+
+    /// Returns `true` if `payload` can be dropped on the widget
+    fn accepts_drop(&self, _payload: &DragPayload) -> bool {
+        false
+    }
+
+    /// Called when an acceptable drag enters (`true`) or leaves (`false`) the widget
+    fn drag_hover(&mut self, _hovered: bool) {}
+
+    /// Called when an acceptable payload is released over the widget
+    fn drop_payload(&mut self, _payload: DragPayload) {}
+
+    /// Returns the bounds of the widget
+    fn bounds(&self) -> Rect {
+        let (position, size) = (self.position(), self.size());
+
+        transform::transform_rect(
+            self.matrix(),
+            Rect {
+                x: position.x,
+                y: position.y,
+                width: size.x,
+                height: size.y,
+            },
+        )
+    }
+
+    /// Returns `true` if `point` is over the widget, the whole untransformed area by default,
+    /// so a rotated widget responds on its visible area only
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - Position to test, in the UI space
+    fn hit_test(&self, point: Vec2) -> bool {
+        let (position, size) = (*self.position(), *self.size());
+        let area = Rect {
+            x: position.x,
+            y: position.y,
+            width: size.x,
+            height: size.y,
+        };
+
+        transform::untransform_point(self.matrix(), point).is_some_and(|point| area.contains(point))
+    }
+
+    /// Cursor shown while the pointer hovers `point`, `None` lets the parent decide
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - Position of the pointer, over the widget
+    fn cursor(&self, _point: Vec2) -> Option<CursorIcon> {
+        None
+    }
+
+    /// Description of the widget exposed to assistive technologies
+    fn accessibility(&self) -> AccessNode {
+        AccessNode::new(Role::Group, self.bounds())
+    }
 }
@@ -0,0 +1,249 @@
+//! Widgets moved along curves, e.g. tutorial pointers or HUD elements, at a constant speed
+//! whatever the spacing of the control points
+
+use glam::Vec2;
+
+use crate::graphics::Transformable;
+
+/// Samples of each segment the arc length is measured with
+const SAMPLES_PER_SEGMENT: usize = 32;
+
+/// Returns the point of a cubic Bézier segment at `t`
+fn bezier_point([p0, p1, p2, p3]: [Vec2; 4], t: f32) -> Vec2 {
+    let u = 1. - t;
+
+    p0 * (u * u * u) + p1 * (3. * u * u * t) + p2 * (3. * u * t * t) + p3 * (t * t * t)
+}
+
+/// Returns the derivative of a cubic Bézier segment at `t`, pointing forward
+fn bezier_tangent([p0, p1, p2, p3]: [Vec2; 4], t: f32) -> Vec2 {
+    let u = 1. - t;
+
+    (p1 - p0) * (3. * u * u) + (p2 - p1) * (6. * u * t) + (p3 - p2) * (3. * t * t)
+}
+
+/// Curve made of cubic Bézier segments, parameterized by the distance travelled along it
+#[derive(Debug, Clone, PartialEq)]
+pub struct MotionPath {
+    segments: Vec<[Vec2; 4]>,
+    /// Distance from the start to each sample, `SAMPLES_PER_SEGMENT + 1` per segment
+    lengths: Vec<f32>,
+}
+
+impl MotionPath {
+    /// Create a path from cubic Bézier segments
+    ///
+    /// # Arguments
+    ///
+    /// * `segments` - Start, two control points and end of each segment, a segment should start
+    ///   where the previous one ends
+    pub fn bezier(segments: Vec<[Vec2; 4]>) -> Self {
+        let mut lengths = Vec::with_capacity(segments.len() * (SAMPLES_PER_SEGMENT + 1));
+        let mut length = 0.;
+
+        for segment in &segments {
+            let mut previous = segment[0];
+            lengths.push(length);
+
+            for sample in 1..=SAMPLES_PER_SEGMENT {
+                let point = bezier_point(*segment, sample as f32 / SAMPLES_PER_SEGMENT as f32);
+                length += point.distance(previous);
+                lengths.push(length);
+                previous = point;
+            }
+        }
+
+        Self { segments, lengths }
+    }
+
+    /// Create a path going through every point, smoothly curved between them
+    ///
+    /// # Arguments
+    ///
+    /// * `points` - Points of the path, at least two
+    pub fn catmull_rom(points: &[Vec2]) -> Self {
+        let segments = (0..points.len().saturating_sub(1))
+            .map(|i| {
+                let p0 = points[i.saturating_sub(1)];
+                let (p1, p2) = (points[i], points[i + 1]);
+                let p3 = points[(i + 2).min(points.len() - 1)];
+
+                [p1, p1 + (p2 - p0) / 6., p2 - (p3 - p1) / 6., p2]
+            })
+            .collect();
+
+        Self::bezier(segments)
+    }
+
+    /// Returns the length of the path, in pixels
+    pub fn length(&self) -> f32 {
+        self.lengths.last().copied().unwrap_or(0.)
+    }
+
+    /// Returns the point `distance` away from the start along the path and the direction of the
+    /// path there, as a clockwise angle in radians
+    ///
+    /// # Arguments
+    ///
+    /// * `distance` - Distance travelled, clamped to the path
+    pub fn sample(&self, distance: f32) -> (Vec2, f32) {
+        let Some(last) = self.segments.len().checked_sub(1) else {
+            return (Vec2::ZERO, 0.);
+        };
+        let distance = distance.clamp(0., self.length());
+
+        // First sample at or past the distance, skipping the sample starting each segment
+        let index = self
+            .lengths
+            .partition_point(|length| *length < distance)
+            .clamp(1, self.lengths.len() - 1);
+        let segment = ((index - 1) / (SAMPLES_PER_SEGMENT + 1)).min(last);
+        let sample = index - segment * (SAMPLES_PER_SEGMENT + 1);
+
+        let (before, after) = (self.lengths[index - 1], self.lengths[index]);
+        let fraction = if after > before {
+            (distance - before) / (after - before)
+        } else {
+            0.
+        };
+        let t = ((sample as f32 - 1. + fraction) / SAMPLES_PER_SEGMENT as f32).clamp(0., 1.);
+
+        let segment = self.segments[segment];
+        let tangent = bezier_tangent(segment, t);
+        // The y axis points down, so the angle turns clockwise on screen
+        let angle = if tangent == Vec2::ZERO {
+            0.
+        } else {
+            tangent.y.atan2(tangent.x)
+        };
+
+        (bezier_point(segment, t), angle)
+    }
+}
+
+/// Moves an object along a path at a constant speed
+#[derive(Debug, Clone)]
+pub struct PathAnimation {
+    path: MotionPath,
+    /// Time to travel the whole path, in seconds
+    duration: f32,
+    elapsed: f32,
+    /// Rotate the object to face the direction of the path
+    follow_orientation: bool,
+    /// Start over once the end is reached
+    repeat: bool,
+}
+
+impl PathAnimation {
+    /// Create an animation travelling `path` in `duration` seconds
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path followed by the origin of the object
+    /// * `duration` - Time to reach the end, in seconds
+    pub fn new(path: MotionPath, duration: f32) -> Self {
+        Self {
+            path,
+            duration,
+            elapsed: 0.,
+            follow_orientation: false,
+            repeat: false,
+        }
+    }
+
+    /// Rotate the object along the path, its rotation is the clockwise angle of the path
+    pub fn with_orientation(mut self, follow: bool) -> Self {
+        self.follow_orientation = follow;
+        self
+    }
+
+    pub fn with_repeat(mut self, repeat: bool) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn path(&self) -> &MotionPath {
+        &self.path
+    }
+
+    /// Returns `true` once the end of a non repeating path is reached
+    pub fn is_done(&self) -> bool {
+        !self.repeat && self.elapsed >= self.duration
+    }
+
+    /// Returns the position of the origin and the rotation at the current time
+    pub fn current(&self) -> (Vec2, f32) {
+        let progress = if self.duration > 0. {
+            (self.elapsed / self.duration).min(1.)
+        } else {
+            1.
+        };
+
+        self.path.sample(progress * self.path.length())
+    }
+
+    /// Advance the animation and move `target` so its origin is on the path
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Time elapsed since the last frame, in seconds
+    /// * `target` - Animated object, e.g. a widget
+    pub fn tick<T: Transformable + ?Sized>(&mut self, dt: f32, target: &mut T) {
+        self.elapsed += dt;
+        if self.repeat && self.duration > 0. {
+            self.elapsed %= self.duration;
+        }
+
+        let (point, angle) = self.current();
+        target.set_position(point - target.origin());
+        if self.follow_orientation {
+            target.set_rotation(angle);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use glam::Vec2;
+
+    use super::MotionPath;
+
+    #[test]
+    fn distances_are_travelled_at_a_constant_speed() {
+        // Control points bunched at the start, a uniform parameter would speed up at the end
+        let path = MotionPath::bezier(vec![[
+            Vec2::ZERO,
+            Vec2::new(1., 0.),
+            Vec2::new(2., 0.),
+            Vec2::new(90., 0.),
+        ]]);
+        assert!((path.length() - 90.).abs() < 1e-2);
+
+        for step in 0..=9 {
+            let (point, angle) = path.sample(step as f32 * 10.);
+            assert!((point.x - step as f32 * 10.).abs() < 0.5, "{point}");
+            assert_eq!(angle, 0.);
+        }
+    }
+
+    #[test]
+    fn catmull_rom_goes_through_its_points() {
+        let points = [Vec2::ZERO, Vec2::new(0., 50.), Vec2::new(50., 50.)];
+        let path = MotionPath::catmull_rom(&points);
+
+        let (start, angle) = path.sample(0.);
+        assert_eq!(start, Vec2::ZERO);
+        // Heading down the screen
+        assert!((angle - FRAC_PI_2).abs() < 0.3);
+
+        let (end, _) = path.sample(path.length());
+        assert!(end.distance(points[2]) < 1e-3);
+
+        let through = (0..=100)
+            .map(|step| path.sample(path.length() * step as f32 / 100.).0)
+            .any(|point| point.distance(points[1]) < 1.);
+        assert!(through);
+    }
+}
@@ -0,0 +1,312 @@
+//! Constraints of floating panels and popups: kept on screen and snapped to the edges of the
+//! window or of the other panels while dragged, snaps being remembered afterwards
+
+use glam::Vec2;
+
+use super::WidgetId;
+use crate::math::Rect;
+
+/// Distance under which dragged panels snap, in pixels
+pub const SNAP_THRESHOLD: f32 = 12.;
+
+/// Constraints of a widget, see [`super::Ui::set_pose_constraints`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoseConstraints {
+    /// Moved back inside the window, e.g. once it shrinks
+    pub keep_on_screen: bool,
+    /// Snap to the edges of the window while dragged
+    pub snap_to_edges: bool,
+    /// Snap to the edges of the other constrained widgets while dragged
+    pub snap_to_panels: bool,
+    /// Distance under which the widget snaps, in pixels
+    pub threshold: f32,
+}
+
+impl Default for PoseConstraints {
+    fn default() -> Self {
+        Self {
+            keep_on_screen: true,
+            snap_to_edges: true,
+            snap_to_panels: true,
+            threshold: SNAP_THRESHOLD,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl Edge {
+    fn is_horizontal(self) -> bool {
+        matches!(self, Self::Left | Self::Right)
+    }
+
+    /// Returns the coordinate of the edge of `rect`
+    fn of(self, rect: Rect) -> f32 {
+        match self {
+            Self::Left => rect.x,
+            Self::Right => rect.x + rect.width,
+            Self::Top => rect.y,
+            Self::Bottom => rect.y + rect.height,
+        }
+    }
+
+    /// Returns `rect` moved so that its edge is at `coordinate`
+    fn place(self, rect: Rect, coordinate: f32) -> Rect {
+        let offset = coordinate - self.of(rect);
+
+        if self.is_horizontal() {
+            Rect {
+                x: rect.x + offset,
+                ..rect
+            }
+        } else {
+            Rect {
+                y: rect.y + offset,
+                ..rect
+            }
+        }
+    }
+}
+
+/// What a widget snapped to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapTarget {
+    /// Edges of the window
+    Screen,
+    Panel(WidgetId),
+}
+
+/// Edge of a widget kept against an edge of its target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Snap {
+    pub edge: Edge,
+    pub target: SnapTarget,
+    pub target_edge: Edge,
+}
+
+/// Snaps of a widget, one per axis
+pub type Snaps = [Option<Snap>; 2];
+
+/// Returns the rectangle of a target, `None` once a panel is gone
+pub type Targets<'a> = &'a dyn Fn(SnapTarget) -> Option<Rect>;
+
+/// Returns `true` if `a` and `b` are within `threshold` of each other along `horizontal`'s
+/// other axis, so that their edges along it can meet
+fn facing(a: Rect, b: Rect, horizontal: bool, threshold: f32) -> bool {
+    if horizontal {
+        a.y <= b.y + b.height + threshold && b.y <= a.y + a.height + threshold
+    } else {
+        a.x <= b.x + b.width + threshold && b.x <= a.x + a.width + threshold
+    }
+}
+
+/// Returns a dragged widget snapped to the closest edges within the threshold, and its snaps
+///
+/// # Arguments
+///
+/// * `rect` - Bounds of the widget where the drag put it
+/// * `screen` - Bounds of the window
+/// * `panels` - Bounds of the other panels it may snap to
+/// * `constraints` - Constraints of the widget
+pub fn snap(
+    rect: Rect,
+    screen: Rect,
+    panels: &[(WidgetId, Rect)],
+    constraints: &PoseConstraints,
+) -> (Rect, Snaps) {
+    let mut snapped = rect;
+    let mut snaps = [None, None];
+
+    for (axis, [start, end]) in [[Edge::Left, Edge::Right], [Edge::Top, Edge::Bottom]]
+        .into_iter()
+        .enumerate()
+    {
+        let mut candidates = Vec::new();
+        if constraints.snap_to_edges {
+            candidates.push((start, SnapTarget::Screen, start, screen));
+            candidates.push((end, SnapTarget::Screen, end, screen));
+        }
+        if constraints.snap_to_panels {
+            for (id, panel) in panels {
+                if !facing(rect, *panel, axis == 0, constraints.threshold) {
+                    continue;
+                }
+
+                // Side by side, then aligned
+                let target = SnapTarget::Panel(*id);
+                candidates.push((start, target, end, *panel));
+                candidates.push((end, target, start, *panel));
+                candidates.push((start, target, start, *panel));
+                candidates.push((end, target, end, *panel));
+            }
+        }
+
+        let closest = candidates
+            .into_iter()
+            .map(|(edge, target, target_edge, bounds)| {
+                let distance = (edge.of(rect) - target_edge.of(bounds)).abs();
+                (distance, edge, target, target_edge, bounds)
+            })
+            .filter(|(distance, ..)| *distance <= constraints.threshold)
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+
+        if let Some((_, edge, target, target_edge, bounds)) = closest {
+            snapped = edge.place(snapped, target_edge.of(bounds));
+            snaps[axis] = Some(Snap {
+                edge,
+                target,
+                target_edge,
+            });
+        }
+    }
+
+    (snapped, snaps)
+}
+
+/// Returns a widget kept against the targets it snapped to, e.g. following a panel or the
+/// right edge of a resized window, along with the snaps whose target still exists
+///
+/// # Arguments
+///
+/// * `rect` - Bounds of the widget
+/// * `snaps` - Remembered snaps
+/// * `targets` - Current bounds of the targets
+pub fn follow(rect: Rect, snaps: Snaps, targets: Targets) -> (Rect, Snaps) {
+    let mut rect = rect;
+    let snaps = snaps.map(|snap| {
+        let snap = snap?;
+        let bounds = targets(snap.target)?;
+        rect = snap.edge.place(rect, snap.target_edge.of(bounds));
+
+        Some(snap)
+    });
+
+    (rect, snaps)
+}
+
+/// Returns `rect` moved inside `screen`, its top-left corner staying visible when it is larger
+pub fn keep_on_screen(rect: Rect, screen: Rect) -> Rect {
+    let max = Vec2::new(
+        screen.x + screen.width - rect.width,
+        screen.y + screen.height - rect.height,
+    );
+
+    Rect {
+        x: rect.x.min(max.x).max(screen.x),
+        y: rect.y.min(max.y).max(screen.y),
+        ..rect
+    }
+}
+
+/// Constraints of a widget and what it snapped to
+#[derive(Debug, Clone)]
+pub(super) struct Pose {
+    pub constraints: PoseConstraints,
+    pub snaps: Snaps,
+    /// Position the widget was last left at, a widget moved elsewhere by the application
+    /// forgets its snaps
+    pub position: Option<Vec2>,
+}
+
+impl Pose {
+    pub fn new(constraints: PoseConstraints) -> Self {
+        Self {
+            constraints,
+            snaps: [None, None],
+            position: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{follow, keep_on_screen, snap, Edge, PoseConstraints, Snap, SnapTarget};
+    use crate::math::Rect;
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    const SCREEN: Rect = Rect {
+        x: 0.,
+        y: 0.,
+        width: 800.,
+        height: 600.,
+    };
+
+    #[test]
+    fn dragged_panels_snap_to_the_closest_edges() {
+        let other = (7, rect(300., 120., 200., 200.));
+        let constraints = PoseConstraints::default();
+
+        // Near the right side of the other panel and the top of the window
+        let (snapped, snaps) = snap(rect(508., 6., 100., 100.), SCREEN, &[other], &constraints);
+        assert_eq!(snapped, rect(500., 0., 100., 100.));
+        assert_eq!(
+            snaps,
+            [
+                Some(Snap {
+                    edge: Edge::Left,
+                    target: SnapTarget::Panel(other.0),
+                    target_edge: Edge::Right,
+                }),
+                Some(Snap {
+                    edge: Edge::Top,
+                    target: SnapTarget::Screen,
+                    target_edge: Edge::Top,
+                }),
+            ]
+        );
+
+        // Out of reach, left where dropped
+        let far = rect(650., 400., 100., 100.);
+        assert_eq!(
+            snap(far, SCREEN, &[other], &constraints),
+            (far, [None, None])
+        );
+    }
+
+    #[test]
+    fn snaps_follow_their_target() {
+        let snaps = [
+            Some(Snap {
+                edge: Edge::Right,
+                target: SnapTarget::Screen,
+                target_edge: Edge::Right,
+            }),
+            None,
+        ];
+        let wider = rect(0., 0., 1000., 600.);
+
+        let (followed, kept) = follow(rect(700., 50., 100., 100.), snaps, &|_| Some(wider));
+        assert_eq!(followed, rect(900., 50., 100., 100.));
+        assert_eq!(kept, snaps);
+
+        let (_, kept) = follow(rect(700., 50., 100., 100.), snaps, &|_| None);
+        assert_eq!(kept, [None, None]);
+    }
+
+    #[test]
+    fn panels_are_moved_back_on_screen() {
+        assert_eq!(
+            keep_on_screen(rect(750., -20., 100., 100.), SCREEN),
+            rect(700., 0., 100., 100.)
+        );
+        // Larger than the window, the top-left corner stays visible
+        assert_eq!(
+            keep_on_screen(rect(-50., 10., 900., 100.), SCREEN),
+            rect(0., 10., 900., 100.)
+        );
+    }
+}
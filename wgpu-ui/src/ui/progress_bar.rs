@@ -0,0 +1,217 @@
+use glam::Vec2;
+use wgpu::RenderPass;
+use winit::event::WindowEvent;
+
+use super::{
+    direction::layout_direction,
+    state::{Binding, State},
+    Opacity, Uid, Widget,
+};
+use crate::accessibility::{AccessNode, Role};
+use crate::graphics::shape::{RectangleShape, Shape};
+use crate::graphics::text::Text;
+use crate::graphics::{
+    color::{Color, GREEN, WHITE},
+    Drawable, Transformable,
+};
+use crate::{Ctx, ASSETS};
+
+/// Fill speed of the bar, in fraction of the total width per second
+const FILL_SPEED: f32 = 2.;
+
+/// Determinate progress indicator
+pub struct ProgressBar<'a> {
+    background: RectangleShape,
+    fill: RectangleShape,
+    label: Option<Text<'a>>,
+    position: Vec2,
+    size: Vec2,
+    /// Progress to reach, between 0 and 1
+    value: f32,
+    /// Progress currently displayed, animated toward `value`
+    displayed: f32,
+    visible: bool,
+    uid: Uid,
+    opacity: Opacity,
+    /// State the progress follows
+    binding: Option<Binding<f32>>,
+}
+
+impl<'a> ProgressBar<'a> {
+    pub fn new(context: Ctx, size: Vec2) -> Self {
+        let mut background =
+            RectangleShape::new_with_label(context.clone(), size, "ProgressBar/background");
+        background.set_fill_color(Color::from((60, 60, 60)));
+
+        let mut fill =
+            RectangleShape::new_with_label(context, (0., size.y).into(), "ProgressBar/fill");
+        fill.set_fill_color(GREEN);
+
+        Self {
+            background,
+            fill,
+            label: None,
+            position: Default::default(),
+            size,
+            value: 0.,
+            displayed: 0.,
+            visible: true,
+            uid: Uid::new(),
+            opacity: Opacity::default(),
+            binding: None,
+        }
+    }
+
+    /// Display the percentage of completion above the bar
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Context used to build the label
+    pub fn with_label(mut self, context: Ctx) -> Self {
+        let mut label = Text::new_with_label(
+            context,
+            "0%",
+            ASSETS.get_font("Roboto.ttf").unwrap(),
+            self.size.y * 0.8,
+            "ProgressBar/label",
+        );
+        label.set_fill_color(WHITE);
+        self.label = Some(label);
+        self.update();
+
+        self
+    }
+
+    /// Returns the progress to reach, between 0 and 1
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Set the progress, the fill is animated toward it on each `tick`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Progress between 0 and 1
+    pub fn set_value(&mut self, value: f32) {
+        self.value = value.clamp(0., 1.);
+    }
+
+    /// Follow the progress held by `state`, its changes are displayed on the next frame
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - State holding a progress between 0 and 1
+    pub fn bind_value(&mut self, state: State<f32>) {
+        self.binding = Some(Binding::new(state));
+    }
+
+    fn update_fill(&mut self) {
+        // The fill grows from the start side
+        let width = self.size.x * self.displayed;
+        self.fill.set_size((width, self.size.y).into());
+        self.fill.set_position(Vec2::new(
+            self.position.x + layout_direction().place(self.size.x, 0., width),
+            self.position.y,
+        ));
+
+        if let Some(label) = &mut self.label {
+            label.set_string(&format!("{}%", (self.displayed * 100.).round() as u32));
+        }
+    }
+
+    fn apply_opacity(&mut self) {
+        let opacity = self.opacity.effective();
+        self.background.set_opacity(opacity);
+        self.fill.set_opacity(opacity);
+
+        if let Some(label) = &mut self.label {
+            label.set_opacity(opacity);
+        }
+    }
+}
+
+impl<'a> Transformable for ProgressBar<'a> {
+    fn position(&self) -> &Vec2 {
+        &self.position
+    }
+
+    fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+
+        self.update();
+    }
+}
+
+impl<'a> Widget for ProgressBar<'a> {
+    impl_widget_state!();
+    impl_widget_opacity!();
+
+    fn process_events(&mut self, _event: &WindowEvent) {}
+
+    fn update(&mut self) {
+        self.background.set_position(self.position);
+        self.update_fill();
+
+        if let Some(label) = &mut self.label {
+            let bounds = label.bounds();
+            label.set_position(Vec2 {
+                x: self.position.x + (self.size.x - bounds.width) / 2.,
+                y: self.position.y + (self.size.y - bounds.height) / 2.,
+            });
+        }
+    }
+
+    fn tick(&mut self, dt: f32) {
+        if let Some(value) = self.binding.as_mut().and_then(Binding::changed) {
+            self.set_value(value);
+        }
+
+        if self.displayed == self.value {
+            return;
+        }
+
+        let step = FILL_SPEED * dt;
+        self.displayed = if self.displayed < self.value {
+            (self.displayed + step).min(self.value)
+        } else {
+            (self.displayed - step).max(self.value)
+        };
+
+        self.update_fill();
+    }
+
+    fn size(&self) -> &Vec2 {
+        &self.size
+    }
+
+    fn debug_name(&self) -> String {
+        String::from("ProgressBar")
+    }
+
+    fn accessibility(&self) -> AccessNode {
+        AccessNode::new(Role::ProgressIndicator, self.bounds())
+            .with_numeric_value(self.value as f64 * 100.)
+    }
+
+    fn set_size(&mut self, size: Vec2) {
+        self.size = size;
+        self.background.set_size(size);
+        self.update_fill();
+        self.update();
+    }
+}
+
+impl<'a> Drawable for ProgressBar<'a> {
+    fn draw<'b>(&'b mut self, render_pass: &mut RenderPass<'b>) {
+        if !self.visible {
+            return;
+        }
+
+        self.background.draw(render_pass);
+        self.fill.draw(render_pass);
+
+        if let Some(label) = &mut self.label {
+            label.draw(render_pass);
+        }
+    }
+}
@@ -0,0 +1,343 @@
+//! Inputs received by the Ui recorded with their time, replayed later to test the widgets
+//! without a human at the mouse
+//!
+//! Times follow the `dt` given to each frame rather than the wall clock, so a replay driven by
+//! the same frame times injects every input on the frame it was received on.
+
+use std::path::Path;
+
+use anyhow::Result;
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use winit::event::{
+    DeviceId, ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta, Touch,
+    TouchPhase, VirtualKeyCode, WindowEvent,
+};
+
+/// Input event which can be saved, positions are in the UI space
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RecordedEvent {
+    CursorMoved {
+        x: f64,
+        y: f64,
+    },
+    CursorEntered,
+    CursorLeft,
+    MouseInput {
+        state: ElementState,
+        button: MouseButton,
+    },
+    MouseWheel {
+        delta: MouseScrollDelta,
+        phase: TouchPhase,
+    },
+    KeyboardInput {
+        scancode: u32,
+        state: ElementState,
+        virtual_keycode: Option<VirtualKeyCode>,
+    },
+    ReceivedCharacter(char),
+    ModifiersChanged(ModifiersState),
+    Touch {
+        id: u64,
+        phase: TouchPhase,
+        x: f64,
+        y: f64,
+    },
+}
+
+impl RecordedEvent {
+    /// Returns the recordable part of `event`, `None` for events which aren't inputs
+    pub fn from_window_event(event: &WindowEvent) -> Option<Self> {
+        Some(match event {
+            WindowEvent::CursorMoved { position, .. } => Self::CursorMoved {
+                x: position.x,
+                y: position.y,
+            },
+            WindowEvent::CursorEntered { .. } => Self::CursorEntered,
+            WindowEvent::CursorLeft { .. } => Self::CursorLeft,
+            WindowEvent::MouseInput { state, button, .. } => Self::MouseInput {
+                state: *state,
+                button: *button,
+            },
+            WindowEvent::MouseWheel { delta, phase, .. } => Self::MouseWheel {
+                delta: *delta,
+                phase: *phase,
+            },
+            WindowEvent::KeyboardInput { input, .. } => Self::KeyboardInput {
+                scancode: input.scancode,
+                state: input.state,
+                virtual_keycode: input.virtual_keycode,
+            },
+            WindowEvent::ReceivedCharacter(c) => Self::ReceivedCharacter(*c),
+            WindowEvent::ModifiersChanged(modifiers) => Self::ModifiersChanged(*modifiers),
+            WindowEvent::Touch(touch) => Self::Touch {
+                id: touch.id,
+                phase: touch.phase,
+                x: touch.location.x,
+                y: touch.location.y,
+            },
+            _ => return None,
+        })
+    }
+
+    /// Returns the window event replaying this input
+    // `modifiers` is deprecated but still has to be filled
+    #[allow(deprecated)]
+    pub fn to_window_event(self) -> WindowEvent<'static> {
+        // Replayed events don't come from a device
+        let device_id = unsafe { DeviceId::dummy() };
+
+        match self {
+            Self::CursorMoved { x, y } => WindowEvent::CursorMoved {
+                device_id,
+                position: (x, y).into(),
+                modifiers: Default::default(),
+            },
+            Self::CursorEntered => WindowEvent::CursorEntered { device_id },
+            Self::CursorLeft => WindowEvent::CursorLeft { device_id },
+            Self::MouseInput { state, button } => WindowEvent::MouseInput {
+                device_id,
+                state,
+                button,
+                modifiers: Default::default(),
+            },
+            Self::MouseWheel { delta, phase } => WindowEvent::MouseWheel {
+                device_id,
+                delta,
+                phase,
+                modifiers: Default::default(),
+            },
+            Self::KeyboardInput {
+                scancode,
+                state,
+                virtual_keycode,
+            } => WindowEvent::KeyboardInput {
+                device_id,
+                input: KeyboardInput {
+                    scancode,
+                    state,
+                    virtual_keycode,
+                    modifiers: Default::default(),
+                },
+                is_synthetic: true,
+            },
+            Self::ReceivedCharacter(c) => WindowEvent::ReceivedCharacter(c),
+            Self::ModifiersChanged(modifiers) => WindowEvent::ModifiersChanged(modifiers),
+            Self::Touch { id, phase, x, y } => WindowEvent::Touch(Touch {
+                device_id,
+                phase,
+                location: (x, y).into(),
+                force: None,
+                id,
+            }),
+        }
+    }
+}
+
+/// Returns the events of a left click at `position`
+///
+/// # Arguments
+///
+/// * `position` - Position of the click, in the UI space
+pub fn click(position: Vec2) -> [RecordedEvent; 3] {
+    let mouse_input = |state| RecordedEvent::MouseInput {
+        state,
+        button: MouseButton::Left,
+    };
+
+    [
+        RecordedEvent::CursorMoved {
+            x: position.x as f64,
+            y: position.y as f64,
+        },
+        mouse_input(ElementState::Pressed),
+        mouse_input(ElementState::Released),
+    ]
+}
+
+/// Returns the events of a key press and its release
+///
+/// # Arguments
+///
+/// * `keycode` - Key pressed
+pub fn key_press(keycode: VirtualKeyCode) -> [RecordedEvent; 2] {
+    [ElementState::Pressed, ElementState::Released].map(|state| RecordedEvent::KeyboardInput {
+        scancode: 0,
+        state,
+        virtual_keycode: Some(keycode),
+    })
+}
+
+/// Returns the events of typing `text`, one received character per character
+pub fn typing(text: &str) -> Vec<RecordedEvent> {
+    text.chars().map(RecordedEvent::ReceivedCharacter).collect()
+}
+
+/// Inputs and the time they were received at, in seconds since the recording started
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    pub events: Vec<(f32, RecordedEvent)>,
+}
+
+impl Recording {
+    pub fn from_ron(source: &str) -> Result<Self> {
+        Ok(ron::from_str(source)?)
+    }
+
+    pub fn to_ron(&self) -> Result<String> {
+        Ok(ron::ser::to_string_pretty(self, Default::default())?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::from_ron(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        Ok(std::fs::write(path, self.to_ron()?)?)
+    }
+
+    /// Returns the time of the last event, in seconds
+    pub fn duration(&self) -> f32 {
+        self.events.last().map_or(0., |(time, _)| *time)
+    }
+}
+
+/// Records the inputs as they are received
+#[derive(Debug, Default)]
+pub struct Recorder {
+    /// Sum of the frame times since the recording started, in seconds
+    time: f32,
+    recording: Recording,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `event` if it's an input
+    pub fn record(&mut self, event: &WindowEvent) {
+        if let Some(event) = RecordedEvent::from_window_event(event) {
+            self.recording.events.push((self.time, event));
+        }
+    }
+
+    /// Advance the recording time, the inputs received until the next call are stamped with it
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Time elapsed since the last frame, in seconds
+    pub fn tick(&mut self, dt: f32) {
+        self.time += dt;
+    }
+
+    pub fn finish(self) -> Recording {
+        self.recording
+    }
+}
+
+/// Replays a recording, following the time elapsed between the frames
+#[derive(Debug)]
+pub struct Playback {
+    recording: Recording,
+    elapsed: f32,
+    /// Index of the next event to replay
+    next: usize,
+}
+
+impl Playback {
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            recording,
+            elapsed: 0.,
+            next: 0,
+        }
+    }
+
+    /// Advance the playback and returns the events due, in their order
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Time elapsed since the last frame, in seconds
+    pub fn advance(&mut self, dt: f32) -> Vec<RecordedEvent> {
+        self.elapsed += dt;
+
+        let due = self.recording.events[self.next..]
+            .iter()
+            .take_while(|(time, _)| *time <= self.elapsed)
+            .map(|(_, event)| *event)
+            .collect::<Vec<_>>();
+        self.next += due.len();
+
+        due
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next == self.recording.events.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+    use winit::event::VirtualKeyCode;
+
+    use super::{click, key_press, typing, Playback, RecordedEvent, Recorder, Recording};
+
+    #[test]
+    fn events_survive_a_round_trip() {
+        let mut events = click(Vec2::new(10., 20.)).to_vec();
+        events.extend(key_press(VirtualKeyCode::Back));
+        events.extend(typing("hi"));
+
+        for event in &events {
+            let window_event = event.to_window_event();
+            assert_eq!(
+                RecordedEvent::from_window_event(&window_event),
+                Some(*event)
+            );
+        }
+
+        let recording = Recording {
+            events: events.into_iter().map(|event| (0.5, event)).collect(),
+        };
+        let source = recording.to_ron().unwrap();
+        assert_eq!(Recording::from_ron(&source).unwrap(), recording);
+    }
+
+    #[test]
+    fn playback_follows_the_recorded_times() {
+        let [moved, pressed, released] = click(Vec2::ZERO);
+        let mut playback = Playback::new(Recording {
+            events: vec![(0., moved), (0.1, pressed), (0.25, released)],
+        });
+
+        assert_eq!(playback.advance(0.05), vec![moved]);
+        assert_eq!(playback.advance(0.1), vec![pressed]);
+        assert!(playback.advance(0.05).is_empty());
+        assert_eq!(playback.advance(0.1), vec![released]);
+        assert!(playback.is_done());
+    }
+
+    #[test]
+    fn inputs_replay_on_the_frame_they_were_received_on() {
+        let frame_times = [0.016, 0.033, 0.016, 0.05];
+        let [moved, pressed, released] = click(Vec2::new(5., 5.));
+        // Inputs received before each frame
+        let frames = [vec![moved], vec![], vec![pressed, released], vec![moved]];
+
+        let mut recorder = Recorder::new();
+        for (dt, inputs) in frame_times.iter().zip(&frames) {
+            inputs
+                .iter()
+                .for_each(|input| recorder.record(&input.to_window_event()));
+            recorder.tick(*dt);
+        }
+
+        let mut playback = Playback::new(recorder.finish());
+        let mut replayed = vec![playback.advance(0.)];
+        replayed.extend(frame_times[..3].iter().map(|dt| playback.advance(*dt)));
+        assert_eq!(replayed, frames);
+    }
+}
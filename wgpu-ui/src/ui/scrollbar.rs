@@ -0,0 +1,407 @@
+//! Scrollbars of the scrollable widgets, either classic ones beside the content or overlay ones
+//! fading over it while idle, see [`ScrollbarStyle`]
+
+use glam::Vec2;
+use wgpu::RenderPass;
+use winit::event::{ElementState, MouseButton, WindowEvent};
+
+use super::theme::ScrollbarStyle;
+use crate::graphics::{
+    color::Color,
+    polyline::{LineCap, Polyline},
+    shape::{RectangleShape, Shape},
+    Drawable, Transformable,
+};
+use crate::math::Rect;
+use crate::Ctx;
+
+/// Width of classic scrollbars, taken from the width of the content
+pub const CLASSIC_WIDTH: f32 = 12.;
+/// Width of overlay scrollbars away from the cursor
+const OVERLAY_WIDTH: f32 = 4.;
+/// Width of overlay scrollbars under the cursor
+const OVERLAY_HOVERED_WIDTH: f32 = 10.;
+/// Space between overlay scrollbars and the edges of the content
+const OVERLAY_MARGIN: f32 = 2.;
+/// Shortest thumb, so it can still be grabbed over long contents
+const MIN_THUMB_LENGTH: f32 = 24.;
+/// Time overlay scrollbars stay visible after the last scroll, in seconds
+const FADE_DELAY: f32 = 1.;
+/// Time overlay scrollbars take to disappear, in seconds
+const FADE_DURATION: f32 = 0.3;
+/// Fraction of the remaining width overlay scrollbars widen or narrow by per second
+const RESIZE_SPEED: f32 = 12.;
+
+/// Returns the thumb of a scrollbar
+///
+/// # Arguments
+///
+/// * `track` - Area the thumb moves in
+/// * `visible` - Fraction of the content visible, from 0 to 1
+/// * `offset` - Fraction of the content scrolled past, from 0 to `1 - visible`
+pub fn thumb_rect(track: Rect, visible: f32, offset: f32) -> Rect {
+    let length = (track.height * visible)
+        .max(MIN_THUMB_LENGTH)
+        .min(track.height);
+    let scrollable = 1. - visible;
+    let progress = if scrollable > 0. {
+        (offset / scrollable).clamp(0., 1.)
+    } else {
+        0.
+    };
+
+    Rect {
+        x: track.x,
+        y: track.y + (track.height - length) * progress,
+        width: track.width,
+        height: length,
+    }
+}
+
+/// Returns the opacity of an overlay scrollbar
+///
+/// # Arguments
+///
+/// * `idle` - Time since the last scroll, in seconds
+pub fn overlay_opacity(idle: f32) -> f32 {
+    (1. - (idle - FADE_DELAY) / FADE_DURATION).clamp(0., 1.)
+}
+
+/// Vertical scrollbar along the right edge of a scrollable widget, drawn by the widget
+pub struct Scrollbar {
+    style: ScrollbarStyle,
+    /// Only drawn by classic scrollbars
+    track: RectangleShape,
+    /// Rounded by the caps of the line
+    thumb: Polyline,
+    /// Area of the scrolled content
+    bounds: Rect,
+    /// Fraction of the content visible
+    visible: f32,
+    /// Fraction of the content scrolled past
+    offset: f32,
+    /// Time since the last scroll, in seconds
+    idle: f32,
+    hovered: bool,
+    /// Current width of an overlay scrollbar
+    width: f32,
+    /// Distance between the top of the thumb and the cursor while the thumb is dragged
+    grab: Option<f32>,
+    mouse_position: Vec2,
+    opacity: f32,
+}
+
+impl Scrollbar {
+    /// Create a scrollbar for content which fits, hidden until the content overflows
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `style` - Classic or overlay, usually the one of the theme
+    /// * `label` - Debug name of the GPU resources, e.g. `ListView/scrollbar`
+    pub fn new(context: Ctx, style: ScrollbarStyle, label: &str) -> Self {
+        let mut track =
+            RectangleShape::new_with_label(context.clone(), Vec2::ZERO, &format!("{label}/track"));
+        track.set_fill_color(Color::from((45, 45, 45)));
+
+        let mut thumb = Polyline::new(context, Vec::new(), OVERLAY_WIDTH);
+        thumb.set_cap(LineCap::Round);
+        thumb.set_color(match style {
+            ScrollbarStyle::Classic => Color::from((110, 110, 110)),
+            ScrollbarStyle::Overlay => Color::from((170, 170, 170)),
+        });
+
+        Self {
+            style,
+            track,
+            thumb,
+            bounds: Rect::default(),
+            visible: 1.,
+            offset: 0.,
+            // Overlay scrollbars only show up once scrolled
+            idle: f32::INFINITY,
+            hovered: false,
+            width: OVERLAY_WIDTH,
+            grab: None,
+            mouse_position: Vec2::ZERO,
+            opacity: 1.,
+        }
+    }
+
+    pub fn style(&self) -> ScrollbarStyle {
+        self.style
+    }
+
+    /// Returns `true` if the content overflows, scrollbars of content which fits aren't drawn
+    pub fn is_scrollable(&self) -> bool {
+        self.visible < 1.
+    }
+
+    /// Returns the width taken from the content, only classic scrollbars of overflowing content
+    /// take room
+    pub fn reserved_width(&self) -> f32 {
+        match self.style {
+            ScrollbarStyle::Classic if self.is_scrollable() => CLASSIC_WIDTH,
+            _ => 0.,
+        }
+    }
+
+    /// Returns `true` if `point` is over the scrollbar, overlay scrollbars are reached at their
+    /// hovered width
+    pub fn contains(&self, point: Vec2) -> bool {
+        self.is_scrollable() && self.hit_area().contains(point)
+    }
+
+    /// Place the scrollbar along the right edge of `bounds`
+    ///
+    /// # Arguments
+    ///
+    /// * `bounds` - Area of the scrolled content, classic scrollbars included
+    pub fn set_bounds(&mut self, bounds: Rect) {
+        self.bounds = bounds;
+
+        self.update();
+    }
+
+    /// Move the thumb, overlay scrollbars show up when the offset changes
+    ///
+    /// # Arguments
+    ///
+    /// * `visible` - Fraction of the content visible, 1 or more when it fits
+    /// * `offset` - Fraction of the content scrolled past
+    pub fn set_range(&mut self, visible: f32, offset: f32) {
+        let visible = visible.min(1.);
+        if offset != self.offset {
+            self.idle = 0.;
+        }
+        if (visible, offset) == (self.visible, self.offset) {
+            return;
+        }
+
+        self.visible = visible;
+        self.offset = offset;
+        self.update();
+        self.apply_opacity();
+    }
+
+    /// Set the opacity inherited from the scrolled widget
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+
+        self.apply_opacity();
+    }
+
+    /// Hover and drag the thumb, returns the offset the content has to be scrolled to while the
+    /// thumb is dragged
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - Event received by the scrolled widget
+    pub fn process_events(&mut self, event: &WindowEvent) -> Option<f32> {
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.mouse_position = Vec2::new(position.x as f32, position.y as f32);
+                self.hovered = self.contains(self.mouse_position);
+
+                self.dragged_offset()
+            }
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => {
+                if *state == ElementState::Released || !self.contains(self.mouse_position) {
+                    self.grab = None;
+                    return None;
+                }
+
+                let thumb = thumb_rect(self.track_rect(), self.visible, self.offset);
+                if (thumb.y..=thumb.y + thumb.height).contains(&self.mouse_position.y) {
+                    self.grab = Some(self.mouse_position.y - thumb.y);
+                    None
+                } else {
+                    // Pressed on the track, the middle of the thumb jumps under the cursor
+                    self.grab = Some(thumb.height / 2.);
+                    self.dragged_offset()
+                }
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.hovered = false;
+
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Fade and widen overlay scrollbars
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Time elapsed since the last frame, in seconds
+    pub fn tick(&mut self, dt: f32) {
+        if self.style != ScrollbarStyle::Overlay || !self.is_scrollable() {
+            return;
+        }
+
+        let active = self.hovered || self.grab.is_some();
+        let fade = overlay_opacity(self.idle);
+        if active {
+            self.idle = 0.;
+        } else {
+            self.idle += dt;
+        }
+        if overlay_opacity(self.idle) != fade {
+            self.apply_opacity();
+        }
+
+        let target = if active {
+            OVERLAY_HOVERED_WIDTH
+        } else {
+            OVERLAY_WIDTH
+        };
+        if self.width != target {
+            self.width += (target - self.width) * (RESIZE_SPEED * dt).min(1.);
+            if (self.width - target).abs() < 0.1 {
+                self.width = target;
+            }
+            self.update();
+        }
+    }
+
+    /// Returns the offset putting the grabbed point of the thumb under the cursor
+    fn dragged_offset(&self) -> Option<f32> {
+        let grab = self.grab?;
+        let track = self.track_rect();
+        let thumb = thumb_rect(track, self.visible, self.offset);
+        let travel = track.height - thumb.height;
+        if travel <= 0. {
+            return None;
+        }
+
+        let progress = (self.mouse_position.y - grab - track.y) / travel;
+        Some(progress.clamp(0., 1.) * (1. - self.visible))
+    }
+
+    /// Returns the area the thumb moves in
+    fn track_rect(&self) -> Rect {
+        let right = self.bounds.x + self.bounds.width;
+
+        match self.style {
+            ScrollbarStyle::Classic => Rect {
+                x: right - CLASSIC_WIDTH,
+                y: self.bounds.y,
+                width: CLASSIC_WIDTH,
+                height: self.bounds.height,
+            },
+            ScrollbarStyle::Overlay => Rect {
+                x: right - OVERLAY_MARGIN - self.width,
+                y: self.bounds.y + OVERLAY_MARGIN,
+                width: self.width,
+                height: self.bounds.height - 2. * OVERLAY_MARGIN,
+            },
+        }
+    }
+
+    /// Returns the area reacting to the cursor
+    fn hit_area(&self) -> Rect {
+        match self.style {
+            ScrollbarStyle::Classic => self.track_rect(),
+            ScrollbarStyle::Overlay => {
+                let width = OVERLAY_HOVERED_WIDTH + 2. * OVERLAY_MARGIN;
+
+                Rect {
+                    x: self.bounds.x + self.bounds.width - width,
+                    width,
+                    ..self.bounds
+                }
+            }
+        }
+    }
+
+    fn update(&mut self) {
+        let track = self.track_rect();
+        self.track.set_position(track.position());
+        self.track.set_size(Vec2::new(track.width, track.height));
+
+        let thumb = thumb_rect(track, self.visible, self.offset);
+        let thickness = match self.style {
+            // Inset in the track
+            ScrollbarStyle::Classic => track.width - 4.,
+            ScrollbarStyle::Overlay => track.width,
+        };
+        // The round caps extend the line by half its thickness
+        let radius = (thickness / 2.).min(thumb.height / 2.);
+        self.thumb
+            .set_position(Vec2::new(thumb.x + track.width / 2., thumb.y));
+        self.thumb.set_thickness(thickness);
+        self.thumb.set_points(vec![
+            Vec2::new(0., radius),
+            Vec2::new(0., (thumb.height - radius).max(radius + 0.01)),
+        ]);
+    }
+
+    fn apply_opacity(&mut self) {
+        self.track.set_opacity(self.opacity);
+
+        let fade = match self.style {
+            ScrollbarStyle::Classic => 1.,
+            ScrollbarStyle::Overlay => overlay_opacity(self.idle),
+        };
+        self.thumb.set_opacity(self.opacity * fade);
+    }
+}
+
+impl Drawable for Scrollbar {
+    fn draw<'a>(&'a mut self, render_pass: &mut RenderPass<'a>) {
+        if !self.is_scrollable() {
+            return;
+        }
+
+        match self.style {
+            ScrollbarStyle::Classic => {
+                self.track.draw(render_pass);
+                self.thumb.draw(render_pass);
+            }
+            ScrollbarStyle::Overlay if overlay_opacity(self.idle) > 0. => {
+                self.thumb.draw(render_pass)
+            }
+            ScrollbarStyle::Overlay => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{overlay_opacity, thumb_rect, FADE_DELAY, FADE_DURATION, MIN_THUMB_LENGTH};
+    use crate::math::Rect;
+
+    #[test]
+    fn thumbs_show_the_visible_part_of_the_content() {
+        let track = Rect {
+            x: 90.,
+            y: 0.,
+            width: 10.,
+            height: 200.,
+        };
+
+        let top = thumb_rect(track, 0.25, 0.);
+        assert_eq!((top.y, top.height), (0., 50.));
+        let bottom = thumb_rect(track, 0.25, 0.75);
+        assert_eq!((bottom.y, bottom.height), (150., 50.));
+
+        // Long contents keep a thumb which can be grabbed
+        let long = thumb_rect(track, 0.001, 0.999);
+        assert_eq!(long.height, MIN_THUMB_LENGTH);
+        assert_eq!(long.y + long.height, 200.);
+    }
+
+    #[test]
+    fn overlay_scrollbars_fade_once_idle() {
+        assert_eq!(overlay_opacity(0.), 1.);
+        assert_eq!(overlay_opacity(FADE_DELAY), 1.);
+        assert!((overlay_opacity(FADE_DELAY + FADE_DURATION / 2.) - 0.5).abs() < 1e-4);
+        assert_eq!(overlay_opacity(FADE_DELAY + FADE_DURATION + 0.01), 0.);
+        assert_eq!(overlay_opacity(f32::INFINITY), 0.);
+    }
+}
@@ -0,0 +1,160 @@
+use std::f32::consts::TAU;
+
+use glam::Vec2;
+use wgpu::RenderPass;
+use winit::event::WindowEvent;
+use winit::window::CursorIcon;
+
+use super::{Opacity, Uid, Widget};
+use crate::accessibility::{AccessNode, Role};
+use crate::graphics::shape::{RectangleShape, Shape};
+use crate::graphics::{
+    color::{Color, WHITE},
+    Drawable, Transformable,
+};
+use crate::Ctx;
+
+const DOT_COUNT: usize = 8;
+/// Time spent highlighting each dot, in seconds
+const STEP_DURATION: f32 = 0.1;
+
+/// Indeterminate progress indicator made of dots laid out on a circle
+pub struct Spinner {
+    dots: Vec<RectangleShape>,
+    position: Vec2,
+    size: Vec2,
+    radius: f32,
+    dot_size: f32,
+    elapsed: f32,
+    active: usize,
+    visible: bool,
+    uid: Uid,
+    opacity: Opacity,
+}
+
+impl Spinner {
+    pub fn new(context: Ctx, radius: f32) -> Self {
+        let dot_size = (radius / 3.).max(2.);
+        let dots = (0..DOT_COUNT)
+            .map(|i| {
+                RectangleShape::new_with_label(
+                    context.clone(),
+                    (dot_size, dot_size).into(),
+                    &format!("Spinner/dot{i}"),
+                )
+            })
+            .collect();
+
+        let mut spinner = Self {
+            dots,
+            position: Default::default(),
+            size: Vec2::splat(radius * 2. + dot_size),
+            radius,
+            dot_size,
+            elapsed: 0.,
+            active: 0,
+            visible: true,
+            uid: Uid::new(),
+            opacity: Opacity::default(),
+        };
+        spinner.update();
+        spinner.update_colors();
+
+        spinner
+    }
+
+    fn update_colors(&mut self) {
+        let active = self.active;
+
+        self.dots.iter_mut().enumerate().for_each(|(i, dot)| {
+            if i == active {
+                dot.set_fill_color(WHITE);
+            } else {
+                dot.set_fill_color(Color::from((90, 90, 90)));
+            }
+        });
+    }
+
+    fn apply_opacity(&mut self) {
+        let opacity = self.opacity.effective();
+        self.dots
+            .iter_mut()
+            .for_each(|dot| dot.set_opacity(opacity));
+    }
+}
+
+impl Transformable for Spinner {
+    fn position(&self) -> &Vec2 {
+        &self.position
+    }
+
+    fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+
+        self.update();
+    }
+}
+
+impl Widget for Spinner {
+    impl_widget_state!();
+    impl_widget_opacity!();
+
+    fn process_events(&mut self, _event: &WindowEvent) {}
+
+    fn update(&mut self) {
+        let center = self.position + Vec2::splat(self.radius);
+        let (radius, dot_size) = (self.radius, self.dot_size);
+
+        self.dots.iter_mut().enumerate().for_each(|(i, dot)| {
+            let angle = TAU * i as f32 / DOT_COUNT as f32;
+            dot.set_size((dot_size, dot_size).into());
+            dot.set_position(center + Vec2::new(angle.cos(), angle.sin()) * radius);
+        });
+    }
+
+    fn tick(&mut self, dt: f32) {
+        self.elapsed += dt;
+
+        if self.elapsed < STEP_DURATION {
+            return;
+        }
+
+        self.active = (self.active + (self.elapsed / STEP_DURATION) as usize) % DOT_COUNT;
+        self.elapsed %= STEP_DURATION;
+        self.update_colors();
+    }
+
+    fn size(&self) -> &Vec2 {
+        &self.size
+    }
+
+    fn debug_name(&self) -> String {
+        String::from("Spinner")
+    }
+
+    fn accessibility(&self) -> AccessNode {
+        AccessNode::new(Role::ProgressIndicator, self.bounds()).with_name("Loading")
+    }
+
+    fn cursor(&self, _point: Vec2) -> Option<CursorIcon> {
+        Some(CursorIcon::Progress)
+    }
+
+    fn set_size(&mut self, size: Vec2) {
+        self.size = size;
+        self.dot_size = (size.x.min(size.y) / 7.).max(2.);
+        self.radius = (size.x.min(size.y) - self.dot_size) / 2.;
+
+        self.update();
+    }
+}
+
+impl Drawable for Spinner {
+    fn draw<'b>(&'b mut self, render_pass: &mut RenderPass<'b>) {
+        if !self.visible {
+            return;
+        }
+
+        self.dots.iter_mut().for_each(|dot| dot.draw(render_pass));
+    }
+}
@@ -0,0 +1,218 @@
+//! Observable values widgets are bound to, instead of polling their events
+
+use std::sync::{Arc, Mutex};
+
+type Observer<T> = Box<dyn FnMut(&T) + Send>;
+
+/// Handle of an observer, to remove it with [`State::unobserve`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObserverId(u64);
+
+struct Shared<T> {
+    value: T,
+    /// Incremented on each change, bindings compare it with the last one they applied
+    version: u64,
+    next_id: u64,
+    observers: Vec<(ObserverId, Observer<T>)>,
+}
+
+/// Value shared by the application and the widgets bound to it
+///
+/// Clones share the same value. Widgets apply the changes on their next frame, and set the
+/// value when the user edits it, notifying the observers.
+pub struct State<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+}
+
+impl<T> Clone for State<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for State<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let shared = self.shared.lock().unwrap();
+
+        f.debug_struct("State")
+            .field("value", &shared.value)
+            .field("version", &shared.version)
+            .finish()
+    }
+}
+
+impl<T: Clone + PartialEq> State<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            shared: Arc::new(Mutex::new(Shared {
+                value,
+                version: 0,
+                next_id: 0,
+                observers: Vec::new(),
+            })),
+        }
+    }
+
+    /// Returns a copy of the value
+    pub fn get(&self) -> T {
+        self.shared.lock().unwrap().value.clone()
+    }
+
+    /// Returns the number of changes of the value
+    pub fn version(&self) -> u64 {
+        self.shared.lock().unwrap().version
+    }
+
+    /// Change the value and notify the observers, nothing happens if the value is the same
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - New value
+    pub fn set(&self, value: T) {
+        self.replace(value);
+    }
+
+    /// Same as [`State::set`], returns the version of the value
+    fn replace(&self, value: T) -> u64 {
+        let mut shared = self.shared.lock().unwrap();
+        if shared.value == value {
+            return shared.version;
+        }
+
+        shared.value = value.clone();
+        shared.version += 1;
+        let version = shared.version;
+        // Observers run unlocked, they may read or set the state
+        let mut observers = std::mem::take(&mut shared.observers);
+        drop(shared);
+
+        observers
+            .iter_mut()
+            .for_each(|(_, observer)| observer(&value));
+
+        let mut shared = self.shared.lock().unwrap();
+        // Observers added meanwhile come after the previous ones
+        observers.append(&mut shared.observers);
+        shared.observers = observers;
+
+        version
+    }
+
+    /// Change the value in place, see [`State::set`]
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - Function modifying a copy of the value
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        let mut value = self.get();
+        f(&mut value);
+
+        self.set(value);
+    }
+
+    /// Call `observer` with the new value after each change
+    pub fn observe(&self, observer: impl FnMut(&T) + Send + 'static) -> ObserverId {
+        let mut shared = self.shared.lock().unwrap();
+        shared.next_id += 1;
+        let id = ObserverId(shared.next_id);
+        shared.observers.push((id, Box::new(observer)));
+
+        id
+    }
+
+    /// Remove an observer, returns `false` if it was already removed
+    ///
+    /// Observers can't remove themselves while they run.
+    pub fn unobserve(&self, id: ObserverId) -> bool {
+        let mut shared = self.shared.lock().unwrap();
+        let count = shared.observers.len();
+        shared.observers.retain(|(other, _)| *other != id);
+
+        shared.observers.len() < count
+    }
+}
+
+/// State bound to a widget property, remembering the last version the widget displays
+#[derive(Debug)]
+pub struct Binding<T> {
+    state: State<T>,
+    /// `None` until the value is first applied
+    version: Option<u64>,
+}
+
+impl<T: Clone + PartialEq> Binding<T> {
+    /// Bind to `state`, its current value is returned by the first [`Binding::changed`]
+    pub fn new(state: State<T>) -> Self {
+        Self {
+            state,
+            version: None,
+        }
+    }
+
+    /// Returns the value if it changed since the last call, e.g. from each `tick` of the widget
+    pub fn changed(&mut self) -> Option<T> {
+        let (value, version) = {
+            let shared = self.state.shared.lock().unwrap();
+            (shared.value.clone(), shared.version)
+        };
+
+        if self.version == Some(version) {
+            return None;
+        }
+        self.version = Some(version);
+
+        Some(value)
+    }
+
+    /// Set the value from the widget, which already displays it
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Value entered by the user
+    pub fn set(&mut self, value: T) {
+        self.version = Some(self.state.replace(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::{Binding, State};
+
+    #[test]
+    fn observers_are_notified_of_changes() {
+        let state = State::new(1);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let shared = seen.clone();
+        let id = state.observe(move |value| shared.lock().unwrap().push(*value));
+
+        state.set(2);
+        state.set(2);
+        state.update(|value| *value += 1);
+        assert!(state.unobserve(id));
+        state.set(4);
+
+        assert_eq!(*seen.lock().unwrap(), vec![2, 3]);
+        assert_eq!(state.version(), 3);
+    }
+
+    #[test]
+    fn bindings_apply_each_change_once() {
+        let state = State::new(String::from("a"));
+        let mut binding = Binding::new(state.clone());
+
+        assert_eq!(binding.changed().as_deref(), Some("a"));
+        assert_eq!(binding.changed(), None);
+
+        state.set(String::from("b"));
+        assert_eq!(binding.changed().as_deref(), Some("b"));
+
+        // Edits of the widget aren't applied back to it
+        binding.set(String::from("c"));
+        assert_eq!(binding.changed(), None);
+        assert_eq!(state.get(), "c");
+    }
+}
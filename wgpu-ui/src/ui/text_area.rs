@@ -0,0 +1,1133 @@
+//! Multi-line editable text, stored in a pluggable [`TextBuffer`] so that large files are edited
+//! smoothly
+
+use std::ops::Range;
+
+use glam::Vec2;
+use rusttype::{point, Font, Scale};
+use wgpu::RenderPass;
+use winit::event::{
+    ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta, VirtualKeyCode,
+    WindowEvent,
+};
+use winit::window::CursorIcon;
+
+use super::{
+    highlight::{self, HighlightCache, Highlighter, Highlighting},
+    scrollbar::Scrollbar,
+    text_buffer::{Mark, MarkId, Marks, RopeBuffer, TextBuffer},
+    EventQueue, Opacity, Uid, Widget, WidgetEvent,
+};
+use crate::accessibility::{AccessNode, Role};
+use crate::graphics::polyline::{LineCap, Polyline};
+use crate::graphics::shape::{RectangleShape, Shape};
+use crate::graphics::text::{Text, TextSpan};
+use crate::graphics::{
+    color::{Color, WHITE},
+    Drawable, Transformable,
+};
+use crate::{Ctx, ASSETS};
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum TextAreaEvent {
+    Changed,
+}
+
+impl WidgetEvent for TextAreaEvent {}
+
+/// Time the caret stays shown then hidden, in seconds
+const CARET_BLINK: f32 = 0.5;
+/// Space between the edges and the text, in pixels
+const PADDING: f32 = 6.;
+/// Height of a line relative to the character size
+const LINE_SPACING: f32 = 1.25;
+/// Spaces inserted by the tab key
+const TAB: &str = "    ";
+/// Width of the column of the line markers, left of the line numbers
+const MARKER_COLUMN: f32 = 16.;
+/// Diameter of the line markers
+const MARKER_SIZE: f32 = 9.;
+
+/// Decides the marker of a line whose number was clicked, from its current marker, e.g. to
+/// toggle a breakpoint
+pub type MarkerCallback = Box<dyn FnMut(usize, Option<Color>) -> Option<Color>>;
+
+/// Returns the width of `text` on a single line
+fn advance(font: &Font, size: f32, text: &str) -> f32 {
+    font.layout(text, Scale::uniform(size), point(0., 0.))
+        .last()
+        .map_or(0., |glyph| {
+            glyph.position().x + glyph.unpositioned().h_metrics().advance_width
+        })
+}
+
+/// Returns the byte offset of `line` closest to `x` pixels from its start
+fn column_at(font: &Font, size: f32, line: &str, x: f32) -> usize {
+    let boundaries = line
+        .char_indices()
+        .map(|(at, _)| at)
+        .chain(std::iter::once(line.len()));
+
+    boundaries
+        .map(|at| (at, (advance(font, size, &line[..at]) - x).abs()))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map_or(0, |(at, _)| at)
+}
+
+/// Returns the number of digits of the largest line number, at least two so that the gutter
+/// doesn't widen on the tenth line
+fn number_digits(lines: usize) -> usize {
+    (lines.max(1).ilog10() as usize + 1).max(2)
+}
+
+/// Returns the caret moved by `lines` lines, on the same character column or the end of a
+/// shorter line
+///
+/// # Arguments
+///
+/// * `buffer` - Edited text
+/// * `caret` - Byte offset of the caret
+/// * `lines` - Lines to move by, positive downward
+fn move_vertically(buffer: &dyn TextBuffer, caret: usize, lines: isize) -> usize {
+    let line = buffer.byte_to_line(caret);
+    let start = buffer.line_to_byte(line);
+    let column = buffer.slice(start..caret).chars().count();
+
+    let target = line
+        .saturating_add_signed(lines)
+        .min(buffer.len_lines() - 1);
+    let text = buffer.line(target);
+    let offset = text
+        .char_indices()
+        .nth(column)
+        .map_or(text.len(), |(at, _)| at);
+
+    buffer.line_to_byte(target) + offset
+}
+
+/// Returns the offset of the character before `caret`, or after it when `forward`, line breaks
+/// being a single character
+fn step(buffer: &dyn TextBuffer, caret: usize, forward: bool) -> usize {
+    let line = buffer.byte_to_line(caret);
+
+    if forward {
+        let rest = buffer.slice(caret..buffer.line_to_byte(line + 1));
+        if rest.starts_with("\r\n") {
+            return caret + 2;
+        }
+
+        caret + rest.chars().next().map_or(0, char::len_utf8)
+    } else {
+        let before = buffer.slice(buffer.line_to_byte(line.saturating_sub(1))..caret);
+        if before.ends_with("\r\n") {
+            return caret - 2;
+        }
+
+        caret - before.chars().next_back().map_or(0, char::len_utf8)
+    }
+}
+
+/// Column left of the text showing the line numbers and markers
+struct Gutter {
+    background: RectangleShape,
+    /// One per visible line, like the lines of the text
+    numbers: Vec<Text<'static>>,
+    /// Dots of the markers of the visible lines, recycled
+    dots: Vec<Polyline>,
+    shown_dots: usize,
+    width: f32,
+}
+
+impl Gutter {
+    fn new(context: Ctx) -> Self {
+        let mut background = RectangleShape::new_with_label(context, Vec2::ZERO, "TextArea/gutter");
+        background.set_fill_color(Color::from((24, 24, 24)));
+
+        Self {
+            background,
+            numbers: Vec::new(),
+            dots: Vec::new(),
+            shown_dots: 0,
+            width: 0.,
+        }
+    }
+
+    fn set_opacity(&mut self, opacity: f32) {
+        self.background.set_opacity(opacity);
+        self.numbers
+            .iter_mut()
+            .for_each(|number| number.set_opacity(opacity));
+        self.dots
+            .iter_mut()
+            .for_each(|dot| dot.set_opacity(opacity));
+    }
+}
+
+impl Drawable for Gutter {
+    fn draw<'b>(&'b mut self, render_pass: &mut RenderPass<'b>) {
+        self.background.draw(render_pass);
+        for number in &mut self.numbers {
+            number.draw(render_pass);
+        }
+        for dot in &mut self.dots[..self.shown_dots] {
+            dot.draw(render_pass);
+        }
+    }
+}
+
+/// Multi-line editable text field, scrolled by whole lines
+///
+/// Only the visible lines are laid out, and the text is edited through a [`TextBuffer`], a
+/// [`RopeBuffer`] unless another backend is given to [`TextArea::with_buffer`].
+pub struct TextArea {
+    context: Ctx,
+    background: RectangleShape,
+    buffer: Box<dyn TextBuffer>,
+    /// Highlighted ranges and their color
+    marks: Marks<Color>,
+    /// Empty marks at the start of the marked lines, so that they follow the edits
+    line_markers: Marks<Color>,
+    on_marker_click: Option<MarkerCallback>,
+    gutter: Option<Gutter>,
+    /// Drawn behind the line of the caret
+    current_line: RectangleShape,
+    current_line_shown: bool,
+    /// Column of the ruler and the line drawn there
+    ruler: Option<(usize, RectangleShape)>,
+    /// Styles of the syntax, kept for the lines displayed so far
+    highlighting: Option<Box<dyn Highlighting>>,
+    font: &'static Font<'static>,
+    character_size: f32,
+    /// One per visible line, displaying line `first + index`
+    lines: Vec<Text<'static>>,
+    /// Drawn behind the marked text of the visible lines, recycled
+    mark_shapes: Vec<RectangleShape>,
+    shown_marks: usize,
+    /// First displayed line
+    first: usize,
+    /// Scrolling not amounting to a whole line yet, in lines
+    scrolled: f32,
+    scrollbar: Scrollbar,
+    /// Byte offset of the caret
+    caret: usize,
+    /// Allocated while the area is mounted
+    caret_shape: Option<RectangleShape>,
+    caret_visible: bool,
+    caret_elapsed: f32,
+    position: Vec2,
+    size: Vec2,
+    mouse_position: Vec2,
+    modifiers: ModifiersState,
+    focused: bool,
+    visible: bool,
+    uid: Uid,
+    opacity: Opacity,
+    events: EventQueue<TextAreaEvent>,
+}
+
+impl TextArea {
+    pub fn new(context: Ctx, size: Vec2) -> Self {
+        Self::with_buffer(context, size, Box::<RopeBuffer>::default())
+    }
+
+    /// Create an area editing the text of `buffer`
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `size` - Size of the area, the number of lines fitting in its height are laid out
+    /// * `buffer` - Storage of the text, e.g. a [`RopeBuffer`] or a `String`
+    pub fn with_buffer(context: Ctx, size: Vec2, buffer: Box<dyn TextBuffer>) -> Self {
+        let mut background =
+            RectangleShape::new_with_label(context.clone(), size, "TextArea/background");
+        background.set_fill_color(Color::from((30, 30, 30)));
+        let style = context.lock().unwrap().theme.scrollbars;
+        let scrollbar = Scrollbar::new(context.clone(), style, "TextArea/scrollbar");
+        let mut current_line =
+            RectangleShape::new_with_label(context.clone(), Vec2::ZERO, "TextArea/current-line");
+        current_line.set_fill_color(Color::from((42, 42, 42)));
+
+        let mut area = Self {
+            context,
+            background,
+            buffer,
+            marks: Marks::new(),
+            line_markers: Marks::new(),
+            on_marker_click: None,
+            gutter: None,
+            current_line,
+            current_line_shown: false,
+            ruler: None,
+            highlighting: None,
+            font: ASSETS.get_font("Roboto.ttf").unwrap(),
+            character_size: 16.,
+            lines: Vec::new(),
+            mark_shapes: Vec::new(),
+            shown_marks: 0,
+            first: 0,
+            scrolled: 0.,
+            scrollbar,
+            caret: 0,
+            caret_shape: None,
+            caret_visible: true,
+            caret_elapsed: 0.,
+            position: Default::default(),
+            size,
+            mouse_position: Default::default(),
+            modifiers: ModifiersState::empty(),
+            focused: false,
+            visible: true,
+            uid: Uid::new(),
+            opacity: Opacity::default(),
+            events: EventQueue::new(),
+        };
+        area.update();
+
+        area
+    }
+
+    /// Returns the storage of the text, edits go through [`TextArea::insert`] and
+    /// [`TextArea::delete`] so that the marks and the caret follow them
+    pub fn buffer(&self) -> &dyn TextBuffer {
+        self.buffer.as_ref()
+    }
+
+    /// Returns the whole text
+    pub fn text(&self) -> String {
+        self.buffer.text()
+    }
+
+    /// Replace the whole text, the marks are dropped
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - New content
+    pub fn set_text(&mut self, text: &str) {
+        self.marks.clear();
+        self.replace(0..self.buffer.len_bytes(), text);
+        self.set_caret(0);
+    }
+
+    /// Iterate over the lines, without their line break
+    pub fn lines(&self) -> impl Iterator<Item = std::borrow::Cow<'_, str>> {
+        self.buffer.lines()
+    }
+
+    /// Insert text, the caret and the marks after it move along
+    ///
+    /// # Arguments
+    ///
+    /// * `at` - Byte offset, on a character boundary
+    /// * `text` - Inserted text
+    pub fn insert(&mut self, at: usize, text: &str) {
+        self.replace(at..at, text);
+    }
+
+    /// Delete text, the caret and the marks after it move back
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - Byte offsets, on character boundaries
+    pub fn delete(&mut self, range: Range<usize>) {
+        self.replace(range, "");
+    }
+
+    /// Highlight a range of the text, returns the identifier to unmark it with
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - Byte offsets of the marked text, following the edits
+    /// * `color` - Drawn behind the text
+    pub fn mark(&mut self, range: Range<usize>, color: Color) -> MarkId {
+        let id = self.marks.add(range, color);
+        self.update();
+
+        id
+    }
+
+    pub fn unmark(&mut self, id: MarkId) -> Option<Mark<Color>> {
+        let mark = self.marks.remove(id);
+        self.update();
+
+        mark
+    }
+
+    pub fn marks(&self) -> &Marks<Color> {
+        &self.marks
+    }
+
+    /// Highlight the syntax of the text, the lines are highlighted as they are displayed and
+    /// again once edited
+    ///
+    /// # Arguments
+    ///
+    /// * `highlighter` - Maps the bytes of each line to styles, e.g. on top of syntect
+    pub fn set_highlighter(&mut self, highlighter: impl Highlighter + 'static) {
+        self.highlighting = Some(Box::new(HighlightCache::new(highlighter)));
+
+        self.update();
+    }
+
+    /// Display the text in a single color again
+    pub fn clear_highlighter(&mut self) {
+        self.highlighting = None;
+
+        self.update();
+    }
+
+    /// Show the line numbers and markers left of the text
+    pub fn set_gutter(&mut self, visible: bool) {
+        self.gutter = visible.then(|| {
+            let mut gutter = Gutter::new(self.context.clone());
+            gutter.set_opacity(self.opacity.effective());
+
+            gutter
+        });
+
+        self.update();
+    }
+
+    /// Set the callback deciding the marker of a line whose number is clicked
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - Receives the line and its marker, returns its new marker
+    pub fn on_marker_click(
+        &mut self,
+        callback: impl FnMut(usize, Option<Color>) -> Option<Color> + 'static,
+    ) {
+        self.on_marker_click = Some(Box::new(callback));
+    }
+
+    /// Returns the marker of `line`, e.g. a breakpoint
+    pub fn line_marker(&self, line: usize) -> Option<Color> {
+        self.line_markers
+            .iter()
+            .find(|(_, mark)| self.buffer.byte_to_line(mark.range.start) == line)
+            .map(|(_, mark)| mark.data)
+    }
+
+    /// Returns the marked lines and their marker, in no particular order
+    pub fn line_markers(&self) -> impl Iterator<Item = (usize, Color)> + '_ {
+        self.line_markers
+            .iter()
+            .map(|(_, mark)| (self.buffer.byte_to_line(mark.range.start), mark.data))
+    }
+
+    /// Mark a line in the gutter, the marker follows the line as the text is edited
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - Marked line
+    /// * `marker` - Color of the marker, `None` to remove it
+    pub fn set_line_marker(&mut self, line: usize, marker: Option<Color>) {
+        let ids = self
+            .line_markers
+            .iter()
+            .filter(|(_, mark)| self.buffer.byte_to_line(mark.range.start) == line)
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        ids.into_iter().for_each(|id| {
+            self.line_markers.remove(id);
+        });
+
+        if let Some(color) = marker.filter(|_| line < self.buffer.len_lines()) {
+            let start = self.buffer.line_to_byte(line);
+            self.line_markers.add(start..start, color);
+        }
+
+        self.update();
+    }
+
+    /// Draw a vertical line after a column of characters, e.g. to keep lines short
+    ///
+    /// # Arguments
+    ///
+    /// * `column` - Number of digit widths left of the ruler, `None` to hide it
+    pub fn set_ruler(&mut self, column: Option<usize>) {
+        self.ruler = column.map(|column| {
+            let mut line =
+                RectangleShape::new_with_label(self.context.clone(), Vec2::ZERO, "TextArea/ruler");
+            line.set_fill_color(Color::from((60, 60, 60)));
+            line.set_opacity(self.opacity.effective());
+
+            (column, line)
+        });
+
+        self.update();
+    }
+
+    /// Returns the byte offset of the caret
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    /// Move the caret and scroll the least needed for it to be visible
+    ///
+    /// # Arguments
+    ///
+    /// * `at` - Byte offset, clamped to the text
+    pub fn set_caret(&mut self, at: usize) {
+        self.caret = at.min(self.buffer.len_bytes());
+        self.caret_visible = true;
+        self.caret_elapsed = 0.;
+
+        let line = self.buffer.byte_to_line(self.caret);
+        let capacity = self.capacity();
+        if line < self.first {
+            self.first = line;
+        } else if line >= self.first + capacity {
+            self.first = line + 1 - capacity;
+        }
+
+        self.update();
+    }
+
+    pub fn set_character_size(&mut self, character_size: f32) {
+        self.character_size = character_size;
+        self.lines.clear();
+
+        self.update();
+    }
+
+    pub fn focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+        self.caret_visible = true;
+        self.caret_elapsed = 0.;
+    }
+
+    /// Replace a range of the text, every edit goes through here
+    fn replace(&mut self, range: Range<usize>, text: &str) {
+        if range.is_empty() && text.is_empty() {
+            return;
+        }
+
+        let first = self.buffer.byte_to_line(range.start);
+        let removed = self.buffer.byte_to_line(range.end) - first + 1;
+
+        if !range.is_empty() {
+            self.buffer.delete(range.clone());
+            self.marks.deleted(range.clone());
+        }
+        self.buffer.insert(range.start, text);
+        self.marks.inserted(range.start, text.len());
+        if !range.is_empty() {
+            self.line_markers.deleted(range.clone());
+        }
+        self.line_markers.inserted(range.start, text.len());
+
+        if let Some(highlighting) = &mut self.highlighting {
+            let inserted = self.buffer.byte_to_line(range.start + text.len()) - first + 1;
+            highlighting.edited(first, removed, inserted);
+        }
+
+        if self.caret >= range.end {
+            self.caret = self.caret - range.len() + text.len();
+        } else if self.caret > range.start {
+            self.caret = range.start;
+        }
+        self.first = self.first.min(self.last_first());
+        self.events.push(TextAreaEvent::Changed);
+
+        self.update();
+    }
+
+    fn line_height(&self) -> f32 {
+        self.character_size * LINE_SPACING
+    }
+
+    /// Returns the number of lines fitting in the area
+    fn capacity(&self) -> usize {
+        (((self.size.y - 2. * PADDING) / self.line_height()) as usize).max(1)
+    }
+
+    /// Returns the first displayed line once scrolled to the end
+    fn last_first(&self) -> usize {
+        self.buffer.len_lines().saturating_sub(self.capacity())
+    }
+
+    /// Returns the width of the gutter, 0 without gutter
+    fn gutter_width(&self) -> f32 {
+        self.gutter.as_ref().map_or(0., |gutter| gutter.width)
+    }
+
+    /// Returns the position of the start of the visible line `row`
+    fn row_origin(&self, row: usize) -> Vec2 {
+        self.position
+            + Vec2::new(
+                self.gutter_width() + PADDING,
+                PADDING + row as f32 * self.line_height(),
+            )
+    }
+
+    /// Returns the line displayed at `point`, clamped to the text
+    fn line_at(&self, point: Vec2) -> usize {
+        let row = ((point.y - self.position.y - PADDING) / self.line_height()).max(0.) as usize;
+
+        (self.first + row).min(self.buffer.len_lines() - 1)
+    }
+
+    /// Returns the byte offset closest to `point`
+    fn offset_at(&self, point: Vec2) -> usize {
+        let line = self.line_at(point);
+        let text = self.buffer.line(line);
+        let x = point.x - self.row_origin(0).x;
+
+        self.buffer.line_to_byte(line) + column_at(self.font, self.character_size, &text, x)
+    }
+
+    /// Scroll by a number of lines, positive toward the end of the text
+    fn scroll(&mut self, lines: f32) {
+        self.scrolled += lines;
+        let whole = self.scrolled.trunc();
+        self.scrolled -= whole;
+
+        let first = (self.first as i64 + whole as i64).clamp(0, self.last_first() as i64) as usize;
+        if first != self.first {
+            self.first = first;
+            self.update();
+        }
+    }
+
+    fn paste(&mut self) {
+        let text = self.context.lock().unwrap().clipboard().get_text();
+
+        if let Some(text) = text {
+            let at = self.caret;
+            self.insert(at, &text);
+            self.set_caret(at + text.len());
+        }
+    }
+
+    fn process_key(&mut self, keycode: VirtualKeyCode) {
+        let buffer = self.buffer.as_ref();
+        let caret = self.caret;
+        let line = buffer.byte_to_line(caret);
+
+        match keycode {
+            VirtualKeyCode::V if self.modifiers.ctrl() => self.paste(),
+            VirtualKeyCode::Left => self.set_caret(step(buffer, caret, false)),
+            VirtualKeyCode::Right => self.set_caret(step(buffer, caret, true)),
+            VirtualKeyCode::Up => self.set_caret(move_vertically(buffer, caret, -1)),
+            VirtualKeyCode::Down => self.set_caret(move_vertically(buffer, caret, 1)),
+            VirtualKeyCode::PageUp => {
+                let lines = self.capacity() as isize;
+                self.set_caret(move_vertically(buffer, caret, -lines));
+            }
+            VirtualKeyCode::PageDown => {
+                let lines = self.capacity() as isize;
+                self.set_caret(move_vertically(buffer, caret, lines));
+            }
+            VirtualKeyCode::Home => self.set_caret(buffer.line_to_byte(line)),
+            VirtualKeyCode::End => {
+                let end = buffer.line_to_byte(line) + buffer.line(line).len();
+                self.set_caret(end);
+            }
+            VirtualKeyCode::Back => {
+                let start = step(buffer, caret, false);
+                self.delete(start..caret);
+                self.set_caret(start);
+            }
+            VirtualKeyCode::Delete => {
+                let end = step(buffer, caret, true);
+                self.delete(caret..end);
+            }
+            VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => self.type_text("\n"),
+            VirtualKeyCode::Tab => self.type_text(TAB),
+            _ => {}
+        }
+    }
+
+    /// Insert text at the caret and move the caret after it
+    fn type_text(&mut self, text: &str) {
+        let at = self.caret;
+        self.insert(at, text);
+        self.set_caret(at + text.len());
+    }
+
+    fn update_caret(&mut self) {
+        let line = self.buffer.byte_to_line(self.caret);
+        let row = line
+            .checked_sub(self.first)
+            .filter(|row| *row < self.capacity());
+        let start = self.buffer.line_to_byte(line);
+        let x = advance(
+            self.font,
+            self.character_size,
+            &self.buffer.slice(start..self.caret),
+        );
+
+        let height = self.line_height();
+        let origin = row.map(|row| self.row_origin(row));
+        if let (Some(caret), Some(origin)) = (&mut self.caret_shape, origin) {
+            caret.set_size((2., height).into());
+            caret.set_position(origin + Vec2::new(x, 0.));
+        }
+
+        // Across the whole line, from the gutter to the scrollbar
+        self.current_line_shown = origin.is_some();
+        if let Some(origin) = origin {
+            let left = self.position.x + self.gutter_width();
+            let width = self.size.x - self.gutter_width() - self.scrollbar.reserved_width();
+            self.current_line.set_position(Vec2::new(left, origin.y));
+            self.current_line.set_size((width, height).into());
+        }
+    }
+
+    /// Number the visible lines and place their markers
+    fn update_gutter(&mut self) {
+        let Some(gutter) = &mut self.gutter else {
+            return;
+        };
+
+        let digit = advance(self.font, self.character_size, "0");
+        let digits = number_digits(self.buffer.len_lines());
+        gutter.width = MARKER_COLUMN + digits as f32 * digit + PADDING;
+        gutter.background.set_position(self.position);
+        gutter
+            .background
+            .set_size(Vec2::new(gutter.width, self.size.y));
+
+        let opacity = self.opacity.effective();
+        let height = self.character_size * LINE_SPACING;
+        let current = self.buffer.byte_to_line(self.caret);
+        gutter.numbers.truncate(self.lines.len());
+        for row in 0..self.lines.len() {
+            if gutter.numbers.len() == row {
+                let mut number = Text::new_with_label(
+                    self.context.clone(),
+                    "",
+                    self.font,
+                    self.character_size,
+                    &format!("TextArea/number{row}"),
+                );
+                number.set_opacity(opacity);
+                gutter.numbers.push(number);
+            }
+
+            let line = self.first + row;
+            let label = if line < self.buffer.len_lines() {
+                (line + 1).to_string()
+            } else {
+                String::new()
+            };
+            // Right aligned against the text
+            let x = MARKER_COLUMN + digits as f32 * digit
+                - advance(self.font, self.character_size, &label);
+            let number = &mut gutter.numbers[row];
+            number.set_string(&label);
+            number.set_fill_color(if line == current {
+                Color::from((200, 200, 200))
+            } else {
+                Color::from((110, 110, 110))
+            });
+            number.set_position(self.position + Vec2::new(x, PADDING + row as f32 * height));
+        }
+
+        gutter.shown_dots = 0;
+        let visible = self.first..self.first + self.lines.len();
+        for (_, mark) in self.line_markers.iter() {
+            let line = self.buffer.byte_to_line(mark.range.start);
+            if !visible.contains(&line) {
+                continue;
+            }
+
+            if gutter.shown_dots == gutter.dots.len() {
+                let mut dot = Polyline::new(self.context.clone(), Vec::new(), MARKER_SIZE);
+                dot.set_cap(LineCap::Round);
+                gutter.dots.push(dot);
+            }
+
+            // A short round capped line looks like a dot
+            let center = self.position
+                + Vec2::new(
+                    MARKER_COLUMN / 2.,
+                    PADDING + (line - self.first) as f32 * height + height / 2.,
+                );
+            let dot = &mut gutter.dots[gutter.shown_dots];
+            dot.set_points(vec![center, center + Vec2::new(0.5, 0.)]);
+            dot.set_color(mark.data);
+            dot.set_opacity(opacity);
+            gutter.shown_dots += 1;
+        }
+    }
+
+    /// Place the highlights of the marks overlapping the visible lines
+    fn update_marks(&mut self) {
+        self.shown_marks = 0;
+        let opacity = self.opacity.effective();
+        let height = self.line_height();
+
+        for row in 0..self.lines.len() {
+            let line = self.first + row;
+            if line >= self.buffer.len_lines() {
+                break;
+            }
+
+            let start = self.buffer.line_to_byte(line);
+            let text = self.buffer.line(line);
+            let end = start + text.len();
+            let origin = self.row_origin(row);
+
+            let highlights = self
+                .marks
+                .overlapping(start..end + 1)
+                .filter(|(_, mark)| !mark.range.is_empty())
+                .map(|(_, mark)| {
+                    let from = mark.range.start.clamp(start, end) - start;
+                    let to = mark.range.end.clamp(start, end) - start;
+                    let x = advance(self.font, self.character_size, &text[..from]);
+                    let width = advance(self.font, self.character_size, &text[..to]) - x;
+
+                    (x, width.max(4.), mark.data)
+                })
+                .collect::<Vec<_>>();
+
+            for (x, width, color) in highlights {
+                if self.shown_marks == self.mark_shapes.len() {
+                    let shape = RectangleShape::new_with_label(
+                        self.context.clone(),
+                        Vec2::ZERO,
+                        &format!("TextArea/mark{}", self.mark_shapes.len()),
+                    );
+                    self.mark_shapes.push(shape);
+                }
+
+                let shape = &mut self.mark_shapes[self.shown_marks];
+                shape.set_fill_color(color);
+                shape.set_opacity(opacity);
+                shape.set_size((width, height).into());
+                shape.set_position(origin + Vec2::new(x, 0.));
+                self.shown_marks += 1;
+            }
+        }
+    }
+
+    fn apply_opacity(&mut self) {
+        let opacity = self.opacity.effective();
+        self.background.set_opacity(opacity);
+        self.scrollbar.set_opacity(opacity);
+        self.lines
+            .iter_mut()
+            .for_each(|line| line.set_opacity(opacity));
+        self.mark_shapes
+            .iter_mut()
+            .for_each(|shape| shape.set_opacity(opacity));
+
+        self.current_line.set_opacity(opacity);
+
+        if let Some(gutter) = &mut self.gutter {
+            gutter.set_opacity(opacity);
+        }
+        if let Some((_, ruler)) = &mut self.ruler {
+            ruler.set_opacity(opacity);
+        }
+        if let Some(caret) = &mut self.caret_shape {
+            caret.set_opacity(opacity);
+        }
+    }
+}
+
+impl Transformable for TextArea {
+    fn position(&self) -> &Vec2 {
+        &self.position
+    }
+
+    fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+
+        self.update();
+    }
+}
+
+impl Widget for TextArea {
+    impl_widget_state!();
+    impl_widget_opacity!();
+    impl_widget_events!();
+
+    fn update(&mut self) {
+        self.background.set_position(self.position);
+        self.background.set_size(self.size);
+
+        let count = self.buffer.len_lines() as f32;
+        self.scrollbar
+            .set_range(self.capacity() as f32 / count, self.first as f32 / count);
+        self.scrollbar.set_bounds(self.bounds());
+
+        let opacity = self.opacity.effective();
+        while self.lines.len() < self.capacity() {
+            let mut text = Text::new_with_label(
+                self.context.clone(),
+                "",
+                self.font,
+                self.character_size,
+                &format!("TextArea/line{}", self.lines.len()),
+            );
+            text.set_fill_color(WHITE);
+            text.set_opacity(opacity);
+            self.lines.push(text);
+        }
+        self.lines.truncate(self.capacity());
+        self.update_gutter();
+
+        if let Some(highlighting) = &mut self.highlighting {
+            highlighting.refresh(self.buffer.as_ref(), self.first + self.lines.len());
+        }
+
+        for row in 0..self.lines.len() {
+            let line = self.first + row;
+            let content = if line < self.buffer.len_lines() {
+                self.buffer.line(line).into_owned()
+            } else {
+                String::new()
+            };
+            let highlights = self
+                .highlighting
+                .as_ref()
+                .map_or(&[][..], |highlighting| highlighting.highlights(line));
+            let spans = highlight::runs(&content, highlights, WHITE)
+                .into_iter()
+                .map(|(range, color)| {
+                    TextSpan::new(&content[range], self.font, self.character_size).with_color(color)
+                })
+                .collect::<Vec<_>>();
+            let origin = self.row_origin(row);
+
+            // Laid out again only once changed
+            let text = &mut self.lines[row];
+            let unchanged = text.spans().len() == spans.len()
+                && text.spans().iter().zip(&spans).all(|(shown, span)| {
+                    shown.content == span.content && shown.color == span.color
+                });
+            if !unchanged {
+                text.set_spans(spans);
+            }
+            text.set_position(origin);
+        }
+
+        if let Some((column, ruler)) = &mut self.ruler {
+            let digit = advance(self.font, self.character_size, "0");
+            let x = self.position.x + self.gutter.as_ref().map_or(0., |gutter| gutter.width);
+            ruler.set_position(Vec2::new(
+                x + PADDING + *column as f32 * digit,
+                self.position.y,
+            ));
+            ruler.set_size(Vec2::new(1., self.size.y));
+        }
+
+        self.update_marks();
+        self.update_caret();
+    }
+
+    fn on_mount(&mut self, context: Ctx) {
+        let mut caret = RectangleShape::new_with_label(
+            context,
+            (2., self.line_height()).into(),
+            "TextArea/caret",
+        );
+        caret.set_fill_color(WHITE);
+        caret.set_opacity(self.opacity.effective());
+        self.caret_shape = Some(caret);
+
+        self.update_caret();
+    }
+
+    fn on_unmount(&mut self, _context: Ctx) {
+        self.caret_shape = None;
+    }
+
+    fn tick(&mut self, dt: f32) {
+        self.scrollbar.tick(dt);
+
+        if !self.focused {
+            return;
+        }
+
+        self.caret_elapsed += dt;
+        if self.caret_elapsed >= CARET_BLINK {
+            self.caret_elapsed %= CARET_BLINK;
+            self.caret_visible = !self.caret_visible;
+        }
+    }
+
+    fn process_events(&mut self, event: &WindowEvent) {
+        if !self.visible {
+            return;
+        }
+
+        if let Some(offset) = self.scrollbar.process_events(event) {
+            let first = (offset * self.buffer.len_lines() as f32).round() as usize;
+            self.first = first.min(self.last_first());
+            self.scrolled = 0.;
+            self.update();
+        }
+
+        match event {
+            WindowEvent::ModifiersChanged(modifiers) => self.modifiers = *modifiers,
+            WindowEvent::CursorMoved { position, .. } => {
+                self.mouse_position = (position.x as f32, position.y as f32).into();
+            }
+            WindowEvent::MouseWheel { delta, .. }
+                if self.bounds().contains(self.mouse_position) =>
+            {
+                let lines = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => -y * 3.,
+                    MouseScrollDelta::PixelDelta(position) => {
+                        -position.y as f32 / self.line_height()
+                    }
+                };
+                self.scroll(lines);
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } if self.mouse_position.x < self.position.x + self.gutter_width()
+                && self.bounds().contains(self.mouse_position) =>
+            {
+                let line = self.line_at(self.mouse_position);
+                let marker = self.line_marker(line);
+
+                if let Some(callback) = &mut self.on_marker_click {
+                    let marker = callback(line, marker);
+                    self.set_line_marker(line, marker);
+                }
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } if !self.scrollbar.contains(self.mouse_position) => {
+                let focused = self.bounds().contains(self.mouse_position);
+                self.set_focus(focused);
+                if focused {
+                    self.set_caret(self.offset_at(self.mouse_position));
+                }
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(keycode),
+                        ..
+                    },
+                ..
+            } if self.focused => self.process_key(*keycode),
+            WindowEvent::ReceivedCharacter(c) if self.focused => {
+                // Control characters (backspace, tab, ctrl shortcuts, ..) are handled as key
+                // inputs
+                if !c.is_control() && !self.modifiers.ctrl() {
+                    self.type_text(c.encode_utf8(&mut [0; 4]));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn size(&self) -> &Vec2 {
+        &self.size
+    }
+
+    fn set_size(&mut self, size: Vec2) {
+        self.size = size;
+        self.first = self.first.min(self.last_first());
+
+        self.update();
+    }
+
+    fn debug_name(&self) -> String {
+        String::from("TextArea")
+    }
+
+    fn cursor(&self, point: Vec2) -> Option<CursorIcon> {
+        (!self.scrollbar.contains(point)).then_some(CursorIcon::Text)
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.visible
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.set_focus(focused);
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    fn accessibility(&self) -> AccessNode {
+        let mut node = AccessNode::new(Role::TextInput, self.bounds()).with_value(&self.text());
+        node.focused = self.focused;
+
+        node
+    }
+}
+
+impl Drawable for TextArea {
+    fn draw<'b>(&'b mut self, render_pass: &mut RenderPass<'b>) {
+        if !self.visible {
+            return;
+        }
+
+        self.background.draw(render_pass);
+        if let Some(gutter) = &mut self.gutter {
+            gutter.draw(render_pass);
+        }
+        if self.current_line_shown {
+            self.current_line.draw(render_pass);
+        }
+        if let Some((_, ruler)) = &mut self.ruler {
+            ruler.draw(render_pass);
+        }
+        for shape in &mut self.mark_shapes[..self.shown_marks] {
+            shape.draw(render_pass);
+        }
+        for line in &mut self.lines {
+            line.draw(render_pass);
+        }
+
+        if self.focused && self.caret_visible {
+            if let Some(caret) = &mut self.caret_shape {
+                caret.draw(render_pass);
+            }
+        }
+
+        self.scrollbar.draw(render_pass);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{move_vertically, number_digits, step};
+    use crate::ui::text_buffer::RopeBuffer;
+
+    #[test]
+    fn caret_keeps_its_column_across_lines() {
+        let buffer = RopeBuffer::new("héllo\nab\nworld");
+
+        // From after "hél", the second line is shorter
+        assert_eq!(move_vertically(&buffer, 4, 1), 9);
+        assert_eq!(move_vertically(&buffer, 4, 2), 13);
+        assert_eq!(move_vertically(&buffer, 13, -2), 4);
+        assert_eq!(move_vertically(&buffer, 4, -1), 4);
+        assert_eq!(move_vertically(&buffer, 4, 10), 13);
+
+        // "é" is two bytes
+        assert_eq!(step(&buffer, 1, true), 3);
+        assert_eq!(step(&buffer, 3, false), 1);
+        assert_eq!(step(&buffer, 0, false), 0);
+        assert_eq!(step(&buffer, 15, true), 15);
+    }
+
+    #[test]
+    fn gutter_fits_the_largest_line_number() {
+        assert_eq!(number_digits(1), 2);
+        assert_eq!(number_digits(99), 2);
+        assert_eq!(number_digits(100), 3);
+        assert_eq!(number_digits(12_345), 5);
+    }
+}
@@ -0,0 +1,322 @@
+//! Storage of the text edited by a [`super::text_area::TextArea`], a rope by default so that
+//! large files are edited without copying them around
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use ropey::Rope;
+
+/// Text edited by byte offsets, which must fall on character boundaries
+///
+/// Lines are separated by `\n`, a `\r` ending a line is not part of it. A text ending with a
+/// line break ends with an empty line, an empty text has one empty line.
+pub trait TextBuffer {
+    /// Returns the length of the text, in bytes
+    fn len_bytes(&self) -> usize;
+
+    /// Returns the number of lines, at least one
+    fn len_lines(&self) -> usize;
+
+    /// Insert `text` at the byte offset `at`
+    fn insert(&mut self, at: usize, text: &str);
+
+    /// Remove the bytes of `range`
+    fn delete(&mut self, range: Range<usize>);
+
+    /// Returns the line `index` without its line break
+    fn line(&self, index: usize) -> Cow<'_, str>;
+
+    /// Returns the byte offset the line `index` starts at, the length of the text past the last
+    /// line
+    fn line_to_byte(&self, index: usize) -> usize;
+
+    /// Returns the line the byte offset `at` is on
+    fn byte_to_line(&self, at: usize) -> usize;
+
+    /// Returns the bytes of `range`
+    fn slice(&self, range: Range<usize>) -> Cow<'_, str>;
+
+    /// Returns the whole text
+    fn text(&self) -> String {
+        self.slice(0..self.len_bytes()).into_owned()
+    }
+
+    /// Iterate over the lines, without their line break
+    fn lines(&self) -> Box<dyn Iterator<Item = Cow<'_, str>> + '_> {
+        Box::new((0..self.len_lines()).map(|index| self.line(index)))
+    }
+}
+
+/// Drops the line break ending `line`
+fn trim_line_break(line: &str) -> &str {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+/// Text stored in a rope, edits and line lookups take logarithmic time whatever its size
+#[derive(Debug, Clone, Default)]
+pub struct RopeBuffer {
+    rope: Rope,
+}
+
+impl RopeBuffer {
+    pub fn new(text: &str) -> Self {
+        Self {
+            rope: Rope::from_str(text),
+        }
+    }
+
+    pub fn rope(&self) -> &Rope {
+        &self.rope
+    }
+}
+
+impl TextBuffer for RopeBuffer {
+    fn len_bytes(&self) -> usize {
+        self.rope.len_bytes()
+    }
+
+    fn len_lines(&self) -> usize {
+        self.rope.len_lines()
+    }
+
+    fn insert(&mut self, at: usize, text: &str) {
+        self.rope.insert(self.rope.byte_to_char(at), text);
+    }
+
+    fn delete(&mut self, range: Range<usize>) {
+        let start = self.rope.byte_to_char(range.start);
+        let end = self.rope.byte_to_char(range.end);
+
+        self.rope.remove(start..end);
+    }
+
+    fn line(&self, index: usize) -> Cow<'_, str> {
+        match Cow::from(self.rope.line(index)) {
+            Cow::Borrowed(line) => Cow::Borrowed(trim_line_break(line)),
+            Cow::Owned(line) => Cow::Owned(trim_line_break(&line).to_string()),
+        }
+    }
+
+    fn line_to_byte(&self, index: usize) -> usize {
+        self.rope.line_to_byte(index.min(self.rope.len_lines()))
+    }
+
+    fn byte_to_line(&self, at: usize) -> usize {
+        self.rope.byte_to_line(at)
+    }
+
+    fn slice(&self, range: Range<usize>) -> Cow<'_, str> {
+        self.rope.byte_slice(range).into()
+    }
+}
+
+/// Text stored in a single string, fine for short texts
+impl TextBuffer for String {
+    fn len_bytes(&self) -> usize {
+        self.len()
+    }
+
+    fn len_lines(&self) -> usize {
+        self.matches('\n').count() + 1
+    }
+
+    fn insert(&mut self, at: usize, text: &str) {
+        self.insert_str(at, text);
+    }
+
+    fn delete(&mut self, range: Range<usize>) {
+        self.replace_range(range, "");
+    }
+
+    fn line(&self, index: usize) -> Cow<'_, str> {
+        let line = self.split('\n').nth(index).unwrap_or_default();
+
+        Cow::Borrowed(line.strip_suffix('\r').unwrap_or(line))
+    }
+
+    fn line_to_byte(&self, index: usize) -> usize {
+        match index.checked_sub(1) {
+            None => 0,
+            Some(breaks) => self
+                .match_indices('\n')
+                .nth(breaks)
+                .map_or(self.len(), |(at, _)| at + 1),
+        }
+    }
+
+    fn byte_to_line(&self, at: usize) -> usize {
+        self[..at].matches('\n').count()
+    }
+
+    fn slice(&self, range: Range<usize>) -> Cow<'_, str> {
+        Cow::Borrowed(&self[range])
+    }
+}
+
+/// Identifier of a range marked in a text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MarkId(u64);
+
+/// Range of a text tagged with `data`, e.g. a search result or an error
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mark<T> {
+    /// Byte offsets, following the edits of the text
+    pub range: Range<usize>,
+    pub data: T,
+}
+
+/// Ranges marked in a text, moved along by its edits
+///
+/// Text inserted at either end of a mark stays out of it, a mark whose text is deleted is kept
+/// empty where the text was.
+#[derive(Debug, Clone)]
+pub struct Marks<T> {
+    next_id: u64,
+    marks: BTreeMap<MarkId, Mark<T>>,
+}
+
+impl<T> Default for Marks<T> {
+    fn default() -> Self {
+        Self {
+            next_id: 0,
+            marks: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> Marks<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark a range, returns its identifier
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - Byte offsets of the marked text
+    /// * `data` - Meaning of the mark
+    pub fn add(&mut self, range: Range<usize>, data: T) -> MarkId {
+        let id = MarkId(self.next_id);
+        self.next_id += 1;
+        self.marks.insert(id, Mark { range, data });
+
+        id
+    }
+
+    pub fn remove(&mut self, id: MarkId) -> Option<Mark<T>> {
+        self.marks.remove(&id)
+    }
+
+    pub fn clear(&mut self) {
+        self.marks.clear();
+    }
+
+    pub fn get(&self, id: MarkId) -> Option<&Mark<T>> {
+        self.marks.get(&id)
+    }
+
+    /// Iterate over the marks, in the order they were added
+    pub fn iter(&self) -> impl Iterator<Item = (MarkId, &Mark<T>)> {
+        self.marks.iter().map(|(id, mark)| (*id, mark))
+    }
+
+    /// Iterate over the marks overlapping `range`, empty marks count when inside it
+    pub fn overlapping(&self, range: Range<usize>) -> impl Iterator<Item = (MarkId, &Mark<T>)> {
+        self.iter().filter(move |(_, mark)| {
+            mark.range.start < range.end && mark.range.end > range.start
+                || mark.range.is_empty() && range.contains(&mark.range.start)
+        })
+    }
+
+    /// Move the marks after text was inserted
+    ///
+    /// # Arguments
+    ///
+    /// * `at` - Byte offset of the insertion
+    /// * `len` - Length of the inserted text, in bytes
+    pub fn inserted(&mut self, at: usize, len: usize) {
+        for mark in self.marks.values_mut() {
+            if mark.range.start >= at {
+                mark.range.start += len;
+            }
+            if mark.range.end > at {
+                mark.range.end += len;
+            }
+            mark.range.end = mark.range.end.max(mark.range.start);
+        }
+    }
+
+    /// Move the marks after text was deleted
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - Byte offsets of the deleted text, before the deletion
+    pub fn deleted(&mut self, range: Range<usize>) {
+        let shift = |offset: usize| {
+            if offset <= range.start {
+                offset
+            } else if offset >= range.end {
+                offset - range.len()
+            } else {
+                range.start
+            }
+        };
+
+        for mark in self.marks.values_mut() {
+            mark.range = shift(mark.range.start)..shift(mark.range.end);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Marks, RopeBuffer, TextBuffer};
+
+    #[test]
+    fn backends_agree_on_lines() {
+        let text = "fn main() {\r\n    println!(\"é\");\n}\n";
+        let backends: [Box<dyn TextBuffer>; 2] =
+            [Box::new(RopeBuffer::new(text)), Box::new(text.to_string())];
+
+        for mut buffer in backends {
+            assert_eq!(buffer.len_lines(), 4);
+            assert_eq!(
+                buffer.lines().collect::<Vec<_>>(),
+                ["fn main() {", "    println!(\"é\");", "}", ""]
+            );
+            assert_eq!(buffer.line_to_byte(1), 13);
+            assert_eq!(buffer.byte_to_line(13), 1);
+            assert_eq!(buffer.line_to_byte(4), text.len());
+
+            buffer.insert(13, "    let x = 1;\n");
+            buffer.delete(0..3);
+            assert_eq!(buffer.line(1), "    let x = 1;");
+            assert_eq!(buffer.slice(0..4), "main");
+            assert_eq!(buffer.len_bytes(), text.len() + 12);
+        }
+    }
+
+    #[test]
+    fn marks_follow_the_edits() {
+        let mut marks = Marks::new();
+        let word = marks.add(4..9, "word");
+        let caret = marks.add(12..12, "caret");
+
+        // Insertions at the ends stay out of the mark
+        marks.inserted(4, 2);
+        marks.inserted(11, 1);
+        assert_eq!(marks.get(word).unwrap().range, 6..11);
+        assert_eq!(marks.get(caret).unwrap().range, 15..15);
+
+        marks.deleted(8..13);
+        assert_eq!(marks.get(word).unwrap().range, 6..8);
+        assert_eq!(marks.get(caret).unwrap().range, 10..10);
+        assert_eq!(marks.overlapping(0..7).count(), 1);
+        assert_eq!(marks.overlapping(9..11).count(), 1);
+
+        marks.deleted(0..20);
+        assert_eq!(marks.get(word).unwrap().range, 0..0);
+    }
+}
@@ -0,0 +1,381 @@
+use glam::Vec2;
+use wgpu::RenderPass;
+use winit::event::{
+    ElementState, KeyboardInput, ModifiersState, MouseButton, VirtualKeyCode, WindowEvent,
+};
+use winit::window::CursorIcon;
+
+use super::{
+    direction::layout_direction,
+    state::{Binding, State},
+    EventQueue, Opacity, Uid, Widget, WidgetEvent,
+};
+use crate::accessibility::{AccessNode, Role};
+use crate::graphics::shape::{RectangleShape, Shape};
+use crate::graphics::text::Text;
+use crate::graphics::{
+    color::{Color, WHITE},
+    Drawable, Transformable,
+};
+use crate::{Ctx, ASSETS};
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum TextInputEvent {
+    Changed,
+    Submit,
+}
+
+impl WidgetEvent for TextInputEvent {}
+
+/// Time the caret stays shown then hidden, in seconds
+const CARET_BLINK: f32 = 0.5;
+
+/// Single line editable text field
+pub struct TextInput<'a> {
+    context: Ctx,
+    background: RectangleShape,
+    label: Text<'a>,
+    /// Allocated while the field is mounted
+    caret: Option<RectangleShape>,
+    caret_visible: bool,
+    caret_elapsed: f32,
+    value: String,
+    position: Vec2,
+    mouse_position: Vec2,
+    modifiers: ModifiersState,
+    focused: bool,
+    visible: bool,
+    uid: Uid,
+    opacity: Opacity,
+    events: EventQueue<TextInputEvent>,
+    /// State following the content, both ways
+    binding: Option<Binding<String>>,
+    /// The content failed its validation
+    invalid: bool,
+}
+
+impl<'a> TextInput<'a> {
+    pub fn new(context: Ctx, size: Vec2) -> Self {
+        let mut background =
+            RectangleShape::new_with_label(context.clone(), size, "TextInput/background");
+        background.set_fill_color(Color::from((40, 40, 40)));
+
+        let mut label = Text::new_with_label(
+            context.clone(),
+            "",
+            ASSETS.get_font("Roboto.ttf").unwrap(),
+            size.y * 0.7,
+            "TextInput/label",
+        );
+        label.set_fill_color(WHITE);
+
+        Self {
+            context,
+            background,
+            label,
+            caret: None,
+            caret_visible: true,
+            caret_elapsed: 0.,
+            value: String::new(),
+            position: Default::default(),
+            mouse_position: Default::default(),
+            modifiers: ModifiersState::empty(),
+            focused: false,
+            visible: true,
+            uid: Uid::new(),
+            opacity: Opacity::default(),
+            events: EventQueue::new(),
+            binding: None,
+            invalid: false,
+        }
+    }
+
+    /// Returns the current content of the field
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Replace the content of the field
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - New content
+    pub fn set_value(&mut self, value: &str) {
+        self.show_value(value);
+
+        if let Some(binding) = &mut self.binding {
+            binding.set(self.value.clone());
+        }
+    }
+
+    /// Display the content of `state` and keep it up to date with the field, the changes of the
+    /// state are displayed on the next frame
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - State holding the content
+    pub fn bind_value(&mut self, state: State<String>) {
+        self.binding = Some(Binding::new(state));
+        self.apply_binding();
+    }
+
+    fn show_value(&mut self, value: &str) {
+        self.value = value.to_string();
+        self.label.set_string(&self.value);
+        self.events.push(TextInputEvent::Changed);
+    }
+
+    fn apply_binding(&mut self) {
+        if let Some(value) = self.binding.as_mut().and_then(Binding::changed) {
+            self.show_value(&value);
+            self.update_caret();
+        }
+    }
+
+    pub fn focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+        self.caret_visible = true;
+        self.caret_elapsed = 0.;
+
+        self.background.set_fill_color(if focused {
+            Color::from((70, 70, 70))
+        } else {
+            Color::from((40, 40, 40))
+        });
+    }
+
+    fn update_caret(&mut self) {
+        let (label_position, label_width) = (*self.label.position(), self.label.bounds().width);
+        let height = self.background.size().y;
+
+        if let Some(caret) = &mut self.caret {
+            caret.set_position(Vec2 {
+                x: label_position.x + label_width + 1.,
+                y: self.position.y + (height - caret.size().y) / 2.,
+            });
+        }
+    }
+
+    fn copy(&self) {
+        self.context
+            .lock()
+            .unwrap()
+            .clipboard()
+            .set_text(&self.value);
+    }
+
+    fn paste(&mut self) {
+        let text = self.context.lock().unwrap().clipboard().get_text();
+
+        if let Some(text) = text {
+            // Single line field, drop line breaks
+            let text = text.replace(['\r', '\n'], "");
+            let value = format!("{}{text}", self.value);
+            self.set_value(&value);
+        }
+    }
+
+    fn process_key(&mut self, keycode: VirtualKeyCode) {
+        match keycode {
+            VirtualKeyCode::C if self.modifiers.ctrl() => self.copy(),
+            VirtualKeyCode::X if self.modifiers.ctrl() => {
+                self.copy();
+                self.set_value("");
+            }
+            VirtualKeyCode::V if self.modifiers.ctrl() => self.paste(),
+            VirtualKeyCode::Back => {
+                let mut value = self.value.clone();
+                if value.pop().is_some() {
+                    self.set_value(&value);
+                }
+            }
+            VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+                self.events.push(TextInputEvent::Submit)
+            }
+            _ => {}
+        }
+    }
+
+    /// Flag the content as failing its validation, the background turns red
+    ///
+    /// # Arguments
+    ///
+    /// * `invalid` - `true` until the content is fixed
+    pub fn set_invalid(&mut self, invalid: bool) {
+        self.invalid = invalid;
+        self.background.set_fill_color(if invalid {
+            Color::from((90, 30, 30))
+        } else {
+            Color::from((40, 40, 40))
+        });
+    }
+
+    fn apply_opacity(&mut self) {
+        let opacity = self.opacity.effective();
+        self.background.set_opacity(opacity);
+        self.label.set_opacity(opacity);
+
+        if let Some(caret) = &mut self.caret {
+            caret.set_opacity(opacity);
+        }
+    }
+}
+
+impl<'a> Transformable for TextInput<'a> {
+    fn position(&self) -> &Vec2 {
+        &self.position
+    }
+
+    fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+
+        self.update();
+    }
+}
+
+impl<'a> Widget for TextInput<'a> {
+    impl_widget_state!();
+    impl_widget_opacity!();
+    impl_widget_events!();
+
+    fn update(&mut self) {
+        self.background.set_position(self.position);
+        let width = self.background.size().x;
+        self.label.set_position(Vec2 {
+            x: self.position.x + layout_direction().place(width, 5., self.label.bounds().width),
+            y: self.position.y + (self.background.size().y - self.label.bounds().height) / 2.,
+        });
+        self.update_caret();
+    }
+
+    fn on_mount(&mut self, context: Ctx) {
+        let height = self.background.size().y * 0.7;
+        let mut caret =
+            RectangleShape::new_with_label(context, (2., height).into(), "TextInput/caret");
+        caret.set_fill_color(WHITE);
+        caret.set_opacity(self.opacity.effective());
+        self.caret = Some(caret);
+
+        self.update_caret();
+    }
+
+    fn on_unmount(&mut self, _context: Ctx) {
+        self.caret = None;
+    }
+
+    fn tick(&mut self, dt: f32) {
+        self.apply_binding();
+
+        if !self.focused {
+            return;
+        }
+
+        self.caret_elapsed += dt;
+        if self.caret_elapsed >= CARET_BLINK {
+            self.caret_elapsed %= CARET_BLINK;
+            self.caret_visible = !self.caret_visible;
+            self.update_caret();
+        }
+    }
+
+    fn process_events(&mut self, event: &WindowEvent) {
+        if !self.visible {
+            return;
+        }
+
+        match event {
+            WindowEvent::ModifiersChanged(modifiers) => self.modifiers = *modifiers,
+            WindowEvent::CursorMoved { position, .. } => {
+                self.mouse_position = (position.x as f32, position.y as f32).into();
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } => {
+                let focused = self.background.bounds().contains(self.mouse_position);
+                self.set_focus(focused);
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(keycode),
+                        ..
+                    },
+                ..
+            } if self.focused => self.process_key(*keycode),
+            WindowEvent::ReceivedCharacter(c) if self.focused => {
+                // Control characters (backspace, ctrl shortcuts, ..) are handled as key inputs
+                if !c.is_control() && !self.modifiers.ctrl() {
+                    let value = format!("{}{c}", self.value);
+                    self.set_value(&value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn size(&self) -> &Vec2 {
+        self.background.size()
+    }
+
+    fn debug_name(&self) -> String {
+        String::from("TextInput")
+    }
+
+    fn cursor(&self, _point: Vec2) -> Option<CursorIcon> {
+        Some(CursorIcon::Text)
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.visible
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.set_focus(focused);
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    fn is_invalid(&self) -> bool {
+        self.invalid
+    }
+
+    fn accessibility(&self) -> AccessNode {
+        let mut node =
+            AccessNode::new(Role::TextInput, self.background.bounds()).with_value(&self.value);
+        node.focused = self.focused;
+
+        node
+    }
+
+    fn set_size(&mut self, size: Vec2) {
+        self.background.set_size(size);
+
+        self.update();
+    }
+}
+
+impl<'a> Drawable for TextInput<'a> {
+    fn draw<'b>(&'b mut self, render_pass: &mut RenderPass<'b>) {
+        if !self.visible {
+            return;
+        }
+
+        self.background.draw(render_pass);
+        self.label.draw(render_pass);
+
+        if self.focused && self.caret_visible {
+            if let Some(caret) = &mut self.caret {
+                caret.draw(render_pass);
+            }
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use glam::{Vec2, Vec4};
+
+use crate::audio::SoundStyle;
+
+/// How much room the widgets take, from dense desktop tools to touch-friendly layouts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Density {
+    Compact,
+    #[default]
+    Normal,
+    /// Bigger targets and texts, for touch screens
+    Comfortable,
+}
+
+/// Default sizes of the widgets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Metrics {
+    /// Character size of buttons
+    pub font_size: f32,
+    /// Character size of title bars and their buttons
+    pub small_font_size: f32,
+    /// Horizontal and vertical space between the edges of a button and its label
+    pub padding: Vec2,
+    /// Height of single line controls, e.g. text inputs
+    pub control_height: f32,
+    /// Height of progress bars
+    pub bar_height: f32,
+    pub titlebar_height: f32,
+    /// Space between the edges of a dialog body and its content
+    pub margin: f32,
+    /// Space between the widgets of a layout
+    pub spacing: f32,
+}
+
+impl Metrics {
+    /// Returns the paddings of a button: left, top, bottom and right
+    pub fn paddings(&self) -> Vec4 {
+        Vec4::new(
+            self.padding.x,
+            self.padding.y,
+            self.padding.y,
+            self.padding.x,
+        )
+    }
+}
+
+impl Density {
+    /// Returns the default sizes of the widgets at this density
+    pub fn metrics(self) -> Metrics {
+        match self {
+            Self::Compact => Metrics {
+                font_size: 22.,
+                small_font_size: 13.,
+                padding: Vec2::new(4., 2.),
+                control_height: 24.,
+                bar_height: 14.,
+                titlebar_height: 16.,
+                margin: 4.,
+                spacing: 2.,
+            },
+            Self::Normal => Metrics {
+                font_size: 30.,
+                small_font_size: 16.,
+                padding: Vec2::ZERO,
+                control_height: 30.,
+                bar_height: 20.,
+                titlebar_height: 20.,
+                margin: 8.,
+                spacing: 3.,
+            },
+            Self::Comfortable => Metrics {
+                font_size: 34.,
+                small_font_size: 20.,
+                padding: Vec2::new(16., 12.),
+                control_height: 44.,
+                bar_height: 28.,
+                titlebar_height: 32.,
+                margin: 16.,
+                spacing: 12.,
+            },
+        }
+    }
+}
+
+/// Height of the widgets above the background, in pixels, raised widgets cast a shadow
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Elevations {
+    pub button: f32,
+    pub dialog: f32,
+}
+
+impl Default for Elevations {
+    fn default() -> Self {
+        Self {
+            button: 2.,
+            dialog: 8.,
+        }
+    }
+}
+
+/// Feedback of the buttons to the pointer, their background grows or shrinks around their
+/// label while hovered and pressed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ButtonStyle {
+    /// Factor applied to the size of the background while hovered, e.g. 1.05 to grow slightly
+    pub hover_scale: f32,
+    /// Factor applied to the size of the background while pressed
+    pub press_scale: f32,
+    /// Added to the paddings of the background while hovered: left, top, bottom and right
+    pub hover_paddings: Vec4,
+    /// Added to the paddings of the background while pressed
+    pub press_paddings: Vec4,
+    /// Time to go from a state to another, in seconds, 0 to change instantly
+    pub duration: f32,
+}
+
+impl Default for ButtonStyle {
+    fn default() -> Self {
+        Self {
+            hover_scale: 1.04,
+            press_scale: 0.96,
+            hover_paddings: Vec4::ZERO,
+            press_paddings: Vec4::ZERO,
+            duration: 0.12,
+        }
+    }
+}
+
+impl ButtonStyle {
+    /// Returns the style of buttons changing instantly without changing size
+    pub fn instant() -> Self {
+        Self {
+            hover_scale: 1.,
+            press_scale: 1.,
+            duration: 0.,
+            ..Self::default()
+        }
+    }
+}
+
+/// How the scrollable widgets show their scrolling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScrollbarStyle {
+    /// Track and thumb always shown beside the content
+    #[default]
+    Classic,
+    /// Thin rounded thumb over the content, fading out once the scrolling stops and widening
+    /// under the cursor
+    Overlay,
+}
+
+/// Look of the widgets, read from the context when they are created
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Theme {
+    pub density: Density,
+    pub elevations: Elevations,
+    /// Sounds of the buttons and check boxes, silent by default
+    pub sounds: SoundStyle,
+    pub scrollbars: ScrollbarStyle,
+    pub buttons: ButtonStyle,
+}
+
+impl Theme {
+    /// Returns the default sizes of the widgets
+    pub fn metrics(&self) -> Metrics {
+        self.density.metrics()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Density;
+
+    #[test]
+    fn denser_presets_are_smaller() {
+        let [compact, normal, comfortable] =
+            [Density::Compact, Density::Normal, Density::Comfortable].map(Density::metrics);
+
+        assert!(compact.control_height < normal.control_height);
+        assert!(normal.control_height < comfortable.control_height);
+        assert!(compact.font_size < normal.font_size && normal.font_size < comfortable.font_size);
+        // Touch targets are at least 44 pixels high
+        assert!(comfortable.control_height >= 44.);
+    }
+}
@@ -0,0 +1,312 @@
+//! Changes of the properties held by states recorded on a timeline, replayed later for demo
+//! reels, walkthroughs or to reproduce an animation bug
+//!
+//! Times follow the `dt` given to each frame rather than the wall clock, so a replay driven by
+//! the same frame times sets the same values on the same frames.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+use super::loader::Property;
+use super::state::State;
+
+/// Value a state can hold to be recorded on a timeline
+pub trait Animatable: Clone + PartialEq + Send + 'static {
+    fn to_property(&self) -> Property;
+
+    /// Returns the value stored in `property`, `None` if it holds another type
+    fn from_property(property: &Property) -> Option<Self>;
+}
+
+impl Animatable for f32 {
+    fn to_property(&self) -> Property {
+        Property::Number(*self)
+    }
+
+    fn from_property(property: &Property) -> Option<Self> {
+        match property {
+            Property::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+}
+
+impl Animatable for bool {
+    fn to_property(&self) -> Property {
+        Property::Bool(*self)
+    }
+
+    fn from_property(property: &Property) -> Option<Self> {
+        match property {
+            Property::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl Animatable for String {
+    fn to_property(&self) -> Property {
+        Property::Text(self.clone())
+    }
+
+    fn from_property(property: &Property) -> Option<Self> {
+        match property {
+            Property::Text(text) => Some(text.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl Animatable for Vec2 {
+    fn to_property(&self) -> Property {
+        Property::List(vec![self.x, self.y])
+    }
+
+    fn from_property(property: &Property) -> Option<Self> {
+        match property {
+            Property::List(list) => match list.as_slice() {
+                [x, y] => Some(Vec2::new(*x, *y)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Value taken by a property at a given time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Keyframe {
+    /// Seconds since the recording started
+    pub time: f32,
+    /// Name the property was tracked under
+    pub property: String,
+    pub value: Property,
+}
+
+/// Keyframes of every tracked property, in time order
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Timeline {
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl Timeline {
+    pub fn from_ron(source: &str) -> Result<Self> {
+        Ok(ron::from_str(source)?)
+    }
+
+    pub fn to_ron(&self) -> Result<String> {
+        Ok(ron::ser::to_string_pretty(self, Default::default())?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::from_ron(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        Ok(std::fs::write(path, self.to_ron()?)?)
+    }
+
+    /// Returns the time of the last keyframe, in seconds
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0., |keyframe| keyframe.time)
+    }
+}
+
+/// Keyframes recorded so far and the recording time, shared with the observers of the states
+#[derive(Debug, Default)]
+struct Track {
+    time: f32,
+    keyframes: Vec<Keyframe>,
+}
+
+/// Records the changes of the tracked states
+pub struct PropertyRecorder {
+    track: Arc<Mutex<Track>>,
+    /// Stop observing the tracked states
+    untrack: Vec<Box<dyn FnOnce()>>,
+}
+
+impl Default for PropertyRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PropertyRecorder {
+    pub fn new() -> Self {
+        Self {
+            track: Arc::new(Mutex::new(Track::default())),
+            untrack: Vec::new(),
+        }
+    }
+
+    /// Record the changes of `state`, starting with a snapshot of its current value
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the property on the timeline, bound again by name on replay
+    /// * `state` - State holding the property
+    pub fn track<T: Animatable>(&mut self, name: &str, state: &State<T>) {
+        let record = {
+            let track = self.track.clone();
+            let name = name.to_string();
+
+            move |value: &T| {
+                let mut track = track.lock().unwrap();
+                let time = track.time;
+                track.keyframes.push(Keyframe {
+                    time,
+                    property: name.clone(),
+                    value: value.to_property(),
+                });
+            }
+        };
+
+        let mut snapshot = record.clone();
+        snapshot(&state.get());
+
+        let id = state.observe(record);
+        let state = state.clone();
+        self.untrack.push(Box::new(move || {
+            state.unobserve(id);
+        }));
+    }
+
+    /// Advance the recording time
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Time elapsed since the last frame, in seconds
+    pub fn tick(&mut self, dt: f32) {
+        self.track.lock().unwrap().time += dt;
+    }
+
+    /// Stop observing the states and returns the recorded timeline
+    pub fn finish(self) -> Timeline {
+        self.untrack.into_iter().for_each(|untrack| untrack());
+
+        let track = std::mem::take(&mut *self.track.lock().unwrap());
+        Timeline {
+            keyframes: track.keyframes,
+        }
+    }
+}
+
+/// Replays a timeline, setting the bound states when their keyframes are due
+pub struct PropertyPlayer {
+    timeline: Timeline,
+    elapsed: f32,
+    /// Index of the next keyframe to apply
+    next: usize,
+    /// Apply a keyframe value, `false` if it holds another type
+    bindings: Vec<(String, Box<dyn FnMut(&Property) -> bool>)>,
+}
+
+impl PropertyPlayer {
+    pub fn new(timeline: Timeline) -> Self {
+        Self {
+            timeline,
+            elapsed: 0.,
+            next: 0,
+            bindings: Vec::new(),
+        }
+    }
+
+    /// Set `state` from the keyframes of the property `name`
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name the property was tracked under
+    /// * `state` - State receiving the values, usually bound to a widget
+    pub fn bind<T: Animatable>(&mut self, name: &str, state: State<T>) {
+        self.bindings.push((
+            name.to_string(),
+            Box::new(move |property| match T::from_property(property) {
+                Some(value) => {
+                    state.set(value);
+                    true
+                }
+                None => false,
+            }),
+        ));
+    }
+
+    /// Advance the replay and apply the keyframes due, in their order
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Time elapsed since the last frame, in seconds
+    pub fn advance(&mut self, dt: f32) {
+        self.elapsed += dt;
+
+        while let Some(keyframe) = self.timeline.keyframes.get(self.next) {
+            if keyframe.time > self.elapsed {
+                break;
+            }
+            self.next += 1;
+
+            for (name, apply) in &mut self.bindings {
+                if *name == keyframe.property && !apply(&keyframe.value) {
+                    tracing::warn!(
+                        "keyframe of {name} at {}s doesn't match the bound state",
+                        keyframe.time
+                    );
+                }
+            }
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next == self.timeline.keyframes.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::{PropertyPlayer, PropertyRecorder, Timeline};
+    use crate::ui::state::State;
+
+    #[test]
+    fn replay_sets_the_recorded_values_at_their_time() {
+        let progress = State::new(0f32);
+        let offset = State::new(Vec2::ZERO);
+        let mut recorder = PropertyRecorder::new();
+        recorder.track("progress", &progress);
+        recorder.track("offset", &offset);
+
+        recorder.tick(0.5);
+        progress.set(0.25);
+        recorder.tick(0.5);
+        offset.set(Vec2::new(3., 4.));
+        progress.set(1.);
+        let timeline = recorder.finish();
+        // No longer tracked
+        progress.set(0.5);
+
+        assert_eq!(timeline.keyframes.len(), 5);
+        assert_eq!(timeline.duration(), 1.);
+        let timeline = Timeline::from_ron(&timeline.to_ron().unwrap()).unwrap();
+
+        let (replayed, replayed_offset) = (State::new(-1f32), State::new(Vec2::ONE));
+        let mut player = PropertyPlayer::new(timeline);
+        player.bind("progress", replayed.clone());
+        player.bind("offset", replayed_offset.clone());
+
+        player.advance(0.);
+        assert_eq!((replayed.get(), replayed_offset.get()), (0., Vec2::ZERO));
+        player.advance(0.6);
+        assert_eq!(replayed.get(), 0.25);
+        player.advance(0.4);
+        assert_eq!(
+            (replayed.get(), replayed_offset.get()),
+            (1., Vec2::new(3., 4.))
+        );
+        assert!(player.is_done());
+    }
+}
@@ -1,8 +1,11 @@
 use glam::Vec2;
 use winit::event::{ElementState, MouseButton};
+use winit::window::CursorIcon;
 
 use super::button::{Button, ButtonEvent};
-use super::{Widget, WidgetEvent};
+use super::direction::layout_direction;
+use super::{EventQueue, Opacity, Uid, Widget, WidgetEvent};
+use crate::accessibility::{AccessNode, Role};
 use crate::graphics::shape::{RectangleShape, Shape};
 use crate::graphics::text::Text;
 use crate::graphics::{
@@ -24,17 +27,29 @@ pub struct Window<'a> {
     mouse_position: Vec2,
     click_position: Option<Vec2>,
     visible: bool,
+    uid: Uid,
+    opacity: Opacity,
     close_btn: Button<'a>,
-    events: Vec<ButtonEvent>,
+    events: EventQueue<ButtonEvent>,
     size: Vec2,
 }
 
 impl<'a> Window<'a> {
     pub fn new(context: Ctx, title: &str) -> Self {
-        let mut titlebar = RectangleShape::new(context.clone(), (150., 20.).into());
+        let metrics = context.lock().unwrap().theme.metrics();
+
+        let mut titlebar = RectangleShape::new_with_label(
+            context.clone(),
+            (150., metrics.titlebar_height).into(),
+            &format!("Window#{title}/titlebar"),
+        );
         titlebar.set_fill_color(BLUE);
 
-        let mut body = RectangleShape::new(context.clone(), (150., 150.).into());
+        let mut body = RectangleShape::new_with_label(
+            context.clone(),
+            (150., 150.).into(),
+            &format!("Window#{title}/body"),
+        );
         body.set_fill_color(RED);
         body.set_position(Vec2 {
             x: 0.,
@@ -42,39 +57,47 @@ impl<'a> Window<'a> {
         });
 
         let mut close_btn = Button::new("x", context.clone());
-        close_btn.set_character_size(16.);
+        close_btn.set_character_size(metrics.small_font_size);
 
         let mut window = Self {
             size: *titlebar.size() + *body.size(),
             titlebar,
             body,
-            title: Text::new(context, title, ASSETS.get_font("Roboto.ttf").unwrap(), 16.),
+            title: Text::new_with_label(
+                context,
+                title,
+                ASSETS.get_font("Roboto.ttf").unwrap(),
+                metrics.small_font_size,
+                &format!("Window#{title}/title"),
+            ),
             mouse_position: Default::default(),
             click_position: None,
             visible: true,
+            uid: Uid::new(),
+            opacity: Opacity::default(),
             close_btn,
-            events: Vec::new(),
+            events: EventQueue::new(),
         };
 
         window.set_position((0., 0.).into());
 
         window
     }
-}
 
-impl<'a> Widget for Window<'a> {
-    fn events(&mut self, event_handler: Box<dyn Fn(u32)>) {
-        self.events.drain(..).for_each(|e| event_handler(e as u32));
+    fn apply_opacity(&mut self) {
+        let opacity = self.opacity.effective();
+        self.titlebar.set_opacity(opacity);
+        self.body.set_opacity(opacity);
+        self.title.set_opacity(opacity);
+        self.close_btn.set_inherited_opacity(opacity);
     }
+}
 
-    fn emitted(&mut self, event: u32) -> bool {
-        !self
-            .events
-            .drain(..)
-            .filter(|e| *e as u32 == event)
-            .collect::<Vec<_>>()
-            .is_empty()
-    }
+impl<'a> Widget for Window<'a> {
+    impl_widget_state!();
+    impl_widget_opacity!();
+    impl_widget_events!();
+    impl_widget_children!(close_btn);
 
     fn process_events(&mut self, event: &winit::event::WindowEvent) {
         // Prevent events handling if widget is not displayed
@@ -141,36 +164,47 @@ impl<'a> Widget for Window<'a> {
         }
     }
 
-    fn set_visibility(&mut self, visibility: bool) {
-        self.visible = visibility;
+    fn size(&self) -> &Vec2 {
+        &self.size
     }
 
-    fn visible(&self) -> bool {
-        self.visible
+    fn debug_name(&self) -> String {
+        format!("Window#{}", self.title.string())
     }
 
-    fn size(&self) -> &Vec2 {
-        &self.size
+    fn accessibility(&self) -> AccessNode {
+        AccessNode::new(Role::Window, self.bounds()).with_name(self.title.string())
+    }
+
+    fn cursor(&self, point: Vec2) -> Option<CursorIcon> {
+        self.titlebar
+            .bounds()
+            .contains(point)
+            .then_some(CursorIcon::Move)
     }
 }
 
 impl<'a> Transformable for Window<'a> {
     fn position(&self) -> &glam::Vec2 {
-        self.title.position()
+        // The title moves to the right in right-to-left layouts
+        self.titlebar.position()
     }
 
     fn set_position(&mut self, position: glam::Vec2) {
         self.titlebar.set_position(position);
 
-        // Calculate title position
+        // Calculate title position, on the start side
+        let direction = layout_direction();
+        let width = self.titlebar.bounds().width;
+        let title_bounds = self.title.bounds();
         self.title.set_position(Vec2 {
-            x: position.x + 5.,
-            y: position.y + (self.titlebar.bounds().height - self.title.bounds().height) / 2.,
+            x: position.x + direction.place(width, 5., title_bounds.width),
+            y: position.y + (self.titlebar.bounds().height - title_bounds.height) / 2.,
         });
 
-        let close_btn_size = self.close_btn.size();
+        let close_btn_size = *self.close_btn.size();
         self.close_btn.set_position(Vec2 {
-            x: position.x + self.titlebar.bounds().width - close_btn_size.x - 5.,
+            x: position.x + direction.place(width, width - close_btn_size.x - 5., close_btn_size.x),
             y: position.y + (self.titlebar.bounds().height - close_btn_size.y) / 2.,
         });
 
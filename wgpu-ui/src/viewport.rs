@@ -0,0 +1,234 @@
+//! Several views of the scene side by side in one window, e.g. the 4-way split of an editor
+
+use glam::Vec2;
+use winit::event::WindowEvent;
+use winit::window::CursorIcon;
+
+use crate::graphics::{layer::Layer, Drawable};
+use crate::math::Rect;
+use crate::ui::Ui;
+use crate::Ctx;
+
+/// Returns the areas of a grid of `columns` by `rows` cells, rows first, as fractions of the
+/// split area
+///
+/// # Arguments
+///
+/// * `columns` - Number of cells per row
+/// * `rows` - Number of rows
+pub fn grid(columns: u32, rows: u32) -> Vec<Rect> {
+    let (width, height) = (1. / columns as f32, 1. / rows as f32);
+
+    (0..rows)
+        .flat_map(|row| {
+            (0..columns).map(move |column| Rect {
+                x: column as f32 * width,
+                y: row as f32 * height,
+                width,
+                height,
+            })
+        })
+        .collect()
+}
+
+/// Returns the part of `window` covered by `area`
+///
+/// # Arguments
+///
+/// * `window` - Area of the window the UI is rendered to, in physical pixels
+/// * `area` - Fractions of the window size
+pub fn area_rect(window: Rect, area: Rect) -> Rect {
+    Rect {
+        x: window.x + area.x * window.width,
+        y: window.y + area.y * window.height,
+        width: area.width * window.width,
+        height: area.height * window.height,
+    }
+}
+
+/// Part of the window with its own camera and an optional widget overlay
+///
+/// The whole UI space is mapped to the viewport, so drawables and widgets are positioned as if
+/// they had the window to themselves.
+pub struct Viewport {
+    name: String,
+    /// Covered part of the window viewport, as fractions of its size
+    area: Rect,
+    /// Drawn through the camera of the viewport
+    layer: Layer<'static>,
+    /// Identity camera the widgets are drawn through
+    overlay: Layer<'static>,
+    ui: Option<Ui>,
+}
+
+impl Viewport {
+    /// Create an empty viewport
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Rendering context
+    /// * `name` - Debug name of the viewport
+    /// * `area` - Covered part of the window, as fractions of its size, see [`grid`]
+    pub fn new(context: Ctx, name: &str, area: Rect) -> Self {
+        Self {
+            name: name.to_string(),
+            area,
+            layer: Layer::new(context.clone(), &format!("{name}/world")),
+            overlay: Layer::new(context, &format!("{name}/ui")),
+            ui: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn area(&self) -> Rect {
+        self.area
+    }
+
+    pub fn set_area(&mut self, area: Rect) {
+        self.area = area;
+    }
+
+    /// Returns the layer drawn in the viewport, to add drawables or move its camera
+    pub fn layer_mut(&mut self) -> &mut Layer<'static> {
+        &mut self.layer
+    }
+
+    /// Draw widgets over the content of the viewport, they receive the inputs occurring in it
+    ///
+    /// # Arguments
+    ///
+    /// * `ui` - Widgets of the viewport
+    pub fn set_ui(&mut self, ui: Ui) {
+        self.ui = Some(ui);
+    }
+
+    pub fn ui_mut(&mut self) -> Option<&mut Ui> {
+        self.ui.as_mut()
+    }
+
+    /// Returns the area covered by the viewport, in physical pixels
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - Area of the window the UI is rendered to, see [`crate::Context::viewport`]
+    pub fn rect(&self, window: Rect) -> Rect {
+        area_rect(window, self.area)
+    }
+
+    /// Convert a position in the window to the UI space of the viewport
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - Area of the window the UI is rendered to
+    /// * `screen_size` - Size of the UI space
+    /// * `position` - Position in physical pixels, relative to the window
+    pub fn window_to_local(&self, window: Rect, screen_size: (f32, f32), position: Vec2) -> Vec2 {
+        let rect = self.rect(window);
+
+        Vec2 {
+            x: (position.x - rect.x) * screen_size.0 / rect.width,
+            y: (position.y - rect.y) * screen_size.1 / rect.height,
+        }
+    }
+
+    /// Forward an event to the widgets of the viewport, positions must already be converted with
+    /// [`Viewport::window_to_local`]
+    pub fn process_events(&mut self, event: &WindowEvent) {
+        if let Some(ui) = &mut self.ui {
+            ui.process_events(event);
+        }
+    }
+
+    /// Returns the cursor wanted by the hovered widget, see [`Ui::cursor_icon`]
+    pub fn cursor_icon(&self) -> Option<CursorIcon> {
+        self.ui.as_ref().and_then(Ui::cursor_icon)
+    }
+
+    /// Advance the widgets of the viewport
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Time elapsed since the last frame, in seconds
+    pub fn tick(&mut self, dt: f32) {
+        if let Some(ui) = &mut self.ui {
+            ui.tick(dt);
+        }
+    }
+
+    /// Upload the cameras, must be called when the screen size changes
+    pub fn update(&mut self) {
+        self.layer.update();
+        self.overlay.update();
+    }
+
+    /// Prepare the content then render the pending snapshots of the widgets, see
+    /// [`Ui::prepare`]
+    pub fn prepare<'a>(
+        &'a mut self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        index_buffer: &'a wgpu::Buffer,
+    ) {
+        self.layer.prepare(encoder);
+        if let Some(ui) = &mut self.ui {
+            ui.prepare(encoder, index_buffer);
+        }
+    }
+
+    /// Draw the content then the widgets, restricted to the viewport
+    ///
+    /// # Arguments
+    ///
+    /// * `render_pass` - Pass drawing to the window
+    /// * `window` - Area of the window the UI is rendered to
+    pub fn draw<'a>(&'a mut self, render_pass: &mut wgpu::RenderPass<'a>, window: Rect) {
+        let rect = self.rect(window);
+        // Clip space is mapped to the viewport, anything outside of it is clipped
+        render_pass.set_viewport(rect.x, rect.y, rect.width, rect.height, 0., 1.);
+
+        render_pass.push_debug_group(&self.name);
+        self.layer.draw(render_pass);
+
+        if let Some(ui) = &mut self.ui {
+            self.overlay.bind(render_pass);
+            ui.draw(render_pass);
+        }
+        render_pass.pop_debug_group();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::{area_rect, grid};
+    use crate::math::Rect;
+
+    #[test]
+    fn grid_splits_in_cells() {
+        let cells = grid(2, 2);
+
+        assert_eq!(cells.len(), 4);
+        assert_eq!(cells[1].position(), Vec2::new(0.5, 0.));
+        assert_eq!(cells[2].position(), Vec2::new(0., 0.5));
+        assert!(cells
+            .iter()
+            .all(|cell| cell.width == 0.5 && cell.height == 0.5));
+    }
+
+    #[test]
+    fn areas_are_placed_in_the_window() {
+        let window = Rect {
+            x: 100.,
+            y: 0.,
+            width: 800.,
+            height: 600.,
+        };
+        let rect = area_rect(window, grid(2, 2)[3]);
+
+        assert_eq!(rect.position(), Vec2::new(500., 300.));
+        assert_eq!((rect.width, rect.height), (400., 300.));
+    }
+}